@@ -7,8 +7,8 @@ use gpui::{
     ParentElement as _, Render, SharedString, StatefulInteractiveElement, Styled, View,
     ViewContext, VisualContext as _,
 };
-use language::{LanguageRegistry, LanguageServerBinaryStatus, LanguageServerName};
-use project::{LanguageServerProgress, Project};
+use language::{Buffer, LanguageRegistry, LanguageServerBinaryStatus, LanguageServerName};
+use project::{headless_tasks::HeadlessTaskStatus, LanguageServerProgress, Project};
 use smallvec::SmallVec;
 use std::{cmp::Reverse, fmt::Write, sync::Arc};
 use ui::prelude::*;
@@ -22,6 +22,7 @@ const WARNING_ICON: &str = "icons/warning.svg";
 
 pub enum Event {
     ShowError { lsp_name: Arc<str>, error: String },
+    ShowTaskOutput { output: Model<Buffer> },
 }
 
 pub struct ActivityIndicator {
@@ -103,6 +104,14 @@ impl ActivityIndicator {
                     );
                 }
             }
+            Event::ShowTaskOutput { output } => {
+                workspace.add_item_to_active_pane(
+                    Box::new(cx.new_view(|cx| {
+                        Editor::for_buffer(output.clone(), Some(project.clone()), cx)
+                    })),
+                    cx,
+                );
+            }
         })
         .detach();
         this
@@ -195,6 +204,47 @@ impl ActivityIndicator {
             };
         }
 
+        // Show any task running in the background, or the last one to fail.
+        let headless_task_runs = self.project.read(cx).headless_task_runs();
+        let mut running_tasks = headless_task_runs
+            .iter()
+            .filter(|run| run.status == HeadlessTaskStatus::Running);
+        if let Some(run) = running_tasks.next() {
+            let mut message = format!("Running {}…", run.label);
+            let additional_task_count = running_tasks.count();
+            if additional_task_count > 0 {
+                write!(&mut message, " + {} more", additional_task_count).unwrap();
+            }
+            let output = run.output.clone();
+            return Content {
+                icon: None,
+                message,
+                on_click: Some(Arc::new(move |_, cx| {
+                    cx.emit(Event::ShowTaskOutput {
+                        output: output.clone(),
+                    })
+                })),
+            };
+        }
+        if let Some(run) = headless_task_runs
+            .iter()
+            .rfind(|run| run.status == HeadlessTaskStatus::Failed)
+        {
+            let id = run.id.clone();
+            let output = run.output.clone();
+            return Content {
+                icon: Some(WARNING_ICON),
+                message: format!("Task {} failed. Click to see output.", run.label),
+                on_click: Some(Arc::new(move |this, cx| {
+                    this.project
+                        .update(cx, |project, cx| project.dismiss_headless_task_run(&id, cx));
+                    cx.emit(Event::ShowTaskOutput {
+                        output: output.clone(),
+                    })
+                })),
+            };
+        }
+
         // Show any language server installation info.
         let mut downloading = SmallVec::<[_; 3]>::new();
         let mut checking_for_update = SmallVec::<[_; 3]>::new();
@@ -1,5 +1,5 @@
 use crate::db::ExtensionVersionConstraints;
-use crate::{db::NewExtensionVersion, AppState, Error, Result};
+use crate::{db::NewExtensionVersion, AppState, Config, Error, Result};
 use anyhow::{anyhow, Context as _};
 use aws_sdk_s3::presigning::PresigningConfig;
 use axum::{
@@ -155,16 +155,16 @@ async fn download_extension(
     Extension(app): Extension<Arc<AppState>>,
     Path(params): Path<DownloadExtensionParams>,
 ) -> Result<Redirect> {
-    let Some((blob_store_client, bucket)) = app
+    let blob_store = app
         .blob_store_client
         .clone()
-        .zip(app.config.blob_store_bucket.clone())
-    else {
+        .zip(app.config.blob_store_bucket.clone());
+    if blob_store.is_none() && app.config.blob_store_public_url.is_none() {
         Err(Error::Http(
             StatusCode::NOT_IMPLEMENTED,
             "not supported".into(),
         ))?
-    };
+    }
 
     let DownloadExtensionParams {
         extension_id,
@@ -183,12 +183,22 @@ async fn download_extension(
         ))?;
     }
 
+    let key = format!(
+        "{}/{extension_id}/{version}/archive.tar.gz",
+        extensions_prefix(&app.config)
+    );
+    if let Some(public_url) = app.config.blob_store_public_url.as_ref() {
+        let url = format!("{}/{key}", public_url.trim_end_matches('/'));
+        return Ok(Redirect::temporary(&url));
+    }
+
+    let Some((blob_store_client, bucket)) = blob_store else {
+        Err(anyhow!("no blob store to download extensions from"))?
+    };
     let url = blob_store_client
         .get_object()
         .bucket(bucket)
-        .key(format!(
-            "extensions/{extension_id}/{version}/archive.tar.gz"
-        ))
+        .key(key)
         .presigned(PresigningConfig::expires_in(EXTENSION_DOWNLOAD_URL_LIFETIME).unwrap())
         .await
         .map_err(|e| anyhow!("failed to create presigned extension download url {e}"))?;
@@ -196,6 +206,17 @@ async fn download_extension(
     Ok(Redirect::temporary(url.uri()))
 }
 
+const DEFAULT_EXTENSIONS_PREFIX: &str = "extensions";
+
+/// The key prefix of the extensions in the blob store, without surrounding slashes.
+fn extensions_prefix(config: &Config) -> &str {
+    config
+        .blob_store_extensions_prefix
+        .as_deref()
+        .map(|prefix| prefix.trim_matches('/'))
+        .unwrap_or(DEFAULT_EXTENSIONS_PREFIX)
+}
+
 const EXTENSION_FETCH_INTERVAL: Duration = Duration::from_secs(5 * 60);
 const EXTENSION_DOWNLOAD_URL_LIFETIME: Duration = Duration::from_secs(3 * 60);
 
@@ -234,10 +255,11 @@ async fn fetch_extensions_from_blob_store(
 ) -> anyhow::Result<()> {
     log::info!("fetching extensions from blob store");
 
+    let prefix = extensions_prefix(&app_state.config);
     let list = blob_store_client
         .list_objects()
         .bucket(blob_store_bucket)
-        .prefix("extensions/")
+        .prefix(format!("{prefix}/"))
         .send()
         .await?;
 
@@ -248,10 +270,13 @@ async fn fetch_extensions_from_blob_store(
         let Some(key) = object.key.as_ref() else {
             continue;
         };
-        let mut parts = key.split('/');
-        let Some(_) = parts.next().filter(|part| *part == "extensions") else {
+        let Some(key) = key
+            .strip_prefix(prefix)
+            .and_then(|key| key.strip_prefix('/'))
+        else {
             continue;
         };
+        let mut parts = key.split('/');
         let Some(extension_id) = parts.next() else {
             continue;
         };
@@ -281,6 +306,7 @@ async fn fetch_extensions_from_blob_store(
                 if let Some(extension) = fetch_extension_manifest(
                     blob_store_client,
                     blob_store_bucket,
+                    prefix,
                     extension_id,
                     published_version,
                 )
@@ -312,13 +338,14 @@ async fn fetch_extensions_from_blob_store(
 async fn fetch_extension_manifest(
     blob_store_client: &aws_sdk_s3::Client,
     blob_store_bucket: &String,
+    prefix: &str,
     extension_id: &str,
     version: &str,
 ) -> Result<NewExtensionVersion, anyhow::Error> {
     let object = blob_store_client
         .get_object()
         .bucket(blob_store_bucket)
-        .key(format!("extensions/{extension_id}/{version}/manifest.json"))
+        .key(format!("{prefix}/{extension_id}/{version}/manifest.json"))
         .send()
         .await?;
     let manifest_bytes = object
@@ -353,6 +380,7 @@ async fn fetch_extension_manifest(
         repository: manifest.repository,
         schema_version: manifest.schema_version.unwrap_or(0),
         wasm_api_version: manifest.wasm_api_version,
+        archive_sha256: manifest.archive_sha256,
         published_at,
     })
 }
@@ -731,6 +731,7 @@ pub struct NewExtensionVersion {
     pub repository: String,
     pub schema_version: i32,
     pub wasm_api_version: Option<String>,
+    pub archive_sha256: Option<String>,
     pub published_at: PrimitiveDateTime,
 }
 
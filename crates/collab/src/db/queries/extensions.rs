@@ -282,6 +282,7 @@ impl Database {
                         description: ActiveValue::Set(version.description.clone()),
                         schema_version: ActiveValue::Set(version.schema_version),
                         wasm_api_version: ActiveValue::Set(version.wasm_api_version.clone()),
+                        archive_sha256: ActiveValue::Set(version.archive_sha256.clone()),
                         download_count: ActiveValue::NotSet,
                     }
                 }))
@@ -370,6 +371,7 @@ fn metadata_from_extension_and_version(
             repository: version.repository,
             schema_version: Some(version.schema_version),
             wasm_api_version: version.wasm_api_version,
+            archive_sha256: version.archive_sha256,
         },
 
         published_at: convert_time_to_chrono(version.published_at),
@@ -15,6 +15,7 @@ pub struct Model {
     pub description: String,
     pub schema_version: i32,
     pub wasm_api_version: Option<String>,
+    pub archive_sha256: Option<String>,
     pub download_count: i64,
 }
 
@@ -131,6 +131,12 @@ pub struct Config {
     pub blob_store_access_key: Option<String>,
     pub blob_store_secret_key: Option<String>,
     pub blob_store_bucket: Option<String>,
+    /// The key prefix under which extensions are published in the blob store.
+    /// Defaults to `extensions`.
+    pub blob_store_extensions_prefix: Option<String>,
+    /// A base URL that serves the blob store's objects directly, such as a private mirror.
+    /// When set, extension downloads redirect there instead of to presigned blob store URLs.
+    pub blob_store_public_url: Option<String>,
     pub zed_environment: Arc<str>,
     pub openai_api_key: Option<Arc<str>>,
     pub google_ai_api_key: Option<Arc<str>>,
@@ -511,6 +511,8 @@ impl TestServer {
                 blob_store_access_key: None,
                 blob_store_secret_key: None,
                 blob_store_bucket: None,
+                blob_store_extensions_prefix: None,
+                blob_store_public_url: None,
                 openai_api_key: None,
                 google_ai_api_key: None,
                 anthropic_api_key: None,
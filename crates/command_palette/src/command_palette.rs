@@ -219,12 +219,21 @@ impl CommandPaletteDelegate {
         }
         self.commands = commands;
         self.matches = matches;
+        self.preview(Some(&query), cx);
         if self.matches.is_empty() {
             self.selected_ix = 0;
         } else {
             self.selected_ix = cmp::min(self.selected_ix, self.matches.len() - 1);
         }
     }
+
+    fn preview(&self, query: Option<&str>, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(preview) =
+            CommandPaletteInterceptor::try_global(cx).and_then(|interceptor| interceptor.preview())
+        {
+            preview(query, cx);
+        }
+    }
 }
 
 impl PickerDelegate for CommandPaletteDelegate {
@@ -344,6 +353,7 @@ impl PickerDelegate for CommandPaletteDelegate {
     }
 
     fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.preview(None, cx);
         self.command_palette
             .update(cx, |_, cx| cx.emit(DismissEvent))
             .log_err();
@@ -2,11 +2,11 @@
 
 #![deny(missing_docs)]
 
-use std::any::TypeId;
+use std::{any::TypeId, rc::Rc};
 
 use collections::HashSet;
 use derive_more::{Deref, DerefMut};
-use gpui::{Action, AppContext, BorrowAppContext, Global};
+use gpui::{Action, AppContext, BorrowAppContext, Global, WindowContext};
 
 /// Initializes the command palette hooks.
 pub fn init(cx: &mut AppContext) {
@@ -90,11 +90,18 @@ pub struct CommandInterceptResult {
     pub positions: Vec<usize>,
 }
 
+/// A function that previews the effect of the query typed into the command palette.
+///
+/// It is called with the query whenever it changes, and with `None` once the palette
+/// is closed, at which point any preview should be removed.
+pub type CommandPalettePreview = Rc<dyn Fn(Option<&str>, &mut WindowContext)>;
+
 /// An interceptor for the command palette.
 #[derive(Default)]
-pub struct CommandPaletteInterceptor(
-    Option<Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>>,
-);
+pub struct CommandPaletteInterceptor {
+    handler: Option<Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>>,
+    preview: Option<CommandPalettePreview>,
+}
 
 #[derive(Default)]
 struct GlobalCommandPaletteInterceptor(CommandPaletteInterceptor);
@@ -118,16 +125,22 @@ impl CommandPaletteInterceptor {
 
     /// Intercepts the given query from the command palette.
     pub fn intercept(&self, query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
-        let Some(handler) = self.0.as_ref() else {
+        let Some(handler) = self.handler.as_ref() else {
             return None;
         };
 
         (handler)(query, cx)
     }
 
-    /// Clears the global interceptor.
+    /// Returns the function previewing command palette queries, if one is set.
+    pub fn preview(&self) -> Option<CommandPalettePreview> {
+        self.preview.clone()
+    }
+
+    /// Clears the global interceptor, along with its preview.
     pub fn clear(&mut self) {
-        self.0 = None;
+        self.handler = None;
+        self.preview = None;
     }
 
     /// Sets the global interceptor.
@@ -137,6 +150,13 @@ impl CommandPaletteInterceptor {
         &mut self,
         handler: Box<dyn Fn(&str, &AppContext) -> Option<CommandInterceptResult>>,
     ) {
-        self.0 = Some(handler);
+        self.handler = Some(handler);
+    }
+
+    /// Sets the function previewing command palette queries.
+    ///
+    /// This will override the previous preview, if it exists.
+    pub fn set_preview(&mut self, preview: CommandPalettePreview) {
+        self.preview = Some(preview);
     }
 }
@@ -241,6 +241,7 @@ gpui::actions!(
         ShuffleLines,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
+        SpawnRunnable,
         SplitSelectionIntoLines,
         Tab,
         TabPrev,
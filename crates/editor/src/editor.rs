@@ -77,9 +77,9 @@ use itertools::Itertools;
 use language::{
     char_kind,
     language_settings::{self, all_language_settings, InlayHintSettings},
-    markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, Capability, CharKind, CodeLabel,
-    CursorShape, Diagnostic, Documentation, IndentKind, IndentSize, Language, OffsetRangeExt,
-    Point, Selection, SelectionGoal, TransactionId,
+    markdown, point_from_lsp, AutoindentMode, BracketPair, Buffer, BufferSnapshot, Capability,
+    CharKind, CodeLabel, CursorShape, Diagnostic, Documentation, IndentKind, IndentSize, Language,
+    OffsetRangeExt, Point, RunnableRange, Selection, SelectionGoal, TransactionId,
 };
 
 use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
@@ -145,6 +145,8 @@ pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
 #[doc(hidden)]
 pub const DOCUMENT_HIGHLIGHTS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(75);
+#[doc(hidden)]
+pub const RUNNABLES_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
 
 pub(crate) const FORMAT_TIMEOUT: Duration = Duration::from_secs(2);
 
@@ -227,6 +229,7 @@ impl InlayId {
 }
 
 enum DocumentHighlightRead {}
+enum ReplacementPreview {}
 enum DocumentHighlightWrite {}
 enum InputComposition {}
 
@@ -359,6 +362,8 @@ type CompletionId = usize;
 // type OverrideTextStyle = dyn Fn(&EditorStyle) -> Option<HighlightStyle>;
 
 type BackgroundHighlight = (fn(&ThemeColors) -> Hsla, Arc<[Range<Anchor>]>);
+/// The buffer, its version and its parse count, for which runnables were queried.
+type RunnablesKey = (BufferId, clock::Global, usize);
 
 struct ScrollbarMarkerState {
     scrollbar_size: Size<Pixels>,
@@ -432,6 +437,10 @@ pub struct Editor {
     completion_documentation_pre_resolve_debounce: DebouncedDelay,
     available_code_actions: Option<(Model<Buffer>, Arc<[CodeAction]>)>,
     code_actions_task: Option<Task<()>>,
+    /// The ranges and run positions of the buffer's runnables, along with the buffer version
+    /// and parse they were queried for.
+    runnables: Option<(RunnablesKey, Arc<[(Range<text::Anchor>, text::Anchor)]>)>,
+    runnables_task: Option<(RunnablesKey, Task<()>)>,
     document_highlights_task: Option<Task<()>>,
     pending_rename: Option<RenameState>,
     searchable: bool,
@@ -451,8 +460,10 @@ pub struct Editor {
     inline_completion_provider: Option<RegisteredInlineCompletionProvider>,
     active_inline_completion: Option<Inlay>,
     show_inline_completions: bool,
+    edit_preview_inlays: Vec<InlayId>,
     inlay_hint_cache: InlayHintCache,
     next_inlay_id: usize,
+    replacement_preview_inlays: Vec<InlayId>,
     _subscriptions: Vec<Subscription>,
     pixel_position_of_newest_cursor: Option<gpui::Point<Pixels>>,
     gutter_width: Pixels,
@@ -1478,8 +1489,12 @@ impl Editor {
             next_completion_id: 0,
             completion_documentation_pre_resolve_debounce: DebouncedDelay::new(),
             next_inlay_id: 0,
+            replacement_preview_inlays: Vec::new(),
+            edit_preview_inlays: Vec::new(),
             available_code_actions: Default::default(),
             code_actions_task: Default::default(),
+            runnables: None,
+            runnables_task: None,
             document_highlights_task: Default::default(),
             pending_rename: Default::default(),
             searchable: true,
@@ -4121,6 +4136,29 @@ impl Editor {
         self.discard_inline_completion(cx);
     }
 
+    /// Shows each text inline at its position without modifying the buffer, replacing
+    /// any previously previewed text. Used to preview edits before they're applied,
+    /// such as the replacements of a vim `:s` command.
+    pub fn set_edit_preview(
+        &mut self,
+        previews: Vec<(Anchor, String)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let to_remove = mem::take(&mut self.edit_preview_inlays);
+        let to_insert = previews
+            .into_iter()
+            .map(|(position, text)| {
+                Inlay::suggestion(post_inc(&mut self.next_inlay_id), position, text)
+            })
+            .collect::<Vec<_>>();
+        if to_remove.is_empty() && to_insert.is_empty() {
+            return;
+        }
+
+        self.edit_preview_inlays = to_insert.iter().map(|inlay| inlay.id).collect();
+        self.splice_inlays(to_remove, to_insert, cx);
+    }
+
     fn clear_inline_completion(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(old_completion) = self.active_inline_completion.take() {
             self.splice_inlays(vec![old_completion.id], Vec::new(), cx);
@@ -4159,6 +4197,113 @@ impl Editor {
         }
     }
 
+    pub fn render_runnable_indicator(
+        &self,
+        _style: &EditorStyle,
+        row: u32,
+        runnable_start: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        IconButton::new(("runnable_indicator", row as usize), ui::IconName::Play)
+            .icon_size(IconSize::XSmall)
+            .size(ui::ButtonSize::None)
+            .icon_color(Color::Muted)
+            .on_click(cx.listener(move |editor, _e, cx| {
+                editor.change_selections(None, cx, |selections| {
+                    selections.select_ranges([runnable_start..runnable_start])
+                });
+                editor.focus(cx);
+                cx.dispatch_action(Box::new(SpawnRunnable));
+            }))
+    }
+
+    /// Returns the run positions of the buffer's runnables that intersect the given range.
+    /// Once the buffer was edited or reparsed, the runnables are queried again in the
+    /// background after a debounce, and the previous ones are anchored until then.
+    fn runnable_indicator_offsets(
+        &mut self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        cx: &mut ViewContext<Self>,
+    ) -> Vec<usize> {
+        let key = (
+            buffer.remote_id(),
+            buffer.version().clone(),
+            buffer.parse_count(),
+        );
+        let cached = self
+            .runnables
+            .as_ref()
+            .filter(|(cached_key, _)| cached_key.0 == key.0);
+        let is_stale = cached.map_or(true, |(cached_key, _)| *cached_key != key);
+        let is_pending = self
+            .runnables_task
+            .as_ref()
+            .map_or(false, |(pending_key, _)| *pending_key == key);
+        if is_stale && !is_pending {
+            let debounce = cached.is_some();
+            let buffer = buffer.clone();
+            let task_key = key.clone();
+            let task = cx.spawn(|this, mut cx| async move {
+                if debounce {
+                    cx.background_executor()
+                        .timer(RUNNABLES_DEBOUNCE_TIMEOUT)
+                        .await;
+                }
+                let runnables = cx
+                    .background_executor()
+                    .spawn(async move {
+                        buffer
+                            .runnable_ranges(0..buffer.len())
+                            .map(|runnable| {
+                                (
+                                    buffer.anchor_before(runnable.range.start)
+                                        ..buffer.anchor_after(runnable.range.end),
+                                    buffer.anchor_before(runnable.run_range.start),
+                                )
+                            })
+                            .collect::<Arc<[_]>>()
+                    })
+                    .await;
+                this.update(&mut cx, |this, cx| {
+                    this.runnables = Some((task_key, runnables));
+                    cx.notify();
+                })
+                .log_err();
+            });
+            self.runnables_task = Some((key, task));
+        }
+
+        let Some((_, runnables)) = cached else {
+            return Vec::new();
+        };
+        runnables
+            .iter()
+            .filter(|(runnable_range, _)| {
+                let runnable_range = runnable_range.to_offset(buffer);
+                runnable_range.start < range.end && runnable_range.end > range.start
+            })
+            .map(|(_, run_start)| text::ToOffset::to_offset(run_start, buffer))
+            .collect()
+    }
+
+    /// Returns the innermost runnable around the newest cursor, or on its line,
+    /// as found by the `runnables` query of the buffer's language.
+    pub fn runnable_at_cursor(&self, cx: &AppContext) -> Option<(BufferSnapshot, RunnableRange)> {
+        let buffer = self.buffer.read(cx).as_singleton()?.read(cx).snapshot();
+        let cursor = self.selections.newest::<usize>(cx).head();
+        let row = buffer.offset_to_point(cursor).row;
+        let line = Point::new(row, 0)..Point::new(row, buffer.line_len(row));
+        let runnable = buffer
+            .runnable_ranges(line)
+            .filter(|runnable| {
+                runnable.range.contains(&cursor)
+                    || buffer.offset_to_point(runnable.run_range.start).row == row
+            })
+            .min_by_key(|runnable| runnable.range.len())?;
+        Some((buffer, runnable))
+    }
+
     pub fn render_fold_indicators(
         &mut self,
         fold_data: Vec<Option<(FoldStatus, u32, bool)>>,
@@ -9308,6 +9453,68 @@ impl Editor {
         cx.notify();
     }
 
+    /// Previews replacing each of the given ranges with some text: the ranges get struck through,
+    /// and their replacement is displayed right after them. Replaces any previous preview.
+    pub fn set_replacement_previews(
+        &mut self,
+        previews: Vec<(Range<Anchor>, String)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let status = cx.theme().status();
+        let (replaced_style, replacement_style) = (
+            HighlightStyle {
+                background_color: Some(status.deleted_background),
+                strikethrough: Some(StrikethroughStyle {
+                    thickness: px(1.),
+                    color: Some(status.deleted),
+                }),
+                ..HighlightStyle::default()
+            },
+            HighlightStyle {
+                color: Some(status.created),
+                background_color: Some(status.created_background),
+                ..HighlightStyle::default()
+            },
+        );
+
+        let mut replaced_ranges = Vec::with_capacity(previews.len());
+        let mut inlays = Vec::with_capacity(previews.len());
+        let mut inlay_highlights = Vec::with_capacity(previews.len());
+        for (range, replacement) in previews {
+            if !replacement.is_empty() {
+                let inlay = Inlay::suggestion(
+                    post_inc(&mut self.next_inlay_id),
+                    range.end,
+                    replacement.as_str(),
+                );
+                inlay_highlights.push(InlayHighlight {
+                    inlay: inlay.id,
+                    inlay_position: inlay.position,
+                    range: 0..replacement.len(),
+                });
+                inlays.push(inlay);
+            }
+            replaced_ranges.push(range);
+        }
+
+        let previous_inlays = mem::replace(
+            &mut self.replacement_preview_inlays,
+            inlays.iter().map(|inlay| inlay.id).collect(),
+        );
+        self.splice_inlays(previous_inlays, inlays, cx);
+        self.clear_highlights::<ReplacementPreview>(cx);
+        self.highlight_text::<ReplacementPreview>(replaced_ranges, replaced_style, cx);
+        self.highlight_inlays::<ReplacementPreview>(inlay_highlights, replacement_style, cx);
+    }
+
+    pub fn clear_replacement_previews(&mut self, cx: &mut ViewContext<Self>) {
+        let previous_inlays = mem::take(&mut self.replacement_preview_inlays);
+        if !previous_inlays.is_empty() {
+            self.splice_inlays(previous_inlays, Vec::new(), cx);
+        }
+        self.clear_highlights::<ReplacementPreview>(cx);
+    }
+
     pub(crate) fn highlight_inlays<T: 'static>(
         &mut self,
         highlights: Vec<InlayHighlight>,
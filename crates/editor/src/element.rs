@@ -17,7 +17,7 @@ use crate::{
     SoftWrap, ToPoint, CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
 };
 use anyhow::Result;
-use collections::{BTreeMap, HashMap};
+use collections::{BTreeMap, HashMap, HashSet};
 use git::{blame::BlameEntry, diff::DiffHunkStatus, Oid};
 use gpui::{
     anchored, deferred, div, fill, outline, point, px, quad, relative, size, svg,
@@ -1187,6 +1187,69 @@ impl EditorElement {
         Some(button)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_runnable_indicators(
+        &self,
+        snapshot: &EditorSnapshot,
+        rows: Range<u32>,
+        code_actions_row: Option<u32>,
+        line_height: Pixels,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        cx: &mut ElementContext,
+    ) -> Vec<AnyElement> {
+        let Some((_, _, buffer)) = snapshot.buffer_snapshot.as_singleton() else {
+            return Vec::new();
+        };
+        let start =
+            DisplayPoint::new(rows.start, 0).to_offset(&snapshot.display_snapshot, Bias::Left);
+        let end = DisplayPoint::new(rows.end, 0).to_offset(&snapshot.display_snapshot, Bias::Right);
+
+        let run_offsets = self.editor.update(cx, |editor, cx| {
+            editor.runnable_indicator_offsets(buffer, start..end, cx)
+        });
+        let mut indicator_rows = HashSet::default();
+        let mut indicators = Vec::new();
+        for run_offset in run_offsets {
+            let row = snapshot
+                .buffer_snapshot
+                .offset_to_point(run_offset)
+                .to_display_point(&snapshot.display_snapshot)
+                .row();
+            if !rows.contains(&row) || Some(row) == code_actions_row || !indicator_rows.insert(row)
+            {
+                continue;
+            }
+
+            let mut button = self
+                .editor
+                .update(cx, |editor, cx| {
+                    editor.render_runnable_indicator(&self.style, row, run_offset, cx)
+                })
+                .into_any_element();
+            let available_space = size(
+                AvailableSpace::MinContent,
+                AvailableSpace::Definite(line_height),
+            );
+            let indicator_size = button.measure(available_space, cx);
+
+            let blame_width = gutter_dimensions
+                .git_blame_entries_width
+                .unwrap_or(Pixels::ZERO);
+            let available_width = gutter_dimensions.margin + gutter_dimensions.left_padding
+                - indicator_size.width
+                - blame_width;
+            let x = blame_width + available_width / 2.;
+            let y = row as f32 * line_height - scroll_pixel_position.y
+                + (line_height - indicator_size.height) / 2.;
+
+            button.layout(gutter_hitbox.origin + point(x, y), available_space, cx);
+            indicators.push(button);
+        }
+        indicators
+    }
+
     fn calculate_relative_line_numbers(
         &self,
         buffer_rows: Vec<Option<u32>>,
@@ -2103,6 +2166,10 @@ impl EditorElement {
             if let Some(indicator) = layout.code_actions_indicator.as_mut() {
                 indicator.paint(cx);
             }
+
+            for indicator in layout.runnable_indicators.iter_mut() {
+                indicator.paint(cx);
+            }
         })
     }
 
@@ -3626,6 +3693,25 @@ impl Element for EditorElement {
                     }
                 }
 
+                let runnable_indicators = if gutter_settings.runnables {
+                    let code_actions_row = code_actions_indicator
+                        .as_ref()
+                        .and(newest_selection_head)
+                        .map(|head| head.row());
+                    self.layout_runnable_indicators(
+                        &snapshot,
+                        start_row..end_row,
+                        code_actions_row,
+                        line_height,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        cx,
+                    )
+                } else {
+                    Vec::new()
+                };
+
                 if !context_menu_visible && !cx.has_active_drag() {
                     self.layout_hover_popovers(
                         &snapshot,
@@ -3725,6 +3811,7 @@ impl Element for EditorElement {
                     selections,
                     mouse_context_menu,
                     code_actions_indicator,
+                    runnable_indicators,
                     fold_indicators,
                     tab_invisible,
                     space_invisible,
@@ -3814,6 +3901,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     max_row: u32,
     code_actions_indicator: Option<AnyElement>,
+    runnable_indicators: Vec<AnyElement>,
     fold_indicators: Vec<Option<AnyElement>>,
     mouse_context_menu: Option<AnyElement>,
     tab_invisible: ShapedLine,
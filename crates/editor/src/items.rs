@@ -1,7 +1,7 @@
 use crate::{
-    editor_settings::SeedQuerySetting, persistence::DB, scroll::ScrollAnchor, Anchor, Autoscroll,
-    Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange, MultiBuffer, MultiBufferSnapshot,
-    NavigationData, ToPoint as _,
+    editor_settings::SeedQuerySetting, persistence::DB, scroll::ScrollAnchor, Anchor,
+    AnchorRangeExt as _, Autoscroll, Editor, EditorEvent, EditorSettings, ExcerptId, ExcerptRange,
+    MultiBuffer, MultiBufferSnapshot, NavigationData, ToPoint as _,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::HashSet;
@@ -1037,19 +1037,10 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if let Some(replacement) = replacement_for_match(&snapshot, identifier, query) {
             self.transact(cx, |this, cx| {
-                this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
+                this.edit([(identifier.clone(), Arc::from(replacement))], cx);
             });
         }
     }
@@ -1180,6 +1171,31 @@ pub fn active_match_index(
     }
 }
 
+/// Returns the text that replacing a search match with the query's replacement would produce.
+pub fn replacement_for_match(
+    buffer: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    if query.is_structural() {
+        let range = range.to_offset(buffer);
+        let excerpt = buffer.excerpt_containing(range.clone())?;
+        return query
+            .structural_replacement_for(excerpt.buffer(), excerpt.map_range_to_buffer(range));
+    }
+
+    let text = buffer.text_for_range(range.clone()).collect::<Vec<_>>();
+    let text: Cow<_> = if text.len() == 1 {
+        text.first().cloned().unwrap().into()
+    } else {
+        let joined_chunks = text.join("");
+        joined_chunks.into()
+    };
+    query
+        .replacement_for(&text)
+        .map(|replacement| replacement.into_owned())
+}
+
 pub fn entry_label_color(selected: bool) -> Color {
     if selected {
         Color::Default
@@ -482,7 +482,9 @@ impl ExtensionBuilder {
     }
 }
 
-fn populate_defaults(manifest: &mut ExtensionManifest, extension_path: &Path) -> Result<()> {
+/// Fills in the languages, themes and grammars that an extension provides by
+/// convention, without listing them in its manifest.
+pub fn populate_defaults(manifest: &mut ExtensionManifest, extension_path: &Path) -> Result<()> {
     // For legacy extensions on the v0 schema (aka, using `extension.json`), clear out any existing
     // contents of the computed fields, since we don't care what the existing values are.
     if manifest.schema_version.is_v0() {
@@ -0,0 +1,298 @@
+use anyhow::{anyhow, Result};
+use fs::normalize_path;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use url::Url;
+
+/// The capabilities that an extension is allowed to use from the host.
+///
+/// Extensions declare the capabilities they need in the `[capabilities]` section
+/// of their `extension.toml`. The same structure is used by the
+/// `extension_capabilities` setting, to restrict what any extension may do.
+#[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ExtensionCapabilities {
+    /// The hosts that files may be downloaded from.
+    ///
+    /// `*` allows any host, and `*.example.com` allows any subdomain of `example.com`.
+    #[serde(default)]
+    pub download_hosts: Vec<String>,
+    /// The npm packages that may be installed.
+    ///
+    /// `*` allows any package, and `@scope/*` allows any package within a scope.
+    #[serde(default)]
+    pub npm_packages: Vec<String>,
+    /// The paths within the extension's work directory that may be written to,
+    /// either by downloading files or by making files executable.
+    ///
+    /// `.` allows writing anywhere in the work directory.
+    #[serde(default)]
+    pub writable_paths: Vec<PathBuf>,
+}
+
+impl ExtensionCapabilities {
+    /// The capabilities granted to extensions that don't declare any.
+    pub fn unrestricted() -> Self {
+        Self {
+            download_hosts: vec!["*".into()],
+            npm_packages: vec!["*".into()],
+            writable_paths: vec![".".into()],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.download_hosts.is_empty()
+            && self.npm_packages.is_empty()
+            && self.writable_paths.is_empty()
+    }
+
+    pub fn allows_download_host(&self, host: &str) -> bool {
+        self.download_hosts
+            .iter()
+            .any(|pattern| pattern_matches(pattern, host, '.'))
+    }
+
+    pub fn allows_npm_package(&self, package_name: &str) -> bool {
+        self.npm_packages
+            .iter()
+            .any(|pattern| pattern_matches(pattern, package_name, '/'))
+    }
+
+    /// Returns whether the given path, relative to the extension's work directory,
+    /// may be written to.
+    pub fn allows_writing(&self, relative_path: &Path) -> bool {
+        let relative_path = normalize_path(relative_path);
+        self.writable_paths
+            .iter()
+            .any(|allowed_path| relative_path.starts_with(normalize_path(allowed_path)))
+    }
+
+    /// Returns whether everything allowed by `self` is also allowed by `other`.
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.download_hosts.iter().all(|pattern| {
+            other
+                .download_hosts
+                .iter()
+                .any(|other_pattern| pattern_covers(other_pattern, pattern, '.'))
+        }) && self.npm_packages.iter().all(|pattern| {
+            other
+                .npm_packages
+                .iter()
+                .any(|other_pattern| pattern_covers(other_pattern, pattern, '/'))
+        }) && self
+            .writable_paths
+            .iter()
+            .all(|path| other.allows_writing(path))
+    }
+}
+
+/// Matches a host or package name against a pattern, where `*` matches anything
+/// and a `*` followed by the separator matches anything under the given prefix.
+fn pattern_matches(pattern: &str, value: &str, separator: char) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    match separator {
+        '.' => {
+            if let Some(suffix) = pattern.strip_prefix("*.") {
+                return value
+                    .strip_suffix(suffix)
+                    .map_or(false, |rest| rest.ends_with('.'));
+            }
+            pattern.eq_ignore_ascii_case(value)
+        }
+        _ => {
+            if let Some(prefix) = pattern.strip_suffix("/*") {
+                return value
+                    .strip_prefix(prefix)
+                    .map_or(false, |rest| rest.starts_with(separator));
+            }
+            pattern == value
+        }
+    }
+}
+
+/// Returns whether every value matched by `pattern` is also matched by `covering_pattern`.
+fn pattern_covers(covering_pattern: &str, pattern: &str, separator: char) -> bool {
+    if covering_pattern == "*" || covering_pattern == pattern {
+        return true;
+    }
+    if pattern.contains('*') {
+        let literal_part = pattern
+            .trim_start_matches("*.")
+            .trim_end_matches("/*")
+            .to_string();
+        let is_wildcard = covering_pattern.starts_with("*.") || covering_pattern.ends_with("/*");
+        return is_wildcard && pattern_matches(covering_pattern, &literal_part, separator);
+    }
+    pattern_matches(covering_pattern, pattern, separator)
+}
+
+/// The capabilities in effect for a loaded extension: those declared in its manifest,
+/// further restricted by the `extension_capabilities` setting.
+#[derive(Clone, Debug, Default)]
+pub struct CapabilityPolicy {
+    pub declared: Option<ExtensionCapabilities>,
+    pub restriction: Option<ExtensionCapabilities>,
+}
+
+impl CapabilityPolicy {
+    fn evaluate(&self, allows: impl Fn(&ExtensionCapabilities) -> bool) -> (bool, bool) {
+        (
+            self.declared.as_ref().map_or(true, &allows),
+            self.restriction.as_ref().map_or(true, &allows),
+        )
+    }
+
+    fn ensure(&self, (declared, restricted): (bool, bool), description: String) -> Result<()> {
+        if !declared {
+            Err(anyhow!(
+                "{description} is not among the capabilities declared by this extension"
+            ))
+        } else if !restricted {
+            Err(anyhow!(
+                "{description} is not allowed by the extension_capabilities setting"
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn check_download_url(&self, url: &str) -> Result<()> {
+        let url = Url::parse(url)?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| anyhow!("download url {url} has no host"))?;
+        self.ensure(
+            self.evaluate(|capabilities| capabilities.allows_download_host(host)),
+            format!("downloading from {host}"),
+        )
+    }
+
+    pub fn check_npm_package(&self, package_name: &str) -> Result<()> {
+        self.ensure(
+            self.evaluate(|capabilities| capabilities.allows_npm_package(package_name)),
+            format!("installing the npm package {package_name}"),
+        )
+    }
+
+    pub fn check_writable_path(&self, relative_path: &Path) -> Result<()> {
+        self.ensure(
+            self.evaluate(|capabilities| capabilities.allows_writing(relative_path)),
+            format!("writing to {relative_path:?}"),
+        )
+    }
+
+    /// The paths within the extension's work directory that may be written to, normalized,
+    /// with an empty path standing for the whole work directory.
+    pub fn writable_paths(&self) -> Vec<PathBuf> {
+        let unrestricted = ExtensionCapabilities::unrestricted();
+        let mut writable_paths = Vec::new();
+        for capabilities in [self.declared.as_ref(), self.restriction.as_ref()] {
+            for path in &capabilities.unwrap_or(&unrestricted).writable_paths {
+                let is_relative = path
+                    .components()
+                    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+                let path = normalize_path(path);
+                if is_relative
+                    && self.check_writable_path(&path).is_ok()
+                    && !writable_paths.contains(&path)
+                {
+                    writable_paths.push(path);
+                }
+            }
+        }
+        writable_paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capability_matching() {
+        let capabilities = ExtensionCapabilities {
+            download_hosts: vec!["github.com".into(), "*.githubusercontent.com".into()],
+            npm_packages: vec!["typescript".into(), "@vue/*".into()],
+            writable_paths: vec!["bin".into()],
+        };
+
+        assert!(capabilities.allows_download_host("github.com"));
+        assert!(capabilities.allows_download_host("objects.githubusercontent.com"));
+        assert!(!capabilities.allows_download_host("githubusercontent.com"));
+        assert!(!capabilities.allows_download_host("evil-github.com"));
+
+        assert!(capabilities.allows_npm_package("typescript"));
+        assert!(capabilities.allows_npm_package("@vue/language-server"));
+        assert!(!capabilities.allows_npm_package("@vuex/store"));
+
+        assert!(capabilities.allows_writing(Path::new("bin/server")));
+        assert!(capabilities.allows_writing(Path::new("./bin")));
+        assert!(!capabilities.allows_writing(Path::new("bin/../lib")));
+
+        assert!(capabilities.is_subset_of(&ExtensionCapabilities::unrestricted()));
+        assert!(!ExtensionCapabilities::unrestricted().is_subset_of(&capabilities));
+        assert!(ExtensionCapabilities::default().is_subset_of(&capabilities));
+
+        let expanded = ExtensionCapabilities {
+            download_hosts: vec!["*.github.com".into()],
+            ..capabilities.clone()
+        };
+        assert!(!expanded.is_subset_of(&capabilities));
+    }
+
+    #[test]
+    fn test_capability_policy() {
+        let policy = CapabilityPolicy {
+            declared: Some(ExtensionCapabilities {
+                download_hosts: vec!["*".into()],
+                ..Default::default()
+            }),
+            restriction: Some(ExtensionCapabilities {
+                download_hosts: vec!["github.com".into()],
+                ..ExtensionCapabilities::unrestricted()
+            }),
+        };
+
+        assert!(policy
+            .check_download_url("https://github.com/owner/repo/archive.tar.gz")
+            .is_ok());
+        assert!(policy
+            .check_download_url("https://example.com/server.zip")
+            .is_err());
+        assert!(policy.check_npm_package("typescript").is_err());
+        assert!(CapabilityPolicy::default()
+            .check_npm_package("typescript")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_writable_paths() {
+        assert_eq!(
+            CapabilityPolicy::default().writable_paths(),
+            [PathBuf::new()]
+        );
+
+        let policy = CapabilityPolicy {
+            declared: Some(ExtensionCapabilities {
+                writable_paths: vec![".".into(), "/etc".into(), "../other".into()],
+                ..Default::default()
+            }),
+            restriction: Some(ExtensionCapabilities {
+                writable_paths: vec!["bin/".into(), "cache".into()],
+                ..Default::default()
+            }),
+        };
+        assert_eq!(
+            policy.writable_paths(),
+            [PathBuf::from("bin"), PathBuf::from("cache")]
+        );
+
+        let policy = CapabilityPolicy {
+            declared: Some(ExtensionCapabilities::default()),
+            restriction: None,
+        };
+        assert!(policy.writable_paths().is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+use crate::wasm_host::{wit::FormatterConfig, WasmExtension};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::FutureExt;
+use language::{BufferFormatter, LspAdapterDelegate};
+use std::{ops::Range, sync::Arc};
+use wasmtime_wasi::WasiView as _;
+
+pub struct ExtensionFormatter {
+    pub(crate) extension: WasmExtension,
+    pub(crate) config: FormatterConfig,
+}
+
+#[async_trait(?Send)]
+impl BufferFormatter for ExtensionFormatter {
+    fn name(&self) -> Arc<str> {
+        self.config.name.clone().into()
+    }
+
+    async fn format(
+        &self,
+        text: String,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<Vec<(Range<usize>, String)>> {
+        let config = self.config.clone();
+        let edits = self
+            .extension
+            .call(|extension, store| {
+                async move {
+                    let resource = store.data_mut().table().push(delegate)?;
+                    let edits = extension
+                        .call_format_buffer(store, &config, &text, resource)
+                        .await?
+                        .map_err(|e| anyhow!("{}", e))?;
+                    anyhow::Ok(edits)
+                }
+                .boxed()
+            })
+            .await?;
+        Ok(edits
+            .into_iter()
+            .map(|edit| {
+                (
+                    edit.range.start as usize..edit.range.end as usize,
+                    edit.new_text,
+                )
+            })
+            .collect())
+    }
+}
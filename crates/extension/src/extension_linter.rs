@@ -0,0 +1,58 @@
+use crate::wasm_host::{
+    wit::{DiagnosticSeverity, LinterConfig},
+    WasmExtension,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::FutureExt;
+use language::{BufferLinter, LintDiagnostic, LspAdapterDelegate};
+use std::sync::Arc;
+use wasmtime_wasi::WasiView as _;
+
+pub struct ExtensionLinter {
+    pub(crate) extension: WasmExtension,
+    pub(crate) config: LinterConfig,
+}
+
+#[async_trait(?Send)]
+impl BufferLinter for ExtensionLinter {
+    fn name(&self) -> Arc<str> {
+        self.config.name.clone().into()
+    }
+
+    async fn lint(
+        &self,
+        text: String,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<Vec<LintDiagnostic>> {
+        let config = self.config.clone();
+        let diagnostics = self
+            .extension
+            .call(|extension, store| {
+                async move {
+                    let resource = store.data_mut().table().push(delegate)?;
+                    let diagnostics = extension
+                        .call_lint_buffer(store, &config, &text, resource)
+                        .await?
+                        .map_err(|e| anyhow!("{}", e))?;
+                    anyhow::Ok(diagnostics)
+                }
+                .boxed()
+            })
+            .await?;
+        Ok(diagnostics
+            .into_iter()
+            .map(|diagnostic| LintDiagnostic {
+                range: diagnostic.range.start as usize..diagnostic.range.end as usize,
+                severity: match diagnostic.severity {
+                    DiagnosticSeverity::Error => lsp::DiagnosticSeverity::ERROR,
+                    DiagnosticSeverity::Warning => lsp::DiagnosticSeverity::WARNING,
+                    DiagnosticSeverity::Information => lsp::DiagnosticSeverity::INFORMATION,
+                    DiagnosticSeverity::Hint => lsp::DiagnosticSeverity::HINT,
+                },
+                message: diagnostic.message,
+                code: diagnostic.code,
+            })
+            .collect())
+    }
+}
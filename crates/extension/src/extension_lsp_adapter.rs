@@ -6,6 +6,9 @@ use futures::{Future, FutureExt};
 use gpui::AsyncAppContext;
 use language::{Language, LanguageServerName, LspAdapter, LspAdapterDelegate};
 use lsp::LanguageServerBinary;
+use project::project_settings::ProjectSettings;
+use serde_json::Value;
+use settings::Settings;
 use std::{
     any::Any,
     path::{Path, PathBuf},
@@ -20,6 +23,59 @@ pub struct ExtensionLspAdapter {
     pub(crate) host: Arc<WasmHost>,
 }
 
+impl ExtensionLspAdapter {
+    pub(crate) async fn language_server_command(
+        self: Arc<Self>,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<LanguageServerBinary> {
+        let command = self
+            .extension
+            .call({
+                let this = self.clone();
+                |extension, store| {
+                    async move {
+                        let resource = store.data_mut().table().push(delegate)?;
+                        let command = extension
+                            .call_language_server_command(store, &this.config, resource)
+                            .await?
+                            .map_err(|e| anyhow!("{}", e))?;
+                        anyhow::Ok(command)
+                    }
+                    .boxed()
+                }
+            })
+            .await?;
+
+        let path = self
+            .host
+            .path_from_extension(&self.extension.manifest.id, command.command.as_ref());
+
+        // TODO: This should now be done via the `zed::make_file_executable` function in
+        // Zed extension API, but we're leaving these existing usages in place temporarily
+        // to avoid any compatibility issues between Zed and the extension versions.
+        //
+        // We can remove once the following extension versions no longer see any use:
+        // - toml@0.0.2
+        // - zig@0.0.1
+        if ["toml", "zig"].contains(&self.extension.manifest.id.as_ref()) {
+            #[cfg(not(windows))]
+            {
+                use std::fs::{self, Permissions};
+                use std::os::unix::fs::PermissionsExt;
+
+                fs::set_permissions(&path, Permissions::from_mode(0o755))
+                    .context("failed to set file permissions")?;
+            }
+        }
+
+        Ok(LanguageServerBinary {
+            path,
+            arguments: command.args.into_iter().map(|arg| arg.into()).collect(),
+            env: Some(command.env.into_iter().collect()),
+        })
+    }
+}
+
 #[async_trait(?Send)]
 impl LspAdapter for ExtensionLspAdapter {
     fn name(&self) -> LanguageServerName {
@@ -34,54 +90,7 @@ impl LspAdapter for ExtensionLspAdapter {
         _: futures::lock::MutexGuard<'a, Option<LanguageServerBinary>>,
         _: &'a mut AsyncAppContext,
     ) -> Pin<Box<dyn 'a + Future<Output = Result<LanguageServerBinary>>>> {
-        async move {
-            let command = self
-                .extension
-                .call({
-                    let this = self.clone();
-                    |extension, store| {
-                        async move {
-                            let resource = store.data_mut().table().push(delegate)?;
-                            let command = extension
-                                .call_language_server_command(store, &this.config, resource)
-                                .await?
-                                .map_err(|e| anyhow!("{}", e))?;
-                            anyhow::Ok(command)
-                        }
-                        .boxed()
-                    }
-                })
-                .await?;
-
-            let path = self
-                .host
-                .path_from_extension(&self.extension.manifest.id, command.command.as_ref());
-
-            // TODO: This should now be done via the `zed::make_file_executable` function in
-            // Zed extension API, but we're leaving these existing usages in place temporarily
-            // to avoid any compatibility issues between Zed and the extension versions.
-            //
-            // We can remove once the following extension versions no longer see any use:
-            // - toml@0.0.2
-            // - zig@0.0.1
-            if ["toml", "zig"].contains(&self.extension.manifest.id.as_ref()) {
-                #[cfg(not(windows))]
-                {
-                    use std::fs::{self, Permissions};
-                    use std::os::unix::fs::PermissionsExt;
-
-                    fs::set_permissions(&path, Permissions::from_mode(0o755))
-                        .context("failed to set file permissions")?;
-                }
-            }
-
-            Ok(LanguageServerBinary {
-                path,
-                arguments: command.args.into_iter().map(|arg| arg.into()).collect(),
-                env: Some(command.env.into_iter().collect()),
-            })
-        }
-        .boxed_local()
+        self.language_server_command(delegate).boxed_local()
     }
 
     async fn fetch_latest_server_version(
@@ -165,4 +174,49 @@ impl LspAdapter for ExtensionLspAdapter {
             None
         })
     }
+    async fn workspace_configuration(
+        self: Arc<Self>,
+        delegate: &Arc<dyn LspAdapterDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Value> {
+        let delegate = delegate.clone();
+        let settings = cx.update(|cx| {
+            ProjectSettings::get_global(cx)
+                .lsp
+                .get(self.config.name.as_str())
+                .and_then(|lsp_settings| lsp_settings.settings.clone())
+        })?;
+        let settings = settings
+            .map(|settings| serde_json::to_string(&settings))
+            .transpose()?;
+        let json_options = self
+            .extension
+            .call({
+                let this = self.clone();
+                |extension, store| {
+                    async move {
+                        let resource = store.data_mut().table().push(delegate)?;
+                        let options = extension
+                            .call_language_server_workspace_configuration(
+                                store,
+                                &this.config,
+                                settings,
+                                resource,
+                            )
+                            .await?
+                            .map_err(|e| anyhow!("{}", e))?;
+                        anyhow::Ok(options)
+                    }
+                    .boxed()
+                }
+            })
+            .await?;
+        Ok(if let Some(json_options) = json_options {
+            serde_json::from_str(&json_options).with_context(|| {
+                format!("failed to parse workspace_configuration from extension: {json_options}")
+            })?
+        } else {
+            serde_json::json!({})
+        })
+    }
 }
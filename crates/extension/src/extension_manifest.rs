@@ -1,3 +1,4 @@
+use crate::ExtensionCapabilities;
 use anyhow::{anyhow, Context, Result};
 use collections::{BTreeMap, HashMap};
 use fs::Fs;
@@ -74,6 +75,18 @@ pub struct ExtensionManifest {
     pub grammars: BTreeMap<Arc<str>, GrammarManifestEntry>,
     #[serde(default)]
     pub language_servers: BTreeMap<LanguageServerName, LanguageServerManifestEntry>,
+    #[serde(default)]
+    pub formatters: BTreeMap<Arc<str>, FormatterManifestEntry>,
+    #[serde(default)]
+    pub linters: BTreeMap<Arc<str>, LinterManifestEntry>,
+    #[serde(default)]
+    pub snippets: Vec<SnippetsManifestEntry>,
+    #[serde(default)]
+    pub tasks: Vec<PathBuf>,
+    #[serde(default)]
+    pub keymaps: Vec<PathBuf>,
+    #[serde(default)]
+    pub capabilities: Option<ExtensionCapabilities>,
 }
 
 #[derive(Clone, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
@@ -103,7 +116,38 @@ pub struct LanguageServerManifestEntry {
     pub language_ids: HashMap<String, String>,
 }
 
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct FormatterManifestEntry {
+    pub languages: Vec<Arc<str>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct LinterManifestEntry {
+    pub languages: Vec<Arc<str>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub struct SnippetsManifestEntry {
+    pub path: PathBuf,
+    /// The languages these snippets apply to. Applies to every language when empty.
+    #[serde(default)]
+    pub languages: Vec<Arc<str>>,
+}
+
 impl ExtensionManifest {
+    /// Returns the capabilities this extension needs from the host.
+    ///
+    /// Extensions without a library never call into the host, and extensions
+    /// that don't declare their capabilities are unrestricted.
+    pub fn requested_capabilities(&self) -> ExtensionCapabilities {
+        if self.lib.kind.is_none() {
+            return ExtensionCapabilities::default();
+        }
+        self.capabilities
+            .clone()
+            .unwrap_or_else(ExtensionCapabilities::unrestricted)
+    }
+
     pub async fn load(fs: Arc<dyn Fs>, extension_dir: &Path) -> Result<Self> {
         let extension_name = extension_dir
             .file_name()
@@ -165,5 +209,11 @@ fn manifest_from_old_manifest(
             .map(|grammar_name| (grammar_name, Default::default()))
             .collect(),
         language_servers: Default::default(),
+        formatters: Default::default(),
+        linters: Default::default(),
+        snippets: Vec::new(),
+        tasks: Vec::new(),
+        keymaps: Vec::new(),
+        capabilities: None,
     }
 }
@@ -0,0 +1,189 @@
+use anyhow::{anyhow, bail, Context as _, Result};
+use chrono::{DateTime, Utc};
+use client::{ExtensionApiManifest, ExtensionMetadata};
+use collections::{btree_map, BTreeMap};
+use fs::Fs;
+use futures::StreamExt as _;
+use schemars::JsonSchema;
+use semantic_version::SemanticVersion;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use url::Url;
+use util::ResultExt;
+
+/// A registry to fetch extensions from, in place of zed.dev.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionRegistry {
+    /// The base URL of a server that implements the same extensions API as zed.dev.
+    Url(String),
+    /// A local directory of extensions packaged by `zed-extension`, laid out
+    /// as `<extension_id>/<version>/{manifest.json,archive.tar.gz}`.
+    Directory(PathBuf),
+}
+
+impl ExtensionRegistry {
+    /// Builds the URL of an API endpoint on a [`ExtensionRegistry::Url`] registry.
+    pub fn build_api_url(base_url: &str, path: &str, query: &[(&str, &str)]) -> Result<Url> {
+        Ok(Url::parse_with_params(
+            &format!("{}{}", base_url.trim_end_matches('/'), path),
+            query,
+        )?)
+    }
+
+    pub fn local_archive_path(root: &Path, extension_id: &str, version: &str) -> PathBuf {
+        root.join(extension_id).join(version).join("archive.tar.gz")
+    }
+}
+
+/// Lists every version of every extension in a local registry directory.
+pub(crate) async fn local_registry_extensions(
+    fs: Arc<dyn Fs>,
+    root: PathBuf,
+) -> Result<Vec<ExtensionMetadata>> {
+    let mut extensions = Vec::new();
+    let mut extension_dirs = fs
+        .read_dir(&root)
+        .await
+        .with_context(|| format!("failed to read extension registry {root:?}"))?;
+    while let Some(extension_dir) = extension_dirs.next().await {
+        let Ok(extension_dir) = extension_dir else {
+            continue;
+        };
+        let Some(extension_id) = extension_dir.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let Ok(mut version_dirs) = fs.read_dir(&extension_dir).await else {
+            continue;
+        };
+        while let Some(version_dir) = version_dirs.next().await {
+            let Ok(version_dir) = version_dir else {
+                continue;
+            };
+            let manifest_path = version_dir.join("manifest.json");
+            let Some(manifest_content) = fs.load(&manifest_path).await.log_err() else {
+                continue;
+            };
+            let Some(manifest) = serde_json::from_str::<ExtensionApiManifest>(&manifest_content)
+                .with_context(|| format!("invalid extension manifest {manifest_path:?}"))
+                .log_err()
+            else {
+                continue;
+            };
+            let published_at = fs
+                .metadata(&manifest_path)
+                .await
+                .ok()
+                .flatten()
+                .map_or_else(Utc::now, |metadata| DateTime::<Utc>::from(metadata.mtime));
+
+            extensions.push(ExtensionMetadata {
+                id: extension_id.into(),
+                manifest,
+                published_at,
+                download_count: 0,
+            });
+        }
+    }
+    Ok(extensions)
+}
+
+/// Keeps only the latest version of each extension.
+pub(crate) fn latest_versions(extensions: Vec<ExtensionMetadata>) -> Vec<ExtensionMetadata> {
+    let mut latest = BTreeMap::<Arc<str>, ExtensionMetadata>::default();
+    for extension in extensions {
+        match latest.entry(extension.id.clone()) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(extension);
+            }
+            btree_map::Entry::Occupied(mut entry) => {
+                if parse_version(&extension) > parse_version(entry.get()) {
+                    entry.insert(extension);
+                }
+            }
+        }
+    }
+    latest.into_values().collect()
+}
+
+pub(crate) fn parse_version(extension: &ExtensionMetadata) -> Option<SemanticVersion> {
+    SemanticVersion::from_str(&extension.manifest.version).ok()
+}
+
+/// Checks that a downloaded extension archive matches its published checksum.
+pub(crate) fn verify_archive_checksum(archive: &[u8], expected_sha256: &str) -> Result<()> {
+    let actual_sha256 = format!("{:x}", Sha256::digest(archive));
+    if !actual_sha256.eq_ignore_ascii_case(expected_sha256.trim()) {
+        bail!(
+            "extension archive checksum mismatch: expected {expected_sha256}, got {actual_sha256}"
+        );
+    }
+    Ok(())
+}
+
+/// Finds the published checksum of the given extension version.
+pub(crate) fn archive_checksum(
+    extensions: Vec<ExtensionMetadata>,
+    extension_id: &str,
+    version: &str,
+) -> Result<String> {
+    extensions
+        .into_iter()
+        .find(|extension| {
+            extension.id.as_ref() == extension_id && extension.manifest.version.as_ref() == version
+        })
+        .ok_or_else(|| anyhow!("extension {extension_id} {version} not found in registry"))?
+        .manifest
+        .archive_sha256
+        .ok_or_else(|| anyhow!("extension {extension_id} {version} has no archive checksum"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(id: &str, version: &str) -> ExtensionMetadata {
+        ExtensionMetadata {
+            id: id.into(),
+            manifest: ExtensionApiManifest {
+                name: id.into(),
+                version: version.into(),
+                description: None,
+                authors: Vec::new(),
+                repository: String::new(),
+                schema_version: Some(1),
+                wasm_api_version: None,
+                archive_sha256: Some(format!("{:x}", Sha256::digest(version.as_bytes()))),
+            },
+            published_at: Utc::now(),
+            download_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_latest_versions_and_checksums() {
+        let extensions = vec![
+            metadata("a", "0.2.0"),
+            metadata("a", "0.10.0"),
+            metadata("b", "1.0.0"),
+        ];
+        let latest = latest_versions(extensions)
+            .into_iter()
+            .map(|extension| (extension.id, extension.manifest.version))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            latest,
+            vec![("a".into(), "0.10.0".into()), ("b".into(), "1.0.0".into())]
+        );
+
+        let checksum = archive_checksum(vec![metadata("a", "0.2.0")], "a", "0.2.0").unwrap();
+        assert!(verify_archive_checksum(b"0.2.0", &checksum).is_ok());
+        assert!(verify_archive_checksum(b"tampered", &checksum).is_err());
+        assert!(archive_checksum(vec![metadata("a", "0.2.0")], "a", "0.3.0").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    extension_lsp_adapter::ExtensionLspAdapter,
+    wasm_host::{wit, WasmExtension, WasmHost},
+    ExtensionManifest,
+};
+use anyhow::{anyhow, Context as _, Result};
+use fs::Fs;
+use gpui::{BackgroundExecutor, Task};
+use language::{LanguageRegistry, LanguageServerName, LspAdapterDelegate};
+use lsp::LanguageServerBinary;
+use node_runtime::NodeRuntime;
+use std::{path::PathBuf, sync::Arc};
+use util::http::HttpClient;
+
+/// Runs a compiled extension outside of the [`ExtensionStore`](crate::ExtensionStore),
+/// so that tooling such as `zed-extension test` can exercise it directly.
+pub struct ExtensionRunner {
+    host: Arc<WasmHost>,
+    extension: WasmExtension,
+}
+
+impl ExtensionRunner {
+    /// Loads the `extension.wasm` in `extension_dir`, giving it a work directory
+    /// within `work_dir`.
+    pub async fn load(
+        extension_dir: PathBuf,
+        manifest: Arc<ExtensionManifest>,
+        work_dir: PathBuf,
+        fs: Arc<dyn Fs>,
+        http_client: Arc<dyn HttpClient>,
+        node_runtime: Arc<dyn NodeRuntime>,
+        executor: BackgroundExecutor,
+    ) -> Result<Self> {
+        let mut wasm_bytes = Vec::new();
+        fs.open_sync(&extension_dir.join("extension.wasm"))
+            .await
+            .context("failed to open extension.wasm")?
+            .read_to_end(&mut wasm_bytes)
+            .context("failed to read extension.wasm")?;
+
+        let language_registry = Arc::new(LanguageRegistry::new(Task::ready(()), executor.clone()));
+        let host = WasmHost::new(fs, http_client, node_runtime, language_registry, work_dir);
+        let extension = host
+            .load_extension(wasm_bytes, manifest, None, executor)
+            .await
+            .context("failed to load wasm extension")?;
+
+        Ok(Self { host, extension })
+    }
+
+    /// Asks the extension for the command that starts the given language server
+    /// in the given worktree.
+    pub async fn language_server_command(
+        &self,
+        language_server_name: &LanguageServerName,
+        worktree: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<LanguageServerBinary> {
+        let language_server = self
+            .extension
+            .manifest
+            .language_servers
+            .get(language_server_name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "extension does not provide a language server named '{}'",
+                    language_server_name.0
+                )
+            })?;
+
+        let adapter = Arc::new(ExtensionLspAdapter {
+            extension: self.extension.clone(),
+            host: self.host.clone(),
+            config: wit::LanguageServerConfig {
+                name: language_server_name.0.to_string(),
+                language_name: language_server.language.to_string(),
+            },
+        });
+        adapter.language_server_command(worktree).await
+    }
+}
@@ -1,3 +1,4 @@
+use crate::{ExtensionCapabilities, ExtensionRegistry};
 use anyhow::Result;
 use collections::HashMap;
 use gpui::AppContext;
@@ -10,6 +11,13 @@ use std::sync::Arc;
 pub struct ExtensionSettings {
     #[serde(default)]
     pub auto_update_extensions: HashMap<Arc<str>, bool>,
+    /// Restricts the capabilities available to every extension, regardless
+    /// of the capabilities that the extension declares.
+    #[serde(default)]
+    pub extension_capabilities: Option<ExtensionCapabilities>,
+    /// Fetches extensions from a private registry, instead of from zed.dev.
+    #[serde(default)]
+    pub extension_registry: Option<ExtensionRegistry>,
 }
 
 impl ExtensionSettings {
@@ -1,21 +1,36 @@
 pub mod extension_builder;
+mod extension_capabilities;
+mod extension_formatter;
+mod extension_linter;
 mod extension_lsp_adapter;
 mod extension_manifest;
+mod extension_registry;
+pub mod extension_runner;
 mod extension_settings;
+mod extension_task_source;
 mod wasm_host;
 
 #[cfg(test)]
 mod extension_store_test;
 
 use crate::extension_manifest::SchemaVersion;
-use crate::{extension_lsp_adapter::ExtensionLspAdapter, wasm_host::wit};
+use crate::{
+    extension_formatter::ExtensionFormatter,
+    extension_linter::ExtensionLinter,
+    extension_lsp_adapter::ExtensionLspAdapter,
+    extension_registry::{
+        archive_checksum, latest_versions, local_registry_extensions, parse_version,
+        verify_archive_checksum,
+    },
+    wasm_host::wit,
+};
 use anyhow::{anyhow, bail, Context as _, Result};
 use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use client::{telemetry::Telemetry, Client, ExtensionMetadata, GetExtensionsResponse};
 use collections::{btree_map, BTreeMap, HashSet};
 use extension_builder::{CompileExtensionOptions, ExtensionBuilder};
-use fs::{Fs, RemoveOptions};
+use fs::{Fs, RemoveOptions, RenameOptions};
 use futures::{
     channel::{
         mpsc::{unbounded, UnboundedSender},
@@ -30,12 +45,12 @@ use gpui::{
 };
 use language::{
     ContextProviderWithTasks, LanguageConfig, LanguageMatcher, LanguageQueries, LanguageRegistry,
-    QUERY_FILENAME_PREFIXES,
+    SnippetDefinition, QUERY_FILENAME_PREFIXES,
 };
 use node_runtime::NodeRuntime;
 use semantic_version::SemanticVersion;
 use serde::{Deserialize, Serialize};
-use settings::Settings;
+use settings::{ExtensionKeymaps, KeymapFile, Settings, SettingsStore};
 use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::{
@@ -44,6 +59,7 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
+use task::static_source::{Definition, TaskDefinitions};
 use theme::{ThemeRegistry, ThemeSettings};
 use url::Url;
 use util::{
@@ -57,10 +73,13 @@ use wasm_host::{
     WasmExtension, WasmHost,
 };
 
+pub use extension_capabilities::{CapabilityPolicy, ExtensionCapabilities};
 pub use extension_manifest::{
     ExtensionLibraryKind, ExtensionManifest, GrammarManifestEntry, OldExtensionManifest,
 };
+pub use extension_registry::ExtensionRegistry;
 pub use extension_settings::ExtensionSettings;
+pub use extension_task_source::ExtensionTaskSource;
 
 const RELOAD_DEBOUNCE_DURATION: Duration = Duration::from_millis(200);
 const FS_WATCH_LATENCY: Duration = Duration::from_millis(100);
@@ -103,6 +122,7 @@ pub struct ExtensionStore {
     reload_tx: UnboundedSender<Option<Arc<str>>>,
     reload_complete_senders: Vec<oneshot::Sender<()>>,
     installed_dir: PathBuf,
+    staging_dir: PathBuf,
     outstanding_operations: BTreeMap<Arc<str>, ExtensionOperation>,
     index_path: PathBuf,
     language_registry: Arc<LanguageRegistry>,
@@ -110,9 +130,15 @@ pub struct ExtensionStore {
     modified_extensions: HashSet<Arc<str>>,
     wasm_host: Arc<WasmHost>,
     wasm_extensions: Vec<(Arc<ExtensionManifest>, WasmExtension)>,
+    extension_tasks: BTreeMap<Arc<str>, TaskDefinitions>,
+    capability_consent_prompt: Option<CapabilityConsentPrompt>,
     tasks: Vec<Task<()>>,
 }
 
+/// Asks the user whether an extension that is being installed or upgraded may
+/// use the capabilities requested in its manifest.
+type CapabilityConsentPrompt = Box<dyn Fn(&ExtensionManifest, &mut AppContext) -> Task<bool>>;
+
 #[derive(Clone, Copy)]
 pub enum ExtensionOperation {
     Upgrade,
@@ -120,6 +146,12 @@ pub enum ExtensionOperation {
     Remove,
 }
 
+/// Where the archive of an extension being installed is read from.
+enum ExtensionArchiveSource {
+    Url(Url),
+    Path(PathBuf),
+}
+
 #[derive(Clone)]
 pub enum Event {
     ExtensionsUpdated,
@@ -220,12 +252,14 @@ impl ExtensionStore {
         let work_dir = extensions_dir.join("work");
         let build_dir = build_dir.unwrap_or_else(|| extensions_dir.join("build"));
         let installed_dir = extensions_dir.join("installed");
+        let staging_dir = extensions_dir.join("staging");
         let index_path = extensions_dir.join("index.json");
 
         let (reload_tx, mut reload_rx) = unbounded();
         let mut this = Self {
             extension_index: Default::default(),
             installed_dir,
+            staging_dir,
             index_path,
             builder: Arc::new(ExtensionBuilder::new(build_dir)),
             outstanding_operations: Default::default(),
@@ -239,6 +273,8 @@ impl ExtensionStore {
                 work_dir,
             ),
             wasm_extensions: Vec::new(),
+            extension_tasks: BTreeMap::default(),
+            capability_consent_prompt: None,
             fs,
             http_client,
             telemetry,
@@ -333,6 +369,26 @@ impl ExtensionStore {
             .map(drop)
         }));
 
+        // Reload every extension when the capabilities setting changes, so that
+        // the new restriction is applied to them.
+        let mut capability_restriction = ExtensionSettings::get_global(cx)
+            .extension_capabilities
+            .clone();
+        cx.observe_global::<SettingsStore>(move |this, cx| {
+            let new_restriction = ExtensionSettings::get_global(cx)
+                .extension_capabilities
+                .clone();
+            if new_restriction != capability_restriction {
+                capability_restriction = new_restriction;
+                for extension_id in this.extension_index.extensions.keys() {
+                    this.reload_tx
+                        .unbounded_send(Some(extension_id.clone()))
+                        .ok();
+                }
+            }
+        })
+        .detach();
+
         // Watch the installed extensions directory for changes. Whenever changes are
         // detected, rebuild the extension index, and load/unload any extensions that
         // have been added, removed, or modified.
@@ -363,6 +419,27 @@ impl ExtensionStore {
         this
     }
 
+    /// Sets the prompt used to ask for consent when an extension that is being
+    /// installed or upgraded requests capabilities beyond those it was previously
+    /// granted. Without a prompt, the requested capabilities are granted.
+    pub fn set_capability_consent_prompt(
+        &mut self,
+        prompt: impl Fn(&ExtensionManifest, &mut AppContext) -> Task<bool> + 'static,
+    ) {
+        self.capability_consent_prompt = Some(Box::new(prompt));
+    }
+
+    fn request_capability_consent(
+        &self,
+        manifest: &ExtensionManifest,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<bool> {
+        match &self.capability_consent_prompt {
+            Some(prompt) => prompt(manifest, cx),
+            None => Task::ready(true),
+        }
+    }
+
     fn reload(
         &mut self,
         modified_extension: Option<Arc<str>>,
@@ -415,6 +492,34 @@ impl ExtensionStore {
         search: Option<&str>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Vec<ExtensionMetadata>>> {
+        if let Some(ExtensionRegistry::Directory(root)) = self.extension_registry(cx) {
+            let extensions = local_registry_extensions(self.fs.clone(), root);
+            let search = search.map(|search| search.to_lowercase());
+            return cx.background_executor().spawn(async move {
+                let mut extensions = latest_versions(
+                    extensions
+                        .await?
+                        .into_iter()
+                        .filter(is_version_compatible)
+                        .collect(),
+                );
+                if let Some(search) = search {
+                    extensions.retain(|extension| {
+                        extension.id.to_lowercase().contains(&search)
+                            || extension.manifest.name.to_lowercase().contains(&search)
+                            || extension
+                                .manifest
+                                .description
+                                .as_ref()
+                                .map_or(false, |description| {
+                                    description.to_lowercase().contains(&search)
+                                })
+                    });
+                }
+                Ok(extensions)
+            });
+        }
+
         let version = CURRENT_SCHEMA_VERSION.to_string();
         let mut query = vec![("max_schema_version", version.as_str())];
         if let Some(search) = search {
@@ -435,24 +540,44 @@ impl ExtensionStore {
             .extension_index
             .extensions
             .keys()
-            .map(|id| id.as_ref())
             .filter(|id| extension_settings.should_auto_update(id))
-            .collect::<Vec<_>>()
-            .join(",");
-        let task = self.fetch_extensions_from_api(
-            "/extensions/updates",
-            &[
-                ("min_schema_version", &schema_versions.start().to_string()),
-                ("max_schema_version", &schema_versions.end().to_string()),
-                (
-                    "min_wasm_api_version",
-                    &wasm_api_versions.start().to_string(),
-                ),
-                ("max_wasm_api_version", &wasm_api_versions.end().to_string()),
-                ("ids", &extension_ids),
-            ],
-            cx,
-        );
+            .cloned()
+            .collect::<HashSet<_>>();
+        let task = if let Some(ExtensionRegistry::Directory(root)) = self.extension_registry(cx) {
+            let extensions = local_registry_extensions(self.fs.clone(), root);
+            cx.background_executor().spawn(async move {
+                Ok(latest_versions(
+                    extensions
+                        .await?
+                        .into_iter()
+                        .filter(|extension| {
+                            extension_ids.contains(&extension.id)
+                                && is_version_compatible(extension)
+                        })
+                        .collect(),
+                ))
+            })
+        } else {
+            let extension_ids = extension_ids
+                .iter()
+                .map(|id| id.as_ref())
+                .collect::<Vec<_>>()
+                .join(",");
+            self.fetch_extensions_from_api(
+                "/extensions/updates",
+                &[
+                    ("min_schema_version", &schema_versions.start().to_string()),
+                    ("max_schema_version", &schema_versions.end().to_string()),
+                    (
+                        "min_wasm_api_version",
+                        &wasm_api_versions.start().to_string(),
+                    ),
+                    ("max_wasm_api_version", &wasm_api_versions.end().to_string()),
+                    ("ids", &extension_ids),
+                ],
+                cx,
+            )
+        };
         cx.spawn(move |this, mut cx| async move {
             let extensions = task.await?;
             this.update(&mut cx, |this, _cx| {
@@ -476,9 +601,27 @@ impl ExtensionStore {
         extension_id: &str,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Vec<ExtensionMetadata>>> {
+        if let Some(ExtensionRegistry::Directory(root)) = self.extension_registry(cx) {
+            let extensions = local_registry_extensions(self.fs.clone(), root);
+            let extension_id = extension_id.to_string();
+            return cx.background_executor().spawn(async move {
+                let mut versions = extensions
+                    .await?
+                    .into_iter()
+                    .filter(|extension| extension.id.as_ref() == extension_id)
+                    .collect::<Vec<_>>();
+                versions.sort_by(|a, b| parse_version(b).cmp(&parse_version(a)));
+                Ok(versions)
+            });
+        }
+
         self.fetch_extensions_from_api(&format!("/extensions/{extension_id}"), &[], cx)
     }
 
+    fn extension_registry(&self, cx: &AppContext) -> Option<ExtensionRegistry> {
+        ExtensionSettings::get_global(cx).extension_registry.clone()
+    }
+
     pub fn check_for_updates(&mut self, cx: &mut ModelContext<Self>) {
         let task = self.fetch_extensions_with_update_available(cx);
         cx.spawn(move |this, mut cx| async move {
@@ -523,7 +666,12 @@ impl ExtensionStore {
         query: &[(&str, &str)],
         cx: &mut ModelContext<'_, ExtensionStore>,
     ) -> Task<Result<Vec<ExtensionMetadata>>> {
-        let url = self.http_client.build_zed_api_url(path, &query);
+        let url = match self.extension_registry(cx) {
+            Some(ExtensionRegistry::Url(base_url)) => {
+                ExtensionRegistry::build_api_url(&base_url, path, query)
+            }
+            _ => self.http_client.build_zed_api_url(path, &query),
+        };
         let http_client = self.http_client.clone();
         cx.spawn(move |_, _| async move {
             let mut response = http_client
@@ -563,11 +711,13 @@ impl ExtensionStore {
     fn install_or_upgrade_extension_at_endpoint(
         &mut self,
         extension_id: Arc<str>,
-        url: Url,
+        source: ExtensionArchiveSource,
+        expected_sha256: Option<String>,
         operation: ExtensionOperation,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<()>> {
         let extension_dir = self.installed_dir.join(extension_id.as_ref());
+        let staged_extension_dir = self.staging_dir.join(extension_id.as_ref());
         let http_client = self.http_client.clone();
         let fs = self.fs.clone();
 
@@ -591,23 +741,79 @@ impl ExtensionStore {
                 }
             });
 
-            let mut response = http_client
-                .get(&url.as_ref(), Default::default(), true)
-                .await
-                .map_err(|err| anyhow!("error downloading extension: {}", err))?;
+            let mut archive_bytes = Vec::new();
+            match source {
+                ExtensionArchiveSource::Url(url) => {
+                    let mut response = http_client
+                        .get(&url.as_ref(), Default::default(), true)
+                        .await
+                        .map_err(|err| anyhow!("error downloading extension: {}", err))?;
+                    response
+                        .body_mut()
+                        .read_to_end(&mut archive_bytes)
+                        .await
+                        .context("error downloading extension")?;
+                }
+                ExtensionArchiveSource::Path(path) => {
+                    fs.open_sync(&path)
+                        .await
+                        .with_context(|| format!("failed to open extension archive {path:?}"))?
+                        .read_to_end(&mut archive_bytes)
+                        .with_context(|| format!("failed to read extension archive {path:?}"))?;
+                }
+            }
+            if let Some(expected_sha256) = &expected_sha256 {
+                verify_archive_checksum(&archive_bytes, expected_sha256)
+                    .with_context(|| format!("failed to verify extension {extension_id}"))?;
+            }
 
-            fs.remove_dir(
+            let remove_options = RemoveOptions {
+                recursive: true,
+                ignore_if_not_exists: true,
+            };
+            fs.remove_dir(&staged_extension_dir, remove_options).await?;
+
+            // Unpack the extension into a staging directory first, so that it isn't
+            // loaded until the capabilities it requests have been granted.
+            let decompressed_bytes = GzipDecoder::new(BufReader::new(archive_bytes.as_slice()));
+            let archive = Archive::new(decompressed_bytes);
+            archive.unpack(&staged_extension_dir).await?;
+
+            let manifest = ExtensionManifest::load(fs.clone(), &staged_extension_dir).await?;
+            let granted_capabilities = this.update(&mut cx, |this, _| {
+                this.extension_index
+                    .extensions
+                    .get(&extension_id)
+                    .map(|extension| extension.manifest.requested_capabilities())
+                    .unwrap_or_default()
+            })?;
+            if !manifest
+                .requested_capabilities()
+                .is_subset_of(&granted_capabilities)
+            {
+                let consented = this
+                    .update(&mut cx, |this, cx| {
+                        this.request_capability_consent(&manifest, cx)
+                    })?
+                    .await;
+                if !consented {
+                    fs.remove_dir(&staged_extension_dir, remove_options)
+                        .await
+                        .log_err();
+                    bail!("extension {extension_id} was not granted the capabilities it requested");
+                }
+            }
+
+            fs.remove_dir(&extension_dir, remove_options).await?;
+            fs.rename(
+                &staged_extension_dir,
                 &extension_dir,
-                RemoveOptions {
-                    recursive: true,
-                    ignore_if_not_exists: true,
+                RenameOptions {
+                    overwrite: false,
+                    ignore_if_exists: false,
                 },
             )
             .await?;
-
-            let decompressed_bytes = GzipDecoder::new(BufReader::new(response.body_mut()));
-            let archive = Archive::new(decompressed_bytes);
-            archive.unpack(extension_dir).await?;
             this.update(&mut cx, |this, cx| {
                 this.reload(Some(extension_id.clone()), cx)
             })?
@@ -634,6 +840,29 @@ impl ExtensionStore {
     ) {
         log::info!("installing extension {extension_id} latest version");
 
+        if self.extension_registry(cx).is_some() {
+            let versions = self.fetch_extension_versions(&extension_id, cx);
+            cx.spawn(move |this, mut cx| async move {
+                let latest_version = versions
+                    .await?
+                    .into_iter()
+                    .filter(is_version_compatible)
+                    .max_by_key(parse_version)
+                    .ok_or_else(|| anyhow!("no compatible version of {extension_id} found"))?;
+                this.update(&mut cx, |this, cx| {
+                    this.install_or_upgrade_extension(
+                        extension_id,
+                        latest_version.manifest.version,
+                        ExtensionOperation::Install,
+                        cx,
+                    )
+                })?
+                .await
+            })
+            .detach_and_log_err(cx);
+            return;
+        }
+
         let schema_versions = schema_version_range();
         let wasm_api_versions = wasm_api_version_range();
 
@@ -658,7 +887,8 @@ impl ExtensionStore {
 
         self.install_or_upgrade_extension_at_endpoint(
             extension_id,
-            url,
+            ExtensionArchiveSource::Url(url),
+            None,
             ExtensionOperation::Install,
             cx,
         )
@@ -682,6 +912,39 @@ impl ExtensionStore {
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<()>> {
         log::info!("installing extension {extension_id} {version}");
+
+        // Extensions from a private registry must match the checksum it publishes
+        // for their archive.
+        if let Some(registry) = self.extension_registry(cx) {
+            let source = match &registry {
+                ExtensionRegistry::Url(base_url) => match ExtensionRegistry::build_api_url(
+                    base_url,
+                    &format!("/extensions/{extension_id}/{version}/download"),
+                    &[],
+                ) {
+                    Ok(url) => ExtensionArchiveSource::Url(url),
+                    Err(error) => return Task::ready(Err(error)),
+                },
+                ExtensionRegistry::Directory(root) => ExtensionArchiveSource::Path(
+                    ExtensionRegistry::local_archive_path(root, &extension_id, &version),
+                ),
+            };
+            let versions = self.fetch_extension_versions(&extension_id, cx);
+            return cx.spawn(move |this, mut cx| async move {
+                let checksum = archive_checksum(versions.await?, &extension_id, &version)?;
+                this.update(&mut cx, |this, cx| {
+                    this.install_or_upgrade_extension_at_endpoint(
+                        extension_id,
+                        source,
+                        Some(checksum),
+                        operation,
+                        cx,
+                    )
+                })?
+                .await
+            });
+        }
+
         let Some(url) = self
             .http_client
             .build_zed_api_url(
@@ -693,7 +956,13 @@ impl ExtensionStore {
             return Task::ready(Ok(()));
         };
 
-        self.install_or_upgrade_extension_at_endpoint(extension_id, url, operation, cx)
+        self.install_or_upgrade_extension_at_endpoint(
+            extension_id,
+            ExtensionArchiveSource::Url(url),
+            None,
+            operation,
+            cx,
+        )
     }
 
     pub fn uninstall_extension(&mut self, extension_id: Arc<str>, cx: &mut ModelContext<Self>) {
@@ -964,6 +1233,21 @@ impl ExtensionStore {
                 self.language_registry
                     .remove_lsp_adapter(config.language.as_ref(), language_server_name);
             }
+            for (formatter_name, config) in extension.manifest.formatters.iter() {
+                for language in &config.languages {
+                    self.language_registry
+                        .remove_formatter(language.as_ref(), formatter_name);
+                }
+            }
+            for (linter_name, config) in extension.manifest.linters.iter() {
+                for language in &config.languages {
+                    self.language_registry
+                        .remove_linter(language.as_ref(), linter_name);
+                }
+            }
+            self.language_registry.remove_snippets(extension_id);
+            self.extension_tasks.remove(extension_id);
+            ExtensionKeymaps::remove(extension_id, cx);
         }
 
         self.wasm_extensions
@@ -977,6 +1261,13 @@ impl ExtensionStore {
             .iter()
             .filter(|(_, entry)| extensions_to_load.contains(&entry.extension))
             .collect::<Vec<_>>();
+        let mut extension_languages = BTreeMap::<Arc<str>, Vec<Arc<str>>>::default();
+        for (language_name, language) in &languages_to_add {
+            extension_languages
+                .entry(language.extension.clone())
+                .or_default()
+                .push((*language_name).clone());
+        }
         let mut grammars_to_add = Vec::new();
         let mut themes_to_add = Vec::new();
         for extension_id in &extensions_to_load {
@@ -1031,6 +1322,9 @@ impl ExtensionStore {
         let wasm_host = self.wasm_host.clone();
         let root_dir = self.installed_dir.clone();
         let theme_registry = self.theme_registry.clone();
+        let capability_restriction = ExtensionSettings::get_global(cx)
+            .extension_capabilities
+            .clone();
         let extension_entries = extensions_to_load
             .iter()
             .filter_map(|name| new_index.extensions.get(name).cloned())
@@ -1041,9 +1335,15 @@ impl ExtensionStore {
         cx.emit(Event::ExtensionsUpdated);
 
         cx.spawn(|this, mut cx| async move {
-            cx.background_executor()
+            let extension_resources = cx
+                .background_executor()
                 .spawn({
                     let fs = fs.clone();
+                    let root_dir = root_dir.clone();
+                    let manifests = extension_entries
+                        .iter()
+                        .map(|extension| extension.manifest.clone())
+                        .collect::<Vec<_>>();
                     async move {
                         for theme_path in &themes_to_add {
                             theme_registry
@@ -1051,6 +1351,16 @@ impl ExtensionStore {
                                 .await
                                 .log_err();
                         }
+
+                        let mut extension_resources = Vec::new();
+                        for manifest in manifests {
+                            let extension_dir = root_dir.join(manifest.id.as_ref());
+                            let resources =
+                                ExtensionResources::load(&manifest, &extension_dir, fs.as_ref())
+                                    .await;
+                            extension_resources.push((manifest.id.clone(), resources));
+                        }
+                        extension_resources
                     }
                 })
                 .await;
@@ -1078,6 +1388,7 @@ impl ExtensionStore {
                         .load_extension(
                             wasm_bytes,
                             extension.manifest.clone().clone(),
+                            capability_restriction.clone(),
                             cx.background_executor().clone(),
                         )
                         .await
@@ -1097,6 +1408,31 @@ impl ExtensionStore {
                 }
             }
 
+            // Besides their `tasks.json` files, languages can get tasks from the code of
+            // their extension, e.g. to run the runnables found by their `runnables.scm` query.
+            let mut language_tasks = Vec::new();
+            for (manifest, wasm_extension) in &wasm_extensions {
+                for language_name in extension_languages.get(&manifest.id).into_iter().flatten() {
+                    let name = language_name.to_string();
+                    let tasks = wasm_extension
+                        .call(|extension, store| {
+                            async move {
+                                extension
+                                    .call_language_tasks(store, &name)
+                                    .await?
+                                    .map_err(|e| anyhow!("{}", e))
+                            }
+                            .boxed()
+                        })
+                        .await
+                        .with_context(|| format!("failed to get the tasks of {language_name}"))
+                        .log_err();
+                    if let Some(tasks) = tasks {
+                        language_tasks.push((manifest.id.clone(), tasks));
+                    }
+                }
+            }
+
             this.update(&mut cx, |this, cx| {
                 this.reload_complete_senders.clear();
 
@@ -1115,8 +1451,64 @@ impl ExtensionStore {
                             }),
                         );
                     }
+                    for (formatter_name, formatter_config) in &manifest.formatters {
+                        for language in &formatter_config.languages {
+                            this.language_registry.register_formatter(
+                                language.clone(),
+                                Arc::new(ExtensionFormatter {
+                                    extension: wasm_extension.clone(),
+                                    config: wit::FormatterConfig {
+                                        name: formatter_name.to_string(),
+                                        language_name: language.to_string(),
+                                    },
+                                }),
+                            );
+                        }
+                    }
+                    for (linter_name, linter_config) in &manifest.linters {
+                        for language in &linter_config.languages {
+                            this.language_registry.register_linter(
+                                language.clone(),
+                                Arc::new(ExtensionLinter {
+                                    extension: wasm_extension.clone(),
+                                    config: wit::LinterConfig {
+                                        name: linter_name.to_string(),
+                                        language_name: language.to_string(),
+                                    },
+                                }),
+                            );
+                        }
+                    }
                 }
                 this.wasm_extensions.extend(wasm_extensions);
+
+                for (extension_id, resources) in extension_resources {
+                    for (languages, snippets) in resources.snippets {
+                        this.language_registry.register_snippets(
+                            extension_id.clone(),
+                            languages,
+                            snippets,
+                        );
+                    }
+                    if !resources.tasks.0.is_empty() {
+                        this.extension_tasks
+                            .insert(extension_id.clone(), resources.tasks);
+                    }
+                    if !resources.keymaps.is_empty() {
+                        ExtensionKeymaps::insert(extension_id, resources.keymaps, cx);
+                    }
+                }
+                for (extension_id, tasks) in language_tasks {
+                    if !tasks.is_empty() {
+                        this.extension_tasks
+                            .entry(extension_id)
+                            .or_default()
+                            .0
+                            .extend(tasks.into_iter().map(task_definition));
+                    }
+                }
+                cx.notify();
+
                 ThemeSettings::reload_current_theme(cx)
             })
             .ok();
@@ -1266,6 +1658,88 @@ impl ExtensionStore {
     }
 }
 
+/// The snippets, task templates and keymaps declared in an extension's manifest.
+#[derive(Default)]
+struct ExtensionResources {
+    snippets: Vec<(Vec<Arc<str>>, Vec<SnippetDefinition>)>,
+    tasks: TaskDefinitions,
+    keymaps: Vec<KeymapFile>,
+}
+
+impl ExtensionResources {
+    /// Loads the resources from the extension's directory, logging and
+    /// skipping any files that fail to load.
+    async fn load(manifest: &ExtensionManifest, extension_dir: &Path, fs: &dyn Fs) -> Self {
+        let mut resources = Self::default();
+
+        for entry in &manifest.snippets {
+            let snippets = maybe!(async {
+                let content = fs.load(&resource_path(extension_dir, &entry.path)?).await?;
+                SnippetDefinition::parse_file(&content)
+            })
+            .await
+            .with_context(|| format!("failed to load snippets from {:?}", entry.path))
+            .log_err();
+            if let Some(snippets) = snippets {
+                resources.snippets.push((entry.languages.clone(), snippets));
+            }
+        }
+
+        for tasks_path in &manifest.tasks {
+            let tasks = maybe!(async {
+                let content = fs.load(&resource_path(extension_dir, tasks_path)?).await?;
+                Ok(serde_json_lenient::from_str::<TaskDefinitions>(&content)?)
+            })
+            .await
+            .with_context(|| format!("failed to load tasks from {tasks_path:?}"))
+            .log_err();
+            if let Some(tasks) = tasks {
+                resources.tasks.0.extend(tasks.0);
+            }
+        }
+
+        for keymap_path in &manifest.keymaps {
+            let keymap = maybe!(async {
+                let content = fs.load(&resource_path(extension_dir, keymap_path)?).await?;
+                KeymapFile::parse(&content)
+            })
+            .await
+            .with_context(|| format!("failed to load keymap from {keymap_path:?}"))
+            .log_err();
+            resources.keymaps.extend(keymap);
+        }
+
+        resources
+    }
+}
+
+/// Resolves a path from the extension's manifest in its directory,
+/// rejecting the ones that lead out of it.
+fn resource_path(extension_dir: &Path, path: &Path) -> Result<PathBuf> {
+    let leaves_extension_dir = path.components().any(|component| {
+        matches!(
+            component,
+            path::Component::ParentDir | path::Component::RootDir | path::Component::Prefix(_)
+        )
+    });
+    if leaves_extension_dir {
+        bail!("path {path:?} is outside of the extension's directory");
+    }
+    Ok(extension_dir.join(path))
+}
+
+fn task_definition(template: wit::TaskTemplate) -> Definition {
+    Definition {
+        label: template.label,
+        command: template.command,
+        args: template.args,
+        env: template.env.into_iter().collect(),
+        cwd: template.cwd,
+        tags: template.tags,
+        ..Definition::default()
+    }
+}
+
 fn load_plugin_queries(root_path: &Path) -> LanguageQueries {
     let mut result = LanguageQueries::default();
     if let Some(entries) = std::fs::read_dir(root_path).log_err() {
@@ -1,7 +1,7 @@
 use crate::extension_manifest::SchemaVersion;
 use crate::extension_settings::ExtensionSettings;
 use crate::{
-    Event, ExtensionIndex, ExtensionIndexEntry, ExtensionIndexLanguageEntry,
+    resource_path, Event, ExtensionIndex, ExtensionIndexEntry, ExtensionIndexLanguageEntry,
     ExtensionIndexThemeEntry, ExtensionManifest, ExtensionStore, GrammarManifestEntry,
     RELOAD_DEBOUNCE_DURATION,
 };
@@ -13,7 +13,10 @@ use gpui::{Context, TestAppContext};
 use language::{LanguageMatcher, LanguageRegistry, LanguageServerBinaryStatus, LanguageServerName};
 use node_runtime::FakeNodeRuntime;
 use parking_lot::Mutex;
-use project::Project;
+use project::{
+    project_settings::{LspSettings, ProjectSettings},
+    Project,
+};
 use serde_json::json;
 use settings::{Settings as _, SettingsStore};
 use std::{
@@ -158,6 +161,12 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         .into_iter()
                         .collect(),
                         language_servers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
+                        linters: BTreeMap::default(),
+                        snippets: Vec::new(),
+                        tasks: Vec::new(),
+                        keymaps: Vec::new(),
+                        capabilities: None,
                     }),
                     dev: false,
                 },
@@ -181,6 +190,12 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         languages: Default::default(),
                         grammars: BTreeMap::default(),
                         language_servers: BTreeMap::default(),
+                        formatters: BTreeMap::default(),
+                        linters: BTreeMap::default(),
+                        snippets: Vec::new(),
+                        tasks: Vec::new(),
+                        keymaps: Vec::new(),
+                        capabilities: None,
                     }),
                     dev: false,
                 },
@@ -335,6 +350,12 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 languages: Default::default(),
                 grammars: BTreeMap::default(),
                 language_servers: BTreeMap::default(),
+                formatters: BTreeMap::default(),
+                linters: BTreeMap::default(),
+                snippets: Vec::new(),
+                tasks: Vec::new(),
+                keymaps: Vec::new(),
+                capabilities: None,
             }),
             dev: false,
         },
@@ -665,6 +686,184 @@ async fn test_extension_store_with_gleam_extension(cx: &mut TestAppContext) {
     assert!(fs.metadata(&expected_server_path).await.unwrap().is_none());
 }
 
+#[gpui::test]
+async fn test_extension_language_server_workspace_configuration(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let root_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap();
+    let cache_dir = root_dir.join("target");
+    let extension_api_dir = root_dir.join("crates").join("extension_api");
+
+    // An extension wrapping the user's settings of its language server into its own configuration.
+    let extension_dir = temp_tree(json!({
+        "extension.toml": r#"
+            id = "test-configuration"
+            name = "Test Configuration"
+            version = "0.0.1"
+            schema_version = 1
+
+            [language_servers.test-server]
+            name = "Test Server"
+            language = "Test"
+        "#,
+        "Cargo.toml": format!(
+            r#"
+            [package]
+            name = "test_configuration"
+            version = "0.0.1"
+            edition = "2021"
+
+            [lib]
+            path = "src/lib.rs"
+            crate-type = ["cdylib"]
+
+            [dependencies]
+            zed_extension_api = {{ path = {:?} }}
+            "#,
+            extension_api_dir
+        ),
+        "src": {
+            "lib.rs": r##"
+                use zed_extension_api::{self as zed, Result};
+
+                struct TestExtension;
+
+                impl zed::Extension for TestExtension {
+                    fn new() -> Self {
+                        Self
+                    }
+
+                    fn language_server_command(
+                        &mut self,
+                        _: zed::LanguageServerConfig,
+                        _: &zed::Worktree,
+                    ) -> Result<zed::Command> {
+                        Ok(zed::Command {
+                            command: "test-server".into(),
+                            args: Vec::new(),
+                            env: Vec::new(),
+                        })
+                    }
+
+                    fn language_server_workspace_configuration(
+                        &mut self,
+                        _: zed::LanguageServerConfig,
+                        settings: Option<String>,
+                        _: &zed::Worktree,
+                    ) -> Result<Option<String>> {
+                        let settings = settings.unwrap_or_else(|| "null".into());
+                        Ok(Some(format!(r#"{{"test": {{"user": {settings}}}}}"#)))
+                    }
+                }
+
+                zed::register_extension!(TestExtension);
+            "##,
+        },
+        "languages": {
+            "test": {
+                "config.toml": r#"
+                    name = "Test"
+                    path_suffixes = ["test"]
+                "#,
+            },
+        },
+    }));
+    let extensions_dir = temp_tree(json!({
+        "installed": {},
+        "work": {}
+    }));
+    let project_dir = temp_tree(json!({
+        "file.test": ""
+    }));
+    let extension_dir = extension_dir.path().canonicalize().unwrap();
+    let extensions_dir = extensions_dir.path().canonicalize().unwrap();
+    let project_dir = project_dir.path().canonicalize().unwrap();
+
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.lsp.insert(
+                    "test-server".into(),
+                    LspSettings {
+                        binary: None,
+                        initialization_options: None,
+                        settings: Some(json!({ "enabled": true })),
+                    },
+                );
+            });
+        });
+    });
+
+    let fs = Arc::new(RealFs::default());
+    let project = Project::test(fs.clone(), [project_dir.as_path()], cx).await;
+    let language_registry = project.read_with(cx, |project, _cx| project.languages().clone());
+    let extension_store = cx.new_model(|cx| {
+        ExtensionStore::new(
+            extensions_dir,
+            Some(cache_dir),
+            fs.clone(),
+            FakeHttpClient::with_404_response(),
+            None,
+            FakeNodeRuntime::new(),
+            language_registry.clone(),
+            Arc::new(ThemeRegistry::new(Box::new(()))),
+            cx,
+        )
+    });
+
+    // Ensure that debounces fire.
+    let mut events = cx.events(&extension_store);
+    let executor = cx.executor();
+    let _task = cx.executor().spawn(async move {
+        while let Some(event) = events.next().await {
+            if let crate::Event::StartedReloading = event {
+                executor.advance_clock(RELOAD_DEBOUNCE_DURATION);
+            }
+        }
+    });
+
+    extension_store
+        .update(cx, |store, cx| {
+            store.install_dev_extension(extension_dir, cx)
+        })
+        .await
+        .unwrap();
+
+    let mut fake_servers = language_registry.fake_language_servers("Test");
+    let _buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(project_dir.join("file.test"), cx)
+        })
+        .await
+        .unwrap();
+
+    let mut fake_server = fake_servers.next().await.unwrap();
+    let configuration = fake_server
+        .receive_notification::<lsp::notification::DidChangeConfiguration>()
+        .await;
+    assert_eq!(
+        configuration.settings,
+        json!({ "test": { "user": { "enabled": true } } })
+    );
+}
+
+#[test]
+fn test_resource_paths_stay_in_extension_dir() {
+    let extension_dir = Path::new("/extensions/installed/test");
+    assert_eq!(
+        resource_path(extension_dir, Path::new("snippets/rust.json")).unwrap(),
+        extension_dir.join("snippets/rust.json")
+    );
+    assert!(resource_path(extension_dir, Path::new("../other/keymap.json")).is_err());
+    assert!(resource_path(extension_dir, Path::new("tasks/../../tasks.json")).is_err());
+    assert!(resource_path(extension_dir, Path::new("/etc/tasks.json")).is_err());
+}
+
 fn init_test(cx: &mut TestAppContext) {
     cx.update(|cx| {
         let store = SettingsStore::test(cx);
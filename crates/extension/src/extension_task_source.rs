@@ -0,0 +1,43 @@
+use crate::ExtensionStore;
+use gpui::{AppContext, Context, Model, ModelContext, Subscription};
+use std::{path::Path, sync::Arc};
+use task::{static_source::tasks_for, TaskSource};
+
+/// A source that pulls in the task templates provided by installed extensions.
+pub struct ExtensionTaskSource {
+    store: Model<ExtensionStore>,
+    _subscription: Subscription,
+}
+
+impl ExtensionTaskSource {
+    pub fn new(store: Model<ExtensionStore>, cx: &mut AppContext) -> Model<Box<dyn TaskSource>> {
+        cx.new_model(|cx| {
+            let _subscription = cx.observe(&store, |_, _, cx| cx.notify());
+            Box::new(Self {
+                store,
+                _subscription,
+            }) as Box<_>
+        })
+    }
+}
+
+impl TaskSource for ExtensionTaskSource {
+    fn as_any(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn tasks_for_path(
+        &mut self,
+        _: Option<&Path>,
+        cx: &mut ModelContext<Box<dyn TaskSource>>,
+    ) -> Vec<Arc<dyn task::Task>> {
+        self.store
+            .read(cx)
+            .extension_tasks
+            .iter()
+            .flat_map(|(extension_id, definitions)| {
+                tasks_for(definitions.clone(), &format!("extension_{extension_id}"))
+            })
+            .collect()
+    }
+}
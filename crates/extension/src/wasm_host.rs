@@ -1,6 +1,6 @@
 pub(crate) mod wit;
 
-use crate::ExtensionManifest;
+use crate::{CapabilityPolicy, ExtensionCapabilities, ExtensionManifest};
 use anyhow::{anyhow, bail, Context as _, Result};
 use fs::{normalize_path, Fs};
 use futures::{
@@ -46,6 +46,7 @@ pub struct WasmExtension {
 
 pub(crate) struct WasmState {
     manifest: Arc<ExtensionManifest>,
+    pub(crate) capability_policy: CapabilityPolicy,
     pub(crate) table: ResourceTable,
     ctx: wasi::WasiCtx,
     pub(crate) host: Arc<WasmHost>,
@@ -90,6 +91,7 @@ impl WasmHost {
         self: &Arc<Self>,
         wasm_bytes: Vec<u8>,
         manifest: Arc<ExtensionManifest>,
+        capability_restriction: Option<ExtensionCapabilities>,
         executor: BackgroundExecutor,
     ) -> impl 'static + Future<Output = Result<WasmExtension>> {
         let this = self.clone();
@@ -99,10 +101,17 @@ impl WasmHost {
             let component = Component::from_binary(&this.engine, &wasm_bytes)
                 .context("failed to compile wasm component")?;
 
+            let capability_policy = CapabilityPolicy {
+                declared: manifest.capabilities.clone(),
+                restriction: capability_restriction,
+            };
+            let extension_work_dir = this.work_dir.join(manifest.id.as_ref());
             let mut store = wasmtime::Store::new(
                 &this.engine,
                 WasmState {
-                    ctx: this.build_wasi_ctx(&manifest).await?,
+                    ctx: build_wasi_ctx(this.fs.as_ref(), &extension_work_dir, &capability_policy)
+                        .await?,
+                    capability_policy,
                     manifest: manifest.clone(),
                     table: ResourceTable::new(),
                     host: this.clone(),
@@ -135,34 +144,6 @@ impl WasmHost {
         }
     }
 
-    async fn build_wasi_ctx(&self, manifest: &Arc<ExtensionManifest>) -> Result<wasi::WasiCtx> {
-        use cap_std::{ambient_authority, fs::Dir};
-
-        let extension_work_dir = self.work_dir.join(manifest.id.as_ref());
-        self.fs
-            .create_dir(&extension_work_dir)
-            .await
-            .context("failed to create extension work dir")?;
-
-        let work_dir_preopen = Dir::open_ambient_dir(&extension_work_dir, ambient_authority())
-            .context("failed to preopen extension work directory")?;
-        let current_dir_preopen = work_dir_preopen
-            .try_clone()
-            .context("failed to preopen extension current directory")?;
-        let extension_work_dir = extension_work_dir.to_string_lossy();
-
-        let perms = wasi::FilePerms::all();
-        let dir_perms = wasi::DirPerms::all();
-
-        Ok(wasi::WasiCtxBuilder::new()
-            .inherit_stdio()
-            .preopened_dir(current_dir_preopen, dir_perms, perms, ".")
-            .preopened_dir(work_dir_preopen, dir_perms, perms, &extension_work_dir)
-            .env("PWD", &extension_work_dir)
-            .env("RUST_BACKTRACE", "full")
-            .build())
-    }
-
     pub fn path_from_extension(&self, id: &Arc<str>, path: &Path) -> PathBuf {
         let extension_work_dir = self.work_dir.join(id.as_ref());
         normalize_path(&extension_work_dir.join(path))
@@ -177,6 +158,89 @@ impl WasmHost {
             Err(anyhow!("cannot write to path {}", path.display()))
         }
     }
+
+    /// Like [`Self::writeable_path_from_extension`], but also requires the path to be
+    /// within the extension's granted file-system scope.
+    pub(crate) fn writeable_path_from_extension_with_policy(
+        &self,
+        id: &Arc<str>,
+        path: &Path,
+        policy: &CapabilityPolicy,
+    ) -> Result<PathBuf> {
+        let path = self.writeable_path_from_extension(id, path)?;
+        let extension_work_dir = self.work_dir.join(id.as_ref());
+        policy.check_writable_path(path.strip_prefix(&extension_work_dir)?)?;
+        Ok(path)
+    }
+}
+
+/// Builds the WASI context of an extension, which may read anywhere in its work directory,
+/// but may only write to the paths granted by its capability policy.
+async fn build_wasi_ctx(
+    fs: &dyn Fs,
+    extension_work_dir: &Path,
+    policy: &CapabilityPolicy,
+) -> Result<wasi::WasiCtx> {
+    use cap_std::{ambient_authority, fs::Dir};
+
+    fs.create_dir(extension_work_dir)
+        .await
+        .context("failed to create extension work dir")?;
+
+    let writable_paths = policy.writable_paths();
+    let work_dir_is_writable = writable_paths
+        .iter()
+        .any(|path| path.as_os_str().is_empty());
+    let (dir_perms, perms) = if work_dir_is_writable {
+        (wasi::DirPerms::all(), wasi::FilePerms::all())
+    } else {
+        (wasi::DirPerms::READ, wasi::FilePerms::READ)
+    };
+
+    let work_dir_preopen = Dir::open_ambient_dir(extension_work_dir, ambient_authority())
+        .context("failed to preopen extension work directory")?;
+    let current_dir_preopen = work_dir_preopen
+        .try_clone()
+        .context("failed to preopen extension current directory")?;
+    let work_dir_guest_path = extension_work_dir.to_string_lossy();
+
+    let mut builder = wasi::WasiCtxBuilder::new();
+    builder
+        .inherit_stdio()
+        .preopened_dir(current_dir_preopen, dir_perms, perms, ".")
+        .preopened_dir(work_dir_preopen, dir_perms, perms, &work_dir_guest_path)
+        .env("PWD", &work_dir_guest_path)
+        .env("RUST_BACKTRACE", "full");
+
+    if !work_dir_is_writable {
+        for path in writable_paths {
+            let abs_path = extension_work_dir.join(&path);
+            // Writable files can only be written through the functions of the host.
+            if fs.is_file(&abs_path).await {
+                continue;
+            }
+            fs.create_dir(&abs_path)
+                .await
+                .with_context(|| format!("failed to create writable directory {path:?}"))?;
+            let preopen = Dir::open_ambient_dir(&abs_path, ambient_authority())
+                .with_context(|| format!("failed to preopen writable directory {path:?}"))?;
+            builder
+                .preopened_dir(
+                    preopen.try_clone()?,
+                    wasi::DirPerms::all(),
+                    wasi::FilePerms::all(),
+                    path.to_string_lossy(),
+                )
+                .preopened_dir(
+                    preopen,
+                    wasi::DirPerms::all(),
+                    wasi::FilePerms::all(),
+                    abs_path.to_string_lossy(),
+                );
+        }
+    }
+
+    Ok(builder.build())
 }
 
 pub fn parse_wasm_extension_version(
@@ -252,3 +316,106 @@ impl wasi::WasiView for WasmState {
         &mut self.ctx
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::RealFs;
+    use serde_json::json;
+    use util::test::temp_tree;
+    use wasi::bindings::filesystem::{
+        preopens::Host as _,
+        types::{DescriptorFlags, ErrorCode, HostDescriptor as _, OpenFlags, PathFlags},
+    };
+    use wasmtime::component::Resource;
+
+    struct TestState {
+        ctx: wasi::WasiCtx,
+        table: ResourceTable,
+    }
+
+    impl wasi::WasiView for TestState {
+        fn table(&mut self) -> &mut ResourceTable {
+            &mut self.table
+        }
+
+        fn ctx(&mut self) -> &mut wasi::WasiCtx {
+            &mut self.ctx
+        }
+    }
+
+    #[gpui::test]
+    async fn test_wasi_writes_are_limited_to_writable_paths() {
+        let work_dir = temp_tree(json!({ "data.txt": "data" }));
+        let policy = CapabilityPolicy {
+            declared: Some(ExtensionCapabilities {
+                writable_paths: vec!["bin".into()],
+                ..Default::default()
+            }),
+            restriction: None,
+        };
+        let ctx = build_wasi_ctx(&RealFs::default(), work_dir.path(), &policy)
+            .await
+            .unwrap();
+        let mut state = TestState {
+            ctx,
+            table: ResourceTable::new(),
+        };
+
+        let preopens = state.get_directories().unwrap();
+        let preopen = |guest_path: &str| {
+            let (descriptor, _) = preopens
+                .iter()
+                .find(|(_, path)| path == guest_path)
+                .unwrap_or_else(|| panic!("no preopen for {guest_path:?}"));
+            Resource::new_borrow(descriptor.rep())
+        };
+
+        let error = state
+            .open_at(
+                preopen("."),
+                PathFlags::empty(),
+                "data.txt".into(),
+                OpenFlags::empty(),
+                DescriptorFlags::WRITE,
+            )
+            .await
+            .err()
+            .expect("writing outside of the writable paths should fail");
+        assert!(matches!(error.downcast(), Ok(ErrorCode::NotPermitted)));
+        let error = state
+            .open_at(
+                preopen("."),
+                PathFlags::empty(),
+                "new.txt".into(),
+                OpenFlags::CREATE,
+                DescriptorFlags::WRITE,
+            )
+            .await
+            .err()
+            .expect("creating files outside of the writable paths should fail");
+        assert!(matches!(error.downcast(), Ok(ErrorCode::NotPermitted)));
+
+        state
+            .open_at(
+                preopen("."),
+                PathFlags::empty(),
+                "data.txt".into(),
+                OpenFlags::empty(),
+                DescriptorFlags::READ,
+            )
+            .await
+            .expect("the whole work directory should be readable");
+        state
+            .open_at(
+                preopen("bin"),
+                PathFlags::empty(),
+                "server".into(),
+                OpenFlags::CREATE,
+                DescriptorFlags::WRITE,
+            )
+            .await
+            .expect("writable paths should be writable");
+        assert!(work_dir.path().join("bin/server").is_file());
+    }
+}
@@ -1,8 +1,9 @@
 mod since_v0_0_1;
 mod since_v0_0_4;
+mod since_v0_0_6;
 
 use super::{wasm_engine, WasmState};
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use language::LspAdapterDelegate;
 use semantic_version::SemanticVersion;
 use std::ops::RangeInclusive;
@@ -12,9 +13,12 @@ use wasmtime::{
     Store,
 };
 
-use since_v0_0_4 as latest;
+use since_v0_0_6 as latest;
 
-pub use latest::{Command, LanguageServerConfig};
+pub use latest::{
+    Command, DiagnosticSeverity, FormatterConfig, LanguageServerConfig, LintDiagnostic,
+    LinterConfig, TaskTemplate, TextEdit,
+};
 
 pub fn new_linker(
     f: impl Fn(&mut Linker<WasmState>, fn(&mut WasmState) -> &mut WasmState) -> Result<()>,
@@ -41,6 +45,7 @@ pub fn wasm_api_version_range() -> RangeInclusive<SemanticVersion> {
 }
 
 pub enum Extension {
+    V006(since_v0_0_6::Extension),
     V004(since_v0_0_4::Extension),
     V001(since_v0_0_1::Extension),
 }
@@ -51,7 +56,7 @@ impl Extension {
         version: SemanticVersion,
         component: &Component,
     ) -> Result<(Self, Instance)> {
-        if version < latest::MIN_VERSION {
+        if version < since_v0_0_4::MIN_VERSION {
             let (extension, instance) = since_v0_0_1::Extension::instantiate_async(
                 store,
                 &component,
@@ -60,7 +65,7 @@ impl Extension {
             .await
             .context("failed to instantiate wasm extension")?;
             Ok((Self::V001(extension), instance))
-        } else {
+        } else if version < latest::MIN_VERSION {
             let (extension, instance) = since_v0_0_4::Extension::instantiate_async(
                 store,
                 &component,
@@ -69,11 +74,21 @@ impl Extension {
             .await
             .context("failed to instantiate wasm extension")?;
             Ok((Self::V004(extension), instance))
+        } else {
+            let (extension, instance) = since_v0_0_6::Extension::instantiate_async(
+                store,
+                &component,
+                since_v0_0_6::linker(),
+            )
+            .await
+            .context("failed to instantiate wasm extension")?;
+            Ok((Self::V006(extension), instance))
         }
     }
 
     pub async fn call_init_extension(&self, store: &mut Store<WasmState>) -> Result<()> {
         match self {
+            Extension::V006(ext) => ext.call_init_extension(store).await,
             Extension::V004(ext) => ext.call_init_extension(store).await,
             Extension::V001(ext) => ext.call_init_extension(store).await,
         }
@@ -86,10 +101,14 @@ impl Extension {
         resource: Resource<Arc<dyn LspAdapterDelegate>>,
     ) -> Result<Result<Command, String>> {
         match self {
-            Extension::V004(ext) => {
+            Extension::V006(ext) => {
                 ext.call_language_server_command(store, config, resource)
                     .await
             }
+            Extension::V004(ext) => Ok(ext
+                .call_language_server_command(store, &config.clone().into(), resource)
+                .await?
+                .map(|command| command.into())),
             Extension::V001(ext) => Ok(ext
                 .call_language_server_command(store, &config.clone().into(), resource)
                 .await?
@@ -104,10 +123,18 @@ impl Extension {
         resource: Resource<Arc<dyn LspAdapterDelegate>>,
     ) -> Result<Result<Option<String>, String>> {
         match self {
-            Extension::V004(ext) => {
+            Extension::V006(ext) => {
                 ext.call_language_server_initialization_options(store, config, resource)
                     .await
             }
+            Extension::V004(ext) => {
+                ext.call_language_server_initialization_options(
+                    store,
+                    &config.clone().into(),
+                    resource,
+                )
+                .await
+            }
             Extension::V001(ext) => {
                 ext.call_language_server_initialization_options(
                     store,
@@ -118,6 +145,72 @@ impl Extension {
             }
         }
     }
+
+    /// Extensions built against API versions older than 0.0.6 don't export this function, so the
+    /// user's settings are passed through to the language server unchanged.
+    pub async fn call_language_server_workspace_configuration(
+        &self,
+        store: &mut Store<WasmState>,
+        config: &LanguageServerConfig,
+        settings: Option<String>,
+        resource: Resource<Arc<dyn LspAdapterDelegate>>,
+    ) -> Result<Result<Option<String>, String>> {
+        match self {
+            Extension::V006(ext) => {
+                ext.call_language_server_workspace_configuration(
+                    store,
+                    config,
+                    settings.as_deref(),
+                    resource,
+                )
+                .await
+            }
+            Extension::V004(_) | Extension::V001(_) => Ok(Ok(settings)),
+        }
+    }
+
+    pub async fn call_format_buffer(
+        &self,
+        store: &mut Store<WasmState>,
+        config: &FormatterConfig,
+        text: &str,
+        resource: Resource<Arc<dyn LspAdapterDelegate>>,
+    ) -> Result<Result<Vec<TextEdit>, String>> {
+        match self {
+            Extension::V006(ext) => ext.call_format_buffer(store, config, text, resource).await,
+            Extension::V004(_) | Extension::V001(_) => Err(anyhow!(
+                "formatters require extension API version 0.0.6 or later"
+            )),
+        }
+    }
+
+    pub async fn call_lint_buffer(
+        &self,
+        store: &mut Store<WasmState>,
+        config: &LinterConfig,
+        text: &str,
+        resource: Resource<Arc<dyn LspAdapterDelegate>>,
+    ) -> Result<Result<Vec<LintDiagnostic>, String>> {
+        match self {
+            Extension::V006(ext) => ext.call_lint_buffer(store, config, text, resource).await,
+            Extension::V004(_) | Extension::V001(_) => Err(anyhow!(
+                "linters require extension API version 0.0.6 or later"
+            )),
+        }
+    }
+
+    /// Extensions built against API versions older than 0.0.6 can only provide the tasks
+    /// of their languages in `tasks.json` files.
+    pub async fn call_language_tasks(
+        &self,
+        store: &mut Store<WasmState>,
+        language_name: &str,
+    ) -> Result<Result<Vec<TaskTemplate>, String>> {
+        match self {
+            Extension::V006(ext) => ext.call_language_tasks(store, language_name).await,
+            Extension::V004(_) | Extension::V001(_) => Ok(Ok(Vec::new())),
+        }
+    }
 }
 
 trait ToWasmtimeResult<T> {
@@ -19,6 +19,44 @@ pub trait Extension: Send + Sync {
     ) -> Result<Option<String>> {
         Ok(None)
     }
+
+    /// Returns the JSON `workspace/configuration` settings for the language server.
+    ///
+    /// `settings` contains the JSON `lsp.<server>.settings` configured by the user, if any.
+    fn language_server_workspace_configuration(
+        &mut self,
+        _config: LanguageServerConfig,
+        settings: Option<String>,
+        _worktree: &Worktree,
+    ) -> Result<Option<String>> {
+        Ok(settings)
+    }
+
+    /// Returns the edits that format the given text, for a formatter declared in `extension.toml`.
+    fn format_buffer(
+        &mut self,
+        config: FormatterConfig,
+        _text: String,
+        _worktree: &Worktree,
+    ) -> Result<Vec<TextEdit>> {
+        Err(format!("formatter {} is not implemented", config.name))
+    }
+
+    /// Returns the diagnostics for the given text, for a linter declared in `extension.toml`.
+    fn lint_buffer(
+        &mut self,
+        config: LinterConfig,
+        _text: String,
+        _worktree: &Worktree,
+    ) -> Result<Vec<LintDiagnostic>> {
+        Err(format!("linter {} is not implemented", config.name))
+    }
+
+    /// Returns the tasks for a language provided by this extension, such as the ones
+    /// that run the runnables found by its `runnables.scm` query.
+    fn language_tasks(&mut self, _language_name: String) -> Result<Vec<TaskTemplate>> {
+        Ok(Vec::new())
+    }
 }
 
 #[macro_export]
@@ -53,7 +91,7 @@ pub static ZED_API_VERSION: [u8; 6] = *include_bytes!(concat!(env!("OUT_DIR"), "
 mod wit {
     wit_bindgen::generate!({
         skip: ["init-extension"],
-        path: "./wit/since_v0.0.4",
+        path: "./wit/since_v0.0.6",
     });
 }
 
@@ -75,4 +113,32 @@ impl wit::Guest for Component {
     ) -> Result<Option<String>, String> {
         extension().language_server_initialization_options(config, worktree)
     }
+
+    fn language_server_workspace_configuration(
+        config: LanguageServerConfig,
+        settings: Option<String>,
+        worktree: &Worktree,
+    ) -> Result<Option<String>, String> {
+        extension().language_server_workspace_configuration(config, settings, worktree)
+    }
+
+    fn format_buffer(
+        config: FormatterConfig,
+        text: String,
+        worktree: &Worktree,
+    ) -> Result<Vec<TextEdit>, String> {
+        extension().format_buffer(config, text, worktree)
+    }
+
+    fn lint_buffer(
+        config: LinterConfig,
+        text: String,
+        worktree: &Worktree,
+    ) -> Result<Vec<LintDiagnostic>, String> {
+        extension().lint_buffer(config, text, worktree)
+    }
+
+    fn language_tasks(language_name: String) -> Result<Vec<TaskTemplate>, String> {
+        extension().language_tasks(language_name)
+    }
 }
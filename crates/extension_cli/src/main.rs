@@ -1,25 +1,114 @@
 use std::{
-    collections::HashMap,
-    env, fs,
+    env,
+    ffi::OsStr,
+    fs,
     path::{Path, PathBuf},
     process::Command,
-    sync::Arc,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use ::fs::{copy_recursive, CopyOptions, Fs, RealFs};
 use anyhow::{anyhow, bail, Context, Result};
-use clap::Parser;
+use async_compression::futures::bufread::GzipEncoder;
+use async_task::Runnable;
+use async_trait::async_trait;
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use collections::{HashMap, HashSet};
 use extension::{
-    extension_builder::{CompileExtensionOptions, ExtensionBuilder},
-    ExtensionManifest,
+    extension_builder::{populate_defaults, CompileExtensionOptions, ExtensionBuilder},
+    extension_runner::ExtensionRunner,
+    schema_version_range, ExtensionManifest, GrammarManifestEntry,
 };
-use language::LanguageConfig;
+use futures::{io::BufReader, AsyncReadExt as _};
+use gpui::{AppContext, BackgroundExecutor, PlatformDispatcher, TaskLabel};
+use language::{
+    LanguageConfig, LanguageServerBinaryStatus, LanguageServerName, LspAdapterDelegate,
+    SnippetDefinition,
+};
+use node_runtime::RealNodeRuntime;
+use parking::{Parker, Unparker};
+use semantic_version::SemanticVersion;
+use sha2::{Digest, Sha256};
 use theme::ThemeRegistry;
 use tree_sitter::{Language, Query, WasmStore};
+use util::http::{self, HttpClient};
 
 #[derive(Parser, Debug)]
-#[command(name = "zed-extension")]
+#[command(
+    name = "zed-extension",
+    args_conflicts_with_subcommands = true,
+    arg_required_else_help = true
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+    /// The path to the extension directory, when packaging without a subcommand
+    #[arg(long)]
+    source_dir: Option<PathBuf>,
+    /// The output directory to place the packaged extension, when packaging without a subcommand
+    #[arg(long)]
+    output_dir: Option<PathBuf>,
+    /// The path to a directory where build dependencies are downloaded, when packaging
+    /// without a subcommand
+    #[arg(long)]
+    scratch_dir: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Checks the extension's manifest, grammars, languages and themes, without building it.
+    Validate(ValidateArgs),
+    /// Builds the extension and checks the command it returns for a language server.
+    Test(TestArgs),
+    /// Builds the extension and packages it into a reproducible archive.
+    Package(PackageArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    /// The path to the extension directory
+    #[arg(long)]
+    source_dir: PathBuf,
+    /// The path to a directory containing git checkouts of the extension's grammars.
+    /// Defaults to the extension's `grammars` directory.
+    #[arg(long)]
+    grammar_cache_dir: Option<PathBuf>,
+}
+
+#[derive(clap::Args, Debug)]
+struct TestArgs {
+    /// The path to the extension directory
+    #[arg(long)]
+    source_dir: PathBuf,
+    /// The path to a directory where build dependencies are downloaded
+    #[arg(long)]
+    scratch_dir: PathBuf,
+    /// The name of the language server to request a command for
+    #[arg(long)]
+    language_server: String,
+    /// The root of the fake worktree that the language server is started in.
+    /// Defaults to an empty directory.
+    #[arg(long)]
+    worktree_dir: Option<PathBuf>,
+    /// A binary available on the fake worktree's `PATH`, as `NAME=PATH`
+    #[arg(long = "which", value_parser = parse_key_value)]
+    binaries: Vec<(String, String)>,
+    /// A variable in the fake worktree's shell environment, as `NAME=VALUE`
+    #[arg(long = "env", value_parser = parse_key_value)]
+    env: Vec<(String, String)>,
+    /// The expected path of the language server binary. Relative paths match
+    /// the end of the returned path.
+    #[arg(long)]
+    expect_command: Option<PathBuf>,
+    /// The expected arguments to the language server binary
+    #[arg(long, num_args = 0.., allow_hyphen_values = true)]
+    expect_args: Option<Vec<String>>,
+}
+
+#[derive(clap::Args, Debug)]
+struct PackageArgs {
     /// The path to the extension directory
     #[arg(long)]
     source_dir: PathBuf,
@@ -36,7 +125,413 @@ async fn main() -> Result<()> {
     env_logger::init();
 
     let args = Args::parse();
+    let command = match args.command {
+        Some(command) => command,
+        // Packaging used to be the only thing this binary did, so keep accepting
+        // its flags on their own.
+        None => match (args.source_dir, args.output_dir, args.scratch_dir) {
+            (Some(source_dir), Some(output_dir), Some(scratch_dir)) => {
+                Commands::Package(PackageArgs {
+                    source_dir,
+                    output_dir,
+                    scratch_dir,
+                })
+            }
+            _ => Args::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "packaging requires --source-dir, --output-dir and --scratch-dir",
+                )
+                .exit(),
+        },
+    };
+
     let fs = Arc::new(RealFs::default());
+    match command {
+        Commands::Validate(args) => validate(args, fs).await,
+        Commands::Test(args) => test(args, fs).await,
+        Commands::Package(args) => package(args, fs).await,
+    }
+}
+
+async fn validate(args: ValidateArgs, fs: Arc<RealFs>) -> Result<()> {
+    let extension_path = args
+        .source_dir
+        .canonicalize()
+        .context("failed to canonicalize source_dir")?;
+
+    log::info!("loading extension manifest");
+    let mut manifest = ExtensionManifest::load(fs.clone(), &extension_path).await?;
+    populate_defaults(&mut manifest, &extension_path)?;
+
+    let mut errors = validate_manifest(&manifest, &extension_path);
+
+    let grammar_cache_dir = args
+        .grammar_cache_dir
+        .unwrap_or_else(|| extension_path.join("grammars"));
+    for (grammar_name, grammar) in &manifest.grammars {
+        if let Err(error) = check_grammar_revision(grammar_name, grammar, &grammar_cache_dir) {
+            errors.push(error);
+        }
+    }
+
+    // Grammars are only compiled when the extension is built, so the queries of
+    // the languages whose grammars haven't been built yet can't be checked.
+    let mut wasm_store = WasmStore::new(wasmtime::Engine::default())?;
+    let mut grammars = HashMap::default();
+    let mut unbuilt_grammars = Vec::new();
+    let mut unchecked_grammars = HashSet::default();
+    for grammar_name in manifest.grammars.keys() {
+        let grammar_path = compiled_grammar_path(&extension_path, grammar_name);
+        if !grammar_path.exists() {
+            unbuilt_grammars.push(grammar_name.to_string());
+            unchecked_grammars.insert(grammar_name.clone());
+            continue;
+        }
+        match load_grammar(grammar_name, &grammar_path, &mut wasm_store) {
+            Ok(language) => {
+                grammars.insert(grammar_name.to_string(), language);
+            }
+            Err(error) => {
+                errors.push(error);
+                unchecked_grammars.insert(grammar_name.clone());
+            }
+        }
+    }
+    if let Err(error) = test_languages(&manifest, &extension_path, &grammars, &unchecked_grammars) {
+        errors.push(error);
+    }
+    if let Err(error) = test_themes(&manifest, &extension_path, fs.clone()).await {
+        errors.push(error);
+    }
+
+    if !unbuilt_grammars.is_empty() {
+        unbuilt_grammars.sort();
+        log::warn!(
+            "grammar(s) {} have not been built, so the queries that use them were not checked. \
+            Run `zed-extension package` to build them.",
+            unbuilt_grammars.join(", ")
+        );
+    }
+    if errors.is_empty() {
+        log::info!("extension {} is valid", manifest.id);
+        return Ok(());
+    }
+    for error in &errors {
+        log::error!("{error:#}");
+    }
+    bail!(
+        "found {} problem(s) in extension {}",
+        errors.len(),
+        manifest.id
+    )
+}
+
+fn validate_manifest(manifest: &ExtensionManifest, extension_path: &Path) -> Vec<anyhow::Error> {
+    let mut errors = Vec::new();
+
+    if manifest.id.is_empty()
+        || !manifest
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        errors.push(anyhow!(
+            "extension id '{}' must consist of lowercase letters, digits and hyphens",
+            manifest.id
+        ));
+    }
+    if manifest.name.trim().is_empty() {
+        errors.push(anyhow!("missing name in extension manifest"));
+    }
+    if let Err(error) = SemanticVersion::from_str(&manifest.version) {
+        errors.push(anyhow!(
+            "invalid extension version '{}': {error}",
+            manifest.version
+        ));
+    }
+    if !schema_version_range().contains(&manifest.schema_version) {
+        errors.push(anyhow!(
+            "unsupported schema version {}",
+            manifest.schema_version
+        ));
+    }
+    if manifest.repository.is_none() {
+        errors.push(anyhow!("missing repository in extension manifest"));
+    }
+
+    for snippets in &manifest.snippets {
+        let result = fs::read_to_string(extension_path.join(&snippets.path))
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| SnippetDefinition::parse_file(&contents));
+        if let Err(error) = result {
+            errors.push(error.context(format!(
+                "invalid snippets file '{}'",
+                snippets.path.display()
+            )));
+        }
+    }
+    for resource_path in manifest.tasks.iter().chain(&manifest.keymaps) {
+        if !extension_path.join(resource_path).is_file() {
+            errors.push(anyhow!("missing file '{}'", resource_path.display()));
+        }
+    }
+
+    errors
+}
+
+/// Checks that the revision of a grammar is present in a local git checkout of its repository.
+fn check_grammar_revision(
+    grammar_name: &str,
+    grammar: &GrammarManifestEntry,
+    grammar_cache_dir: &Path,
+) -> Result<()> {
+    let git_dir = grammar_cache_dir.join(grammar_name).join(".git");
+    if !git_dir.exists() {
+        bail!(
+            "grammar '{grammar_name}' has no checkout in '{}'",
+            grammar_cache_dir.display()
+        );
+    }
+
+    let remote_output = Command::new("git")
+        .arg("--git-dir")
+        .arg(&git_dir)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .context("failed to execute `git remote get-url`")?;
+    let remote_url = String::from_utf8_lossy(&remote_output.stdout);
+    if remote_url.trim() != grammar.repository {
+        bail!(
+            "checkout of grammar '{grammar_name}' is not a clone of '{}'",
+            grammar.repository
+        );
+    }
+
+    let rev_output = Command::new("git")
+        .arg("--git-dir")
+        .arg(&git_dir)
+        .args(["cat-file", "-e"])
+        .arg(format!("{}^{{commit}}", grammar.rev))
+        .output()
+        .context("failed to execute `git cat-file`")?;
+    if !rev_output.status.success() {
+        bail!(
+            "revision {} of grammar '{grammar_name}' is not in the local checkout of '{}'",
+            grammar.rev,
+            grammar.repository
+        );
+    }
+
+    Ok(())
+}
+
+async fn test(args: TestArgs, fs: Arc<RealFs>) -> Result<()> {
+    let extension_path = args
+        .source_dir
+        .canonicalize()
+        .context("failed to canonicalize source_dir")?;
+    let scratch_dir = args
+        .scratch_dir
+        .canonicalize()
+        .context("failed to canonicalize scratch_dir")?;
+
+    log::info!("loading extension manifest");
+    let mut manifest = ExtensionManifest::load(fs.clone(), &extension_path).await?;
+
+    log::info!("compiling extension");
+    let builder = ExtensionBuilder::new(scratch_dir.clone());
+    builder
+        .compile_extension(
+            &extension_path,
+            &mut manifest,
+            CompileExtensionOptions { release: false },
+        )
+        .await
+        .context("failed to compile extension")?;
+    if manifest.lib.kind.is_none() {
+        bail!("extension {} has no WASM library to test", manifest.id);
+    }
+
+    let work_dir = scratch_dir.join("test-work");
+    fs::remove_dir_all(&work_dir).ok();
+    fs::create_dir_all(&work_dir).context("failed to create work dir")?;
+    let worktree_root = match args.worktree_dir {
+        Some(worktree_dir) => worktree_dir
+            .canonicalize()
+            .context("failed to canonicalize worktree_dir")?,
+        None => {
+            let worktree_dir = scratch_dir.join("test-worktree");
+            fs::create_dir_all(&worktree_dir).context("failed to create worktree dir")?;
+            worktree_dir
+        }
+    };
+
+    let http_client = http::client();
+    let worktree: Arc<dyn LspAdapterDelegate> = Arc::new(FakeWorktree {
+        root: worktree_root,
+        binaries: args
+            .binaries
+            .into_iter()
+            .map(|(name, path)| (name, PathBuf::from(path)))
+            .collect(),
+        env: args.env.into_iter().collect(),
+        http_client: http_client.clone(),
+    });
+    let language_server_name = LanguageServerName(args.language_server.into());
+
+    // There's no application running here, so run the extension's tasks on
+    // tokio's thread pool instead.
+    let executor = BackgroundExecutor::new(Arc::new(TokioDispatcher::new(
+        tokio::runtime::Handle::current(),
+    )));
+    let runner = ExtensionRunner::load(
+        extension_path,
+        Arc::new(manifest),
+        work_dir,
+        fs,
+        http_client.clone(),
+        RealNodeRuntime::new(http_client),
+        executor,
+    )
+    .await?;
+    let binary = runner
+        .language_server_command(&language_server_name, worktree)
+        .await
+        .with_context(|| {
+            format!(
+                "failed to get command for language server '{}'",
+                language_server_name.0
+            )
+        })?;
+
+    let arguments = binary
+        .arguments
+        .iter()
+        .map(|argument| argument.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    println!("{} {}", binary.path.display(), arguments.join(" "));
+
+    if let Some(expected_command) = &args.expect_command {
+        if !binary.path.ends_with(expected_command) {
+            bail!(
+                "expected language server command '{}', but got '{}'",
+                expected_command.display(),
+                binary.path.display()
+            );
+        }
+    }
+    if let Some(expected_args) = &args.expect_args {
+        if &arguments != expected_args {
+            bail!("expected language server arguments {expected_args:?}, but got {arguments:?}");
+        }
+    }
+
+    Ok(())
+}
+
+/// A dispatcher for gpui's executors that runs their tasks on tokio's blocking
+/// thread pool, as there's no platform to dispatch them to in this binary.
+struct TokioDispatcher {
+    runtime: tokio::runtime::Handle,
+    parker: Mutex<Parker>,
+}
+
+impl TokioDispatcher {
+    fn new(runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            runtime,
+            parker: Mutex::new(Parker::new()),
+        }
+    }
+}
+
+impl PlatformDispatcher for TokioDispatcher {
+    fn is_main_thread(&self) -> bool {
+        false
+    }
+
+    fn dispatch(&self, runnable: Runnable, _: Option<TaskLabel>) {
+        self.runtime.spawn_blocking(move || {
+            runnable.run();
+        });
+    }
+
+    fn dispatch_on_main_thread(&self, runnable: Runnable) {
+        self.dispatch(runnable, None);
+    }
+
+    fn dispatch_after(&self, duration: Duration, runnable: Runnable) {
+        let runtime = self.runtime.clone();
+        self.runtime.spawn(async move {
+            tokio::time::sleep(duration).await;
+            runtime.spawn_blocking(move || {
+                runnable.run();
+            });
+        });
+    }
+
+    fn tick(&self, _: bool) -> bool {
+        false
+    }
+
+    fn park(&self) {
+        self.parker.lock().unwrap().park()
+    }
+
+    fn unparker(&self) -> Unparker {
+        self.parker.lock().unwrap().unparker()
+    }
+}
+
+/// A worktree for `zed-extension test`, with a fixed set of binaries on its `PATH`
+/// and a fixed shell environment.
+struct FakeWorktree {
+    root: PathBuf,
+    binaries: HashMap<String, PathBuf>,
+    env: HashMap<String, String>,
+    http_client: Arc<dyn HttpClient>,
+}
+
+#[async_trait]
+impl LspAdapterDelegate for FakeWorktree {
+    fn show_notification(&self, message: &str, _: &mut AppContext) {
+        log::info!("notification: {message}");
+    }
+
+    fn http_client(&self) -> Arc<dyn HttpClient> {
+        self.http_client.clone()
+    }
+
+    fn update_status(&self, language: LanguageServerName, status: LanguageServerBinaryStatus) {
+        log::info!("language server {} status: {status:?}", language.0);
+    }
+
+    fn worktree_root_path(&self) -> &Path {
+        &self.root
+    }
+
+    async fn which(&self, command: &OsStr) -> Option<PathBuf> {
+        self.binaries.get(command.to_str()?).cloned()
+    }
+
+    async fn shell_env(&self) -> HashMap<String, String> {
+        self.env.clone()
+    }
+
+    async fn read_text_file(&self, path: PathBuf) -> Result<String> {
+        Ok(fs::read_to_string(self.root.join(path))?)
+    }
+}
+
+fn parse_key_value(input: &str) -> Result<(String, String)> {
+    let (key, value) = input
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected NAME=VALUE, got '{input}'"))?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+async fn package(args: PackageArgs, fs: Arc<RealFs>) -> Result<()> {
     let engine = wasmtime::Engine::default();
     let mut wasm_store = WasmStore::new(engine)?;
 
@@ -69,7 +564,7 @@ async fn main() -> Result<()> {
         .context("failed to compile extension")?;
 
     let grammars = test_grammars(&manifest, &extension_path, &mut wasm_store)?;
-    test_languages(&manifest, &extension_path, &grammars)?;
+    test_languages(&manifest, &extension_path, &grammars, &HashSet::default())?;
     test_themes(&manifest, &extension_path, fs.clone()).await?;
 
     let archive_dir = output_dir.join("archive");
@@ -78,17 +573,12 @@ async fn main() -> Result<()> {
         .await
         .context("failed to copy extension resources")?;
 
-    let tar_output = Command::new("tar")
-        .current_dir(&output_dir)
-        .args(&["-czvf", "archive.tar.gz", "-C", "archive", "."])
-        .output()
-        .context("failed to run tar")?;
-    if !tar_output.status.success() {
-        bail!(
-            "failed to create archive.tar.gz: {}",
-            String::from_utf8_lossy(&tar_output.stderr)
-        );
-    }
+    let archive_bytes = create_reproducible_archive(&archive_dir)
+        .await
+        .context("failed to create archive.tar.gz")?;
+    fs::write(output_dir.join("archive.tar.gz"), &archive_bytes)
+        .context("failed to write archive.tar.gz")?;
+    let archive_sha256 = format!("{:x}", Sha256::digest(&archive_bytes));
 
     let manifest_json = serde_json::to_string(&rpc::ExtensionApiManifest {
         name: manifest.name,
@@ -100,10 +590,72 @@ async fn main() -> Result<()> {
             .repository
             .ok_or_else(|| anyhow!("missing repository in extension manifest"))?,
         wasm_api_version: manifest.lib.version.map(|version| version.to_string()),
+        archive_sha256: Some(archive_sha256.clone()),
     })?;
+    let manifest_sha256 = format!("{:x}", Sha256::digest(manifest_json.as_bytes()));
     fs::remove_dir_all(&archive_dir)?;
     fs::write(output_dir.join("manifest.json"), manifest_json.as_bytes())?;
+    // Written in the format of `sha256sum`, so that it can be checked with `sha256sum -c`.
+    fs::write(
+        output_dir.join("manifest.json.sha256"),
+        format!("{manifest_sha256}  manifest.json\n"),
+    )?;
+
+    println!("archive.tar.gz {archive_sha256}");
+    println!("manifest.json {manifest_sha256}");
+
+    Ok(())
+}
+
+/// Packs the contents of `archive_dir` into a gzipped tarball that only depends on
+/// the paths and contents of its files, and not on when or by whom it was built.
+async fn create_reproducible_archive(archive_dir: &Path) -> Result<Vec<u8>> {
+    let mut relative_paths = Vec::new();
+    collect_relative_paths(archive_dir, archive_dir, &mut relative_paths)?;
+    relative_paths.sort();
+
+    let mut tar_bytes = Vec::<u8>::new();
+    let mut archive = async_tar::Builder::new(&mut tar_bytes);
+    for relative_path in relative_paths {
+        let path = archive_dir.join(&relative_path);
+        let mut header = async_tar::Header::new_gnu();
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        if path.is_dir() {
+            header.set_entry_type(async_tar::EntryType::Directory);
+            header.set_mode(0o755);
+            header.set_size(0);
+            archive
+                .append_data(&mut header, &relative_path, futures::io::empty())
+                .await?;
+        } else {
+            let contents =
+                fs::read(&path).with_context(|| format!("failed to read '{}'", path.display()))?;
+            header.set_entry_type(async_tar::EntryType::Regular);
+            header.set_mode(0o644);
+            header.set_size(contents.len() as u64);
+            archive
+                .append_data(&mut header, &relative_path, contents.as_slice())
+                .await?;
+        }
+    }
+    archive.into_inner().await?;
+
+    let mut gzipped_bytes = Vec::new();
+    let mut encoder = GzipEncoder::new(BufReader::new(tar_bytes.as_slice()));
+    encoder.read_to_end(&mut gzipped_bytes).await?;
+    Ok(gzipped_bytes)
+}
 
+fn collect_relative_paths(root: &Path, dir: &Path, paths: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        paths.push(path.strip_prefix(root)?.to_path_buf());
+        if path.is_dir() {
+            collect_relative_paths(root, &path, paths)?;
+        }
+    }
     Ok(())
 }
 
@@ -182,6 +734,21 @@ async fn copy_extension_resources(
         }
     }
 
+    let resource_paths = manifest
+        .snippets
+        .iter()
+        .map(|entry| &entry.path)
+        .chain(&manifest.tasks)
+        .chain(&manifest.keymaps);
+    for resource_path in resource_paths {
+        let output_path = output_dir.join(resource_path);
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(extension_path.join(resource_path), &output_path)
+            .with_context(|| format!("failed to copy '{}'", resource_path.display()))?;
+    }
+
     Ok(())
 }
 
@@ -191,31 +758,60 @@ fn test_grammars(
     wasm_store: &mut WasmStore,
 ) -> Result<HashMap<String, Language>> {
     let mut grammars = HashMap::default();
-    let grammars_dir = extension_path.join("grammars");
 
     for grammar_name in manifest.grammars.keys() {
-        let mut grammar_path = grammars_dir.join(grammar_name.as_ref());
-        grammar_path.set_extension("wasm");
-
-        let wasm = fs::read(&grammar_path)?;
-        let language = wasm_store.load_language(grammar_name, &wasm)?;
-        log::info!("loaded grammar {grammar_name}");
+        let grammar_path = compiled_grammar_path(extension_path, grammar_name);
+        let language = load_grammar(grammar_name, &grammar_path, wasm_store)?;
         grammars.insert(grammar_name.to_string(), language);
     }
 
     Ok(grammars)
 }
 
+fn compiled_grammar_path(extension_path: &Path, grammar_name: &str) -> PathBuf {
+    let mut grammar_path = extension_path.join("grammars").join(grammar_name);
+    grammar_path.set_extension("wasm");
+    grammar_path
+}
+
+fn load_grammar(
+    grammar_name: &str,
+    grammar_path: &Path,
+    wasm_store: &mut WasmStore,
+) -> Result<Language> {
+    let wasm = fs::read(grammar_path)
+        .with_context(|| format!("failed to read compiled grammar '{grammar_name}'"))?;
+    let language = wasm_store
+        .load_language(grammar_name, &wasm)
+        .with_context(|| format!("failed to load grammar '{grammar_name}'"))?;
+    log::info!("loaded grammar {grammar_name}");
+    Ok(language)
+}
+
+/// Checks the languages of the extension and their queries. The queries of languages
+/// whose grammar is in `unchecked_grammars` are skipped.
 fn test_languages(
     manifest: &ExtensionManifest,
     extension_path: &Path,
     grammars: &HashMap<String, Language>,
+    unchecked_grammars: &HashSet<Arc<str>>,
 ) -> Result<()> {
     for relative_language_dir in &manifest.languages {
         let language_dir = extension_path.join(relative_language_dir);
         let config_path = language_dir.join("config.toml");
         let config_content = fs::read_to_string(&config_path)?;
         let config: LanguageConfig = toml::from_str(&config_content)?;
+        if let Some(name) = config
+            .grammar
+            .as_ref()
+            .filter(|&name| unchecked_grammars.contains(name))
+        {
+            log::warn!(
+                "skipped the queries of language {}, as grammar '{name}' could not be loaded",
+                config.name
+            );
+            continue;
+        }
         let grammar = if let Some(name) = &config.grammar {
             Some(
                 grammars
@@ -0,0 +1,81 @@
+use extension::{ExtensionCapabilities, ExtensionManifest, ExtensionStore};
+use gpui::{AppContext, PromptLevel, Task};
+
+pub(crate) fn init(cx: &mut AppContext) {
+    ExtensionStore::global(cx).update(cx, |store, _| {
+        store.set_capability_consent_prompt(prompt_for_capabilities);
+    });
+}
+
+/// Asks the user, in the active window, whether the extension may use the capabilities it requests.
+fn prompt_for_capabilities(manifest: &ExtensionManifest, cx: &mut AppContext) -> Task<bool> {
+    let Some(window) = cx
+        .active_window()
+        .or_else(|| cx.windows().into_iter().next())
+    else {
+        return Task::ready(false);
+    };
+
+    let message = format!(
+        "Allow the {} extension to use the following capabilities?",
+        manifest.name
+    );
+    let detail = describe_capabilities(&manifest.requested_capabilities());
+    let answer = window.update(cx, |_, cx| {
+        cx.prompt(
+            PromptLevel::Warning,
+            &message,
+            Some(&detail),
+            &["Allow", "Cancel"],
+        )
+    });
+
+    cx.foreground_executor().spawn(async move {
+        match answer {
+            Ok(answer) => matches!(answer.await, Ok(0)),
+            Err(_) => false,
+        }
+    })
+}
+
+fn describe_capabilities(capabilities: &ExtensionCapabilities) -> String {
+    let mut lines = Vec::new();
+
+    if capabilities.download_hosts.iter().any(|host| host == "*") {
+        lines.push("• Download files from any host".to_string());
+    } else if !capabilities.download_hosts.is_empty() {
+        lines.push(format!(
+            "• Download files from {}",
+            capabilities.download_hosts.join(", ")
+        ));
+    }
+
+    if capabilities
+        .npm_packages
+        .iter()
+        .any(|package| package == "*")
+    {
+        lines.push("• Install any npm package".to_string());
+    } else if !capabilities.npm_packages.is_empty() {
+        lines.push(format!(
+            "• Install the npm packages {}",
+            capabilities.npm_packages.join(", ")
+        ));
+    }
+
+    if capabilities.allows_writing(".".as_ref()) {
+        lines.push("• Write and execute files anywhere in its work directory".to_string());
+    } else if !capabilities.writable_paths.is_empty() {
+        let paths = capabilities
+            .writable_paths
+            .iter()
+            .map(|path| path.to_string_lossy())
+            .collect::<Vec<_>>();
+        lines.push(format!(
+            "• Write and execute files in {} within its work directory",
+            paths.join(", ")
+        ));
+    }
+
+    lines.join("\n")
+}
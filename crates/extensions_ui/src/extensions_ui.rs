@@ -1,3 +1,4 @@
+mod capability_consent;
 mod components;
 mod extension_suggest;
 mod extension_version_selector;
@@ -31,6 +32,8 @@ use workspace::{
 actions!(zed, [Extensions, InstallDevExtension]);
 
 pub fn init(cx: &mut AppContext) {
+    capability_consent::init(cx);
+
     cx.observe_new_views(move |workspace: &mut Workspace, cx| {
         workspace
             .register_action(move |workspace, _: &Extensions, cx| {
@@ -13,7 +13,7 @@ use crate::{
         SyntaxLayer, SyntaxMap, SyntaxMapCapture, SyntaxMapCaptures, SyntaxMapMatches,
         SyntaxSnapshot, ToTreeSitterPoint,
     },
-    LanguageScope, Outline,
+    LanguageScope, Outline, RunnableRange, TextObject,
 };
 use anyhow::{anyhow, Context, Result};
 pub use clock::ReplicaId;
@@ -2972,6 +2972,126 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the ranges of any text objects intersecting the given range, as
+    /// captured by the `textobjects` query of each language in the buffer.
+    pub fn text_object_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, TextObject)> + '_ {
+        let range = range.start.to_offset(self).saturating_sub(1)
+            ..self.len().min(range.end.to_offset(self) + 1);
+
+        let mut matches = self.syntax.matches(range, &self.text, |grammar| {
+            grammar.text_object_config.as_ref().map(|c| &c.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.text_object_config.as_ref())
+            .collect::<Vec<_>>();
+
+        let mut captures = Vec::<(Range<usize>, TextObject)>::new();
+        iter::from_fn(move || loop {
+            if let Some(capture) = captures.pop() {
+                return Some(capture);
+            }
+
+            let mat = matches.peek()?;
+            let Some(config) = configs[mat.grammar_index] else {
+                matches.advance();
+                continue;
+            };
+
+            // A single match may contain several captures for the same text
+            // object (e.g. a comment made of consecutive line comments), so
+            // merge them into one range per text object.
+            for capture in mat.captures {
+                let Some((_, text_object)) = config
+                    .text_objects_by_capture_ix
+                    .iter()
+                    .find(|(ix, _)| *ix == capture.index)
+                else {
+                    continue;
+                };
+                let node_range = capture.node.byte_range();
+                if let Some((range, _)) = captures
+                    .iter_mut()
+                    .find(|(_, existing)| existing == text_object)
+                {
+                    range.start = range.start.min(node_range.start);
+                    range.end = range.end.max(node_range.end);
+                } else {
+                    captures.push((node_range, *text_object));
+                }
+            }
+
+            matches.advance();
+        })
+    }
+
+    /// Returns the runnables intersecting the given range, as captured by the
+    /// `runnables` query of each language in the buffer.
+    pub fn runnable_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = RunnableRange> + '_ {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+
+        let mut matches = self.syntax.matches(range, &self.text, |grammar| {
+            grammar.runnable_config.as_ref().map(|c| &c.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.runnable_config.as_ref())
+            .collect::<Vec<_>>();
+
+        iter::from_fn(move || loop {
+            let mat = matches.peek()?;
+            let Some(config) = configs[mat.grammar_index] else {
+                matches.advance();
+                continue;
+            };
+
+            let mut range = None::<Range<usize>>;
+            let mut run_range = None;
+            let mut extra_captures = Vec::new();
+            for capture in mat.captures {
+                let node_range = capture.node.byte_range();
+                range = Some(match range {
+                    Some(range) => range.start.min(node_range.start)..range.end.max(node_range.end),
+                    None => node_range.clone(),
+                });
+                if capture.index == config.run_capture_ix {
+                    run_range = Some(node_range);
+                } else if let Some((_, name)) = config
+                    .extra_captures
+                    .iter()
+                    .find(|(ix, _)| *ix == capture.index)
+                {
+                    extra_captures.push((name.clone(), node_range));
+                }
+            }
+            let tags = config
+                .query
+                .property_settings(mat.pattern_index)
+                .iter()
+                .filter(|property| &*property.key == "tag")
+                .filter_map(|property| Some(property.value.as_ref()?.to_string()))
+                .collect();
+            matches.advance();
+
+            if let Some((range, run_range)) = range.zip(run_range) {
+                return Some(RunnableRange {
+                    range,
+                    run_range,
+                    tags,
+                    extra_captures,
+                });
+            }
+        })
+    }
+
     /// Returns selections for remote peers intersecting the given range.
     #[allow(clippy::type_complexity)]
     pub fn remote_selections_in_range(
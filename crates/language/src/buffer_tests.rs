@@ -769,6 +769,132 @@ async fn test_outline_with_extra_context(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_text_object_ranges(cx: &mut gpui::TestAppContext) {
+    let language = rust_lang()
+        .with_text_object_query(
+            r#"
+            (function_item
+                body: (_
+                    "{"
+                    (_)* @function.inside
+                    "}")) @function.around
+            (line_comment)+ @comment.around
+            "#,
+        )
+        .unwrap();
+
+    let text = r#"
+        // one
+        // two
+        fn a() {
+            b();
+        }
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(language), cx)
+    });
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+
+    let mut ranges = snapshot
+        .text_object_ranges(0..snapshot.len())
+        .map(|(range, text_object)| {
+            (
+                snapshot.text_for_range(range).collect::<String>(),
+                text_object,
+            )
+        })
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|(text, _)| text.len());
+    assert_eq!(
+        ranges
+            .iter()
+            .map(|(text, text_object)| (text.as_str(), *text_object))
+            .collect::<Vec<_>>(),
+        &[
+            ("b();", TextObject::InsideFunction),
+            ("// one\n// two", TextObject::AroundComment),
+            ("fn a() {\n    b();\n}", TextObject::AroundFunction),
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_runnable_ranges(cx: &mut gpui::TestAppContext) {
+    let language = rust_lang()
+        .with_runnable_query(
+            r#"
+            (
+                (attribute_item (attribute (identifier) @_attribute))
+                .
+                (function_item name: (_) @run) @_function
+                (#eq? @_attribute "test")
+                (#set! tag rust-test)
+            )
+            (
+                (mod_item name: (_) @run @module)
+                (#set! tag rust-mod-test)
+            )
+            "#,
+        )
+        .unwrap();
+
+    let text = r#"
+        mod tests {
+            #[test]
+            fn one() {}
+
+            fn helper() {}
+        }
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| {
+        Buffer::new(0, BufferId::new(cx.entity_id().as_u64()).unwrap(), text)
+            .with_language(Arc::new(language), cx)
+    });
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+
+    let mut runnables = snapshot
+        .runnable_ranges(0..snapshot.len())
+        .map(|runnable| {
+            (
+                snapshot
+                    .text_for_range(runnable.run_range)
+                    .collect::<String>(),
+                snapshot.text_for_range(runnable.range).collect::<String>(),
+                runnable.tags,
+                runnable
+                    .extra_captures
+                    .into_iter()
+                    .map(|(name, range)| (name, snapshot.text_for_range(range).collect::<String>()))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+    runnables.sort();
+    assert_eq!(
+        runnables,
+        vec![
+            (
+                "one".to_string(),
+                "#[test]\n    fn one() {}".to_string(),
+                vec!["rust-test".to_string()],
+                vec![],
+            ),
+            (
+                "tests".to_string(),
+                "tests".to_string(),
+                vec!["rust-mod-test".to_string()],
+                vec![("module".to_string(), "tests".to_string())],
+            ),
+        ]
+    );
+}
+
 #[gpui::test]
 async fn test_symbols_containing(cx: &mut gpui::TestAppContext) {
     let text = r#"
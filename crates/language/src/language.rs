@@ -13,6 +13,7 @@ mod language_registry;
 pub mod language_settings;
 mod outline;
 pub mod proto;
+mod snippet_registry;
 mod syntax_map;
 mod task_context;
 
@@ -71,6 +72,7 @@ pub use language_registry::{
 };
 pub use lsp::LanguageServerId;
 pub use outline::{Outline, OutlineItem};
+pub use snippet_registry::SnippetDefinition;
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer};
 pub use text::LineEnding;
 pub use tree_sitter::{Parser, Tree};
@@ -197,8 +199,15 @@ impl CachedLspAdapter {
         self.adapter.code_action_kinds()
     }
 
-    pub fn workspace_configuration(&self, workspace_root: &Path, cx: &mut AppContext) -> Value {
-        self.adapter.workspace_configuration(workspace_root, cx)
+    pub async fn workspace_configuration(
+        &self,
+        delegate: &Arc<dyn LspAdapterDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Value> {
+        self.adapter
+            .clone()
+            .workspace_configuration(delegate, cx)
+            .await
     }
 
     pub fn process_diagnostics(&self, params: &mut lsp::PublishDiagnosticsParams) {
@@ -241,6 +250,8 @@ pub trait LspAdapterDelegate: Send + Sync {
     fn http_client(&self) -> Arc<dyn HttpClient>;
     fn update_status(&self, language: LanguageServerName, status: LanguageServerBinaryStatus);
 
+    fn worktree_root_path(&self) -> &Path;
+
     async fn which(&self, command: &OsStr) -> Option<PathBuf>;
     async fn shell_env(&self) -> HashMap<String, String>;
     async fn read_text_file(&self, path: PathBuf) -> Result<String>;
@@ -441,8 +452,14 @@ pub trait LspAdapter: 'static + Send + Sync {
         Ok(None)
     }
 
-    fn workspace_configuration(&self, _workspace_root: &Path, _cx: &mut AppContext) -> Value {
-        serde_json::json!({})
+    /// Returns the `workspace/configuration` settings sent to the LSP server, both on startup
+    /// and whenever the settings change.
+    async fn workspace_configuration(
+        self: Arc<Self>,
+        _: &Arc<dyn LspAdapterDelegate>,
+        _cx: &mut AsyncAppContext,
+    ) -> Result<Value> {
+        Ok(serde_json::json!({}))
     }
 
     /// Returns a list of code actions supported by a given LspAdapter
@@ -501,6 +518,47 @@ async fn try_fetch_server_binary<L: LspAdapter + 'static + Send + Sync + ?Sized>
     binary
 }
 
+/// A formatter that runs on the text of a buffer without a language server, such as
+/// one provided by an extension.
+///
+/// It is used when the `formatter` setting is set to `{"extension": "<name>"}`.
+#[async_trait(?Send)]
+pub trait BufferFormatter: 'static + Send + Sync {
+    fn name(&self) -> Arc<str>;
+
+    /// Returns the edits that format the given text, as byte ranges into that text.
+    async fn format(
+        &self,
+        text: String,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<Vec<(Range<usize>, String)>>;
+}
+
+/// A linter that runs on the text of a buffer without a language server, such as
+/// one provided by an extension.
+///
+/// Linters run when a buffer is opened and whenever it is saved.
+#[async_trait(?Send)]
+pub trait BufferLinter: 'static + Send + Sync {
+    fn name(&self) -> Arc<str>;
+
+    async fn lint(
+        &self,
+        text: String,
+        delegate: Arc<dyn LspAdapterDelegate>,
+    ) -> Result<Vec<LintDiagnostic>>;
+}
+
+/// A diagnostic reported by a [`BufferLinter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintDiagnostic {
+    /// The byte range of the linted text this diagnostic applies to.
+    pub range: Range<usize>,
+    pub severity: lsp::DiagnosticSeverity,
+    pub message: String,
+    pub code: Option<String>,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CodeLabel {
     /// The text to display.
@@ -801,6 +859,8 @@ pub struct Grammar {
     pub embedding_config: Option<EmbeddingConfig>,
     pub(crate) injection_config: Option<InjectionConfig>,
     pub(crate) override_config: Option<OverrideConfig>,
+    pub(crate) text_object_config: Option<TextObjectConfig>,
+    pub(crate) runnable_config: Option<RunnableConfig>,
     pub(crate) highlight_map: Mutex<HighlightMap>,
 }
 
@@ -847,6 +907,72 @@ struct OverrideConfig {
     values: HashMap<u32, (String, LanguageConfigOverride)>,
 }
 
+/// A structural region of a buffer, as captured by a language's `textobjects.scm` query.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextObject {
+    InsideFunction,
+    AroundFunction,
+    InsideClass,
+    AroundClass,
+    InsideParameter,
+    AroundParameter,
+    InsideComment,
+    AroundComment,
+}
+
+impl TextObject {
+    pub fn from_capture_name(name: &str) -> Option<TextObject> {
+        match name {
+            "function.inside" => Some(TextObject::InsideFunction),
+            "function.around" => Some(TextObject::AroundFunction),
+            "class.inside" => Some(TextObject::InsideClass),
+            "class.around" => Some(TextObject::AroundClass),
+            "parameter.inside" => Some(TextObject::InsideParameter),
+            "parameter.around" => Some(TextObject::AroundParameter),
+            "comment.inside" => Some(TextObject::InsideComment),
+            "comment.around" => Some(TextObject::AroundComment),
+            _ => None,
+        }
+    }
+
+    /// Returns the text object that contains this one, e.g. `AroundFunction` for `InsideFunction`.
+    pub fn around(&self) -> TextObject {
+        match self {
+            TextObject::InsideFunction | TextObject::AroundFunction => TextObject::AroundFunction,
+            TextObject::InsideClass | TextObject::AroundClass => TextObject::AroundClass,
+            TextObject::InsideParameter | TextObject::AroundParameter => {
+                TextObject::AroundParameter
+            }
+            TextObject::InsideComment | TextObject::AroundComment => TextObject::AroundComment,
+        }
+    }
+}
+
+struct TextObjectConfig {
+    query: Query,
+    text_objects_by_capture_ix: Vec<(u32, TextObject)>,
+}
+
+struct RunnableConfig {
+    query: Query,
+    run_capture_ix: u32,
+    /// The captures whose text is passed to tasks, i.e. those not starting with an underscore.
+    extra_captures: Vec<(u32, String)>,
+}
+
+/// Something that can be run by a task, as captured by a language's `runnables.scm` query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunnableRange {
+    /// The range spanning all the captures of the runnable.
+    pub range: Range<usize>,
+    /// The range of the `@run` capture, on whose line the run indicator is shown.
+    pub run_range: Range<usize>,
+    /// Tags set with `(#set! tag ...)`, that select the tasks able to run this runnable.
+    pub tags: Vec<String>,
+    /// The other captures of the runnable by name, which tasks can use as `ZED_CUSTOM_<name>` variables.
+    pub extra_captures: Vec<(String, Range<usize>)>,
+}
+
 #[derive(Default, Clone)]
 struct InjectionPatternConfig {
     language: Option<Box<str>>,
@@ -883,6 +1009,8 @@ impl Language {
                     injection_config: None,
                     override_config: None,
                     redactions_config: None,
+                    text_object_config: None,
+                    runnable_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
@@ -938,6 +1066,16 @@ impl Language {
                 .with_redaction_query(query.as_ref())
                 .context("Error loading redaction query")?;
         }
+        if let Some(query) = queries.text_objects {
+            self = self
+                .with_text_object_query(query.as_ref())
+                .context("Error loading text object query")?;
+        }
+        if let Some(query) = queries.runnables {
+            self = self
+                .with_runnable_query(query.as_ref())
+                .context("Error loading runnable query")?;
+        }
         Ok(self)
     }
 
@@ -1209,6 +1347,52 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_text_object_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut text_objects_by_capture_ix = Vec::new();
+        for (ix, name) in query.capture_names().iter().enumerate() {
+            if let Some(text_object) = TextObject::from_capture_name(name) {
+                text_objects_by_capture_ix.push((ix as u32, text_object));
+            }
+        }
+
+        grammar.text_object_config = Some(TextObjectConfig {
+            query,
+            text_objects_by_capture_ix,
+        });
+        Ok(self)
+    }
+
+    pub fn with_runnable_query(mut self, source: &str) -> Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut run_capture_ix = None;
+        let mut extra_captures = Vec::new();
+        for (ix, name) in query.capture_names().iter().enumerate() {
+            match &**name {
+                "run" => run_capture_ix = Some(ix as u32),
+                name if name.starts_with('_') => {}
+                name => extra_captures.push((ix as u32, name.to_string())),
+            }
+        }
+
+        if let Some(run_capture_ix) = run_capture_ix {
+            grammar.runnable_config = Some(RunnableConfig {
+                query,
+                run_capture_ix,
+                extra_captures,
+            });
+        }
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> Option<&mut Grammar> {
         Arc::get_mut(self.grammar.as_mut()?)
     }
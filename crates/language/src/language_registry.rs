@@ -1,7 +1,8 @@
 use crate::{
-    language_settings::all_language_settings, task_context::ContextProvider, CachedLspAdapter,
-    File, Language, LanguageConfig, LanguageId, LanguageMatcher, LanguageServerName, LspAdapter,
-    LspAdapterDelegate, PARSER, PLAIN_TEXT,
+    language_settings::all_language_settings, snippet_registry::SnippetRegistry,
+    task_context::ContextProvider, BufferFormatter, BufferLinter, CachedLspAdapter, File, Language,
+    LanguageConfig, LanguageId, LanguageMatcher, LanguageServerName, LspAdapter,
+    LspAdapterDelegate, SnippetDefinition, PARSER, PLAIN_TEXT,
 };
 use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, HashMap};
@@ -41,6 +42,9 @@ struct LanguageRegistryState {
     available_languages: Vec<AvailableLanguage>,
     grammars: HashMap<Arc<str>, AvailableGrammar>,
     lsp_adapters: HashMap<Arc<str>, Vec<Arc<CachedLspAdapter>>>,
+    formatters: HashMap<Arc<str>, Vec<Arc<dyn BufferFormatter>>>,
+    linters: HashMap<Arc<str>, Vec<Arc<dyn BufferLinter>>>,
+    snippets: SnippetRegistry,
     loading_languages: HashMap<LanguageId, Vec<oneshot::Sender<Result<Arc<Language>>>>>,
     subscription: (watch::Sender<()>, watch::Receiver<()>),
     theme: Option<Arc<Theme>>,
@@ -117,6 +121,8 @@ pub const QUERY_FILENAME_PREFIXES: &[(
     ("injections", |q| &mut q.injections),
     ("overrides", |q| &mut q.overrides),
     ("redactions", |q| &mut q.redactions),
+    ("textobjects", |q| &mut q.text_objects),
+    ("runnables", |q| &mut q.runnables),
 ];
 
 /// Tree-sitter language queries for a given language.
@@ -130,6 +136,8 @@ pub struct LanguageQueries {
     pub injections: Option<Cow<'static, str>>,
     pub overrides: Option<Cow<'static, str>>,
     pub redactions: Option<Cow<'static, str>>,
+    pub text_objects: Option<Cow<'static, str>>,
+    pub runnables: Option<Cow<'static, str>>,
 }
 
 #[derive(Clone, Default)]
@@ -147,6 +155,9 @@ impl LanguageRegistry {
                 grammars: Default::default(),
                 loading_languages: Default::default(),
                 lsp_adapters: Default::default(),
+                formatters: Default::default(),
+                linters: Default::default(),
+                snippets: Default::default(),
                 subscription: watch::channel(),
                 theme: Default::default(),
                 version: 0,
@@ -197,6 +208,55 @@ impl LanguageRegistry {
         *state.subscription.0.borrow_mut() = ();
     }
 
+    pub fn register_formatter(&self, language_name: Arc<str>, formatter: Arc<dyn BufferFormatter>) {
+        self.state
+            .write()
+            .formatters
+            .entry(language_name)
+            .or_default()
+            .push(formatter);
+    }
+
+    pub fn remove_formatter(&self, language_name: &str, name: &str) {
+        if let Some(formatters) = self.state.write().formatters.get_mut(language_name) {
+            formatters.retain(|formatter| formatter.name().as_ref() != name)
+        }
+    }
+
+    pub fn register_linter(&self, language_name: Arc<str>, linter: Arc<dyn BufferLinter>) {
+        self.state
+            .write()
+            .linters
+            .entry(language_name)
+            .or_default()
+            .push(linter);
+    }
+
+    pub fn remove_linter(&self, language_name: &str, name: &str) {
+        if let Some(linters) = self.state.write().linters.get_mut(language_name) {
+            linters.retain(|linter| linter.name().as_ref() != name)
+        }
+    }
+
+    /// Registers snippets provided by the given source, for the given languages,
+    /// or for every language if `languages` is empty.
+    pub fn register_snippets(
+        &self,
+        source: Arc<str>,
+        languages: Vec<Arc<str>>,
+        snippets: Vec<SnippetDefinition>,
+    ) {
+        self.state
+            .write()
+            .snippets
+            .register(source, languages, snippets);
+    }
+
+    /// Removes all of the snippets registered by the given source.
+    pub fn remove_snippets(&self, source: &str) {
+        self.state.write().snippets.remove(source);
+    }
+
     #[cfg(any(feature = "test-support", test))]
     pub fn register_test_language(&self, config: LanguageConfig) {
         self.register_language(
@@ -668,6 +728,38 @@ impl LanguageRegistry {
             .unwrap_or_default()
     }
 
+    /// Returns the formatter with the given name registered for the given language.
+    pub fn formatter(
+        &self,
+        language: &Arc<Language>,
+        name: &str,
+    ) -> Option<Arc<dyn BufferFormatter>> {
+        self.state
+            .read()
+            .formatters
+            .get(&language.config.name)?
+            .iter()
+            .find(|formatter| formatter.name().as_ref() == name)
+            .cloned()
+    }
+
+    pub fn linters(&self, language: &Arc<Language>) -> Vec<Arc<dyn BufferLinter>> {
+        self.state
+            .read()
+            .linters
+            .get(&language.config.name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns the snippets that apply to the given language.
+    pub fn snippets(&self, language: &Arc<Language>) -> Vec<Arc<SnippetDefinition>> {
+        self.state
+            .read()
+            .snippets
+            .snippets_for_language(&language.config.name)
+    }
+
     pub fn all_prettier_plugins(&self) -> Vec<Arc<str>> {
         let state = self.state.read();
         state
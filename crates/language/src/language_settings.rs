@@ -295,6 +295,8 @@ pub enum FormatOnSave {
     },
     /// Files should be formatted using code actions executed by language servers.
     CodeActions(HashMap<String, bool>),
+    /// Files should be formatted using the formatter with the given name, provided by an extension.
+    Extension(Arc<str>),
 }
 
 /// Controls how whitespace should be displayedin the editor.
@@ -330,6 +332,8 @@ pub enum Formatter {
     },
     /// Files should be formatted using code actions executed by language servers.
     CodeActions(HashMap<String, bool>),
+    /// Format code using the formatter with the given name, provided by an extension.
+    Extension(Arc<str>),
 }
 
 /// The settings for inlay hints.
@@ -0,0 +1,157 @@
+use anyhow::{Context, Result};
+use collections::BTreeMap;
+use serde::Deserialize;
+use snippet::Snippet;
+use std::sync::Arc;
+use util::ResultExt;
+
+/// A named snippet that can be offered as a completion, e.g. one loaded from an extension.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SnippetDefinition {
+    pub name: String,
+    /// The words that trigger this snippet when typed.
+    pub prefixes: Vec<String>,
+    /// The snippet body, in LSP snippet syntax.
+    pub body: String,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SingleOrMany {
+    Single(String),
+    Many(Vec<String>),
+}
+
+impl SingleOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::Single(value) => vec![value],
+            Self::Many(values) => values,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SnippetFileEntry {
+    prefix: SingleOrMany,
+    body: SingleOrMany,
+    #[serde(default)]
+    description: Option<SingleOrMany>,
+}
+
+impl SnippetDefinition {
+    /// Parses the contents of a snippet file, in the JSON format used by VS Code.
+    ///
+    /// Snippets whose bodies can't be parsed are logged and skipped.
+    pub fn parse_file(contents: &str) -> Result<Vec<Self>> {
+        let entries: BTreeMap<String, SnippetFileEntry> =
+            serde_json_lenient::from_str(contents).context("invalid snippet file")?;
+        Ok(entries
+            .into_iter()
+            .filter_map(|(name, entry)| {
+                let body = entry.body.into_vec().join("\n");
+                Snippet::parse(&body)
+                    .with_context(|| format!("invalid body for snippet '{name}'"))
+                    .log_err()?;
+                Some(Self {
+                    name,
+                    prefixes: entry.prefix.into_vec(),
+                    body,
+                    description: entry
+                        .description
+                        .map(|description| description.into_vec().join("\n")),
+                })
+            })
+            .collect())
+    }
+}
+
+struct SnippetSet {
+    source: Arc<str>,
+    languages: Vec<Arc<str>>,
+    snippets: Vec<Arc<SnippetDefinition>>,
+}
+
+/// The snippets available to the editor, grouped by the source that provided them.
+#[derive(Default)]
+pub(crate) struct SnippetRegistry {
+    sets: Vec<SnippetSet>,
+}
+
+impl SnippetRegistry {
+    /// Registers snippets for the given languages, or for every language if `languages` is empty.
+    pub fn register(
+        &mut self,
+        source: Arc<str>,
+        languages: Vec<Arc<str>>,
+        snippets: Vec<SnippetDefinition>,
+    ) {
+        self.sets.push(SnippetSet {
+            source,
+            languages,
+            snippets: snippets.into_iter().map(Arc::new).collect(),
+        });
+    }
+
+    pub fn remove(&mut self, source: &str) {
+        self.sets.retain(|set| set.source.as_ref() != source);
+    }
+
+    pub fn snippets_for_language(&self, language_name: &str) -> Vec<Arc<SnippetDefinition>> {
+        self.sets
+            .iter()
+            .filter(|set| {
+                set.languages.is_empty()
+                    || set
+                        .languages
+                        .iter()
+                        .any(|language| language.as_ref() == language_name)
+            })
+            .flat_map(|set| set.snippets.iter().cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_snippet_file() {
+        let snippets = SnippetDefinition::parse_file(
+            r#"{
+                // Comments are allowed, as in VS Code.
+                "Print": {
+                    "prefix": ["print", "log"],
+                    "body": ["println!(\"$1\");", "$0"],
+                    "description": "Print a line"
+                },
+                "Invalid": {
+                    "prefix": "bad",
+                    "body": "${1:unterminated"
+                },
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            snippets,
+            vec![SnippetDefinition {
+                name: "Print".into(),
+                prefixes: vec!["print".into(), "log".into()],
+                body: "println!(\"$1\");\n$0".into(),
+                description: Some("Print a line".into()),
+            }]
+        );
+
+        let mut registry = SnippetRegistry::default();
+        registry.register("ext-a".into(), vec!["Rust".into()], snippets.clone());
+        registry.register("ext-b".into(), Vec::new(), snippets);
+        assert_eq!(registry.snippets_for_language("Rust").len(), 2);
+        assert_eq!(registry.snippets_for_language("Python").len(), 1);
+
+        registry.remove("ext-b");
+        assert_eq!(registry.snippets_for_language("Rust").len(), 1);
+        assert!(registry.snippets_for_language("Python").is_empty());
+    }
+}
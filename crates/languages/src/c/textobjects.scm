@@ -0,0 +1,25 @@
+(function_definition
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(struct_specifier
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(enum_specifier
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(parameter_list
+    (_) @parameter.inside)
+
+(argument_list
+    (_) @parameter.inside)
+
+(comment)+ @comment.around
@@ -1,15 +1,12 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use gpui::AppContext;
+use gpui::AsyncAppContext;
 use language::{LanguageServerName, LspAdapter, LspAdapterDelegate};
 use lsp::LanguageServerBinary;
 use project::project_settings::ProjectSettings;
 use serde_json::Value;
 use settings::Settings;
-use std::{
-    any::Any,
-    path::{Path, PathBuf},
-};
+use std::{any::Any, path::PathBuf, sync::Arc};
 
 pub struct DartLanguageServer;
 
@@ -55,15 +52,21 @@ impl LspAdapter for DartLanguageServer {
         None
     }
 
-    fn workspace_configuration(&self, _workspace_root: &Path, cx: &mut AppContext) -> Value {
-        let settings = ProjectSettings::get_global(cx)
-            .lsp
-            .get("dart")
-            .and_then(|s| s.settings.clone())
-            .unwrap_or_default();
+    async fn workspace_configuration(
+        self: Arc<Self>,
+        _: &Arc<dyn LspAdapterDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Value> {
+        let settings = cx.update(|cx| {
+            ProjectSettings::get_global(cx)
+                .lsp
+                .get("dart")
+                .and_then(|s| s.settings.clone())
+                .unwrap_or_default()
+        })?;
 
-        serde_json::json!({
+        Ok(serde_json::json!({
             "dart": settings
-        })
+        }))
     }
 }
@@ -0,0 +1,19 @@
+(class_definition) @class.around
+
+(enum_declaration) @class.around
+
+((function_signature) @function.around
+    .
+    (function_body) @function.around)
+
+((getter_signature) @function.around
+    .
+    (function_body) @function.around)
+
+((setter_signature) @function.around
+    .
+    (function_body) @function.around)
+
+(comment)+ @comment.around
+
+(documentation_comment)+ @comment.around
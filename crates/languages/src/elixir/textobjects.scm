@@ -0,0 +1,22 @@
+(call
+    target: (identifier) @_keyword
+    (do_block
+        "do"
+        (_)* @function.inside
+        "end")
+    (#match? @_keyword "^(def|defp|defdelegate|defguard|defguardp|defmacro|defmacrop|defn|defnp)$")) @function.around
+
+(anonymous_function
+    "fn"
+    (_)* @function.inside
+    "end") @function.around
+
+(call
+    target: (identifier) @_keyword
+    (do_block
+        "do"
+        (_)* @class.inside
+        "end")
+    (#match? @_keyword "^(defmodule|defprotocol|defimpl)$")) @class.around
+
+(comment)+ @comment.around
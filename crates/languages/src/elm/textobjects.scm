@@ -0,0 +1,17 @@
+(value_declaration
+    (eq)
+    .
+    (_) @function.inside) @function.around
+
+(function_declaration_left
+    .
+    (lower_case_identifier)
+    (_) @parameter.inside)
+
+(type_declaration) @class.around
+
+(type_alias_declaration) @class.around
+
+(line_comment)+ @comment.around
+
+(block_comment) @comment.around
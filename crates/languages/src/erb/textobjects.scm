@@ -0,0 +1 @@
+(comment_directive) @comment.around
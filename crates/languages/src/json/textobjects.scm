@@ -0,0 +1,12 @@
+(object
+    "{"
+    (_)* @class.inside
+    "}") @class.around
+
+(object
+    (pair) @parameter.inside)
+
+(array
+    (_) @parameter.inside)
+
+(comment)+ @comment.around
@@ -53,47 +53,7 @@ pub fn init(
     ElixirSettings::register(cx);
     DenoSettings::register(cx);
 
-    languages.register_native_grammars([
-        ("bash", tree_sitter_bash::language()),
-        ("c", tree_sitter_c::language()),
-        ("cpp", tree_sitter_cpp::language()),
-        ("css", tree_sitter_css::language()),
-        ("elixir", tree_sitter_elixir::language()),
-        ("elm", tree_sitter_elm::language()),
-        (
-            "embedded_template",
-            tree_sitter_embedded_template::language(),
-        ),
-        ("glsl", tree_sitter_glsl::language()),
-        ("go", tree_sitter_go::language()),
-        ("gomod", tree_sitter_gomod::language()),
-        ("gowork", tree_sitter_gowork::language()),
-        ("hcl", tree_sitter_hcl::language()),
-        ("heex", tree_sitter_heex::language()),
-        ("jsdoc", tree_sitter_jsdoc::language()),
-        ("json", tree_sitter_json::language()),
-        ("lua", tree_sitter_lua::language()),
-        ("markdown", tree_sitter_markdown::language()),
-        ("nix", tree_sitter_nix::language()),
-        ("nu", tree_sitter_nu::language()),
-        ("ocaml", tree_sitter_ocaml::language_ocaml()),
-        (
-            "ocaml_interface",
-            tree_sitter_ocaml::language_ocaml_interface(),
-        ),
-        ("proto", tree_sitter_proto::language()),
-        ("python", tree_sitter_python::language()),
-        ("racket", tree_sitter_racket::language()),
-        ("regex", tree_sitter_regex::language()),
-        ("ruby", tree_sitter_ruby::language()),
-        ("rust", tree_sitter_rust::language()),
-        ("scheme", tree_sitter_scheme::language()),
-        ("tsx", tree_sitter_typescript::language_tsx()),
-        ("typescript", tree_sitter_typescript::language_typescript()),
-        ("vue", tree_sitter_vue::language()),
-        ("yaml", tree_sitter_yaml::language()),
-        ("dart", tree_sitter_dart::language()),
-    ]);
+    languages.register_native_grammars(native_grammars());
 
     macro_rules! language {
         ($name:literal) => {
@@ -332,6 +292,50 @@ pub fn init(
     );
 }
 
+fn native_grammars() -> Vec<(&'static str, tree_sitter::Language)> {
+    vec![
+        ("bash", tree_sitter_bash::language()),
+        ("c", tree_sitter_c::language()),
+        ("cpp", tree_sitter_cpp::language()),
+        ("css", tree_sitter_css::language()),
+        ("elixir", tree_sitter_elixir::language()),
+        ("elm", tree_sitter_elm::language()),
+        (
+            "embedded_template",
+            tree_sitter_embedded_template::language(),
+        ),
+        ("glsl", tree_sitter_glsl::language()),
+        ("go", tree_sitter_go::language()),
+        ("gomod", tree_sitter_gomod::language()),
+        ("gowork", tree_sitter_gowork::language()),
+        ("hcl", tree_sitter_hcl::language()),
+        ("heex", tree_sitter_heex::language()),
+        ("jsdoc", tree_sitter_jsdoc::language()),
+        ("json", tree_sitter_json::language()),
+        ("lua", tree_sitter_lua::language()),
+        ("markdown", tree_sitter_markdown::language()),
+        ("nix", tree_sitter_nix::language()),
+        ("nu", tree_sitter_nu::language()),
+        ("ocaml", tree_sitter_ocaml::language_ocaml()),
+        (
+            "ocaml_interface",
+            tree_sitter_ocaml::language_ocaml_interface(),
+        ),
+        ("proto", tree_sitter_proto::language()),
+        ("python", tree_sitter_python::language()),
+        ("racket", tree_sitter_racket::language()),
+        ("regex", tree_sitter_regex::language()),
+        ("ruby", tree_sitter_ruby::language()),
+        ("rust", tree_sitter_rust::language()),
+        ("scheme", tree_sitter_scheme::language()),
+        ("tsx", tree_sitter_typescript::language_tsx()),
+        ("typescript", tree_sitter_typescript::language_typescript()),
+        ("vue", tree_sitter_vue::language()),
+        ("yaml", tree_sitter_yaml::language()),
+        ("dart", tree_sitter_dart::language()),
+    ]
+}
+
 #[cfg(any(test, feature = "test-support"))]
 pub fn language(name: &str, grammar: tree_sitter::Language) -> Arc<Language> {
     Arc::new(
@@ -375,3 +379,30 @@ fn load_queries(name: &str) -> LanguageQueries {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_text_object_queries() {
+        let grammars = native_grammars().into_iter().collect::<HashMap<_, _>>();
+        for path in LanguageDir::iter() {
+            let Some(name) = path.strip_suffix("/textobjects.scm") else {
+                continue;
+            };
+            let config = load_config(name);
+            let grammar_name = config
+                .grammar
+                .unwrap_or_else(|| panic!("language {name:?} has text objects but no grammar"));
+            let grammar = grammars
+                .get(grammar_name.as_ref())
+                .unwrap_or_else(|| panic!("no grammar named {grammar_name:?}"));
+            let source = asset_str::<LanguageDir>(path.as_ref());
+            if let Err(error) = tree_sitter::Query::new(grammar, &source) {
+                panic!("invalid textobjects query for language {name:?}: {error}");
+            }
+        }
+    }
+}
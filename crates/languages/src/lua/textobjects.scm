@@ -0,0 +1,14 @@
+(function_declaration
+    body: (block) @function.inside) @function.around
+
+(function_declaration) @function.around
+
+(function_definition
+    body: (block) @function.inside) @function.around
+
+(function_definition) @function.around
+
+(parameters
+    (_) @parameter.inside)
+
+(comment)+ @comment.around
@@ -0,0 +1,5 @@
+; Markdown has no functions or classes of its own, so fenced code blocks stand
+; in for classes. The code inside them gets its text objects from the injected
+; language.
+(fenced_code_block
+    (code_fence_content) @class.inside) @class.around
@@ -0,0 +1,8 @@
+(function_expression
+    ":"
+    .
+    (_) @function.inside) @function.around
+
+(formal) @parameter.inside
+
+(comment)+ @comment.around
@@ -0,0 +1,22 @@
+(decl_def
+    (block
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(decl_module
+    (block
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(parameter_bracks
+    (_) @parameter.inside)
+
+(parameter_parens
+    (_) @parameter.inside)
+
+(parameter_pipes
+    (_) @parameter.inside)
+
+(comment)+ @comment.around
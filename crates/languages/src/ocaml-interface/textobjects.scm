@@ -0,0 +1,11 @@
+(value_specification) @function.around
+
+(module_definition) @class.around
+
+(module_type_definition) @class.around
+
+(type_definition) @class.around
+
+(class_definition) @class.around
+
+(comment)+ @comment.around
@@ -0,0 +1,17 @@
+(value_definition
+    (let_binding
+        body: (_) @function.inside)) @function.around
+
+(module_definition
+    (module_binding
+        (structure) @class.inside)) @class.around
+
+(module_definition) @class.around
+
+(type_definition) @class.around
+
+(class_definition) @class.around
+
+(parameter) @parameter.inside
+
+(comment)+ @comment.around
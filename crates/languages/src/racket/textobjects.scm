@@ -0,0 +1,50 @@
+((list
+    .
+    (symbol) @_define
+    .
+    (list)
+    (_)* @function.inside
+    .) @function.around
+    (#match? @_define "^define$"))
+
+((list
+    .
+    (symbol) @_lambda
+    .
+    (list)
+    (_)* @function.inside
+    .) @function.around
+    (#match? @_lambda "^(lambda|λ)$"))
+
+((list
+    .
+    (symbol) @_define
+    .
+    (list
+        .
+        (symbol)
+        (_) @parameter.inside))
+    (#match? @_define "^define$"))
+
+((list
+    .
+    (symbol) @_lambda
+    .
+    (list
+        (_) @parameter.inside))
+    (#match? @_lambda "^(lambda|λ)$"))
+
+((list
+    .
+    (symbol) @_struct
+    .
+    (symbol)
+    (_)* @class.inside
+    .) @class.around
+    (#match? @_struct "^(struct|define-struct)$"))
+
+(comment)+ @comment.around
+
+(block_comment) @comment.around
+
+(sexp_comment) @comment.around
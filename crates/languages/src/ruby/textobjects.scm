@@ -0,0 +1,22 @@
+(method) @function.around
+
+(singleton_method) @function.around
+
+(lambda) @function.around
+
+(class) @class.around
+
+(singleton_class) @class.around
+
+(module) @class.around
+
+(method_parameters
+    (_) @parameter.inside)
+
+(lambda_parameters
+    (_) @parameter.inside)
+
+(argument_list
+    (_) @parameter.inside)
+
+(comment)+ @comment.around
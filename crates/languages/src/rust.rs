@@ -371,12 +371,14 @@ impl ContextProvider for RustContextProvider {
                     "--".into(),
                     "$ZED_SYMBOL".into(),
                 ],
+                tags: vec!["rust-test".into()],
                 ..Default::default()
             },
             Definition {
                 label: "Rust: cargo run".into(),
                 command: "cargo".into(),
                 args: vec!["run".into()],
+                tags: vec!["rust-main".into()],
                 ..Default::default()
             },
             Definition {
@@ -0,0 +1,20 @@
+; Test functions, e.g. `#[test]`, `#[tokio::test]` or `#[gpui::test]`
+(
+    (attribute_item
+        (attribute
+            [
+                ((identifier) @_attribute)
+                (scoped_identifier name: (identifier) @_attribute)
+            ]))
+    .
+    (function_item name: (_) @run) @_rust_test
+    (#eq? @_attribute "test")
+    (#set! tag rust-test)
+)
+
+; The entry point of a binary
+(
+    (function_item name: (_) @run) @_rust_main
+    (#eq? @run "main")
+    (#set! tag rust-main)
+)
@@ -0,0 +1,57 @@
+(function_item
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(closure_expression
+    body: (_) @function.inside) @function.around
+
+(struct_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(enum_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(union_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(trait_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(impl_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(mod_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(parameters
+    (_) @parameter.inside)
+
+(closure_parameters
+    (_) @parameter.inside)
+
+(arguments
+    (_) @parameter.inside)
+
+(line_comment)+ @comment.around
+
+(block_comment) @comment.around
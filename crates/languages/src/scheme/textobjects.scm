@@ -0,0 +1,48 @@
+((list
+    .
+    (symbol) @_define
+    .
+    (list)
+    (_)* @function.inside
+    .) @function.around
+    (#match? @_define "^define$"))
+
+((list
+    .
+    (symbol) @_lambda
+    .
+    (list)
+    (_)* @function.inside
+    .) @function.around
+    (#match? @_lambda "^(lambda|λ)$"))
+
+((list
+    .
+    (symbol) @_define
+    .
+    (list
+        .
+        (symbol)
+        (_) @parameter.inside))
+    (#match? @_define "^define$"))
+
+((list
+    .
+    (symbol) @_lambda
+    .
+    (list
+        (_) @parameter.inside))
+    (#match? @_lambda "^(lambda|λ)$"))
+
+((list
+    .
+    (symbol) @_record
+    .
+    (symbol)
+    (_)* @class.inside
+    .) @class.around
+    (#match? @_record "^define-record-type$"))
+
+(comment)+ @comment.around
+
+(block_comment) @comment.around
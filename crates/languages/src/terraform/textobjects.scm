@@ -0,0 +1,6 @@
+(block
+    (body) @class.inside) @class.around
+
+(block) @class.around
+
+(comment)+ @comment.around
@@ -0,0 +1,7 @@
+(start_tag
+    [(attribute) (directive_attribute)] @parameter.inside)
+
+(self_closing_tag
+    [(attribute) (directive_attribute)] @parameter.inside)
+
+(comment) @comment.around
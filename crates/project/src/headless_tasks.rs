@@ -0,0 +1,193 @@
+use crate::{load_shell_environment, Project};
+use anyhow::Context as _;
+use futures::{io::BufReader, stream, stream::BoxStream, AsyncBufReadExt, AsyncRead, StreamExt};
+use gpui::{Model, ModelContext, Task};
+use language::Buffer;
+use settings::Settings;
+use smol::process::{Command, Stdio};
+use task::{SpawnInTerminal, TaskId};
+use terminal::terminal_settings::{Shell, TerminalSettings};
+use util::ResultExt;
+
+/// A task, spawned in the background without a terminal.
+pub struct HeadlessTaskRun {
+    pub id: TaskId,
+    pub label: String,
+    pub status: HeadlessTaskStatus,
+    /// Everything the task has printed to its stdout and stderr so far.
+    pub output: Model<Buffer>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeadlessTaskStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl Project {
+    /// The latest run of every task spawned in the background.
+    pub fn headless_task_runs(&self) -> &[HeadlessTaskRun] {
+        &self.headless_task_runs
+    }
+
+    pub fn dismiss_headless_task_run(&mut self, id: &TaskId, cx: &mut ModelContext<Self>) {
+        self.headless_task_runs
+            .retain(|run| &run.id != id || run.status == HeadlessTaskStatus::Running);
+        cx.notify();
+    }
+
+    /// Runs the task's command in the user's shell, collecting its output into a buffer instead of a terminal.
+    /// Resolves to whether the command succeeded.
+    pub fn run_headless_task(
+        &mut self,
+        spawn_in_terminal: &SpawnInTerminal,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<bool>> {
+        let id = spawn_in_terminal.id.clone();
+        let label = spawn_in_terminal.label.clone();
+        if !spawn_in_terminal.allow_concurrent_runs
+            && self
+                .headless_task_runs
+                .iter()
+                .any(|run| run.id == id && run.status == HeadlessTaskStatus::Running)
+        {
+            return Task::ready(Err(anyhow::anyhow!("Task {label:?} is already running")));
+        }
+        let output = match self.create_buffer("", None, cx) {
+            Ok(output) => output,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let settings = TerminalSettings::get_global(cx);
+        let (shell, mut shell_args) = match settings.shell.clone() {
+            Shell::System => (
+                std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string()),
+                Vec::new(),
+            ),
+            Shell::Program(shell) => (shell, Vec::new()),
+            Shell::WithArguments { program, args } => (program, args),
+        };
+        let mut command_line = spawn_in_terminal.command.clone();
+        for arg in &spawn_in_terminal.args {
+            command_line.push(' ');
+            command_line.push_str(arg);
+        }
+        // Unlike in a terminal tab (`-i -c`), the shell is not interactive: without a terminal,
+        // interactive shells warn about job control on every run, which would end up in the output.
+        // The command gets the environment of the user's login shell instead, loaded below.
+        shell_args.extend(["-c".to_owned(), command_line]);
+
+        let mut env = settings.env.clone();
+        env.extend(spawn_in_terminal.env.clone());
+        let working_directory = spawn_in_terminal.cwd.clone().or_else(|| {
+            self.visible_worktrees(cx)
+                .next()
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+        });
+        let python_settings = settings.detect_venv.clone();
+        if let Some((python_settings, working_directory)) =
+            python_settings.as_option().zip(working_directory.as_ref())
+        {
+            self.set_python_venv_path_for_tasks(&python_settings, working_directory, &mut env);
+        }
+
+        let problem_matchers = spawn_in_terminal.problem_matchers.clone();
+        let problems_server_id = (!problem_matchers.is_empty())
+            .then(|| self.start_task_problems(&label, working_directory.as_deref(), cx));
+        self.headless_task_runs.retain(|run| run.id != id);
+        self.headless_task_runs.push(HeadlessTaskRun {
+            id: id.clone(),
+            label: label.clone(),
+            status: HeadlessTaskStatus::Running,
+            output: output.clone(),
+        });
+        cx.notify();
+
+        cx.spawn(|project, mut cx| async move {
+            let run = async {
+                let mut command = Command::new(shell);
+                if let Some(working_directory) = &working_directory {
+                    if let Some(shell_env) =
+                        load_shell_environment(working_directory).await.log_err()
+                    {
+                        command.envs(shell_env);
+                    }
+                    command.current_dir(working_directory);
+                }
+                command
+                    .args(shell_args)
+                    .envs(env)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .kill_on_drop(true);
+                let mut child = command
+                    .spawn()
+                    .with_context(|| format!("spawning task {label:?}"))?;
+                let stdout = output_lines(child.stdout.take().context("taking stdout")?);
+                let stderr = output_lines(child.stderr.take().context("taking stderr")?);
+                let mut lines = stream::select(stdout, stderr);
+                while let Some(line) = lines.next().await {
+                    let mut line = line?;
+                    if !line.ends_with('\n') {
+                        line.push('\n');
+                    }
+                    output.update(&mut cx, |output, cx| {
+                        let end = output.len();
+                        output.edit([(end..end, line)], None, cx);
+                    })?;
+                }
+                anyhow::Ok(child.status().await?.success())
+            };
+            let result = run.await;
+            let succeeded = *result.as_ref().unwrap_or(&false);
+
+            let output_text = output.update(&mut cx, |output, _| output.text())?;
+            let report_problems = project.update(&mut cx, |project, cx| {
+                if let Some(run) = project
+                    .headless_task_runs
+                    .iter_mut()
+                    .find(|run| run.id == id && run.output == output)
+                {
+                    run.status = if succeeded {
+                        HeadlessTaskStatus::Succeeded
+                    } else {
+                        HeadlessTaskStatus::Failed
+                    };
+                }
+                cx.notify();
+                problems_server_id.map(|server_id| {
+                    project.finish_task_problems(
+                        label,
+                        server_id,
+                        problem_matchers,
+                        output_text,
+                        working_directory,
+                        cx,
+                    )
+                })
+            })?;
+            if let Some(report_problems) = report_problems {
+                report_problems.await?;
+            }
+            result
+        })
+    }
+}
+
+/// Reads the lines of a task's output as they come, decoding them lossily, since tasks may print
+/// anything.
+fn output_lines(
+    output: impl AsyncRead + Send + Unpin + 'static,
+) -> BoxStream<'static, std::io::Result<String>> {
+    stream::unfold(BufReader::new(output), |mut reader| async move {
+        let mut line = Vec::new();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) => None,
+            Ok(_) => Some((Ok(String::from_utf8_lossy(&line).into_owned()), reader)),
+            Err(error) => Some((Err(error), reader)),
+        }
+    })
+    .boxed()
+}
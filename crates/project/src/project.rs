@@ -1,9 +1,12 @@
 pub mod debounced_delay;
+pub mod headless_tasks;
 pub mod lsp_command;
 pub mod lsp_ext_command;
 mod prettier_support;
 pub mod project_settings;
 pub mod search;
+pub mod search_index;
+pub mod structural_search;
 mod task_inventory;
 pub mod terminals;
 
@@ -64,11 +67,13 @@ use postage::watch;
 use prettier_support::{DefaultPrettier, PrettierInstance};
 use project_settings::{LspSettings, ProjectSettings};
 use rand::prelude::*;
-use search_history::SearchHistory;
+use search_history::{SavedSearch, SearchHistory};
 use worktree::LocalSnapshot;
 
+use headless_tasks::HeadlessTaskRun;
 use rpc::{ErrorCode, ErrorExt as _};
 use search::SearchQuery;
+use search_index::{SearchIndex, SearchIndexFilter, WorktreeSearchIndex};
 use serde::Serialize;
 use settings::{watch_config_file, Settings, SettingsLocation, SettingsStore};
 use sha2::{Digest, Sha256};
@@ -101,7 +106,8 @@ use util::{
     http::{HttpClient, Url},
     maybe, merge_json_value_into,
     paths::{
-        LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH, LOCAL_VSCODE_TASKS_RELATIVE_PATH,
+        LOCAL_SEARCHES_RELATIVE_PATH, LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH,
+        LOCAL_VSCODE_TASKS_RELATIVE_PATH,
     },
     post_inc, ResultExt, TryFutureExt as _,
 };
@@ -127,6 +133,10 @@ pub const SERVER_PROGRESS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100
 
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
 
+/// The server id assigned to completions that come from registered snippets,
+/// rather than from a language server.
+pub const SNIPPET_COMPLETION_SERVER_ID: LanguageServerId = LanguageServerId(usize::MAX);
+
 pub trait Item {
     fn try_open(
         project: &Model<Project>,
@@ -194,22 +204,28 @@ pub struct Project {
     local_buffer_ids_by_entry_id: HashMap<ProjectEntryId, BufferId>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
     buffers_being_formatted: HashSet<BufferId>,
+    /// The ids under which the diagnostics of each linter are reported, by linter name.
+    linter_server_ids: HashMap<Arc<str>, LanguageServerId>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
     git_diff_debouncer: DebouncedDelay,
     nonce: u128,
     _maintain_buffer_languages: Task<()>,
     _maintain_workspace_config: Task<Result<()>>,
     terminals: Terminals,
+    headless_task_runs: Vec<HeadlessTaskRun>,
     copilot_lsp_subscription: Option<gpui::Subscription>,
     copilot_log_subscription: Option<lsp::Subscription>,
     current_lsp_settings: HashMap<Arc<str>, LspSettings>,
     node: Option<Arc<dyn NodeRuntime>>,
     default_prettier: DefaultPrettier,
     prettiers_per_worktree: HashMap<WorktreeId, HashSet<Option<PathBuf>>>,
+    search_indices: HashMap<WorktreeId, WorktreeSearchIndex>,
     prettier_instances: HashMap<PathBuf, PrettierInstance>,
     tasks: Model<Inventory>,
     hosted_project_id: Option<ProjectId>,
     search_history: SearchHistory,
+    /// Saved searches by the worktree and absolute path of the `.zed/searches.json` they are read from.
+    saved_searches: BTreeMap<(WorktreeId, PathBuf), Vec<SavedSearch>>,
 }
 
 pub enum LanguageServerToQuery {
@@ -333,6 +349,7 @@ pub enum Event {
     CollaboratorLeft(proto::PeerId),
     RefreshInlayHints,
     RevealInProjectPanel(ProjectEntryId),
+    BufferSaved(ProjectPath),
 }
 
 pub enum LanguageServerState {
@@ -539,6 +556,7 @@ enum FormatOperation {
     Lsp(Vec<(Range<Anchor>, String)>),
     External(Diff),
     Prettier(Diff),
+    Extension(Vec<(Range<Anchor>, String)>),
 }
 
 impl FormatTrigger {
@@ -709,22 +727,27 @@ impl Project {
                 last_workspace_edits_by_language_server: Default::default(),
                 language_server_watched_paths: HashMap::default(),
                 buffers_being_formatted: Default::default(),
+                linter_server_ids: Default::default(),
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
+                headless_task_runs: Vec::new(),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 node: Some(node),
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
+                search_indices: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
                 hosted_project_id: None,
                 search_history: Self::new_search_history(),
+                saved_searches: BTreeMap::default(),
             }
         })
     }
@@ -844,23 +867,28 @@ impl Project {
                 language_server_watched_paths: HashMap::default(),
                 opened_buffers: Default::default(),
                 buffers_being_formatted: Default::default(),
+                linter_server_ids: Default::default(),
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
+                    task_diagnostics: HashMap::default(),
                 },
+                headless_task_runs: Vec::new(),
                 copilot_lsp_subscription,
                 copilot_log_subscription: None,
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
                 node: None,
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
+                search_indices: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
                 hosted_project_id: None,
                 search_history: Self::new_search_history(),
+                saved_searches: BTreeMap::default(),
             };
             this.set_role(role, cx);
             for worktree in worktrees {
@@ -1190,6 +1218,59 @@ impl Project {
         &self.tasks
     }
 
+    /// The searches saved in the `.zed/searches.json` files of the local worktrees.
+    pub fn saved_searches(&self) -> impl Iterator<Item = &SavedSearch> {
+        self.saved_searches.values().flatten()
+    }
+
+    /// Writes a search to the `.zed/searches.json` file of the first visible local worktree,
+    /// replacing the one saved under the same name. The file gets reloaded once the worktree notices the change.
+    pub fn save_search(
+        &mut self,
+        saved_search: SavedSearch,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(worktree_abs_path) = self
+            .visible_worktrees(cx)
+            .find_map(|worktree| Some(worktree.read(cx).as_local()?.abs_path().clone()))
+        else {
+            return Task::ready(Err(anyhow!("no local worktree to save the search to")));
+        };
+        let abs_path = worktree_abs_path.join(&*LOCAL_SEARCHES_RELATIVE_PATH);
+        let fs = self.fs.clone();
+        cx.background_executor().spawn(async move {
+            let mut contents = if fs.is_file(&abs_path).await {
+                fs.load(&abs_path).await?
+            } else {
+                String::new()
+            };
+            if contents.trim().is_empty() {
+                contents = serde_json::to_string_pretty(&[saved_search])?;
+            } else {
+                // Edit the file in place, as it is shared and may hold comments.
+                let saved_searches = serde_json_lenient::from_str::<Vec<SavedSearch>>(&contents)
+                    .with_context(|| format!("parsing saved searches at {abs_path:?}"))?;
+                let index = saved_searches
+                    .iter()
+                    .position(|existing_search| existing_search.name == saved_search.name)
+                    .unwrap_or(saved_searches.len());
+                let (range, new_text) = settings::replace_top_level_array_element_in_json_text(
+                    &contents,
+                    index,
+                    2,
+                    &serde_json::to_value(&saved_search)?,
+                )
+                .with_context(|| format!("saved searches at {abs_path:?} are not an array"))?;
+                contents.replace_range(range, &new_text);
+            }
+
+            if let Some(directory) = abs_path.parent() {
+                fs.create_dir(directory).await?;
+            }
+            fs.atomic_write(abs_path, contents).await
+        })
+    }
+
     pub fn search_history(&self) -> &SearchHistory {
         &self.search_history
     }
@@ -2321,6 +2402,82 @@ impl Project {
                         .insert(server.server_id(), vec![snapshot]);
                 }
             }
+
+            self.lint_buffer(buffer_handle, cx);
+        }
+    }
+
+    /// Runs the linters registered for the buffer's language, replacing their diagnostics for it.
+    fn lint_buffer(&mut self, buffer_handle: &Model<Buffer>, cx: &mut ModelContext<Self>) {
+        let buffer = buffer_handle.read(cx);
+        let Some(language) = buffer.language() else {
+            return;
+        };
+        let linters = self.languages.linters(language);
+        if linters.is_empty() {
+            return;
+        }
+        let Some(file) = File::from_dyn(buffer.file()) else {
+            return;
+        };
+        let Some(abs_path) = file.as_local().map(|file| file.abs_path(cx)) else {
+            return;
+        };
+        let snapshot = buffer.text_snapshot();
+        let delegate: Arc<dyn LspAdapterDelegate> =
+            ProjectLspAdapterDelegate::new(self, &file.worktree, cx);
+
+        for linter in linters {
+            let linter_name = linter.name();
+            let server_id = *self
+                .linter_server_ids
+                .entry(linter_name.clone())
+                .or_insert_with(|| self.languages.next_language_server_id());
+            let abs_path = abs_path.clone();
+            let snapshot = snapshot.clone();
+            let delegate = delegate.clone();
+            let buffer = buffer_handle.downgrade();
+            cx.spawn(move |this, mut cx| async move {
+                let diagnostics = linter
+                    .lint(snapshot.text(), delegate)
+                    .await
+                    .with_context(|| format!("failed to run linter {linter_name:?}"))?;
+                this.update(&mut cx, |this, cx| {
+                    // The ranges are offsets into the linted text, which edits made since then invalidate.
+                    // The next save lints the buffer again.
+                    if let Some(buffer) = buffer.upgrade() {
+                        if buffer.read(cx).version() != *snapshot.version() {
+                            return Ok(());
+                        }
+                    }
+                    let entries = diagnostics
+                        .into_iter()
+                        .map(|diagnostic| {
+                            let start = snapshot.offset_to_point_utf16(
+                                snapshot.clip_offset(diagnostic.range.start, Bias::Left),
+                            );
+                            let end = snapshot.offset_to_point_utf16(
+                                snapshot.clip_offset(diagnostic.range.end, Bias::Right),
+                            );
+                            DiagnosticEntry {
+                                range: Unclipped(start)..Unclipped(end),
+                                diagnostic: Diagnostic {
+                                    source: Some(linter_name.to_string()),
+                                    code: diagnostic.code,
+                                    severity: diagnostic.severity,
+                                    message: diagnostic.message,
+                                    group_id: post_inc(&mut this.next_diagnostic_group_id),
+                                    is_primary: true,
+                                    is_disk_based: false,
+                                    is_unnecessary: false,
+                                },
+                            }
+                        })
+                        .collect();
+                    this.update_diagnostic_entries(server_id, abs_path, None, entries, cx)
+                })?
+            })
+            .detach_and_log_err(cx);
         }
     }
 
@@ -2600,11 +2757,17 @@ impl Project {
             BufferEvent::Saved => {
                 let file = File::from_dyn(buffer.read(cx).file())?;
                 let worktree_id = file.worktree_id(cx);
+                cx.emit(Event::BufferSaved(ProjectPath {
+                    worktree_id,
+                    path: file.path.clone(),
+                }));
                 let abs_path = file.as_local()?.abs_path(cx);
                 let text_document = lsp::TextDocumentIdentifier {
                     uri: lsp::Url::from_file_path(abs_path).unwrap(),
                 };
 
+                self.lint_buffer(&buffer, cx);
+
                 for (_, _, server) in self.language_servers_for_worktree(worktree_id) {
                     let text = include_text(server.as_ref()).then(|| buffer.read(cx).text());
 
@@ -2857,21 +3020,33 @@ impl Project {
 
         cx.spawn(move |this, mut cx| async move {
             while let Some(()) = settings_changed_rx.next().await {
-                let servers: Vec<_> = this.update(&mut cx, |this, _| {
-                    this.language_servers
-                        .values()
-                        .filter_map(|state| match state {
-                            LanguageServerState::Starting(_) => None,
-                            LanguageServerState::Running {
-                                adapter, server, ..
-                            } => Some((adapter.clone(), server.clone())),
+                let servers: Vec<_> = this.update(&mut cx, |this, cx| {
+                    this.language_server_ids
+                        .iter()
+                        .filter_map(|((worktree_id, _), server_id)| {
+                            let worktree = this.worktree_for_id(*worktree_id, cx)?;
+                            match this.language_servers.get(server_id)? {
+                                LanguageServerState::Starting(_) => None,
+                                LanguageServerState::Running {
+                                    adapter, server, ..
+                                } => {
+                                    let delegate: Arc<dyn LspAdapterDelegate> =
+                                        ProjectLspAdapterDelegate::new(this, &worktree, cx);
+                                    Some((adapter.clone(), server.clone(), delegate))
+                                }
+                            }
                         })
                         .collect()
                 })?;
 
-                for (adapter, server) in servers {
-                    let settings =
-                        cx.update(|cx| adapter.workspace_configuration(server.root_path(), cx))?;
+                for (adapter, server, delegate) in servers {
+                    let Some(settings) = adapter
+                        .workspace_configuration(&delegate, &mut cx)
+                        .await
+                        .log_err()
+                    else {
+                        continue;
+                    };
 
                     server
                         .notify::<lsp::notification::DidChangeConfiguration>(
@@ -2985,12 +3160,13 @@ impl Project {
         }
 
         let stderr_capture = Arc::new(Mutex::new(Some(String::new())));
+        let lsp_adapter_delegate = ProjectLspAdapterDelegate::new(self, worktree_handle, cx);
         let pending_server = match self.languages.create_pending_language_server(
             stderr_capture.clone(),
             language.clone(),
             adapter.clone(),
             Arc::clone(&worktree_path),
-            ProjectLspAdapterDelegate::new(self, worktree_handle, cx),
+            lsp_adapter_delegate.clone(),
             cx,
         ) {
             Some(pending_server) => pending_server,
@@ -3018,7 +3194,7 @@ impl Project {
             cx.spawn(move |this, mut cx| async move {
                 let result = Self::setup_and_insert_language_server(
                     this.clone(),
-                    &worktree_path,
+                    lsp_adapter_delegate,
                     override_options,
                     pending_server,
                     adapter.clone(),
@@ -3142,7 +3318,7 @@ impl Project {
     #[allow(clippy::too_many_arguments)]
     async fn setup_and_insert_language_server(
         this: WeakModel<Self>,
-        worktree_path: &Path,
+        delegate: Arc<dyn LspAdapterDelegate>,
         override_initialization_options: Option<serde_json::Value>,
         pending_server: PendingLanguageServer,
         adapter: Arc<CachedLspAdapter>,
@@ -3155,7 +3331,7 @@ impl Project {
             this.clone(),
             override_initialization_options,
             pending_server,
-            worktree_path,
+            delegate,
             adapter.clone(),
             server_id,
             cx,
@@ -3185,13 +3361,12 @@ impl Project {
         this: WeakModel<Self>,
         override_options: Option<serde_json::Value>,
         pending_server: PendingLanguageServer,
-        worktree_path: &Path,
+        delegate: Arc<dyn LspAdapterDelegate>,
         adapter: Arc<CachedLspAdapter>,
         server_id: LanguageServerId,
         cx: &mut AsyncAppContext,
     ) -> Result<Arc<LanguageServer>> {
-        let workspace_config =
-            cx.update(|cx| adapter.workspace_configuration(worktree_path, cx))?;
+        let workspace_config = adapter.workspace_configuration(&delegate, cx).await?;
         let (language_server, mut initialization_options) = pending_server.task.await?;
 
         let name = language_server.name();
@@ -3221,13 +3396,13 @@ impl Project {
         language_server
             .on_request::<lsp::request::WorkspaceConfiguration, _, _>({
                 let adapter = adapter.clone();
-                let worktree_path = worktree_path.to_path_buf();
-                move |params, cx| {
+                let delegate = delegate.clone();
+                move |params, mut cx| {
                     let adapter = adapter.clone();
-                    let worktree_path = worktree_path.clone();
+                    let delegate = delegate.clone();
                     async move {
                         let workspace_config =
-                            cx.update(|cx| adapter.workspace_configuration(&worktree_path, cx))?;
+                            adapter.workspace_configuration(&delegate, &mut cx).await?;
                         Ok(params
                             .items
                             .into_iter()
@@ -4702,6 +4877,18 @@ impl Project {
                         format_operation = Some(new_operation);
                     }
                 }
+                (Formatter::Extension(formatter_name), FormatOnSave::On | FormatOnSave::Off)
+                | (_, FormatOnSave::Extension(formatter_name)) => {
+                    format_operation = Some(FormatOperation::Extension(
+                        Self::format_via_extension(&project, buffer, formatter_name, &mut cx)
+                            .await
+                            .with_context(|| {
+                                format!(
+                                    "failed to format via extension formatter {formatter_name:?}"
+                                )
+                            })?,
+                    ));
+                }
             };
 
             buffer.update(&mut cx, |b, cx| {
@@ -4729,6 +4916,9 @@ impl Project {
                         FormatOperation::Prettier(diff) => {
                             b.apply_diff(diff, cx);
                         }
+                        FormatOperation::Extension(edits) => {
+                            b.edit(edits, None, cx);
+                        }
                     }
 
                     if let Some(transaction_id) = whitespace_transaction_id {
@@ -4750,6 +4940,61 @@ impl Project {
         Ok(project_transaction)
     }
 
+    async fn format_via_extension(
+        this: &WeakModel<Self>,
+        buffer: &Model<Buffer>,
+        formatter_name: &str,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Vec<(Range<Anchor>, String)>> {
+        let (formatter, delegate, snapshot) = this.update(cx, |this, cx| {
+            let buffer = buffer.read(cx);
+            let language = buffer
+                .language()
+                .ok_or_else(|| anyhow!("buffer has no language"))?;
+            let formatter = this
+                .languages
+                .formatter(language, formatter_name)
+                .ok_or_else(|| anyhow!("no formatter is registered for {}", language.name()))?;
+            let worktree = File::from_dyn(buffer.file())
+                .ok_or_else(|| anyhow!("buffer has no file"))?
+                .worktree
+                .clone();
+            let delegate: Arc<dyn LspAdapterDelegate> =
+                ProjectLspAdapterDelegate::new(this, &worktree, cx);
+            anyhow::Ok((formatter, delegate, buffer.snapshot()))
+        })??;
+
+        let mut edits = formatter.format(snapshot.text(), delegate).await?;
+        // Extensions may return their edits in any order, but they must not overlap.
+        edits.sort_by_key(|(range, _)| range.start);
+        let mut previous_range: Option<&Range<usize>> = None;
+        for (range, _) in &edits {
+            if range.start > range.end {
+                return Err(anyhow!("formatter returned a reversed range {range:?}"));
+            }
+            if let Some(previous_range) = previous_range {
+                if range.start < previous_range.end {
+                    return Err(anyhow!(
+                        "formatter returned overlapping edits at {previous_range:?} and {range:?}"
+                    ));
+                }
+            }
+            previous_range = Some(range);
+        }
+
+        Ok(edits
+            .into_iter()
+            .map(|(range, new_text)| {
+                let start = snapshot.clip_offset(range.start, Bias::Left);
+                let end = snapshot.clip_offset(range.end, Bias::Right);
+                (
+                    snapshot.anchor_before(start)..snapshot.anchor_after(end),
+                    new_text,
+                )
+            })
+            .collect())
+    }
+
     async fn format_via_lsp(
         this: &WeakModel<Self>,
         buffer: &Model<Buffer>,
@@ -5393,7 +5638,80 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Vec<Completion>>> {
         let position = position.to_point_utf16(buffer.read(cx));
-        self.completions_impl(buffer, position, cx)
+        let snippet_completions = self.snippet_completions(buffer, position, cx);
+        let completions = self.completions_impl(buffer, position, cx);
+        cx.background_executor().spawn(async move {
+            let mut completions = match completions.await {
+                Ok(completions) => completions,
+                // Snippets don't need the language servers, so they are offered when these fail.
+                Err(error) if !snippet_completions.is_empty() => {
+                    log::error!("failed to fetch completions: {error:#}");
+                    Vec::new()
+                }
+                Err(error) => return Err(error),
+            };
+            completions.extend(snippet_completions);
+            Ok(completions)
+        })
+    }
+
+    /// Returns completions for the registered snippets whose prefixes match
+    /// the word before the given position.
+    fn snippet_completions(
+        &self,
+        buffer: &Model<Buffer>,
+        position: PointUtf16,
+        cx: &AppContext,
+    ) -> Vec<Completion> {
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = position.to_offset(&snapshot);
+        let Some(language) = snapshot.language_at(offset) else {
+            return Vec::new();
+        };
+        let snippets = self.languages.snippets(language);
+        if snippets.is_empty() {
+            return Vec::new();
+        }
+
+        let (word_range, _) = snapshot.surrounding_word(offset);
+        let word_start = word_range.start.min(offset);
+        let typed_prefix = snapshot
+            .text_for_range(word_start..offset)
+            .collect::<String>();
+        if typed_prefix.is_empty() {
+            return Vec::new();
+        }
+
+        let old_range = snapshot.anchor_before(word_start)..snapshot.anchor_after(offset);
+        let mut completions = Vec::new();
+        for snippet in snippets {
+            for prefix in &snippet.prefixes {
+                if !prefix.starts_with(&typed_prefix) {
+                    continue;
+                }
+                completions.push(Completion {
+                    old_range: old_range.clone(),
+                    new_text: snippet.body.clone(),
+                    label: CodeLabel::plain(prefix.clone(), None),
+                    server_id: SNIPPET_COMPLETION_SERVER_ID,
+                    documentation: Some(
+                        snippet
+                            .description
+                            .clone()
+                            .map_or(Documentation::Undocumented, Documentation::SingleLine),
+                    ),
+                    lsp_completion: lsp::CompletionItem {
+                        label: prefix.clone(),
+                        kind: Some(lsp::CompletionItemKind::SNIPPET),
+                        detail: Some(snippet.name.clone()),
+                        insert_text: Some(snippet.body.clone()),
+                        insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+                        ..Default::default()
+                    },
+                });
+            }
+        }
+        completions
     }
 
     pub fn resolve_completions(
@@ -5576,6 +5894,10 @@ impl Project {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
 
+        if completion.server_id == SNIPPET_COMPLETION_SERVER_ID {
+            return Task::ready(Ok(None));
+        }
+
         if self.is_local() {
             let server_id = completion.server_id;
             let lang_server = match self.language_server_for_buffer(buffer, server_id, cx) {
@@ -6421,13 +6743,17 @@ impl Project {
         // This isn't as straightforward as running an insertion sort sadly, and would also mean that it would have to care about maintaining match index
         // in face of constantly updating list of sorted matches.
         // Meanwhile, this implementation offers index stability, since the matches are already reported in a sorted order.
-        let snapshots = self
+        let (snapshots, search_indices): (Vec<_>, Vec<_>) = self
             .visible_worktrees(cx)
             .filter_map(|tree| {
                 let tree = tree.read(cx).as_local()?;
-                Some(tree.snapshot())
+                let search_index = self
+                    .search_indices
+                    .get(&tree.id())
+                    .map(|search_index| search_index.index().clone());
+                Some((tree.snapshot(), search_index))
             })
-            .collect::<Vec<_>>();
+            .unzip();
         let include_root = snapshots.len() > 1;
 
         let background = cx.background_executor().clone();
@@ -6453,21 +6779,30 @@ impl Project {
             .iter()
             .filter_map(|(_, b)| {
                 let buffer = b.upgrade()?;
-                let (is_ignored, snapshot) = buffer.update(cx, |buffer, cx| {
-                    let is_ignored = buffer
-                        .project_path(cx)
-                        .and_then(|path| self.entry_for_path(&path, cx))
-                        .map_or(false, |entry| entry.is_ignored);
-                    (is_ignored, buffer.snapshot())
+                let (project_path, entry, snapshot) = buffer.update(cx, |buffer, cx| {
+                    let project_path = buffer.project_path(cx);
+                    let entry = project_path
+                        .as_ref()
+                        .and_then(|path| self.entry_for_path(path, cx));
+                    (project_path, entry, buffer.snapshot())
                 });
+                let is_ignored = entry.as_ref().map_or(false, |entry| entry.is_ignored);
                 if is_ignored && !query.include_ignored() {
                     return None;
                 } else if let Some(file) = snapshot.file() {
-                    let matched_path = if include_root {
-                        query.file_matches(Some(&file.full_path(cx)))
-                    } else {
-                        query.file_matches(Some(file.path()))
-                    };
+                    let in_scope = project_path.map_or(false, |project_path| {
+                        query.scope().contains_path(
+                            project_path.worktree_id,
+                            &project_path.path,
+                            entry.and_then(|entry| entry.git_status),
+                        )
+                    });
+                    let matched_path = in_scope
+                        && if include_root {
+                            query.file_matches(Some(&file.full_path(cx)))
+                        } else {
+                            query.file_matches(Some(file.path()))
+                        };
 
                     if matched_path {
                         Some((file.path().clone(), (buffer, snapshot)))
@@ -6475,11 +6810,20 @@ impl Project {
                         None
                     }
                 } else {
-                    unnamed_files.push(buffer);
+                    if query.scope().includes_untitled_buffers() {
+                        unnamed_files.push(buffer);
+                    }
                     None
                 }
             })
             .collect();
+        // Files that are not open are only looked into when the scope allows it,
+        // yet the opened buffers above are still searched.
+        let (snapshots, search_indices) = if query.scope().includes_unopened_files() {
+            (snapshots, search_indices)
+        } else {
+            (Vec::new(), Vec::new())
+        };
         cx.background_executor()
             .spawn(Self::background_search(
                 unnamed_files,
@@ -6491,6 +6835,7 @@ impl Project {
                 include_root,
                 path_count,
                 snapshots,
+                search_indices,
                 matching_paths_tx,
             ))
             .detach();
@@ -6535,8 +6880,15 @@ impl Project {
                         })?,
                     };
 
-                    chunk_results.push(cx.spawn(|cx| async move {
+                    let this = this.clone();
+                    chunk_results.push(cx.spawn(|mut cx| async move {
                         let buffer = buffer.await?;
+                        if query.is_structural() {
+                            this.update(&mut cx, |this, cx| {
+                                this.assign_language_for_structural_search(&buffer, cx)
+                            })?
+                            .await;
+                        }
                         let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
                         let ranges = cx
                             .background_executor()
@@ -6559,6 +6911,10 @@ impl Project {
                 let chunk_results = futures::future::join_all(chunk_results).await;
                 for result in chunk_results {
                     if let Some((buffer, ranges)) = result.log_err() {
+                        // Structural queries only pick files by the words they contain, which may still have no matches.
+                        if ranges.is_empty() && query.is_structural() {
+                            continue;
+                        }
                         range_count += ranges.len();
                         result_tx
                             .send(SearchResult::Buffer { buffer, ranges })
@@ -6582,6 +6938,35 @@ impl Project {
         result_rx
     }
 
+    /// Structural search matches the syntax trees of the buffers, which are only parsed once
+    /// their language is known, while buffers opened for a search may still be waiting for it to load.
+    fn assign_language_for_structural_search(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<()> {
+        let buffer_ref = buffer.read(cx);
+        if buffer_ref.language().is_some() {
+            return Task::ready(());
+        }
+        let Some(file) = buffer_ref.file() else {
+            return Task::ready(());
+        };
+        let language = self
+            .languages
+            .language_for_file(file, Some(buffer_ref.as_rope()), cx);
+        let buffer = buffer.clone();
+        cx.spawn(move |this, mut cx| async move {
+            let Ok(language) = language.await else {
+                return;
+            };
+            this.update(&mut cx, |this, cx| {
+                this.set_language_for_buffer(&buffer, language, cx)
+            })
+            .ok();
+        })
+    }
+
     /// Pick paths that might potentially contain a match of a given search query.
     #[allow(clippy::too_many_arguments)]
     async fn background_search(
@@ -6594,12 +6979,18 @@ impl Project {
         include_root: bool,
         path_count: usize,
         snapshots: Vec<LocalSnapshot>,
+        search_indices: Vec<Option<Arc<RwLock<SearchIndex>>>>,
         matching_paths_tx: Sender<SearchMatchCandidate>,
     ) {
         let fs = &fs;
         let query = &query;
         let matching_paths_tx = &matching_paths_tx;
         let snapshots = &snapshots;
+        let index_filters = search_indices
+            .iter()
+            .map(|search_index| SearchIndexFilter::new(search_index.as_ref()?, query))
+            .collect::<Vec<_>>();
+        let index_filters = &index_filters;
         for buffer in unnamed_buffers {
             matching_paths_tx
                 .send(SearchMatchCandidate::OpenBuffer {
@@ -6635,6 +7026,7 @@ impl Project {
                             let _guard = limiter.acquire().await;
                             search_snapshots(
                                 snapshots,
+                                index_filters,
                                 worker_start_ix,
                                 worker_end_ix,
                                 query,
@@ -6648,7 +7040,7 @@ impl Project {
                     });
                 }
 
-                if query.include_ignored() {
+                if query.include_ignored() && query.scope().includes_ignored_files() {
                     for snapshot in snapshots {
                         for ignored_entry in snapshot.entries(true).filter(|e| e.is_ignored) {
                             let limiter = Arc::clone(&max_concurrent_workers);
@@ -6963,6 +7355,9 @@ impl Project {
         self.task_inventory().update(cx, |inventory, _| {
             inventory.remove_worktree_sources(id_to_remove);
         });
+        self.search_indices.remove(&id_to_remove);
+        self.saved_searches
+            .retain(|(worktree_id, _), _| *worktree_id != id_to_remove);
 
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
@@ -6991,6 +7386,7 @@ impl Project {
                         this.update_local_worktree_language_servers(&worktree, changes, cx);
                         this.update_local_worktree_settings(&worktree, changes, cx);
                         this.update_prettier_settings(&worktree, changes, cx);
+                        this.update_local_worktree_search_index(&worktree, changes, cx);
                     }
 
                     cx.emit(Event::WorktreeUpdatedEntries(
@@ -7035,10 +7431,64 @@ impl Project {
         })
         .detach();
 
+        self.start_local_worktree_search_index(worktree, cx);
+
         cx.emit(Event::WorktreeAdded);
         self.metadata_changed(cx);
     }
 
+    fn start_local_worktree_search_index(
+        &mut self,
+        worktree: &Model<Worktree>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !ProjectSettings::get_global(cx).search_index.enabled {
+            return;
+        }
+        if !worktree.read(cx).is_visible() {
+            return;
+        }
+        let Some(local_worktree) = worktree.read(cx).as_local() else {
+            return;
+        };
+
+        let worktree_id = local_worktree.id();
+        let search_index = WorktreeSearchIndex::new(
+            &local_worktree.abs_path(),
+            self.fs.clone(),
+            cx.background_executor().clone(),
+        );
+        let scan_complete = local_worktree.scan_complete();
+        self.search_indices.insert(worktree_id, search_index);
+
+        let worktree = worktree.downgrade();
+        cx.spawn(move |this, mut cx| async move {
+            scan_complete.await;
+            this.update(&mut cx, |this, cx| {
+                let snapshot = worktree.upgrade()?.read(cx).as_local()?.snapshot();
+                this.search_indices.get(&worktree_id)?.reconcile(snapshot);
+                Some(())
+            })
+        })
+        .detach();
+    }
+
+    fn update_local_worktree_search_index(
+        &mut self,
+        worktree: &Model<Worktree>,
+        changes: &UpdatedEntriesSet,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let worktree = worktree.read(cx);
+        if let Some((search_index, local_worktree)) = self
+            .search_indices
+            .get(&worktree.id())
+            .zip(worktree.as_local())
+        {
+            search_index.update(local_worktree.snapshot(), changes.clone());
+        }
+    }
+
     fn update_local_worktree_buffers(
         &mut self,
         worktree_handle: &Model<Worktree>,
@@ -7422,6 +7872,25 @@ impl Project {
                         );
                     }
                 })
+            } else if abs_path.ends_with(&*LOCAL_SEARCHES_RELATIVE_PATH) {
+                let key = (remote_worktree_id, abs_path);
+                if removed {
+                    self.saved_searches.remove(&key);
+                } else {
+                    let fs = self.fs.clone();
+                    cx.spawn(move |this, mut cx| async move {
+                        let contents = fs.load(&key.1).await?;
+                        let saved_searches =
+                            serde_json_lenient::from_str::<Vec<SavedSearch>>(&contents)
+                                .with_context(|| {
+                                    format!("parsing saved searches at {:?}", key.1)
+                                })?;
+                        this.update(&mut cx, |this, _| {
+                            this.saved_searches.insert(key, saved_searches);
+                        })
+                    })
+                    .detach_and_log_err(cx);
+                }
             } else if abs_path.ends_with(&*LOCAL_VSCODE_TASKS_RELATIVE_PATH) {
                 self.task_inventory().update(cx, |task_inventory, cx| {
                     if removed {
@@ -9960,6 +10429,7 @@ fn deserialize_code_actions(code_actions: &HashMap<String, bool>) -> Vec<lsp::Co
 #[allow(clippy::too_many_arguments)]
 async fn search_snapshots(
     snapshots: &Vec<LocalSnapshot>,
+    index_filters: &[Option<SearchIndexFilter>],
     worker_start_ix: usize,
     worker_end_ix: usize,
     query: &SearchQuery,
@@ -9971,7 +10441,7 @@ async fn search_snapshots(
     let mut snapshot_start_ix = 0;
     let mut abs_path = PathBuf::new();
 
-    for snapshot in snapshots {
+    for (snapshot, index_filter) in snapshots.iter().zip(index_filters) {
         let snapshot_end_ix = snapshot_start_ix
             + if query.include_ignored() {
                 snapshot.file_count()
@@ -9998,15 +10468,24 @@ async fn search_snapshots(
                     continue;
                 }
 
-                let matched_path = if include_root {
-                    let mut full_path = PathBuf::from(snapshot.root_name());
-                    full_path.push(&entry.path);
-                    query.file_matches(Some(&full_path))
-                } else {
-                    query.file_matches(Some(&entry.path))
-                };
+                let in_scope =
+                    query
+                        .scope()
+                        .contains_path(snapshot.id(), &entry.path, entry.git_status);
+                let matched_path = in_scope
+                    && if include_root {
+                        let mut full_path = PathBuf::from(snapshot.root_name());
+                        full_path.push(&entry.path);
+                        query.file_matches(Some(&full_path))
+                    } else {
+                        query.file_matches(Some(&entry.path))
+                    };
+
+                let may_match = index_filter
+                    .as_ref()
+                    .map_or(true, |filter| filter.may_match(&entry.path, entry.mtime));
 
-                let matches = if matched_path {
+                let matches = if matched_path && may_match {
                     abs_path.clear();
                     abs_path.push(&snapshot.abs_path());
                     abs_path.push(&entry.path);
@@ -10070,7 +10549,13 @@ async fn search_ignored_entry(
                     }
                 }
             } else if !fs_metadata.is_symlink {
+                let Ok(ignored_path) = ignored_abs_path.strip_prefix(snapshot.abs_path()) else {
+                    continue;
+                };
                 if !query.file_matches(Some(&ignored_abs_path))
+                    || !query
+                        .scope()
+                        .contains_path(snapshot.id(), &Arc::from(ignored_path), None)
                     || snapshot.is_path_excluded(ignored_entry.path.to_path_buf())
                 {
                     continue;
@@ -10089,11 +10574,7 @@ async fn search_ignored_entry(
                 if matches {
                     let project_path = SearchMatchCandidate::Path {
                         worktree_id: snapshot.id(),
-                        path: Arc::from(
-                            ignored_abs_path
-                                .strip_prefix(snapshot.abs_path())
-                                .expect("scanning worktree-related files"),
-                        ),
+                        path: Arc::from(ignored_path),
                         is_ignored: true,
                     };
                     if counter_tx.send(project_path).await.is_err() {
@@ -10309,6 +10790,10 @@ impl LspAdapterDelegate for ProjectLspAdapterDelegate {
         self.http_client.clone()
     }
 
+    fn worktree_root_path(&self) -> &Path {
+        self.worktree.abs_path().as_ref()
+    }
+
     async fn shell_env(&self) -> HashMap<String, String> {
         self.load_shell_env().await;
         self.shell_env.lock().as_ref().cloned().unwrap_or_default()
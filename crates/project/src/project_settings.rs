@@ -20,6 +20,20 @@ pub struct ProjectSettings {
     /// Configuration for Git-related features
     #[serde(default)]
     pub git: GitSettings,
+
+    /// Configuration for the trigram index used to speed up project search.
+    #[serde(default)]
+    pub search_index: SearchIndexSettings,
+}
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SearchIndexSettings {
+    /// Whether to maintain a persistent index of the project's files,
+    /// used to skip files that cannot match a search.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
 }
 
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -1,11 +1,18 @@
-use crate::{Event, *};
-use fs::FakeFs;
+use crate::{
+    headless_tasks::HeadlessTaskStatus,
+    search::SearchScope,
+    search_history::{SavedSearch, SerializedSearch, SerializedSearchMode},
+    Event, *,
+};
+use async_trait::async_trait;
+use fs::{repository::GitFileStatus, FakeFs};
 use futures::{future, StreamExt};
 use gpui::AppContext;
 use language::{
-    language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
-    LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
+    language_settings::{AllLanguageSettings, Formatter, LanguageSettingsContent},
+    tree_sitter_rust, tree_sitter_typescript, BufferFormatter, BufferLinter, Diagnostic,
+    FakeLspAdapter, LanguageConfig, LanguageMatcher, LineEnding, LintDiagnostic, OffsetRangeExt,
+    Point, ToPoint,
 };
 use lsp::Url;
 use parking_lot::Mutex;
@@ -14,6 +21,7 @@ use serde_json::json;
 #[cfg(not(windows))]
 use std::os;
 use std::task::Poll;
+use terminal::terminal_settings::{Shell, TerminalSettings};
 use unindent::Unindent as _;
 use util::{assert_set_eq, paths::PathMatcher, test::temp_tree};
 use worktree::WorktreeModelHandle as _;
@@ -89,6 +97,73 @@ async fn test_symlinks(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[cfg(not(windows))]
+#[gpui::test]
+async fn test_headless_task_runs(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+    cx.update(|cx| {
+        terminal::init(cx);
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<TerminalSettings>(cx, |settings| {
+                settings.shell = Some(Shell::Program("/bin/sh".to_string()));
+            });
+        });
+    });
+
+    let dir = temp_tree(json!({ "a.txt": "" }));
+    let project = Project::test(Arc::new(RealFs::default()), [dir.path()], cx).await;
+    let task = |id: &str, command: &str| task::SpawnInTerminal {
+        id: task::TaskId(id.to_string()),
+        label: id.to_string(),
+        command: command.to_string(),
+        args: Vec::new(),
+        cwd: None,
+        env: HashMap::from_iter([("GREETING".to_string(), "hello".to_string())]),
+        use_new_terminal: false,
+        allow_concurrent_runs: false,
+        reveal: task::RevealStrategy::Never,
+        problem_matchers: Vec::new(),
+        dependencies: Vec::new(),
+        depends_order: task::DependsOrder::Parallel,
+        execution: task::ExecutionMode::Headless,
+    };
+
+    let succeeded = project
+        .update(cx, |project, cx| {
+            project.run_headless_task(&task("greet", "echo $GREETING; ls"), cx)
+        })
+        .await
+        .unwrap();
+    assert!(succeeded);
+    let failed = project
+        .update(cx, |project, cx| {
+            project.run_headless_task(&task("fail", "exit 3"), cx)
+        })
+        .await
+        .unwrap();
+    assert!(!failed);
+
+    project.update(cx, |project, cx| {
+        let runs = project
+            .headless_task_runs()
+            .iter()
+            .map(|run| (run.label.as_str(), run.status, run.output.read(cx).text()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            runs,
+            [
+                (
+                    "greet",
+                    HeadlessTaskStatus::Succeeded,
+                    "hello\na.txt\n".to_string()
+                ),
+                ("fail", HeadlessTaskStatus::Failed, String::new()),
+            ]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_managing_project_specific_settings(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2776,6 +2851,231 @@ async fn test_save_in_single_file_worktree(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_buffer_formatters_and_linters(cx: &mut gpui::TestAppContext) {
+    struct UppercaseFormatter;
+
+    #[async_trait(?Send)]
+    impl BufferFormatter for UppercaseFormatter {
+        fn name(&self) -> Arc<str> {
+            "uppercase".into()
+        }
+
+        async fn format(
+            &self,
+            text: String,
+            _: Arc<dyn LspAdapterDelegate>,
+        ) -> Result<Vec<(Range<usize>, String)>> {
+            Ok(vec![(0..text.len(), text.to_uppercase())])
+        }
+    }
+
+    struct FixedEditsFormatter {
+        name: &'static str,
+        edits: Vec<(Range<usize>, String)>,
+    }
+
+    #[async_trait(?Send)]
+    impl BufferFormatter for FixedEditsFormatter {
+        fn name(&self) -> Arc<str> {
+            self.name.into()
+        }
+
+        async fn format(
+            &self,
+            _: String,
+            _: Arc<dyn LspAdapterDelegate>,
+        ) -> Result<Vec<(Range<usize>, String)>> {
+            Ok(self.edits.clone())
+        }
+    }
+
+    struct TodoLinter;
+
+    #[async_trait(?Send)]
+    impl BufferLinter for TodoLinter {
+        fn name(&self) -> Arc<str> {
+            "todo".into()
+        }
+
+        async fn lint(
+            &self,
+            text: String,
+            _: Arc<dyn LspAdapterDelegate>,
+        ) -> Result<Vec<LintDiagnostic>> {
+            Ok(text
+                .match_indices("todo")
+                .map(|(ix, todo)| LintDiagnostic {
+                    range: ix..ix + todo.len(),
+                    severity: lsp::DiagnosticSeverity::WARNING,
+                    message: "unresolved todo".into(),
+                    code: None,
+                })
+                .collect())
+        }
+    }
+
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "fn a() {} // todo" }))
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let languages = project.update(cx, |project, _| project.languages().clone());
+    languages.add(rust_lang());
+    languages.register_formatter("Rust".into(), Arc::new(UppercaseFormatter));
+    languages.register_formatter(
+        "Rust".into(),
+        Arc::new(FixedEditsFormatter {
+            name: "out-of-order",
+            edits: vec![(3..4, "b".into()), (0..2, "fn".into())],
+        }),
+    );
+    languages.register_formatter(
+        "Rust".into(),
+        Arc::new(FixedEditsFormatter {
+            name: "overlapping",
+            edits: vec![(0..4, "x".into()), (2..6, "y".into())],
+        }),
+    );
+    languages.register_linter("Rust".into(), Arc::new(TodoLinter));
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.formatter = Some(Formatter::Extension("uppercase".into()));
+            });
+        })
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+            .map(|entry| (entry.range, entry.diagnostic.message))
+            .collect::<Vec<_>>();
+        assert_eq!(diagnostics, [(13..17, "unresolved todo".to_string())]);
+    });
+
+    project
+        .update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                true,
+                FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "FN A() {} // TODO");
+    });
+
+    let format_with = |formatter_name: &'static str, cx: &mut gpui::TestAppContext| {
+        cx.update(|cx| {
+            cx.update_global(|settings: &mut SettingsStore, cx| {
+                settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings.defaults.formatter = Some(Formatter::Extension(formatter_name.into()));
+                });
+            })
+        });
+        project.update(cx, |project, cx| {
+            project.format(
+                HashSet::from_iter([buffer.clone()]),
+                true,
+                FormatTrigger::Manual,
+                cx,
+            )
+        })
+    };
+
+    format_with("out-of-order", cx).await.unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "fn b() {} // TODO");
+    });
+
+    let error = format_with("overlapping", cx).await.unwrap_err();
+    let error = format!("{error:#}");
+    assert!(
+        error.contains("\"overlapping\"") && error.contains("overlapping edits at 0..4 and 2..6"),
+        "unexpected error: {error}"
+    );
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "fn b() {} // TODO");
+    });
+}
+
+#[gpui::test]
+async fn test_stale_linter_results_are_dropped(cx: &mut gpui::TestAppContext) {
+    /// Reports the todos once told to, by which time the buffer may have changed.
+    struct DelayedTodoLinter(futures::lock::Mutex<futures::channel::mpsc::UnboundedReceiver<()>>);
+
+    #[async_trait(?Send)]
+    impl BufferLinter for DelayedTodoLinter {
+        fn name(&self) -> Arc<str> {
+            "delayed-todo".into()
+        }
+
+        async fn lint(
+            &self,
+            text: String,
+            _: Arc<dyn LspAdapterDelegate>,
+        ) -> Result<Vec<LintDiagnostic>> {
+            self.0.lock().await.next().await;
+            Ok(text
+                .match_indices("todo")
+                .map(|(ix, todo)| LintDiagnostic {
+                    range: ix..ix + todo.len(),
+                    severity: lsp::DiagnosticSeverity::WARNING,
+                    message: "unresolved todo".into(),
+                    code: None,
+                })
+                .collect())
+        }
+    }
+
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "fn a() {} // todo" }))
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let languages = project.update(cx, |project, _| project.languages().clone());
+    languages.add(rust_lang());
+    let (lint_tx, lint_rx) = futures::channel::mpsc::unbounded();
+    languages.register_linter(
+        "Rust".into(),
+        Arc::new(DelayedTodoLinter(futures::lock::Mutex::new(lint_rx))),
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "// a\n")], None, cx));
+    lint_tx.unbounded_send(()).unwrap();
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        let diagnostics = buffer
+            .snapshot()
+            .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+            .count();
+        assert_eq!(
+            diagnostics, 0,
+            "Diagnostics for the text before the edit should be dropped"
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_save_as(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -3792,6 +4092,358 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_saved_searches(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".zed": {
+                "searches.json": r#"[
+                    // Comments are allowed in the shared file.
+                    { "name": "todos", "query": "TODO|FIXME", "mode": "regex" },
+                ]"#,
+            },
+            "one.rs": "const ONE: usize = 1;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    project.read_with(cx, |project, _| {
+        assert_eq!(
+            project.saved_searches().cloned().collect::<Vec<_>>(),
+            vec![SavedSearch {
+                name: "todos".to_string(),
+                search: SerializedSearch {
+                    query: "TODO|FIXME".to_string(),
+                    mode: SerializedSearchMode::Regex,
+                    ..Default::default()
+                },
+            }]
+        );
+    });
+
+    let saved_search = SavedSearch {
+        name: "constants".to_string(),
+        search: SerializedSearch {
+            query: "const".to_string(),
+            case_sensitive: true,
+            files_to_include: "*.rs".to_string(),
+            ..Default::default()
+        },
+    };
+    project
+        .update(cx, |project, cx| {
+            project.save_search(saved_search.clone(), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    project.read_with(cx, |project, _| {
+        assert_eq!(
+            project
+                .saved_searches()
+                .map(|saved_search| saved_search.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["todos", "constants"],
+            "Saved search should be appended to the project file and reloaded"
+        );
+    });
+
+    project
+        .update(cx, |project, cx| {
+            project.save_search(
+                SavedSearch {
+                    name: "constants".to_string(),
+                    search: SerializedSearch::new("static".to_string()),
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    project.read_with(cx, |project, _| {
+        assert_eq!(
+            project
+                .saved_searches()
+                .map(|saved_search| saved_search.search.query.as_str())
+                .collect::<Vec<_>>(),
+            vec!["TODO|FIXME", "static"],
+            "Saving under an existing name should replace that search"
+        );
+    });
+    let contents = fs.load("/dir/.zed/searches.json".as_ref()).await.unwrap();
+    assert!(
+        contents.contains("// Comments are allowed in the shared file."),
+        "Saving a search should keep the comments of the file, got: {contents}"
+    );
+}
+
+#[gpui::test]
+async fn test_search_with_index(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        cx.update_global(|settings: &mut SettingsStore, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.search_index.enabled = true;
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.run_until_parked();
+
+    let candidate_paths = |query: &SearchQuery, cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| {
+            let worktree = project.worktrees().next().unwrap();
+            let worktree = worktree.read(cx);
+            let index = project.search_indices[&worktree.id()].index();
+            assert!(index.read().is_ready());
+            let filter = SearchIndexFilter::new(index, query).unwrap();
+            let mut paths = worktree
+                .as_local()
+                .unwrap()
+                .files(false, 0)
+                .filter(|entry| filter.may_match(&entry.path, entry.mtime))
+                .map(|entry| entry.path.to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        })
+    };
+
+    let query = SearchQuery::text("two", false, false, false, Vec::new(), Vec::new()).unwrap();
+    assert_eq!(candidate_paths(&query, cx), ["three.rs", "two.rs"]);
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/two.rs".to_string(), vec![6..9]),
+            ("dir/three.rs".to_string(), vec![32..35, 37..40])
+        ])
+    );
+
+    let regex = SearchQuery::regex("THRE+\\b", false, true, false, Vec::new(), Vec::new()).unwrap();
+    assert_eq!(candidate_paths(&regex, cx), ["three.rs"]);
+    assert_eq!(
+        search(&project, regex.clone(), cx).await.unwrap(),
+        HashMap::from_iter([("dir/three.rs".to_string(), vec![6..11])])
+    );
+
+    fs.save(
+        "/dir/one.rs".as_ref(),
+        &"const ONE: usize = two::TWO - 1;".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    fs.remove_file("/dir/three.rs".as_ref(), Default::default())
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    assert_eq!(candidate_paths(&query, cx), ["one.rs", "two.rs"]);
+    assert_eq!(
+        search(&project, query, cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![19..22, 24..27]),
+            ("dir/two.rs".to_string(), vec![6..9]),
+        ])
+    );
+    assert!(candidate_paths(&regex, cx).is_empty());
+}
+
+#[gpui::test]
+async fn test_search_scopes(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            "nested": {
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            },
+        }),
+    )
+    .await;
+    fs.set_status_for_repo_via_git_operation(
+        Path::new("/dir/.git"),
+        &[(Path::new("two.rs"), GitFileStatus::Modified)],
+    );
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    cx.executor().run_until_parked();
+    let worktree_id = project.read_with(cx, |project, cx| {
+        project.worktrees().next().unwrap().read(cx).id()
+    });
+    let query = |scope| {
+        SearchQuery::text("ONE", false, true, false, Vec::new(), Vec::new())
+            .unwrap()
+            .with_scope(scope)
+    };
+
+    let _buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/nested/four.rs", cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        search(&project, query(SearchScope::OpenBuffers), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([("dir/nested/four.rs".to_string(), vec![25..28])]),
+        "Only open buffers should be searched"
+    );
+
+    assert_eq!(
+        search(&project, query(SearchScope::GitChanged), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([("dir/two.rs".to_string(), vec![24..27, 35..38])]),
+        "Only files with a git status should be searched"
+    );
+
+    assert_eq!(
+        search(
+            &project,
+            query(SearchScope::Directory(ProjectPath {
+                worktree_id,
+                path: Path::new("nested").into(),
+            })),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/nested/three.rs".to_string(), vec![26..29]),
+            ("dir/nested/four.rs".to_string(), vec![25..28]),
+        ]),
+        "Only files in the directory should be searched"
+    );
+
+    assert_eq!(
+        search(
+            &project,
+            query(SearchScope::Files(Arc::new(HashSet::from_iter([
+                ProjectPath {
+                    worktree_id,
+                    path: Path::new("one.rs").into(),
+                },
+                ProjectPath {
+                    worktree_id,
+                    path: Path::new("nested/three.rs").into(),
+                },
+            ])))),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![6..9]),
+            ("dir/nested/three.rs".to_string(), vec![26..29]),
+        ]),
+        "Only the given files should be searched"
+    );
+}
+
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "fn main() {\n    foo(1, bar(2));\n    foo(3);\n    // foo(4, 5)\n}\n",
+            "two.rs": "fn two() { foo(x + y, x + y); }",
+            "three.txt": "foo(1, 2)",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural("foo($A, $B)", false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![16..30]),
+            ("dir/two.rs".to_string(), vec![11..28])
+        ]),
+        "should match calls with two arguments, but not in comments or files without syntax"
+    );
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural("foo($A, $A)", false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([("dir/two.rs".to_string(), vec![11..28])]),
+        "a repeated metavariable should match the same text"
+    );
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural("foo($$$ARGS)", false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![16..30, 36..42]),
+            ("dir/two.rs".to_string(), vec![11..28])
+        ]),
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+    let query = SearchQuery::structural("foo($A, $B)", false, Vec::new(), Vec::new())
+        .unwrap()
+        .with_replacement("foo($B, $A, $C)".to_string());
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            query
+                .structural_replacement_for(&buffer.snapshot(), 16..30)
+                .as_deref(),
+            Some("foo(bar(2), 1, $C)"),
+            "captured metavariables should be expanded, and the others kept"
+        );
+        assert_eq!(
+            query.structural_replacement_for(&buffer.snapshot(), 36..42),
+            None
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
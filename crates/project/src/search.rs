@@ -1,6 +1,8 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::{Context, Result};
 use client::proto;
+use collections::HashSet;
+use fs::repository::GitFileStatus;
 use itertools::Itertools;
 use language::{char_kind, BufferSnapshot};
 use regex::{Captures, Regex, RegexBuilder};
@@ -9,10 +11,13 @@ use std::{
     borrow::Cow,
     io::{BufRead, BufReader, Read},
     ops::Range,
-    path::Path,
+    path::{Path, PathBuf},
     sync::{Arc, OnceLock},
 };
 use util::paths::PathMatcher;
+use worktree::WorktreeId;
+
+use crate::{structural_search::StructuralPattern, ProjectPath};
 
 static TEXT_REPLACEMENT_SPECIAL_CHARACTERS_REGEX: OnceLock<Regex> = OnceLock::new();
 
@@ -21,6 +26,7 @@ pub struct SearchInputs {
     query: Arc<str>,
     files_to_include: Vec<PathMatcher>,
     files_to_exclude: Vec<PathMatcher>,
+    scope: SearchScope,
 }
 
 impl SearchInputs {
@@ -33,12 +39,116 @@ impl SearchInputs {
     pub fn files_to_exclude(&self) -> &[PathMatcher] {
         &self.files_to_exclude
     }
+    pub fn scope(&self) -> &SearchScope {
+        &self.scope
+    }
+}
+
+/// The set of files a [`SearchQuery`] looks into, narrowed further by its include and exclude globs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SearchScope {
+    /// All files of the visible worktrees, along with the untitled buffers.
+    #[default]
+    Project,
+    /// Only the buffers that are currently open, including the untitled ones.
+    OpenBuffers,
+    /// Only the files that have a git status in their worktree.
+    GitChanged,
+    /// Only the files under the given directory.
+    Directory(ProjectPath),
+    /// Only the given files, e.g. the ones shown in a multibuffer.
+    Files(Arc<HashSet<ProjectPath>>),
 }
+
+impl SearchScope {
+    /// Whether a file at the given worktree path belongs to this scope.
+    /// Open buffers are checked separately, as any file may be opened.
+    pub fn contains_path(
+        &self,
+        worktree_id: WorktreeId,
+        path: &Arc<Path>,
+        git_status: Option<GitFileStatus>,
+    ) -> bool {
+        match self {
+            Self::Project | Self::OpenBuffers => true,
+            Self::GitChanged => git_status.is_some(),
+            Self::Directory(directory) => {
+                directory.worktree_id == worktree_id && path.starts_with(&directory.path)
+            }
+            Self::Files(files) => files.contains(&ProjectPath {
+                worktree_id,
+                path: path.clone(),
+            }),
+        }
+    }
+
+    /// Whether files that are not open should be looked into at all.
+    pub fn includes_unopened_files(&self) -> bool {
+        !matches!(self, Self::OpenBuffers)
+    }
+
+    /// Whether buffers that have no file yet belong to this scope.
+    pub fn includes_untitled_buffers(&self) -> bool {
+        matches!(self, Self::Project | Self::OpenBuffers)
+    }
+
+    /// Whether gitignored files may belong to this scope, as they never have a git status.
+    pub fn includes_ignored_files(&self) -> bool {
+        !matches!(self, Self::OpenBuffers | Self::GitChanged)
+    }
+
+    pub fn to_proto(&self) -> proto::SearchScope {
+        let (kind, paths) = match self {
+            Self::Project => (proto::search_scope::Kind::Project, Vec::new()),
+            Self::OpenBuffers => (proto::search_scope::Kind::OpenBuffers, Vec::new()),
+            Self::GitChanged => (proto::search_scope::Kind::GitChanged, Vec::new()),
+            Self::Directory(directory) => (
+                proto::search_scope::Kind::Directory,
+                vec![search_scope_path_to_proto(directory)],
+            ),
+            Self::Files(files) => (
+                proto::search_scope::Kind::Files,
+                files.iter().map(search_scope_path_to_proto).collect(),
+            ),
+        };
+        proto::SearchScope {
+            kind: kind.into(),
+            paths,
+        }
+    }
+
+    pub fn from_proto(message: proto::SearchScope) -> Result<Self> {
+        let kind = proto::search_scope::Kind::from_i32(message.kind)
+            .context("unknown search scope kind")?;
+        let mut paths = message.paths.into_iter().map(|path| ProjectPath {
+            worktree_id: WorktreeId::from_proto(path.worktree_id),
+            path: PathBuf::from(path.path).into(),
+        });
+        Ok(match kind {
+            proto::search_scope::Kind::Project => Self::Project,
+            proto::search_scope::Kind::OpenBuffers => Self::OpenBuffers,
+            proto::search_scope::Kind::GitChanged => Self::GitChanged,
+            proto::search_scope::Kind::Directory => {
+                Self::Directory(paths.next().context("search scope has no directory")?)
+            }
+            proto::search_scope::Kind::Files => Self::Files(Arc::new(paths.collect())),
+        })
+    }
+}
+
+fn search_scope_path_to_proto(path: &ProjectPath) -> proto::SearchScopePath {
+    proto::SearchScopePath {
+        worktree_id: path.worktree_id.to_proto(),
+        path: path.path.to_string_lossy().to_string(),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
     Text {
         search: Arc<AhoCorasick>,
         replacement: Option<String>,
+        preserve_case: bool,
         whole_word: bool,
         case_sensitive: bool,
         include_ignored: bool,
@@ -48,12 +158,21 @@ pub enum SearchQuery {
     Regex {
         regex: Regex,
         replacement: Option<String>,
+        preserve_case: bool,
         multiline: bool,
         whole_word: bool,
         case_sensitive: bool,
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    /// A code pattern with metavariables, matched against the syntax trees of the buffers.
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 impl SearchQuery {
@@ -73,10 +192,12 @@ impl SearchQuery {
             query: query.into(),
             files_to_exclude,
             files_to_include,
+            scope: SearchScope::default(),
         };
         Ok(Self::Text {
             search: Arc::new(search),
             replacement: None,
+            preserve_case: false,
             whole_word,
             case_sensitive,
             include_ignored,
@@ -111,10 +232,12 @@ impl SearchQuery {
             query: initial_query,
             files_to_exclude,
             files_to_include,
+            scope: SearchScope::default(),
         };
         Ok(Self::Regex {
             regex,
             replacement: None,
+            preserve_case: false,
             multiline,
             whole_word,
             case_sensitive,
@@ -123,8 +246,42 @@ impl SearchQuery {
         })
     }
 
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: Vec<PathMatcher>,
+        files_to_exclude: Vec<PathMatcher>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            scope: SearchScope::default(),
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchProject) -> Result<Self> {
-        if message.regex {
+        let scope = message
+            .scope
+            .map(SearchScope::from_proto)
+            .transpose()?
+            .unwrap_or_default();
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -142,7 +299,8 @@ impl SearchQuery {
                 deserialize_path_matches(&message.files_to_include)?,
                 deserialize_path_matches(&message.files_to_exclude)?,
             )
-        }
+        }?;
+        Ok(query.with_scope(scope))
     }
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
         match self {
@@ -153,17 +311,46 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
             }
         }
     }
+    /// Makes replacements follow the case of the text they replace, see [`preserve_case`].
+    /// Structural replacements are not affected.
+    pub fn with_preserve_case(mut self, new_preserve_case: bool) -> Self {
+        match self {
+            Self::Text {
+                ref mut preserve_case,
+                ..
+            }
+            | Self::Regex {
+                ref mut preserve_case,
+                ..
+            } => *preserve_case = new_preserve_case,
+            Self::Structural { .. } => {}
+        }
+        self
+    }
+    pub fn with_scope(mut self, new_scope: SearchScope) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => inner.scope = new_scope,
+        }
+        self
+    }
     pub fn to_proto(&self, project_id: u64) -> proto::SearchProject {
         proto::SearchProject {
             project_id,
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -177,6 +364,7 @@ impl SearchQuery {
                 .iter()
                 .map(|matcher| matcher.to_string())
                 .join(","),
+            scope: Some(self.scope().to_proto()),
         }
     }
 
@@ -215,18 +403,31 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                BufReader::new(stream).read_to_string(&mut text)?;
+                Ok(pattern.may_match(&text))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
+        let replacement = self.raw_replacement_for(text)?;
+        if self.preserve_case() {
+            Some(Cow::Owned(preserve_case(text, &replacement)))
+        } else {
+            Some(replacement)
+        }
+    }
+    fn raw_replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
             SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
             SearchQuery::Regex {
@@ -248,8 +449,28 @@ impl SearchQuery {
                     None
                 }
             }
+            // The replacement depends on the syntax of the match, see `structural_replacement_for`.
+            SearchQuery::Structural { .. } => None,
         }
     }
+    /// Replaces a structural search hit, at the given range of the buffer, with the replacement template
+    /// expanded with the metavariables captured there.
+    pub fn structural_replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        let SearchQuery::Structural {
+            pattern,
+            replacement: Some(replacement),
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let captures = pattern.captures_at(buffer, range)?;
+        Some(pattern.replacement(replacement, buffer, &captures))
+    }
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -260,6 +481,9 @@ impl SearchQuery {
         if self.as_str().is_empty() {
             return Default::default();
         }
+        if let Self::Structural { pattern, .. } = self {
+            return pattern.search(buffer, subrange).await;
+        }
 
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
         let rope = if let Some(range) = subrange {
@@ -334,6 +558,7 @@ impl SearchQuery {
                     }
                 }
             }
+            Self::Structural { .. } => unreachable!("structural queries are searched above"),
         }
 
         matches
@@ -351,6 +576,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -358,6 +584,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -369,6 +596,16 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
+        }
+    }
+
+    pub fn preserve_case(&self) -> bool {
+        match self {
+            Self::Text { preserve_case, .. } | Self::Regex { preserve_case, .. } => *preserve_case,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -376,6 +613,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &[PathMatcher] {
         self.as_inner().files_to_include()
     }
@@ -384,6 +625,9 @@ impl SearchQuery {
         self.as_inner().files_to_exclude()
     }
 
+    pub fn scope(&self) -> &SearchScope {
+        self.as_inner().scope()
+    }
     pub fn file_matches(&self, file_path: Option<&Path>) -> bool {
         match file_path {
             Some(file_path) => {
@@ -412,9 +656,65 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
+        }
+    }
+}
+
+/// Adapts the case of a replacement to the case of the text it replaces:
+/// `foo` → `bar`, `Foo` → `Bar`, `FOO` → `BAR` and `fooBar` → `bazQux`.
+///
+/// When both consist of the same number of `_` or `-` separated words,
+/// the case of each word is adapted separately.
+pub fn preserve_case(text: &str, replacement: &str) -> String {
+    for separator in ['_', '-'] {
+        let words = text.split(separator).collect::<Vec<_>>();
+        let replacement_words = replacement.split(separator).collect::<Vec<_>>();
+        if words.len() > 1 && words.len() == replacement_words.len() {
+            return words
+                .into_iter()
+                .zip(replacement_words)
+                .map(|(word, replacement_word)| preserve_word_case(word, replacement_word))
+                .join(&separator.to_string());
         }
     }
+    preserve_word_case(text, replacement)
+}
+
+fn preserve_word_case(text: &str, replacement: &str) -> String {
+    let mut cased_chars = text
+        .chars()
+        .filter(|c| c.is_uppercase() || c.is_lowercase());
+    let Some(first) = cased_chars.next() else {
+        return replacement.to_string();
+    };
+    let (mut all_upper, mut all_lower) = (first.is_uppercase(), first.is_lowercase());
+    let mut is_single_char = true;
+    for c in cased_chars {
+        is_single_char = false;
+        all_upper &= c.is_uppercase();
+        all_lower &= c.is_lowercase();
+    }
+
+    if all_lower {
+        replacement.to_lowercase()
+    } else if all_upper && !is_single_char {
+        replacement.to_uppercase()
+    } else {
+        let mut replacement_chars = replacement.chars();
+        let Some(replacement_first) = replacement_chars.next() else {
+            return String::new();
+        };
+        let mut result = if first.is_uppercase() {
+            replacement_first.to_uppercase().collect::<String>()
+        } else {
+            replacement_first.to_lowercase().collect::<String>()
+        };
+        result.push_str(replacement_chars.as_str());
+        result
+    }
 }
 
 fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>> {
@@ -433,6 +733,22 @@ fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>>
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_preserve_case() {
+        assert_eq!(preserve_case("foo", "bar"), "bar");
+        assert_eq!(preserve_case("foo", "Bar"), "bar");
+        assert_eq!(preserve_case("Foo", "bar"), "Bar");
+        assert_eq!(preserve_case("FOO", "bar"), "BAR");
+        assert_eq!(preserve_case("F", "bar"), "Bar");
+        assert_eq!(preserve_case("fooBar", "BazQux"), "bazQux");
+        assert_eq!(preserve_case("FooBar", "bazQux"), "BazQux");
+        assert_eq!(preserve_case("foo_BAR", "baz_qux"), "baz_QUX");
+        assert_eq!(preserve_case("Foo-bar", "baz-Qux"), "Baz-qux");
+        assert_eq!(preserve_case("FOO_BAR", "baz"), "BAZ");
+        assert_eq!(preserve_case("123", "Baz"), "Baz");
+        assert_eq!(preserve_case("Foo", ""), "");
+    }
+
     #[test]
     fn path_matcher_creation_for_valid_paths() {
         for valid_path in [
@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
 /// Determines the behavior to use when inserting a new query into the search history.
 #[derive(Default, Debug, Clone, PartialEq)]
 pub enum QueryInsertionBehavior {
@@ -26,9 +29,84 @@ impl SearchHistoryCursor {
     }
 }
 
+/// The kind of query a [`SerializedSearch`] runs.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerializedSearchMode {
+    #[default]
+    Text,
+    Regex,
+    Structural,
+}
+
+/// The files a [`SerializedSearch`] looks into.
+/// Paths are relative to their worktree, and looked up in the worktrees of the project,
+/// as worktree ids don't outlive a session.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerializedSearchScope {
+    #[default]
+    Project,
+    OpenBuffers,
+    GitChanged,
+    Directory(PathBuf),
+    Files(Vec<PathBuf>),
+}
+
+impl SerializedSearchScope {
+    fn is_project(&self) -> bool {
+        *self == Self::Project
+    }
+}
+
+/// A search along with its options, filters and replacement, as stored in the search history
+/// and in the saved searches of a project.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SerializedSearch {
+    pub query: String,
+    #[serde(default)]
+    pub mode: SerializedSearchMode,
+    #[serde(default)]
+    pub whole_word: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub include_ignored: bool,
+    #[serde(default)]
+    pub preserve_case: bool,
+    /// Comma-separated globs of the files to search in.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub files_to_include: String,
+    /// Comma-separated globs of the files to skip.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub files_to_exclude: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    #[serde(default, skip_serializing_if = "SerializedSearchScope::is_project")]
+    pub scope: SerializedSearchScope,
+}
+
+impl SerializedSearch {
+    /// A plain text search with no options set.
+    pub fn new(query: String) -> Self {
+        Self {
+            query,
+            ..Default::default()
+        }
+    }
+}
+
+/// A named search of a project, read from and saved to its `.zed/searches.json` files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    #[serde(flatten)]
+    pub search: SerializedSearch,
+}
+
 #[derive(Debug, Clone)]
 pub struct SearchHistory {
-    history: Vec<String>,
+    history: Vec<SerializedSearch>,
     max_history_len: Option<usize>,
     insertion_behavior: QueryInsertionBehavior,
 }
@@ -43,23 +121,28 @@ impl SearchHistory {
     }
 
     pub fn add(&mut self, cursor: &mut SearchHistoryCursor, search_string: String) {
+        self.add_search(cursor, SerializedSearch::new(search_string));
+    }
+
+    /// Adds a search along with its options, the entries only differing in their options are kept apart.
+    pub fn add_search(&mut self, cursor: &mut SearchHistoryCursor, search: SerializedSearch) {
         if let Some(selected_ix) = cursor.selection {
-            if self.history.get(selected_ix) == Some(&search_string) {
+            if self.history.get(selected_ix) == Some(&search) {
                 return;
             }
         }
 
         if self.insertion_behavior == QueryInsertionBehavior::ReplacePreviousIfContains {
             if let Some(previously_searched) = self.history.last_mut() {
-                if search_string.contains(previously_searched.as_str()) {
-                    *previously_searched = search_string;
+                if search.query.contains(previously_searched.query.as_str()) {
+                    *previously_searched = search;
                     cursor.selection = Some(self.history.len() - 1);
                     return;
                 }
             }
         }
 
-        self.history.push(search_string);
+        self.history.push(search);
         if let Some(max_history_len) = self.max_history_len {
             if self.history.len() > max_history_len {
                 self.history.remove(0);
@@ -81,13 +164,38 @@ impl SearchHistory {
         }
         let next_index = selected + 1;
         cursor.selection = Some(next_index);
-        Some(&self.history[next_index])
+        Some(&self.history[next_index].query)
     }
 
     pub fn current(&self, cursor: &SearchHistoryCursor) -> Option<&str> {
+        self.current_search(cursor)
+            .map(|search| search.query.as_str())
+    }
+
+    /// Returns the selected search along with its options.
+    pub fn current_search(&self, cursor: &SearchHistoryCursor) -> Option<&SerializedSearch> {
         cursor
             .selection
-            .and_then(|selected_ix| self.history.get(selected_ix).map(|s| s.as_str()))
+            .and_then(|selected_ix| self.history.get(selected_ix))
+    }
+
+    /// All searches in the history, the oldest first.
+    pub fn searches(&self) -> &[SerializedSearch] {
+        &self.history
+    }
+
+    /// Puts previously persisted searches before the ones made since, dropping the oldest
+    /// ones past the maximum history length.
+    ///
+    /// Note: Like adding past the maximum length, this shifts what the existing cursors point to.
+    pub fn load(&mut self, searches: Vec<SerializedSearch>) {
+        let mut history = searches;
+        history.append(&mut self.history);
+        if let Some(max_history_len) = self.max_history_len {
+            let excess = history.len().saturating_sub(max_history_len);
+            history.drain(..excess);
+        }
+        self.history = history;
     }
 
     pub fn previous(&mut self, cursor: &mut SearchHistoryCursor) -> Option<&str> {
@@ -108,7 +216,7 @@ impl SearchHistory {
         };
 
         cursor.selection = Some(prev_index);
-        Some(&self.history[prev_index])
+        Some(&self.history[prev_index].query)
     }
 }
 
@@ -258,4 +366,58 @@ mod tests {
         assert_eq!(search_history.current(&cursor1), None);
         assert_eq!(search_history.current(&cursor2), None);
     }
+
+    #[test]
+    fn test_searches_with_options() {
+        const MAX_HISTORY_LEN: usize = 3;
+        let mut search_history =
+            SearchHistory::new(Some(MAX_HISTORY_LEN), QueryInsertionBehavior::AlwaysInsert);
+        let mut cursor = SearchHistoryCursor::default();
+
+        let regex_search = SerializedSearch {
+            query: "fn \\w+".to_string(),
+            mode: SerializedSearchMode::Regex,
+            files_to_include: "src/**".to_string(),
+            replacement: Some("fn renamed".to_string()),
+            scope: SerializedSearchScope::Directory(PathBuf::from("src")),
+            ..Default::default()
+        };
+        search_history.add_search(&mut cursor, regex_search.clone());
+        search_history.add_search(
+            &mut cursor,
+            SerializedSearch {
+                case_sensitive: true,
+                ..regex_search.clone()
+            },
+        );
+        assert_eq!(
+            search_history.searches().len(),
+            2,
+            "Searches only differing in their options should both be kept"
+        );
+        assert_eq!(search_history.previous(&mut cursor), Some("fn \\w+"));
+        assert_eq!(search_history.current_search(&cursor), Some(&regex_search));
+
+        let serialized = serde_json::to_string(search_history.searches()).unwrap();
+        let mut restored_history =
+            SearchHistory::new(Some(MAX_HISTORY_LEN), QueryInsertionBehavior::AlwaysInsert);
+        let mut restored_cursor = SearchHistoryCursor::default();
+        restored_history.add(&mut restored_cursor, "new".to_string());
+        restored_history.add(&mut restored_cursor, "newer".to_string());
+        restored_history.load(serde_json::from_str(&serialized).unwrap());
+        assert_eq!(
+            restored_history
+                .searches()
+                .iter()
+                .map(|search| search.query.as_str())
+                .collect::<Vec<_>>(),
+            vec!["fn \\w+", "new", "newer"],
+            "Loaded searches should go before the recent ones, dropping the oldest past the limit"
+        );
+        assert_eq!(
+            restored_history.searches()[0].mode,
+            SerializedSearchMode::Regex
+        );
+        assert_eq!(restored_history.searches()[0].scope, regex_search.scope);
+    }
 }
@@ -0,0 +1,860 @@
+//! A persistent trigram index of worktree file contents.
+//!
+//! Project search has to read every candidate file of a worktree to find out whether it
+//! matches, which gets slow in very large repositories. This index records which
+//! (ASCII-case-folded) byte trigrams occur in every file, so that text and regex queries
+//! can be answered with a small set of candidate files first, and only those get matched
+//! exactly.
+//!
+//! The index is kept up to date from the worktree's entry updates and is stored under
+//! [`SEARCH_INDEX_DIR`], so that reopening a project only needs to re-read the files that
+//! changed in between.
+
+use crate::search::SearchQuery;
+use anyhow::{anyhow, Context as _, Result};
+use collections::{HashMap, HashSet};
+use fs::{Fs, RenameOptions};
+use futures::{channel::mpsc, FutureExt as _, StreamExt as _};
+use gpui::{BackgroundExecutor, Task};
+use parking_lot::RwLock;
+use sha2::{Digest, Sha256};
+use std::{
+    io::Read as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use util::{paths::SEARCH_INDEX_DIR, ResultExt as _};
+use worktree::{Entry, LocalSnapshot, PathChange, UpdatedEntriesSet};
+
+/// Files larger than this are not indexed, and are always searched.
+const MAX_INDEXED_FILE_LEN: u64 = 1024 * 1024;
+/// How many files get read and indexed between two updates of the shared index.
+const INDEXING_BATCH_LEN: usize = 256;
+/// How long the index has to stay unchanged before it is written to disk.
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(30);
+/// Tombstoned files are dropped from the posting lists once there are this many of them
+/// (and they make up at least half of the index).
+const COMPACTION_THRESHOLD: usize = 4096;
+
+const FILE_MAGIC: &[u8; 4] = b"ZSIX";
+const FILE_VERSION: u32 = 1;
+
+type Trigram = u32;
+
+/// The trigram index of a single worktree.
+#[derive(Default)]
+pub struct SearchIndex {
+    /// Indexed files by their id. Removed files leave a `None` behind until the next compaction.
+    files: Vec<Option<IndexedFile>>,
+    ids_by_path: HashMap<Arc<Path>, u32>,
+    postings: HashMap<Trigram, PostingList>,
+    tombstones: usize,
+    /// Incremented whenever file ids get reassigned.
+    generation: u64,
+    /// Whether the index reflects the worktree's contents, as opposed to still being built.
+    is_ready: bool,
+}
+
+struct IndexedFile {
+    path: Arc<Path>,
+    mtime: SystemTime,
+}
+
+/// File ids containing a trigram, in increasing order, as delta-encoded varints.
+#[derive(Default)]
+struct PostingList {
+    bytes: Vec<u8>,
+    last_id: u32,
+    len: u32,
+}
+
+impl PostingList {
+    fn push(&mut self, id: u32) {
+        let mut delta = id - self.last_id;
+        loop {
+            let byte = (delta & 0x7f) as u8;
+            delta >>= 7;
+            if delta == 0 {
+                self.bytes.push(byte);
+                break;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+        self.last_id = id;
+        self.len += 1;
+    }
+
+    fn ids(&self) -> impl '_ + Iterator<Item = u32> {
+        let mut bytes = self.bytes.iter();
+        let mut id = 0u32;
+        (0..self.len).map_while(move |_| {
+            let mut delta = 0u32;
+            let mut shift = 0;
+            loop {
+                let byte = *bytes.next()?;
+                delta |= ((byte & 0x7f) as u32) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            id += delta;
+            Some(id)
+        })
+    }
+}
+
+impl SearchIndex {
+    pub fn is_ready(&self) -> bool {
+        self.is_ready
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.ids_by_path.len()
+    }
+
+    fn insert(&mut self, path: Arc<Path>, mtime: SystemTime, trigrams: &[Trigram]) {
+        self.remove(&path);
+        let id = self.files.len() as u32;
+        self.files.push(Some(IndexedFile {
+            path: path.clone(),
+            mtime,
+        }));
+        self.ids_by_path.insert(path, id);
+        for trigram in trigrams {
+            self.postings.entry(*trigram).or_default().push(id);
+        }
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(id) = self.ids_by_path.remove(path) {
+            self.files[id as usize] = None;
+            self.tombstones += 1;
+        }
+    }
+
+    /// Removes a path, or everything below it if it was a directory.
+    fn remove_recursive(&mut self, path: &Path) {
+        if self.ids_by_path.contains_key(path) {
+            self.remove(path);
+            return;
+        }
+
+        let removed_ids = self
+            .ids_by_path
+            .iter()
+            .filter(|(indexed_path, _)| indexed_path.starts_with(path))
+            .map(|(_, id)| *id)
+            .collect::<Vec<_>>();
+        for id in removed_ids {
+            if let Some(file) = self.files[id as usize].take() {
+                self.ids_by_path.remove(&file.path);
+                self.tombstones += 1;
+            }
+        }
+    }
+
+    fn is_up_to_date(&self, entry: &Entry) -> bool {
+        self.ids_by_path
+            .get(&entry.path)
+            .and_then(|id| self.files[*id as usize].as_ref())
+            .map_or(false, |file| Some(file.mtime) == entry.mtime)
+    }
+
+    fn maybe_compact(&mut self) {
+        if self.tombstones >= COMPACTION_THRESHOLD && self.tombstones * 2 >= self.files.len() {
+            self.compact();
+        }
+    }
+
+    /// Drops removed files, reassigning the ids of the remaining ones.
+    fn compact(&mut self) {
+        if self.tombstones == 0 {
+            return;
+        }
+
+        let mut new_ids = vec![None; self.files.len()];
+        let mut files = Vec::with_capacity(self.ids_by_path.len());
+        for (old_id, file) in self.files.drain(..).enumerate() {
+            if let Some(file) = file {
+                let new_id = files.len() as u32;
+                new_ids[old_id] = Some(new_id);
+                self.ids_by_path.insert(file.path.clone(), new_id);
+                files.push(Some(file));
+            }
+        }
+        self.files = files;
+
+        self.postings.retain(|_, postings| {
+            let mut compacted = PostingList::default();
+            for id in postings.ids() {
+                if let Some(new_id) = new_ids[id as usize] {
+                    compacted.push(new_id);
+                }
+            }
+            *postings = compacted;
+            postings.len > 0
+        });
+        self.tombstones = 0;
+        self.generation += 1;
+    }
+
+    /// Returns the sorted ids of the files containing all of the given trigrams.
+    fn files_containing(&self, trigrams: &[Trigram]) -> Vec<u32> {
+        let mut postings = Vec::with_capacity(trigrams.len());
+        for trigram in trigrams {
+            match self.postings.get(trigram) {
+                Some(list) => postings.push(list),
+                None => return Vec::new(),
+            }
+        }
+        postings.sort_by_key(|list| list.len);
+
+        let mut postings = postings.into_iter();
+        let Some(rarest) = postings.next() else {
+            return Vec::new();
+        };
+        let mut ids = rarest.ids().collect::<Vec<_>>();
+        for list in postings {
+            if ids.is_empty() {
+                break;
+            }
+            let mut other_ids = list.ids().peekable();
+            ids.retain(|id| {
+                while other_ids.next_if(|other_id| other_id < id).is_some() {}
+                other_ids.peek() == Some(id)
+            });
+        }
+        ids
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        debug_assert_eq!(self.tombstones, 0);
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(FILE_MAGIC);
+        bytes.extend_from_slice(&FILE_VERSION.to_le_bytes());
+
+        bytes.extend_from_slice(&(self.files.len() as u32).to_le_bytes());
+        for file in self.files.iter().flatten() {
+            let path = file.path.to_string_lossy();
+            let mtime = file.mtime.duration_since(UNIX_EPOCH).unwrap_or_default();
+            bytes.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(path.as_bytes());
+            bytes.extend_from_slice(&mtime.as_secs().to_le_bytes());
+            bytes.extend_from_slice(&mtime.subsec_nanos().to_le_bytes());
+        }
+
+        bytes.extend_from_slice(&(self.postings.len() as u32).to_le_bytes());
+        for (trigram, list) in &self.postings {
+            bytes.extend_from_slice(&trigram.to_le_bytes());
+            bytes.extend_from_slice(&list.last_id.to_le_bytes());
+            bytes.extend_from_slice(&list.len.to_le_bytes());
+            bytes.extend_from_slice(&(list.bytes.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(&list.bytes);
+        }
+        bytes
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let mut reader = ByteReader(bytes);
+        if reader.take(FILE_MAGIC.len())? != FILE_MAGIC {
+            return Err(anyhow!("not a search index"));
+        }
+        let version = reader.u32()?;
+        if version != FILE_VERSION {
+            return Err(anyhow!("unsupported search index version {version}"));
+        }
+
+        let mut index = Self::default();
+        let file_count = reader.u32()?;
+        for id in 0..file_count {
+            let path_len = reader.u32()? as usize;
+            let path = std::str::from_utf8(reader.take(path_len)?)?;
+            let path: Arc<Path> = Arc::from(Path::new(path));
+            let secs = reader.u64()?;
+            let nanos = reader.u32()?;
+            let mtime = UNIX_EPOCH + Duration::new(secs, nanos);
+            index.files.push(Some(IndexedFile {
+                path: path.clone(),
+                mtime,
+            }));
+            index.ids_by_path.insert(path, id);
+        }
+
+        let posting_count = reader.u32()?;
+        for _ in 0..posting_count {
+            let trigram = reader.u32()?;
+            let last_id = reader.u32()?;
+            let len = reader.u32()?;
+            let byte_len = reader.u32()? as usize;
+            let bytes = reader.take(byte_len)?.to_vec();
+            index.postings.insert(
+                trigram,
+                PostingList {
+                    bytes,
+                    last_id,
+                    len,
+                },
+            );
+        }
+        Ok(index)
+    }
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(anyhow!("unexpected end of search index"));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
+
+enum IndexUpdate {
+    /// Bring the index in sync with a fully scanned worktree.
+    Reconcile(LocalSnapshot),
+    Changes(LocalSnapshot, UpdatedEntriesSet),
+}
+
+/// Keeps the [`SearchIndex`] of a local worktree up to date in the background.
+pub struct WorktreeSearchIndex {
+    index: Arc<RwLock<SearchIndex>>,
+    updates_tx: mpsc::UnboundedSender<IndexUpdate>,
+    _maintain_index: Task<()>,
+}
+
+impl WorktreeSearchIndex {
+    pub fn new(abs_path: &Path, fs: Arc<dyn Fs>, executor: BackgroundExecutor) -> Self {
+        let index = Arc::new(RwLock::new(SearchIndex::default()));
+        let (updates_tx, updates_rx) = mpsc::unbounded();
+        let index_path = index_path(abs_path);
+        let _maintain_index = executor.spawn(maintain_index(
+            index.clone(),
+            index_path,
+            updates_rx,
+            fs,
+            executor.clone(),
+        ));
+        Self {
+            index,
+            updates_tx,
+            _maintain_index,
+        }
+    }
+
+    pub fn index(&self) -> &Arc<RwLock<SearchIndex>> {
+        &self.index
+    }
+
+    /// Re-indexes every file of the snapshot whose contents may have changed since it was
+    /// last indexed. Changes are only applied to the index after this has been called once.
+    pub fn reconcile(&self, snapshot: LocalSnapshot) {
+        self.updates_tx
+            .unbounded_send(IndexUpdate::Reconcile(snapshot))
+            .ok();
+    }
+
+    pub fn update(&self, snapshot: LocalSnapshot, changes: UpdatedEntriesSet) {
+        self.updates_tx
+            .unbounded_send(IndexUpdate::Changes(snapshot, changes))
+            .ok();
+    }
+}
+
+fn index_path(worktree_abs_path: &Path) -> PathBuf {
+    let digest = Sha256::digest(worktree_abs_path.to_string_lossy().as_bytes());
+    SEARCH_INDEX_DIR.join(format!("{digest:x}.idx"))
+}
+
+async fn maintain_index(
+    index: Arc<RwLock<SearchIndex>>,
+    index_path: PathBuf,
+    mut updates_rx: mpsc::UnboundedReceiver<IndexUpdate>,
+    fs: Arc<dyn Fs>,
+    executor: BackgroundExecutor,
+) {
+    if fs.is_file(&index_path).await {
+        let loaded = load_index(&index_path, fs.as_ref())
+            .await
+            .with_context(|| format!("loading search index from {index_path:?}"))
+            .log_err();
+        if let Some(loaded) = loaded {
+            *index.write() = loaded;
+        }
+    }
+
+    let mut reconciled = false;
+    let mut dirty = false;
+    loop {
+        let update = if dirty {
+            futures::select_biased! {
+                update = updates_rx.next() => update,
+                _ = executor.timer(SAVE_DEBOUNCE).fuse() => {
+                    save_index(&index, &index_path, fs.as_ref())
+                        .await
+                        .with_context(|| format!("saving search index to {index_path:?}"))
+                        .log_err();
+                    dirty = false;
+                    continue;
+                }
+            }
+        } else {
+            updates_rx.next().await
+        };
+
+        let (snapshot, paths_to_index) = match update {
+            None => break,
+            Some(IndexUpdate::Reconcile(snapshot)) => {
+                let paths_to_index = {
+                    let mut index = index.write();
+                    let stale_paths = index
+                        .ids_by_path
+                        .keys()
+                        .filter(|path| {
+                            snapshot
+                                .entry_for_path(path)
+                                .map_or(true, |entry| !is_indexable(entry))
+                        })
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    for path in stale_paths {
+                        index.remove(&path);
+                    }
+                    snapshot
+                        .files(false, 0)
+                        .filter(|entry| is_indexable(entry) && !index.is_up_to_date(entry))
+                        .map(|entry| entry.path.clone())
+                        .collect::<Vec<_>>()
+                };
+                reconciled = true;
+                (snapshot, paths_to_index)
+            }
+            Some(IndexUpdate::Changes(snapshot, changes)) => {
+                if !reconciled {
+                    continue;
+                }
+                let mut paths_to_index = Vec::new();
+                let mut index = index.write();
+                for (path, _, change) in changes.iter() {
+                    match snapshot.entry_for_path(path) {
+                        Some(entry) if *change != PathChange::Removed && is_indexable(entry) => {
+                            paths_to_index.push(entry.path.clone())
+                        }
+                        _ => index.remove_recursive(path),
+                    }
+                }
+                drop(index);
+                (snapshot, paths_to_index)
+            }
+        };
+
+        for batch in paths_to_index.chunks(INDEXING_BATCH_LEN) {
+            let indexed_files = index_files(batch, &snapshot, fs.as_ref(), &executor).await;
+            let mut index = index.write();
+            for (path, mtime, trigrams) in indexed_files {
+                match trigrams {
+                    Some(trigrams) => index.insert(path, mtime, &trigrams),
+                    None => index.remove(&path),
+                }
+            }
+        }
+
+        let mut index = index.write();
+        index.maybe_compact();
+        index.is_ready = true;
+        dirty = true;
+    }
+}
+
+fn is_indexable(entry: &Entry) -> bool {
+    entry.is_file() && !entry.is_ignored && !entry.is_external && entry.mtime.is_some()
+}
+
+/// Reads the given files concurrently, returning the trigrams of every file that could be
+/// indexed.
+async fn index_files(
+    paths: &[Arc<Path>],
+    snapshot: &LocalSnapshot,
+    fs: &dyn Fs,
+    executor: &BackgroundExecutor,
+) -> Vec<(Arc<Path>, SystemTime, Option<Vec<Trigram>>)> {
+    let workers = executor.num_cpus().min(paths.len()).max(1);
+    let paths_per_worker = (paths.len() + workers - 1) / workers;
+    let mut results = vec![Vec::new(); workers];
+    executor
+        .scoped(|scope| {
+            for (paths, results) in paths.chunks(paths_per_worker).zip(results.iter_mut()) {
+                scope.spawn(async move {
+                    for path in paths {
+                        let Some(mtime) = snapshot.entry_for_path(path).and_then(|e| e.mtime)
+                        else {
+                            continue;
+                        };
+                        let abs_path = snapshot.abs_path().join(path);
+                        let trigrams = read_indexable_file(&abs_path, fs)
+                            .await
+                            .with_context(|| format!("indexing {abs_path:?}"))
+                            .log_err()
+                            .flatten()
+                            .map(|contents| trigrams(&contents));
+                        results.push((path.clone(), mtime, trigrams));
+                    }
+                });
+            }
+        })
+        .await;
+    results.into_iter().flatten().collect()
+}
+
+async fn read_indexable_file(abs_path: &Path, fs: &dyn Fs) -> Result<Option<Vec<u8>>> {
+    let file = fs.open_sync(abs_path).await?;
+    let mut contents = Vec::new();
+    file.take(MAX_INDEXED_FILE_LEN + 1)
+        .read_to_end(&mut contents)?;
+    if contents.len() as u64 > MAX_INDEXED_FILE_LEN {
+        Ok(None)
+    } else {
+        Ok(Some(contents))
+    }
+}
+
+async fn load_index(index_path: &Path, fs: &dyn Fs) -> Result<SearchIndex> {
+    let mut bytes = Vec::new();
+    fs.open_sync(index_path).await?.read_to_end(&mut bytes)?;
+    SearchIndex::deserialize(&bytes)
+}
+
+async fn save_index(index: &RwLock<SearchIndex>, index_path: &Path, fs: &dyn Fs) -> Result<()> {
+    let bytes = {
+        let mut index = index.write();
+        index.compact();
+        index.serialize()
+    };
+    if let Some(dir) = index_path.parent() {
+        fs.create_dir(dir).await?;
+    }
+    let temp_path = index_path.with_extension("idx.tmp");
+    let mut contents = futures::io::Cursor::new(bytes);
+    fs.create_file_with(&temp_path, std::pin::Pin::new(&mut contents))
+        .await?;
+    fs.rename(
+        &temp_path,
+        index_path,
+        RenameOptions {
+            overwrite: true,
+            ignore_if_exists: false,
+        },
+    )
+    .await
+}
+
+/// The candidate files of one worktree for a given query, according to its index.
+pub struct SearchIndexFilter {
+    index: Arc<RwLock<SearchIndex>>,
+    generation: u64,
+    file_count: u32,
+    candidates: Vec<u32>,
+}
+
+impl SearchIndexFilter {
+    /// Returns `None` when the index cannot narrow down the files to search, either because
+    /// it isn't ready yet or because no trigrams are required by the query.
+    pub fn new(index: &Arc<RwLock<SearchIndex>>, query: &SearchQuery) -> Option<Self> {
+        let trigrams = query_trigrams(query)?;
+        let state = index.read();
+        if !state.is_ready {
+            return None;
+        }
+        Some(Self {
+            index: index.clone(),
+            generation: state.generation,
+            file_count: state.files.len() as u32,
+            candidates: state.files_containing(&trigrams),
+        })
+    }
+
+    /// Whether the file at the given path needs to be searched. Files that weren't indexed,
+    /// or whose index entry is out of date, are always searched.
+    pub fn may_match(&self, path: &Path, mtime: Option<SystemTime>) -> bool {
+        let index = self.index.read();
+        if index.generation != self.generation {
+            return true;
+        }
+        let Some(&id) = index.ids_by_path.get(path) else {
+            return true;
+        };
+        if id >= self.file_count {
+            return true;
+        }
+        match &index.files[id as usize] {
+            Some(file) if Some(file.mtime) == mtime => self.candidates.binary_search(&id).is_ok(),
+            _ => true,
+        }
+    }
+}
+
+fn trigram(bytes: &[u8]) -> Trigram {
+    (bytes[0].to_ascii_lowercase() as u32) << 16
+        | (bytes[1].to_ascii_lowercase() as u32) << 8
+        | bytes[2].to_ascii_lowercase() as u32
+}
+
+/// Returns the sorted, deduplicated trigrams of a file's contents.
+fn trigrams(contents: &[u8]) -> Vec<Trigram> {
+    let trigrams = contents.windows(3).map(trigram).collect::<HashSet<_>>();
+    let mut trigrams = trigrams.into_iter().collect::<Vec<_>>();
+    trigrams.sort_unstable();
+    trigrams
+}
+
+/// Returns the trigrams every match of the query has to contain, or `None` if there
+/// aren't any.
+fn query_trigrams(query: &SearchQuery) -> Option<Vec<Trigram>> {
+    let literals = match query {
+        SearchQuery::Text { .. } => vec![query.as_str().as_bytes().to_vec()],
+        SearchQuery::Regex { case_sensitive, .. } => {
+            required_regex_literals(query.as_str(), !case_sensitive)?
+        }
+        SearchQuery::Structural { .. } => return None,
+    };
+    let trigrams = literals
+        .iter()
+        .flat_map(|literal| literal.windows(3).map(trigram))
+        .collect::<HashSet<_>>();
+    if trigrams.is_empty() {
+        None
+    } else {
+        Some(trigrams.into_iter().collect())
+    }
+}
+
+/// Conservatively extracts literal byte strings that any match of the regex has to contain.
+///
+/// Only literals outside of groups and character classes are considered, characters that
+/// are made optional by a quantifier are dropped, and patterns containing alternations are
+/// not handled at all. When the regex is case insensitive, characters that Unicode case
+/// folding could match with a non-ASCII character end the current literal.
+fn required_regex_literals(pattern: &str, mut case_insensitive: bool) -> Option<Vec<Vec<u8>>> {
+    let mut literals = Vec::new();
+    let mut current = String::new();
+    let mut current_ends_with_literal = false;
+    let mut group_depth = 0usize;
+    let mut chars = pattern.chars().peekable();
+
+    fn finish(literals: &mut Vec<Vec<u8>>, current: &mut String) {
+        if current.len() >= 3 {
+            literals.push(current.as_bytes().to_vec());
+        }
+        current.clear();
+    }
+
+    while let Some(c) = chars.next() {
+        let mut literal = None;
+        match c {
+            '|' => return None,
+            '(' => {
+                if chars.peek() == Some(&'?') {
+                    let mut lookahead = chars.clone();
+                    lookahead.next();
+                    let mut flags = String::new();
+                    let mut terminator = None;
+                    for c in lookahead.by_ref() {
+                        if c.is_ascii_alphabetic() || c == '-' {
+                            flags.push(c);
+                        } else {
+                            terminator = Some(c);
+                            break;
+                        }
+                    }
+                    if matches!(terminator, Some(')' | ':')) {
+                        if flags.contains('x') {
+                            return None;
+                        }
+                        if flags.split('-').next().unwrap_or_default().contains('i') {
+                            case_insensitive = true;
+                        }
+                    }
+                    if terminator == Some(')') {
+                        chars = lookahead;
+                        finish(&mut literals, &mut current);
+                        current_ends_with_literal = false;
+                        continue;
+                    }
+                }
+                group_depth += 1;
+            }
+            ')' => group_depth = group_depth.saturating_sub(1),
+            '[' => {
+                let mut class_depth = 1;
+                while class_depth > 0 {
+                    match chars.next()? {
+                        '\\' => {
+                            chars.next()?;
+                        }
+                        '[' => class_depth += 1,
+                        ']' => class_depth -= 1,
+                        _ => {}
+                    }
+                }
+            }
+            '?' | '*' | '{' => {
+                if c == '{' {
+                    while chars.next()? != '}' {}
+                }
+                if current_ends_with_literal {
+                    current.pop();
+                }
+            }
+            '\\' => {
+                let escaped = chars.next()?;
+                if escaped.is_ascii_punctuation() {
+                    literal = Some(escaped);
+                } else {
+                    let argument_len = match escaped {
+                        'x' => 2,
+                        'u' => 4,
+                        'U' => 8,
+                        'p' | 'P' => 1,
+                        _ => 0,
+                    };
+                    if argument_len > 0 {
+                        if chars.peek() == Some(&'{') {
+                            while chars.next()? != '}' {}
+                        } else {
+                            for _ in 0..argument_len {
+                                chars.next()?;
+                            }
+                        }
+                    }
+                }
+            }
+            '+' | '.' | '^' | '$' => {}
+            c => literal = Some(c),
+        }
+
+        match literal {
+            Some(c) if group_depth == 0 && is_case_stable(c, case_insensitive) => {
+                current.push(c);
+                current_ends_with_literal = true;
+            }
+            _ => {
+                finish(&mut literals, &mut current);
+                current_ends_with_literal = false;
+            }
+        }
+    }
+    finish(&mut literals, &mut current);
+
+    if literals.is_empty() {
+        None
+    } else {
+        Some(literals)
+    }
+}
+
+/// Whether a character can only match itself, up to ASCII case.
+fn is_case_stable(c: char, case_insensitive: bool) -> bool {
+    !case_insensitive || (c.is_ascii() && !matches!(c, 'k' | 'K' | 's' | 'S'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_regex_literals() {
+        fn literals(pattern: &str, case_insensitive: bool) -> Option<Vec<String>> {
+            required_regex_literals(pattern, case_insensitive).map(|literals| {
+                literals
+                    .into_iter()
+                    .map(|literal| String::from_utf8(literal).unwrap())
+                    .collect()
+            })
+        }
+
+        assert_eq!(
+            literals("fn main\\(\\)", false),
+            Some(vec!["fn main()".into()])
+        );
+        assert_eq!(
+            literals("foo\\d+bar.baz", false),
+            Some(vec!["foo".into(), "bar".into(), "baz".into()])
+        );
+        assert_eq!(
+            literals("colou?r_value", false),
+            Some(vec!["colo".into(), "r_value".into()])
+        );
+        assert_eq!(
+            literals("prefix(optional)?[a-z]{2,3}suffix", false),
+            Some(vec!["prefix".into(), "suffix".into()])
+        );
+        assert_eq!(literals("task_list", true), Some(vec!["_li".into()]));
+        assert_eq!(
+            literals("(?i)hello world", false),
+            Some(vec!["hello world".into()])
+        );
+        assert_eq!(literals("\\x41BC", false), None);
+        assert_eq!(literals("foo|bar", false), None);
+        assert_eq!(literals("a.b.c", false), None);
+        assert_eq!(literals("(?x) ab c ", false), None);
+        assert_eq!(
+            literals("(?P<name>a(b)cde)?fgh", false),
+            Some(vec!["fgh".into()])
+        );
+    }
+
+    #[test]
+    fn test_index_queries() {
+        let mut index = SearchIndex::default();
+        let mtime = UNIX_EPOCH;
+        index.insert(
+            Path::new("a.rs").into(),
+            mtime,
+            &trigrams(b"fn main() { println!(\"Hello\") }"),
+        );
+        index.insert(
+            Path::new("b.rs").into(),
+            mtime,
+            &trigrams(b"fn helper() -> usize { 1 }"),
+        );
+        index.insert(Path::new("c.txt").into(), mtime, &trigrams(b"hello world"));
+
+        let matching_paths = |index: &SearchIndex, query: &[u8]| {
+            let query_trigrams = query.windows(3).map(trigram).collect::<Vec<_>>();
+            index
+                .files_containing(&query_trigrams)
+                .into_iter()
+                .filter_map(|id| {
+                    let file = index.files[id as usize].as_ref()?;
+                    Some(file.path.to_string_lossy().to_string())
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(matching_paths(&index, b"HELLO"), ["a.rs", "c.txt"]);
+        assert_eq!(matching_paths(&index, b"fn "), ["a.rs", "b.rs"]);
+        assert!(matching_paths(&index, b"goodbye").is_empty());
+
+        index.remove(Path::new("a.rs"));
+        index.insert(Path::new("b.rs").into(), mtime, &trigrams(b"hello again"));
+        index.compact();
+        assert_eq!(index.file_count(), 2);
+        assert_eq!(matching_paths(&index, b"hello"), ["c.txt", "b.rs"]);
+
+        let deserialized = SearchIndex::deserialize(&index.serialize()).unwrap();
+        assert_eq!(matching_paths(&deserialized, b"hello"), ["c.txt", "b.rs"]);
+        assert!(matching_paths(&deserialized, b"main").is_empty());
+    }
+}
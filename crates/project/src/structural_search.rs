@@ -0,0 +1,533 @@
+//! Structural search: code patterns, matched against the syntax trees of the buffers rather than their text.
+//!
+//! A pattern is written in the language it searches, with metavariables standing for the parts that vary:
+//! `$NAME` matches any single syntax node, and `$$$NAME` any sequence of sibling nodes, possibly empty.
+//! A metavariable used several times must match the same text each time, unless its name starts with `_`,
+//! and `$_` and `$$$` match without capturing anything. Replacement templates use the metavariables
+//! to insert the text they captured.
+//!
+//! The pattern is parsed with the grammar of every language it is matched against, so `$A + $B`
+//! finds additions in any language where that parses, while the same pattern may mean nothing in others.
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use language::{BufferSnapshot, GrammarId, Language};
+use parking_lot::Mutex;
+use regex::{Captures as RegexCaptures, Regex};
+use smol::future::yield_now;
+use std::{
+    fmt,
+    ops::Range,
+    sync::{Arc, OnceLock},
+};
+use tree_sitter::{Node, Parser, Tree};
+
+static METAVARIABLE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+fn metavariable_regex() -> &'static Regex {
+    METAVARIABLE_REGEX
+        .get_or_init(|| Regex::new(r"\$\$\$([A-Z_][A-Z0-9_]*)?|\$([A-Z_][A-Z0-9_]*)").unwrap())
+}
+
+/// A metavariable of a pattern, replaced in its source by an identifier, so that it parses in most languages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Metavariable {
+    name: Option<Arc<str>>,
+    /// Whether it matches a sequence of nodes, rather than a single one.
+    multiple: bool,
+}
+
+impl Metavariable {
+    /// Whether all of its occurrences must match the same text.
+    fn is_captured(&self) -> bool {
+        self.name
+            .as_ref()
+            .map_or(false, |name| !name.starts_with('_'))
+    }
+}
+
+pub struct StructuralPattern {
+    query: Arc<str>,
+    /// The query, with the metavariables replaced by identifiers.
+    source: String,
+    metavariables: HashMap<String, Metavariable>,
+    /// The words of the query, which the files matching it must contain.
+    literal_words: Vec<String>,
+    /// The query parsed with each grammar it was matched against, if it parsed.
+    compiled: Mutex<HashMap<GrammarId, Option<Arc<CompiledPattern>>>>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("query", &self.query)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The text captured by the metavariables of a match.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StructuralCaptures(Vec<(Arc<str>, Range<usize>)>);
+
+impl StructuralCaptures {
+    pub fn get(&self, name: &str) -> Option<Range<usize>> {
+        self.0
+            .iter()
+            .find(|(captured, _)| captured.as_ref() == name)
+            .map(|(_, range)| range.clone())
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(query: &str) -> Result<Self> {
+        let mut metavariables = HashMap::default();
+        let mut source = String::with_capacity(query.len());
+        let mut last_end = 0;
+        for captures in metavariable_regex().captures_iter(query) {
+            let whole = captures.get(0).unwrap();
+            let multiple = whole.as_str().starts_with("$$$");
+            let name = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .map(|name| Arc::from(name.as_str()))
+                .filter(|name: &Arc<str>| name.as_ref() != "_");
+            let placeholder = format!(
+                "zed_{}_{}",
+                if multiple { "metas" } else { "meta" },
+                metavariables.len()
+            );
+            source.push_str(&query[last_end..whole.start()]);
+            source.push_str(&placeholder);
+            metavariables.insert(placeholder, Metavariable { name, multiple });
+            last_end = whole.end();
+        }
+        source.push_str(&query[last_end..]);
+
+        if source.trim().is_empty() {
+            return Err(anyhow!("empty structural search pattern"));
+        }
+        if metavariables.contains_key(source.trim()) {
+            return Err(anyhow!(
+                "structural search pattern {query:?} has no code besides its metavariable"
+            ));
+        }
+
+        let literal_words = metavariable_regex()
+            .replace_all(query, " ")
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|word| !word.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+
+        Ok(Self {
+            query: query.into(),
+            source,
+            metavariables,
+            literal_words,
+            compiled: Mutex::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.query
+    }
+
+    /// Whether a file's text may contain a match, as it contains all the words of the pattern.
+    pub fn may_match(&self, text: &str) -> bool {
+        self.literal_words.iter().all(|word| text.contains(word))
+    }
+
+    /// Finds the matches of the pattern in the buffer, restricted to the given range of it.
+    pub async fn search(
+        &self,
+        buffer: &BufferSnapshot,
+        subrange: Option<Range<usize>>,
+    ) -> Vec<Range<usize>> {
+        const YIELD_INTERVAL: usize = 20000;
+
+        let subrange = subrange.unwrap_or(0..buffer.len());
+        let parsed;
+        let layers = buffer.syntax_layers().collect::<Vec<_>>();
+        let trees = if layers.is_empty() {
+            parsed = parse_buffer(buffer);
+            parsed
+                .iter()
+                .map(|(language, tree)| (language, tree.root_node()))
+                .collect::<Vec<_>>()
+        } else {
+            layers
+                .iter()
+                .map(|layer| (layer.language, layer.node()))
+                .collect()
+        };
+
+        let mut matches = Vec::new();
+        let mut visited = 0;
+        for (language, root) in trees {
+            let Some(pattern) = self.compiled_for(language) else {
+                continue;
+            };
+            let pattern_root = pattern.root();
+            let mut stack = vec![root];
+            while let Some(node) = stack.pop() {
+                visited += 1;
+                if visited % YIELD_INTERVAL == 0 {
+                    yield_now().await;
+                }
+
+                let range = node.byte_range();
+                if range.start >= subrange.end || range.end <= subrange.start {
+                    continue;
+                }
+                if subrange.start <= range.start
+                    && range.end <= subrange.end
+                    && node.kind_id() == pattern_root.kind_id()
+                    && Matcher::new(self, buffer).match_node(pattern_root, node)
+                {
+                    // Matches nested in another one are not reported.
+                    matches.push(range.start - subrange.start..range.end - subrange.start);
+                    continue;
+                }
+                stack.extend(
+                    (0..node.child_count())
+                        .rev()
+                        .filter_map(|ix| node.child(ix)),
+                );
+            }
+        }
+
+        // Layers of injected languages may match at the same places as the ones containing them.
+        matches.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        matches.dedup_by(|next, previous| next.start < previous.end);
+        matches
+    }
+
+    /// The captures of the match at the given range of the buffer, to replace it with.
+    pub fn captures_at(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<StructuralCaptures> {
+        let parsed;
+        let layers = buffer.syntax_layers().collect::<Vec<_>>();
+        let trees = if layers.is_empty() {
+            parsed = parse_buffer(buffer);
+            parsed
+                .iter()
+                .map(|(language, tree)| (language, tree.root_node()))
+                .collect::<Vec<_>>()
+        } else {
+            layers
+                .iter()
+                .map(|layer| (layer.language, layer.node()))
+                .collect()
+        };
+
+        trees.into_iter().find_map(|(language, root)| {
+            let pattern = self.compiled_for(language)?;
+            let pattern_root = pattern.root();
+            let mut node = root.descendant_for_byte_range(range.start, range.end)?;
+            // The match is the outermost node of its range, as several may span it.
+            while let Some(parent) = node.parent().filter(|parent| parent.byte_range() == range) {
+                node = parent;
+            }
+            loop {
+                if node.byte_range() != range {
+                    return None;
+                }
+                let mut matcher = Matcher::new(self, buffer);
+                if node.kind_id() == pattern_root.kind_id()
+                    && matcher.match_node(pattern_root, node)
+                {
+                    return Some(matcher.captures);
+                }
+                node = node.named_child(0)?;
+            }
+        })
+    }
+
+    /// Expands the metavariables of the replacement template with the text they captured.
+    pub fn replacement(
+        &self,
+        template: &str,
+        buffer: &BufferSnapshot,
+        captures: &StructuralCaptures,
+    ) -> String {
+        metavariable_regex()
+            .replace_all(template, |template_captures: &RegexCaptures| {
+                let whole = template_captures.get(0).unwrap().as_str();
+                template_captures
+                    .get(1)
+                    .or_else(|| template_captures.get(2))
+                    .and_then(|name| captures.get(name.as_str()))
+                    .map(|range| buffer.text_for_range(range).collect::<String>())
+                    .unwrap_or_else(|| whole.to_string())
+            })
+            .into_owned()
+    }
+
+    fn compiled_for(&self, language: &Arc<Language>) -> Option<Arc<CompiledPattern>> {
+        let grammar = language.grammar()?;
+        self.compiled
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| {
+                let mut parser = Parser::new();
+                parser.set_language(&grammar.ts_language).ok()?;
+                let tree = parser.parse(&self.source, None)?;
+                if contains_error(tree.root_node()) {
+                    return None;
+                }
+                let root_path = pattern_root_path(tree.root_node(), &self.source);
+                Some(Arc::new(CompiledPattern { tree, root_path }))
+            })
+            .clone()
+    }
+}
+
+/// The pattern parsed with a grammar.
+struct CompiledPattern {
+    tree: Tree,
+    /// The indices of the children leading to the node matched, the innermost one spanning the whole pattern.
+    root_path: Vec<usize>,
+}
+
+impl CompiledPattern {
+    fn root(&self) -> Node<'_> {
+        let mut node = self.tree.root_node();
+        for &index in &self.root_path {
+            node = node.child(index).unwrap();
+        }
+        node
+    }
+}
+
+fn pattern_root_path(root: Node, source: &str) -> Vec<usize> {
+    let mut path = Vec::new();
+    let mut node = root;
+    let text = source.trim();
+    loop {
+        let children = significant_children(node);
+        let [(index, child)] = children[..] else {
+            break;
+        };
+        if child.utf8_text(source.as_bytes()).ok().map(str::trim) != Some(text) {
+            break;
+        }
+        path.push(index);
+        node = child;
+    }
+    path
+}
+
+/// Parses the buffer, if its syntax was not parsed yet.
+fn parse_buffer(buffer: &BufferSnapshot) -> Option<(Arc<Language>, Tree)> {
+    let language = buffer.language()?.clone();
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()?.ts_language).ok()?;
+    let tree = parser.parse(buffer.text(), None)?;
+    Some((language, tree))
+}
+
+fn contains_error(node: Node) -> bool {
+    if !node.has_error() {
+        return false;
+    }
+    if node.is_error() {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let has_error = node.children(&mut cursor).any(contains_error);
+    has_error
+}
+
+/// The children of a node to match, without the comments and the tokens tree-sitter inserted to recover from errors.
+fn significant_children(node: Node) -> Vec<(usize, Node)> {
+    let mut cursor = node.walk();
+    let children = node
+        .children(&mut cursor)
+        .enumerate()
+        .filter(|(_, child)| !child.is_extra() && !child.is_missing())
+        .collect();
+    children
+}
+
+struct Matcher<'a> {
+    pattern: &'a StructuralPattern,
+    source: &'a str,
+    buffer: &'a BufferSnapshot,
+    captures: StructuralCaptures,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(pattern: &'a StructuralPattern, buffer: &'a BufferSnapshot) -> Self {
+        Self {
+            pattern,
+            source: &pattern.source,
+            buffer,
+            captures: StructuralCaptures::default(),
+        }
+    }
+
+    fn pattern_text(&self, node: Node) -> &'a str {
+        &self.source[node.byte_range()]
+    }
+
+    fn metavariable(&self, node: Node) -> Option<&'a Metavariable> {
+        self.pattern.metavariables.get(self.pattern_text(node))
+    }
+
+    fn match_node(&mut self, pattern: Node, candidate: Node) -> bool {
+        if let Some(metavariable) = self.metavariable(pattern) {
+            let range = candidate.byte_range();
+            return self.capture(metavariable, range);
+        }
+        if pattern.kind_id() != candidate.kind_id() {
+            return false;
+        }
+
+        let pattern_children = significant_children(pattern);
+        let candidate_children = significant_children(candidate);
+        if pattern_children.is_empty() {
+            return self.candidate_text_eq(candidate.byte_range(), self.pattern_text(pattern));
+        }
+        // The text between the children, like the contents of string literals, must match too.
+        if self.pattern_gaps(pattern) != self.candidate_gaps(candidate) {
+            return false;
+        }
+
+        let pattern_children = pattern_children
+            .into_iter()
+            .map(|(_, child)| child)
+            .collect::<Vec<_>>();
+        let candidate_children = candidate_children
+            .into_iter()
+            .map(|(_, child)| child)
+            .collect::<Vec<_>>();
+        self.match_sequence(&pattern_children, &candidate_children)
+    }
+
+    fn match_sequence(&mut self, patterns: &[Node], candidates: &[Node]) -> bool {
+        let Some((first, rest)) = patterns.split_first() else {
+            return candidates.is_empty();
+        };
+
+        let captured_len = self.captures.0.len();
+        if let Some(metavariable) = self.metavariable(*first).filter(|m| m.multiple) {
+            for len in 0..=candidates.len() {
+                let range = match &candidates[..len] {
+                    [] => {
+                        let position = candidates
+                            .first()
+                            .map_or(first.start_byte(), |candidate| candidate.start_byte());
+                        position..position
+                    }
+                    [first, .., last] => first.start_byte()..last.end_byte(),
+                    [only] => only.byte_range(),
+                };
+                if self.capture(metavariable, range)
+                    && self.match_sequence(rest, &candidates[len..])
+                {
+                    return true;
+                }
+                self.captures.0.truncate(captured_len);
+            }
+            return false;
+        }
+
+        let Some((candidate, candidates_rest)) = candidates.split_first() else {
+            return false;
+        };
+        if self.match_node(*first, *candidate) && self.match_sequence(rest, candidates_rest) {
+            return true;
+        }
+        self.captures.0.truncate(captured_len);
+        false
+    }
+
+    fn capture(&mut self, metavariable: &Metavariable, range: Range<usize>) -> bool {
+        if !metavariable.is_captured() {
+            return true;
+        }
+        let name = metavariable.name.clone().unwrap();
+        if let Some(captured) = self.captures.get(&name) {
+            let captured_text = self.buffer.text_for_range(captured).collect::<String>();
+            return self.candidate_text_eq(range, &captured_text);
+        }
+        self.captures.0.push((name, range));
+        true
+    }
+
+    fn candidate_text_eq(&self, range: Range<usize>, text: &str) -> bool {
+        range.len() == text.len() && self.buffer.contains_str_at(range.start, text)
+    }
+
+    fn pattern_gaps(&self, node: Node) -> String {
+        gaps(node, |range| self.source[range].to_string())
+    }
+
+    fn candidate_gaps(&self, node: Node) -> String {
+        gaps(node, |range| {
+            self.buffer.text_for_range(range).collect::<String>()
+        })
+    }
+}
+
+/// The text of a node outside of its children and comments, without whitespace.
+fn gaps(node: Node, text_for_range: impl Fn(Range<usize>) -> String) -> String {
+    let mut gaps = String::new();
+    let mut offset = node.start_byte();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.start_byte() > offset {
+            gaps.extend(
+                text_for_range(offset..child.start_byte())
+                    .chars()
+                    .filter(|c| !c.is_whitespace()),
+            );
+        }
+        offset = offset.max(child.end_byte());
+    }
+    if node.end_byte() > offset {
+        gaps.extend(
+            text_for_range(offset..node.end_byte())
+                .chars()
+                .filter(|c| !c.is_whitespace()),
+        );
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_patterns() {
+        let pattern = StructuralPattern::new("$$$ARGS.push($ITEM, $_, $$$)").unwrap();
+        assert_eq!(
+            pattern.source,
+            "zed_metas_0.push(zed_meta_1, zed_meta_2, zed_metas_3)"
+        );
+        assert_eq!(
+            pattern.metavariables["zed_metas_0"],
+            Metavariable {
+                name: Some("ARGS".into()),
+                multiple: true
+            }
+        );
+        assert_eq!(
+            pattern.metavariables["zed_meta_2"],
+            Metavariable {
+                name: None,
+                multiple: false
+            }
+        );
+        assert_eq!(pattern.literal_words, vec!["push".to_string()]);
+        assert!(pattern.may_match("items.push(1, 2)"));
+        assert!(!pattern.may_match("items.pop()"));
+
+        assert!(StructuralPattern::new("  ").is_err());
+        assert!(StructuralPattern::new(" $A ").is_err());
+    }
+}
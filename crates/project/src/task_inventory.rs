@@ -37,13 +37,15 @@ pub enum TaskSourceKind {
     Worktree { id: WorktreeId, abs_path: PathBuf },
     /// Buffer-specific task definitions, originating in e.g. language extension.
     Buffer,
+    /// Task templates provided by installed extensions, applicable to any path
+    Extensions,
 }
 
 impl TaskSourceKind {
     fn abs_path(&self) -> Option<&Path> {
         match self {
             Self::AbsPath(abs_path) | Self::Worktree { abs_path, .. } => Some(abs_path),
-            Self::UserInput | Self::Buffer => None,
+            Self::UserInput | Self::Buffer | Self::Extensions => None,
         }
     }
 
@@ -218,6 +220,15 @@ impl Inventory {
             })
     }
 
+    /// Returns the context the task with the given id was last scheduled with, if it ever was.
+    pub fn last_scheduled_context(&self, id: &TaskId) -> Option<&TaskContext> {
+        self.last_scheduled_tasks
+            .iter()
+            .rev()
+            .find(|(scheduled_id, _)| scheduled_id == id)
+            .map(|(_, task_context)| task_context)
+    }
+
     /// Registers task "usage" as being scheduled – to be used for LRU sorting when listing all tasks.
     pub fn task_scheduled(&mut self, id: TaskId, task_context: TaskContext) {
         self.last_scheduled_tasks.push_back((id, task_context));
@@ -1,20 +1,29 @@
 use crate::Project;
-use collections::HashMap;
-use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, WeakModel};
+use collections::{HashMap, HashSet};
+use gpui::{AnyWindowHandle, Context, Entity, Model, ModelContext, Task, WeakModel};
+use language::{Diagnostic, DiagnosticEntry, PointUtf16, Unclipped};
+use lsp::{DiagnosticSeverity, LanguageServerId};
 use settings::Settings;
 use smol::channel::bounded;
-use std::path::{Path, PathBuf};
+use std::{
+    mem,
+    path::{Path, PathBuf},
+};
+use task::problem_matcher::{Problem, ProblemMatcher, ProblemSeverity};
 use terminal::{
-    terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
-    SpawnTask, TaskState, TaskStatus, Terminal, TerminalBuilder,
+    terminal_settings::{self, Shell, TerminalSessions, TerminalSettings, VenvSettingsContent},
+    DetachedSession, SpawnTask, TaskState, TaskStatus, Terminal, TerminalBuilder, TerminalSession,
 };
-use util::ResultExt;
+use util::{post_inc, ResultExt};
 
 // #[cfg(target_os = "macos")]
 // use std::os::unix::ffi::OsStrExt;
 
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakModel<terminal::Terminal>>,
+    /// Problems found in the output of tasks are reported under a diagnostic source per task label,
+    /// each with its own server id and the paths it has reported diagnostics for.
+    pub(crate) task_diagnostics: HashMap<String, (LanguageServerId, HashSet<PathBuf>)>,
 }
 
 impl Project {
@@ -24,15 +33,53 @@ impl Project {
         spawn_task: Option<SpawnTask>,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
-    ) -> anyhow::Result<Model<Terminal>> {
-        anyhow::ensure!(
-            !self.is_remote(),
-            "creating terminals as a guest is not supported yet"
-        );
+    ) -> Task<anyhow::Result<Model<Terminal>>> {
+        self.create_terminal_with_session(working_directory, spawn_task, None, None, window, cx)
+    }
+
+    /// Reopens a terminal from a previous Zed session, with the shell it was running.
+    pub fn restore_terminal(
+        &mut self,
+        working_directory: Option<PathBuf>,
+        shell: Option<Shell>,
+        session: TerminalSession,
+        window: AnyWindowHandle,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<Model<Terminal>>> {
+        self.create_terminal_with_session(working_directory, None, shell, Some(session), window, cx)
+    }
+
+    fn create_terminal_with_session(
+        &mut self,
+        working_directory: Option<PathBuf>,
+        spawn_task: Option<SpawnTask>,
+        shell: Option<Shell>,
+        session: Option<TerminalSession>,
+        window: AnyWindowHandle,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<anyhow::Result<Model<Terminal>>> {
+        if self.is_remote() {
+            return Task::ready(Err(anyhow::anyhow!(
+                "creating terminals as a guest is not supported yet"
+            )));
+        }
 
         let is_terminal = spawn_task.is_none();
+        let problem_matchers = spawn_task
+            .as_ref()
+            .filter(|spawn_task| !spawn_task.problem_matchers.is_empty())
+            .map(|spawn_task| {
+                (
+                    spawn_task.label.clone(),
+                    spawn_task.problem_matchers.clone(),
+                )
+            });
         let settings = TerminalSettings::get_global(cx);
         let python_settings = settings.detect_venv.clone();
+        let blinking = settings.blinking.clone();
+        let alternate_scroll = settings.alternate_scroll;
+        let max_scroll_history_lines = settings.max_scroll_history_lines;
+        let detach = settings.sessions == TerminalSessions::Detach;
         let (completion_tx, completion_rx) = bounded(1);
 
         let mut env = settings.env.clone();
@@ -41,13 +88,18 @@ impl Project {
 
         let venv_base_directory = working_directory
             .as_deref()
-            .unwrap_or_else(|| Path::new(""));
+            .unwrap_or_else(|| Path::new(""))
+            .to_path_buf();
 
         let (spawn_task, shell) = if let Some(spawn_task) = spawn_task {
             env.extend(spawn_task.env);
             // Activate minimal Python virtual environment
             if let Some(python_settings) = &python_settings.as_option() {
-                self.set_python_venv_path_for_tasks(python_settings, venv_base_directory, &mut env);
+                self.set_python_venv_path_for_tasks(
+                    python_settings,
+                    &venv_base_directory,
+                    &mut env,
+                );
             }
             (
                 Some(TaskState {
@@ -62,60 +114,256 @@ impl Project {
                 },
             )
         } else {
-            (None, settings.shell.clone())
+            (None, shell.unwrap_or_else(|| settings.shell.clone()))
         };
 
-        let terminal = TerminalBuilder::new(
-            working_directory.clone(),
-            spawn_task,
-            shell,
-            env,
-            Some(settings.blinking.clone()),
-            settings.alternate_scroll,
-            settings.max_scroll_history_lines,
-            window,
-            completion_tx,
-        )
-        .map(|builder| {
-            let terminal_handle = cx.new_model(|cx| builder.subscribe(cx));
-
-            self.terminals
-                .local_handles
-                .push(terminal_handle.downgrade());
-
-            let id = terminal_handle.entity_id();
-            cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
-                let handles = &mut project.terminals.local_handles;
-
-                if let Some(index) = handles
-                    .iter()
-                    .position(|terminal| terminal.entity_id() == id)
-                {
-                    handles.remove(index);
-                    cx.notify();
-                }
-            })
-            .detach();
+        // Tasks are not restored, so there is no point keeping them running after Zed quits.
+        // Attaching to a session may have to start its daemon first, which is not done on the main thread.
+        let detached_session = (cfg!(unix) && is_terminal && detach).then(|| {
+            let id = session
+                .as_ref()
+                .and_then(|session| session.detached_session_id.clone());
+            let shell = shell.clone();
+            let working_directory = working_directory.clone();
+            let env = env.clone();
+            cx.background_executor()
+                .spawn(async move { DetachedSession::attach(id, &shell, working_directory, env) })
+        });
+
+        cx.spawn(|project, mut cx| async move {
+            let detached_session = match detached_session {
+                Some(detached_session) => Some(detached_session.await?),
+                None => None,
+            };
+            project.update(&mut cx, |project, cx| {
+                let terminal_handle = TerminalBuilder::new(
+                    working_directory.clone(),
+                    spawn_task,
+                    shell,
+                    env,
+                    Some(blinking),
+                    alternate_scroll,
+                    max_scroll_history_lines,
+                    window,
+                    completion_tx,
+                    session,
+                    detached_session,
+                )
+                .map(|builder| cx.new_model(|cx| builder.subscribe(cx)))?;
+
+                project
+                    .terminals
+                    .local_handles
+                    .push(terminal_handle.downgrade());
+
+                let id = terminal_handle.entity_id();
+                cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
+                    let handles = &mut project.terminals.local_handles;
 
-            // if the terminal is not a task, activate full Python virtual environment
-            if is_terminal {
-                if let Some(python_settings) = &python_settings.as_option() {
-                    if let Some(activate_script_path) =
-                        self.find_activate_script_path(python_settings, venv_base_directory)
+                    if let Some(index) = handles
+                        .iter()
+                        .position(|terminal| terminal.entity_id() == id)
                     {
-                        self.activate_python_virtual_environment(
-                            Project::get_activate_command(python_settings),
-                            activate_script_path,
-                            &terminal_handle,
-                            cx,
-                        );
+                        handles.remove(index);
+                        cx.notify();
                     }
+                })
+                .detach();
+
+                if let Some((label, problem_matchers)) = problem_matchers {
+                    project.report_task_problems(
+                        label,
+                        problem_matchers,
+                        working_directory.clone(),
+                        &terminal_handle,
+                        cx,
+                    );
                 }
-            }
-            terminal_handle
+
+                // if the terminal is not a task, activate full Python virtual environment,
+                // unless its shell is still running from a previous session, with the environment activated already
+                if is_terminal && !terminal_handle.read(cx).attached_to_running_session() {
+                    if let Some(python_settings) = &python_settings.as_option() {
+                        if let Some(activate_script_path) =
+                            project.find_activate_script_path(python_settings, &venv_base_directory)
+                        {
+                            project.activate_python_virtual_environment(
+                                Project::get_activate_command(python_settings),
+                                activate_script_path,
+                                &terminal_handle,
+                                cx,
+                            );
+                        }
+                    }
+                }
+                Ok(terminal_handle)
+            })?
+        })
+    }
+
+    /// Clears the problems reported by the previous run of the task, and once the task completes,
+    /// reports the problems its problem matchers find in the terminal output as diagnostics.
+    fn report_task_problems(
+        &mut self,
+        source: String,
+        problem_matchers: Vec<ProblemMatcher>,
+        working_directory: Option<PathBuf>,
+        terminal_handle: &Model<Terminal>,
+        cx: &mut ModelContext<Project>,
+    ) {
+        let server_id = self.start_task_problems(&source, working_directory.as_deref(), cx);
+        let task_completed =
+            terminal_handle.update(cx, |terminal, cx| terminal.wait_for_completed_task(cx));
+        let terminal = terminal_handle.downgrade();
+        cx.spawn(|project, mut cx| async move {
+            task_completed.await;
+            let output = terminal
+                .update(&mut cx, |terminal, _| terminal.scrollback_text())
+                .unwrap_or_default();
+            project
+                .update(&mut cx, |project, cx| {
+                    project.finish_task_problems(
+                        source,
+                        server_id,
+                        problem_matchers,
+                        output,
+                        working_directory,
+                        cx,
+                    )
+                })?
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Clears the diagnostics reported by the previous run of the task, returning the server id to report the new ones under.
+    pub(crate) fn start_task_problems(
+        &mut self,
+        source: &str,
+        working_directory: Option<&Path>,
+        cx: &mut ModelContext<Project>,
+    ) -> LanguageServerId {
+        let languages = self.languages.clone();
+        let server_id = self
+            .terminals
+            .task_diagnostics
+            .entry(source.to_string())
+            .or_insert_with(|| (languages.next_language_server_id(), HashSet::default()))
+            .0;
+        self.update_task_diagnostics(source, Vec::new(), working_directory, cx);
+        self.disk_based_diagnostics_started(server_id, cx);
+        server_id
+    }
+
+    /// Reports the problems found in the output of a finished task as diagnostics.
+    pub(crate) fn finish_task_problems(
+        &mut self,
+        source: String,
+        server_id: LanguageServerId,
+        problem_matchers: Vec<ProblemMatcher>,
+        output: String,
+        working_directory: Option<PathBuf>,
+        cx: &mut ModelContext<Project>,
+    ) -> Task<anyhow::Result<()>> {
+        cx.spawn(|project, mut cx| async move {
+            let problems = cx
+                .background_executor()
+                .spawn(async move {
+                    problem_matchers
+                        .iter()
+                        .flat_map(|matcher| matcher.find_problems(&output))
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            project.update(&mut cx, |project, cx| {
+                project.update_task_diagnostics(
+                    &source,
+                    problems,
+                    working_directory.as_deref(),
+                    cx,
+                );
+                project.disk_based_diagnostics_finished(server_id, cx);
+            })
+        })
+    }
+
+    fn update_task_diagnostics(
+        &mut self,
+        source: &str,
+        problems: Vec<Problem>,
+        working_directory: Option<&Path>,
+        cx: &mut ModelContext<Project>,
+    ) {
+        let Some((server_id, previous_paths)) = self.terminals.task_diagnostics.get_mut(source)
+        else {
+            return;
+        };
+        let server_id = *server_id;
+        let previous_paths = mem::take(previous_paths);
+        let base_directory = working_directory.map(Path::to_path_buf).or_else(|| {
+            self.visible_worktrees(cx)
+                .next()
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
         });
 
-        terminal
+        let mut diagnostics_by_path =
+            HashMap::<PathBuf, Vec<DiagnosticEntry<Unclipped<PointUtf16>>>>::default();
+        for problem in problems {
+            let path = Path::new(&problem.path);
+            let abs_path = if path.is_absolute() {
+                path.to_path_buf()
+            } else if let Some(base_directory) = &base_directory {
+                base_directory.join(path)
+            } else {
+                continue;
+            };
+            let start = PointUtf16::new(problem.line, problem.column);
+            let end = problem
+                .end
+                .map_or(start, |(row, column)| PointUtf16::new(row, column));
+            let severity = match problem.severity {
+                ProblemSeverity::Error => DiagnosticSeverity::ERROR,
+                ProblemSeverity::Warning => DiagnosticSeverity::WARNING,
+                ProblemSeverity::Info => DiagnosticSeverity::INFORMATION,
+                ProblemSeverity::Hint => DiagnosticSeverity::HINT,
+            };
+            diagnostics_by_path
+                .entry(abs_path)
+                .or_default()
+                .push(DiagnosticEntry {
+                    range: Unclipped(start)..Unclipped(end),
+                    diagnostic: Diagnostic {
+                        source: Some(source.to_string()),
+                        code: problem.code,
+                        severity,
+                        message: problem.message,
+                        group_id: post_inc(&mut self.next_diagnostic_group_id),
+                        is_primary: true,
+                        is_disk_based: true,
+                        is_unnecessary: false,
+                    },
+                });
+        }
+
+        for path in previous_paths {
+            if !diagnostics_by_path.contains_key(&path) {
+                self.update_diagnostic_entries(server_id, path, None, Vec::new(), cx)
+                    .log_err();
+            }
+        }
+        let mut reported_paths = HashSet::default();
+        for (path, diagnostics) in diagnostics_by_path {
+            if self
+                .update_diagnostic_entries(server_id, path.clone(), None, diagnostics, cx)
+                .log_err()
+                .is_some()
+            {
+                reported_paths.insert(path);
+            }
+        }
+        if let Some((_, paths)) = self.terminals.task_diagnostics.get_mut(source) {
+            *paths = reported_paths;
+        }
     }
 
     pub fn find_activate_script_path(
@@ -26,7 +26,7 @@ use std::{
     cmp::Ordering,
     ffi::OsStr,
     ops::Range,
-    path::{Path, PathBuf},
+    path::Path,
     sync::Arc,
 };
 use theme::ThemeSettings;
@@ -1012,19 +1012,14 @@ impl ProjectPanel {
     ) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             if entry.is_dir() {
-                let include_root = self.project.read(cx).visible_worktrees(cx).count() > 1;
-                let dir_path = if include_root {
-                    let mut full_path = PathBuf::from(worktree.root_name());
-                    full_path.push(&entry.path);
-                    Arc::from(full_path)
-                } else {
-                    entry.path.clone()
+                let directory = ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: entry.path.clone(),
                 };
-
                 self.workspace
                     .update(cx, |workspace, cx| {
                         search::ProjectSearchView::new_search_in_directory(
-                            workspace, &dir_path, cx,
+                            workspace, directory, cx,
                         );
                     })
                     .ok();
@@ -11,6 +11,9 @@ pub struct ExtensionApiManifest {
     pub repository: String,
     pub schema_version: Option<i32>,
     pub wasm_api_version: Option<String>,
+    /// The SHA-256 checksum of the extension's packaged archive, as a hex string.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_sha256: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
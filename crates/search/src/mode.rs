@@ -1,6 +1,7 @@
 use gpui::{Action, SharedString};
+use project::search_history::SerializedSearchMode;
 
-use crate::{ActivateRegexMode, ActivateTextMode};
+use crate::{ActivateRegexMode, ActivateStructuralMode, ActivateTextMode};
 
 // TODO: Update the default search mode to get from config
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
@@ -8,6 +9,8 @@ pub enum SearchMode {
     #[default]
     Text,
     Regex,
+    /// Code patterns with metavariables, matched against syntax trees. Only the project search supports it.
+    Structural,
 }
 
 impl SearchMode {
@@ -15,6 +18,7 @@ impl SearchMode {
         match self {
             SearchMode::Text => "Text",
             SearchMode::Regex => "Regex",
+            SearchMode::Structural => "Structural",
         }
     }
     pub(crate) fn tooltip(&self) -> SharedString {
@@ -24,13 +28,35 @@ impl SearchMode {
         match self {
             SearchMode::Text => ActivateTextMode.boxed_clone(),
             SearchMode::Regex => ActivateRegexMode.boxed_clone(),
+            SearchMode::Structural => ActivateStructuralMode.boxed_clone(),
         }
     }
 }
 
-pub(crate) fn next_mode(mode: &SearchMode) -> SearchMode {
+impl From<SearchMode> for SerializedSearchMode {
+    fn from(mode: SearchMode) -> Self {
+        match mode {
+            SearchMode::Text => SerializedSearchMode::Text,
+            SearchMode::Regex => SerializedSearchMode::Regex,
+            SearchMode::Structural => SerializedSearchMode::Structural,
+        }
+    }
+}
+
+impl From<SerializedSearchMode> for SearchMode {
+    fn from(mode: SerializedSearchMode) -> Self {
+        match mode {
+            SerializedSearchMode::Text => SearchMode::Text,
+            SerializedSearchMode::Regex => SearchMode::Regex,
+            SerializedSearchMode::Structural => SearchMode::Structural,
+        }
+    }
+}
+
+pub(crate) fn next_mode(mode: &SearchMode, structural_supported: bool) -> SearchMode {
     match mode {
         SearchMode::Text => SearchMode::Regex,
-        SearchMode::Regex => SearchMode::Text,
+        SearchMode::Regex if structural_supported => SearchMode::Structural,
+        SearchMode::Regex | SearchMode::Structural => SearchMode::Text,
     }
 }
@@ -1,13 +1,15 @@
 use crate::{
-    mode::SearchMode, ActivateRegexMode, ActivateTextMode, CycleMode, NextHistoryQuery,
-    PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions, SelectNextMatch, SelectPrevMatch,
-    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleReplace, ToggleWholeWord,
+    mode::SearchMode, ActivateRegexMode, ActivateStructuralMode, ActivateTextMode, CycleMode,
+    NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
+    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored,
+    TogglePreserveCase, ToggleReplace, ToggleWholeWord,
 };
 use anyhow::Context as _;
 use collections::{HashMap, HashSet};
+use db::kvp::KEY_VALUE_STORE;
 use editor::{
     actions::SelectAll,
-    items::active_match_index,
+    items::{active_match_index, replacement_for_match},
     scroll::{Autoscroll, Axis},
     Anchor, Editor, EditorElement, EditorEvent, EditorStyle, MultiBuffer, MAX_TAB_TITLE_LEN,
 };
@@ -19,21 +21,26 @@ use gpui::{
     WeakModel, WeakView, WhiteSpace, WindowContext,
 };
 use menu::Confirm;
-use project::{search::SearchQuery, search_history::SearchHistoryCursor, Project};
+use project::{
+    search::{SearchQuery, SearchScope},
+    search_history::{SearchHistoryCursor, SerializedSearch, SerializedSearchScope},
+    Project, ProjectPath,
+};
 use settings::Settings;
 use smol::stream::StreamExt;
 use std::{
     any::{Any, TypeId},
     mem,
     ops::{Not, Range},
-    path::{Path, PathBuf},
+    path::PathBuf,
+    sync::Arc,
 };
 use theme::ThemeSettings;
 use ui::{
-    h_flex, prelude::*, v_flex, Icon, IconButton, IconName, Label, LabelCommon, LabelSize,
-    Selectable, ToggleButton, Tooltip,
+    h_flex, popover_menu, prelude::*, v_flex, ContextMenu, Icon, IconButton, IconName, Label,
+    LabelCommon, LabelSize, Selectable, ToggleButton, Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, ResultExt as _};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
@@ -42,12 +49,24 @@ use workspace::{
 };
 use workspace::{DeploySearch, NewSearch};
 
+mod saved_searches;
+
 const MIN_INPUT_WIDTH_REMS: f32 = 15.;
 const MAX_INPUT_WIDTH_REMS: f32 = 30.;
+const SEARCH_HISTORY_KEY: &str = "project_search_history";
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        ToggleMatchExclusion,
+        ToggleFileExclusion,
+        SaveSearch,
+        OpenSavedSearch
+    ]
 );
 
 #[derive(Default)]
@@ -57,7 +76,8 @@ impl Global for ActiveSettings {}
 
 pub fn init(cx: &mut AppContext) {
     cx.set_global(ActiveSettings::default());
-    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        load_search_history(workspace, cx);
         register_workspace_action(workspace, move |search_bar, _: &ToggleFilters, cx| {
             search_bar.toggle_filters(cx);
         });
@@ -70,12 +90,28 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
+        register_workspace_action(workspace, move |search_bar, _: &TogglePreserveCase, cx| {
+            search_bar.toggle_preserve_case(cx)
+        });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleMatchExclusion, cx| search_bar.toggle_exclusion(false, cx),
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleFileExclusion, cx| {
+            search_bar.toggle_exclusion(true, cx)
+        });
         register_workspace_action(workspace, move |search_bar, _: &ActivateRegexMode, cx| {
             search_bar.activate_search_mode(SearchMode::Regex, cx)
         });
         register_workspace_action(workspace, move |search_bar, _: &ActivateTextMode, cx| {
             search_bar.activate_search_mode(SearchMode::Text, cx)
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ActivateStructuralMode, cx| {
+                search_bar.activate_search_mode(SearchMode::Structural, cx)
+            },
+        );
         register_workspace_action(workspace, move |search_bar, action: &CycleMode, cx| {
             search_bar.cycle_mode(action, cx)
         });
@@ -96,6 +132,12 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
             ProjectSearchView::search_in_new(workspace, action, cx)
         });
+        register_workspace_action_for_present_search(workspace, |workspace, _: &SaveSearch, cx| {
+            saved_searches::SavedSearchesModal::toggle_save(workspace, cx)
+        });
+        workspace.register_action(|workspace, _: &OpenSavedSearch, cx| {
+            saved_searches::SavedSearchesModal::toggle_open(workspace, cx)
+        });
 
         // Both on present and dismissed search, we need to unconditionally handle those actions to focus from the editor.
         workspace.register_action(move |workspace, action: &DeploySearch, cx| {
@@ -118,11 +160,59 @@ pub fn init(cx: &mut AppContext) {
     .detach();
 }
 
+/// Workspaces without a database id, e.g. the remote ones, are not restored, and neither is their search history.
+fn search_history_key(workspace_id: WorkspaceId) -> Option<String> {
+    if workspace_id == WorkspaceId::default() {
+        None
+    } else {
+        Some(format!("{SEARCH_HISTORY_KEY}-{}", i64::from(workspace_id)))
+    }
+}
+
+fn load_search_history(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(key) = search_history_key(workspace.database_id()) else {
+        return;
+    };
+    let project = workspace.project().downgrade();
+    cx.spawn(|_, mut cx| async move {
+        let searches = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(&key) })
+            .await?;
+        if let Some(searches) = searches {
+            let searches = serde_json::from_str::<Vec<SerializedSearch>>(&searches)?;
+            project.update(&mut cx, |project, _| {
+                project.search_history_mut().load(searches)
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+fn save_search_history(workspace_id: WorkspaceId, project: &Model<Project>, cx: &AppContext) {
+    let Some(key) = search_history_key(workspace_id) else {
+        return;
+    };
+    let Some(searches) =
+        serde_json::to_string(project.read(cx).search_history().searches()).log_err()
+    else {
+        return;
+    };
+    cx.background_executor()
+        .spawn(async move { KEY_VALUE_STORE.write_kvp(key, searches).await })
+        .detach_and_log_err(cx);
+}
+
 struct ProjectSearch {
     project: Model<Project>,
     excerpts: Model<MultiBuffer>,
     pending_search: Option<Task<Option<()>>>,
     match_ranges: Vec<Range<Anchor>>,
+    /// Indices of the matches that `ReplaceAll` skips.
+    excluded_matches: HashSet<usize>,
+    /// Indices of the matches that were already replaced, and no longer match.
+    replaced_matches: HashSet<usize>,
     active_query: Option<SearchQuery>,
     last_search_query_text: Option<String>,
     search_id: usize,
@@ -154,6 +244,12 @@ pub struct ProjectSearchView {
     filters_enabled: bool,
     replace_enabled: bool,
     current_mode: SearchMode,
+    workspace_id: Option<WorkspaceId>,
+    scope: SearchScope,
+    /// Scopes offered for the item that was active when the search got deployed.
+    active_item_scopes: Vec<SearchScope>,
+    /// Whether the scope of a search from the history or a saved one had paths not found in the project.
+    scope_unresolved: bool,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -162,6 +258,7 @@ struct ProjectSearchSettings {
     search_options: SearchOptions,
     filters_enabled: bool,
     current_mode: SearchMode,
+    scope: SearchScope,
 }
 
 pub struct ProjectSearchBar {
@@ -179,6 +276,8 @@ impl ProjectSearch {
             excerpts: cx.new_model(|_| MultiBuffer::new(replica_id, capability)),
             pending_search: Default::default(),
             match_ranges: Default::default(),
+            excluded_matches: Default::default(),
+            replaced_matches: Default::default(),
             active_query: None,
             last_search_query_text: None,
             search_id: 0,
@@ -196,6 +295,8 @@ impl ProjectSearch {
                 .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
             pending_search: Default::default(),
             match_ranges: self.match_ranges.clone(),
+            excluded_matches: self.excluded_matches.clone(),
+            replaced_matches: self.replaced_matches.clone(),
             active_query: self.active_query.clone(),
             last_search_query_text: self.last_search_query_text.clone(),
             search_id: self.search_id,
@@ -205,17 +306,24 @@ impl ProjectSearch {
         })
     }
 
-    fn search(&mut self, query: SearchQuery, cx: &mut ModelContext<Self>) {
+    fn search(
+        &mut self,
+        query: SearchQuery,
+        history_entry: SerializedSearch,
+        cx: &mut ModelContext<Self>,
+    ) {
         let search = self.project.update(cx, |project, cx| {
             project
                 .search_history_mut()
-                .add(&mut self.search_history_cursor, query.as_str().to_string());
+                .add_search(&mut self.search_history_cursor, history_entry);
             project.search(query.clone(), cx)
         });
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
         self.active_query = Some(query);
         self.match_ranges.clear();
+        self.excluded_matches.clear();
+        self.replaced_matches.clear();
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
             let mut matches = search;
             let this = this.upgrade()?;
@@ -467,6 +575,7 @@ impl Item for ProjectSearchView {
     }
 
     fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        self.workspace_id = Some(workspace.database_id());
         self.results_editor
             .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
     }
@@ -539,6 +648,11 @@ impl ProjectSearchView {
             search_options: self.search_options,
             filters_enabled: self.filters_enabled,
             current_mode: self.current_mode,
+            // Directory and multibuffer scopes belong to the item the search was deployed from.
+            scope: match self.scope {
+                SearchScope::Directory(_) | SearchScope::Files(_) => SearchScope::Project,
+                ref scope => scope.clone(),
+            },
         }
     }
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
@@ -557,6 +671,8 @@ impl ProjectSearchView {
             model.no_results = None;
             model.limit_reached = false;
             model.match_ranges.clear();
+            model.excluded_matches.clear();
+            model.replaced_matches.clear();
 
             model.excerpts.update(cx, |excerpts, cx| {
                 excerpts.clear(cx);
@@ -584,6 +700,73 @@ impl ProjectSearchView {
 
         cx.notify();
     }
+
+    fn set_scope(&mut self, scope: SearchScope, cx: &mut ViewContext<Self>) {
+        if self.scope == scope {
+            return;
+        }
+
+        self.clear_search(cx);
+        self.scope = scope;
+        self.scope_unresolved = false;
+        self.active_match_index = None;
+        self.search(cx);
+
+        cx.update_global(|state: &mut ActiveSettings, cx| {
+            state.0.insert(
+                self.model.read(cx).project.downgrade(),
+                self.current_settings(),
+            );
+        });
+
+        cx.notify();
+    }
+
+    fn scope_label(&self, scope: &SearchScope, cx: &AppContext) -> SharedString {
+        match scope {
+            SearchScope::Project => "Entire Project".into(),
+            SearchScope::OpenBuffers => "Open Buffers".into(),
+            SearchScope::GitChanged => "Changed Files".into(),
+            SearchScope::Directory(directory) => {
+                let project = self.model.read(cx).project.read(cx);
+                let mut path = PathBuf::new();
+                if project.visible_worktrees(cx).count() > 1 {
+                    if let Some(worktree) = project.worktree_for_id(directory.worktree_id, cx) {
+                        path.push(worktree.read(cx).root_name());
+                    }
+                }
+                path.push(&directory.path);
+                format!("Directory: {}", path.display()).into()
+            }
+            SearchScope::Files(files) => format!("Multibuffer Files ({})", files.len()).into(),
+        }
+    }
+
+    /// The directory of a file being edited, or the files of a multibuffer, to narrow the search to.
+    fn scopes_for_item(item: &dyn ItemHandle, cx: &AppContext) -> Vec<SearchScope> {
+        let mut paths = Vec::new();
+        item.for_each_project_item(cx, &mut |_, project_item| {
+            if let Some(path) = project_item.project_path(cx) {
+                paths.push(path);
+            }
+        });
+        if item.is_singleton(cx) {
+            paths
+                .into_iter()
+                .filter_map(|path| {
+                    Some(SearchScope::Directory(ProjectPath {
+                        worktree_id: path.worktree_id,
+                        path: path.path.parent()?.into(),
+                    }))
+                })
+                .collect()
+        } else if paths.is_empty() {
+            Vec::new()
+        } else {
+            vec![SearchScope::Files(Arc::new(paths.into_iter().collect()))]
+        }
+    }
+
     fn replace_next(&mut self, _: &ReplaceNext, cx: &mut ViewContext<Self>) {
         if self.model.read(cx).match_ranges.is_empty() {
             return;
@@ -592,21 +775,36 @@ impl ProjectSearchView {
             return;
         };
 
+        if self.model.read(cx).replaced_matches.contains(&active_index) {
+            self.select_match(Direction::Next, cx);
+            return;
+        }
+
         let query = self.model.read(cx).active_query.clone();
         if let Some(query) = query {
-            let query = query.with_replacement(self.replacement(cx));
+            let query = self.replacement_query(query, cx);
 
             // TODO: Do we need the clone here?
             let mat = self.model.read(cx).match_ranges[active_index].clone();
             self.results_editor.update(cx, |editor, cx| {
                 editor.replace(&mat, &query, cx);
             });
+            // Replaced matches no longer match, so don't replace them again.
+            self.model.update(cx, |model, _| {
+                model.replaced_matches.insert(active_index);
+            });
+            self.update_replacement_previews(cx);
             self.select_match(Direction::Next, cx)
         }
     }
     pub fn replacement(&self, cx: &AppContext) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
+    fn replacement_query(&self, query: SearchQuery, cx: &AppContext) -> SearchQuery {
+        query
+            .with_replacement(self.replacement(cx))
+            .with_preserve_case(self.search_options.contains(SearchOptions::PRESERVE_CASE))
+    }
     fn replace_all(&mut self, _: &ReplaceAll, cx: &mut ViewContext<Self>) {
         if self.active_match_index.is_none() {
             return;
@@ -615,7 +813,7 @@ impl ProjectSearchView {
         let Some(query) = self.model.read(cx).active_query.as_ref() else {
             return;
         };
-        let query = query.clone().with_replacement(self.replacement(cx));
+        let query = self.replacement_query(query.clone(), cx);
 
         let match_ranges = self
             .model
@@ -624,15 +822,94 @@ impl ProjectSearchView {
             return;
         }
 
+        let model = self.model.read(cx);
+        let skipped_matches = &model.excluded_matches | &model.replaced_matches;
         self.results_editor.update(cx, |editor, cx| {
-            for item in &match_ranges {
-                editor.replace(item, &query, cx);
+            for (ix, item) in match_ranges.iter().enumerate() {
+                if !skipped_matches.contains(&ix) {
+                    editor.replace(item, &query, cx);
+                }
             }
         });
 
         self.model.update(cx, |model, _cx| {
+            model
+                .replaced_matches
+                .extend((0..match_ranges.len()).filter(|ix| !model.excluded_matches.contains(ix)));
             model.match_ranges = match_ranges;
         });
+        self.update_replacement_previews(cx);
+    }
+
+    /// Excludes the active match from `ReplaceAll`, or includes it again if it was excluded.
+    /// With `whole_file`, this applies to all the matches in the active match's file.
+    fn toggle_exclusion(&mut self, whole_file: bool, cx: &mut ViewContext<Self>) {
+        let Some(active_index) = self.active_match_index else {
+            return;
+        };
+        self.model.update(cx, |model, _| {
+            let Some(active_match) = model.match_ranges.get(active_index) else {
+                return;
+            };
+            let indices = if whole_file {
+                let buffer_id = active_match.start.buffer_id;
+                model
+                    .match_ranges
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, range)| range.start.buffer_id == buffer_id)
+                    .map(|(ix, _)| ix)
+                    .collect::<Vec<_>>()
+            } else {
+                vec![active_index]
+            };
+            let indices = indices
+                .into_iter()
+                .filter(|ix| !model.replaced_matches.contains(ix))
+                .collect::<Vec<_>>();
+
+            if indices.iter().all(|ix| model.excluded_matches.contains(ix)) {
+                for ix in indices {
+                    model.excluded_matches.remove(&ix);
+                }
+            } else {
+                model.excluded_matches.extend(indices);
+            }
+        });
+        self.update_replacement_previews(cx);
+        cx.notify();
+    }
+
+    /// Shows what replacing each match that isn't excluded would produce, while replacing is enabled.
+    fn update_replacement_previews(&mut self, cx: &mut ViewContext<Self>) {
+        let model = self.model.read(cx);
+        let query = model
+            .active_query
+            .clone()
+            .filter(|_| self.replace_enabled && model.pending_search.is_none());
+        let Some(query) = query else {
+            self.results_editor
+                .update(cx, |editor, cx| editor.clear_replacement_previews(cx));
+            return;
+        };
+
+        let query = self.replacement_query(query, cx);
+        let snapshot = model.excerpts.read(cx).snapshot(cx);
+        let previews = model
+            .match_ranges
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| {
+                !model.excluded_matches.contains(ix) && !model.replaced_matches.contains(ix)
+            })
+            .filter_map(|(_, range)| {
+                let replacement = replacement_for_match(&snapshot, range, &query)?;
+                Some((range.clone(), replacement))
+            })
+            .collect::<Vec<_>>();
+        self.results_editor.update(cx, |editor, cx| {
+            editor.set_replacement_previews(previews, cx)
+        });
     }
 
     fn new(
@@ -647,15 +924,22 @@ impl ProjectSearchView {
         let mut subscriptions = Vec::new();
 
         // Read in settings if available
-        let (mut options, current_mode, filters_enabled) = if let Some(settings) = settings {
-            (
-                settings.search_options,
-                settings.current_mode,
-                settings.filters_enabled,
-            )
-        } else {
-            (SearchOptions::NONE, Default::default(), false)
-        };
+        let (mut options, current_mode, filters_enabled, mut scope) =
+            if let Some(settings) = settings {
+                (
+                    settings.search_options,
+                    settings.current_mode,
+                    settings.filters_enabled,
+                    settings.scope,
+                )
+            } else {
+                (
+                    SearchOptions::NONE,
+                    Default::default(),
+                    false,
+                    SearchScope::default(),
+                )
+            };
 
         {
             let model = model.read(cx);
@@ -665,6 +949,7 @@ impl ProjectSearchView {
                 query_text = active_query.as_str().to_string();
                 replacement_text = active_query.replacement().map(ToOwned::to_owned);
                 options = SearchOptions::from_query(active_query);
+                scope = active_query.scope().clone();
             }
         }
         subscriptions.push(cx.observe(&model, |this, _, cx| this.model_changed(cx)));
@@ -689,6 +974,14 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if let EditorEvent::BufferEdited = event {
+                    this.update_replacement_previews(cx);
+                }
+            },
+        ));
         let results_editor = cx.new_view(|cx| {
             let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), cx);
             editor.set_searchable(false);
@@ -759,6 +1052,10 @@ impl ProjectSearchView {
             excluded_files_editor,
             filters_enabled,
             current_mode,
+            workspace_id: None,
+            scope,
+            active_item_scopes: Vec::new(),
+            scope_unresolved: false,
             replace_enabled: false,
             _subscriptions: subscriptions,
         };
@@ -768,25 +1065,34 @@ impl ProjectSearchView {
 
     pub fn new_search_in_directory(
         workspace: &mut Workspace,
-        dir_path: &Path,
+        directory: ProjectPath,
         cx: &mut ViewContext<Workspace>,
     ) {
-        let Some(filter_str) = dir_path.to_str() else {
-            return;
-        };
-
         let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
         let search = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
         workspace.add_item_to_active_pane(Box::new(search.clone()), cx);
         search.update(cx, |search, cx| {
-            search
-                .included_files_editor
-                .update(cx, |editor, cx| editor.set_text(filter_str, cx));
-            search.filters_enabled = true;
+            let scope = SearchScope::Directory(directory);
+            search.active_item_scopes = vec![scope.clone()];
+            search.set_scope(scope, cx);
             search.focus_query_editor(cx)
         });
     }
 
+    fn run_saved_search(
+        workspace: &mut Workspace,
+        search: &SerializedSearch,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let view = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+        workspace.add_item_to_active_pane(Box::new(view.clone()), cx);
+        view.update(cx, |view, cx| {
+            view.apply_serialized_search(search, cx);
+            view.search(cx);
+        });
+    }
+
     // Re-activate the most recently activated search in this pane or the most recent if it has been closed.
     // If no search exists in the workspace, create a new one.
     fn deploy_search(
@@ -809,7 +1115,9 @@ impl ProjectSearchView {
             .and_then(|item| item.downcast::<ProjectSearchView>())
         {
             let new_query = search_view.update(cx, |search_view, cx| {
-                let new_query = search_view.build_search_query(cx);
+                let new_query = search_view
+                    .build_search_query(cx)
+                    .map(|query| (query, search_view.serialized_search(cx)));
                 if new_query.is_some() {
                     if let Some(old_query) = search_view.model.read(cx).active_query.clone() {
                         search_view.query_editor.update(cx, |editor, cx| {
@@ -820,12 +1128,13 @@ impl ProjectSearchView {
                 }
                 new_query
             });
-            if let Some(new_query) = new_query {
+            if let Some((new_query, history_entry)) = new_query {
                 let model = cx.new_model(|cx| {
                     let mut model = ProjectSearch::new(workspace.project().clone(), cx);
-                    model.search(new_query, cx);
+                    model.search(new_query, history_entry, cx);
                     model
                 });
+                save_search_history(workspace.database_id(), workspace.project(), cx);
                 workspace.add_item_to_active_pane(
                     Box::new(cx.new_view(|cx| ProjectSearchView::new(model, cx, None))),
                     cx,
@@ -857,6 +1166,10 @@ impl ProjectSearchView {
                 Some(query)
             }
         });
+        let active_item_scopes = workspace
+            .active_item(cx)
+            .filter(|item| item.downcast::<ProjectSearchView>().is_none())
+            .map(|item| Self::scopes_for_item(item.as_ref(), cx));
 
         let search = if let Some(existing) = existing {
             workspace.activate_item(&existing, cx);
@@ -884,16 +1197,130 @@ impl ProjectSearchView {
             if let Some(query) = query {
                 search.set_query(&query, cx);
             }
+            if let Some(active_item_scopes) = active_item_scopes {
+                search.active_item_scopes = active_item_scopes;
+            }
             search.focus_query_editor(cx)
         });
     }
 
     fn search(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(query) = self.build_search_query(cx) {
-            self.model.update(cx, |model, cx| model.search(query, cx));
+            let history_entry = self.serialized_search(cx);
+            self.model
+                .update(cx, |model, cx| model.search(query, history_entry, cx));
+            if let Some(workspace_id) = self.workspace_id {
+                save_search_history(workspace_id, &self.model.read(cx).project, cx);
+            }
+        }
+    }
+
+    /// The search as currently entered, along with its options, filters and replacement.
+    fn serialized_search(&self, cx: &AppContext) -> SerializedSearch {
+        let replacement = self.replacement(cx);
+        SerializedSearch {
+            query: self.query_editor.read(cx).text(cx),
+            mode: self.current_mode.into(),
+            whole_word: self.search_options.contains(SearchOptions::WHOLE_WORD),
+            case_sensitive: self.search_options.contains(SearchOptions::CASE_SENSITIVE),
+            include_ignored: self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+            preserve_case: self.search_options.contains(SearchOptions::PRESERVE_CASE),
+            files_to_include: self.included_files_editor.read(cx).text(cx),
+            files_to_exclude: self.excluded_files_editor.read(cx).text(cx),
+            replacement: (self.replace_enabled && !replacement.is_empty()).then_some(replacement),
+            scope: self.serialize_scope(),
         }
     }
 
+    /// The scope of the search, with its paths relative to their worktrees.
+    fn serialize_scope(&self) -> SerializedSearchScope {
+        let path = |project_path: &ProjectPath| project_path.path.to_path_buf();
+        match &self.scope {
+            SearchScope::Project => SerializedSearchScope::Project,
+            SearchScope::OpenBuffers => SerializedSearchScope::OpenBuffers,
+            SearchScope::GitChanged => SerializedSearchScope::GitChanged,
+            SearchScope::Directory(directory) => SerializedSearchScope::Directory(path(directory)),
+            SearchScope::Files(files) => {
+                let mut paths = files.iter().map(path).collect::<Vec<_>>();
+                paths.sort();
+                SerializedSearchScope::Files(paths)
+            }
+        }
+    }
+
+    /// Resolves a serialized scope against the worktrees of the project, in the first one having each path,
+    /// along with whether all of its paths were found.
+    /// Scopes none of whose paths were found fall back to the entire project.
+    fn deserialize_scope(
+        &self,
+        scope: &SerializedSearchScope,
+        cx: &AppContext,
+    ) -> (SearchScope, bool) {
+        let project = self.model.read(cx).project.read(cx);
+        let project_path = |path: &PathBuf| {
+            project.worktrees().find_map(|worktree| {
+                let worktree = worktree.read(cx);
+                worktree.entry_for_path(path)?;
+                Some(ProjectPath {
+                    worktree_id: worktree.id(),
+                    path: path.as_path().into(),
+                })
+            })
+        };
+        match scope {
+            SerializedSearchScope::Project => (SearchScope::Project, true),
+            SerializedSearchScope::OpenBuffers => (SearchScope::OpenBuffers, true),
+            SerializedSearchScope::GitChanged => (SearchScope::GitChanged, true),
+            SerializedSearchScope::Directory(directory) => match project_path(directory) {
+                Some(directory) => (SearchScope::Directory(directory), true),
+                None => (SearchScope::Project, false),
+            },
+            SerializedSearchScope::Files(paths) => {
+                let files = paths
+                    .iter()
+                    .filter_map(project_path)
+                    .collect::<HashSet<_>>();
+                let resolved = files.len() == paths.len();
+                if files.is_empty() {
+                    (SearchScope::Project, resolved)
+                } else {
+                    (SearchScope::Files(Arc::new(files)), resolved)
+                }
+            }
+        }
+    }
+
+    /// Fills in a search from the history or a saved one, without running it.
+    fn apply_serialized_search(&mut self, search: &SerializedSearch, cx: &mut ViewContext<Self>) {
+        self.set_query(&search.query, cx);
+        self.current_mode = search.mode.into();
+        let mut search_options = SearchOptions::NONE;
+        search_options.set(SearchOptions::WHOLE_WORD, search.whole_word);
+        search_options.set(SearchOptions::CASE_SENSITIVE, search.case_sensitive);
+        search_options.set(SearchOptions::INCLUDE_IGNORED, search.include_ignored);
+        search_options.set(SearchOptions::PRESERVE_CASE, search.preserve_case);
+        self.search_options = search_options;
+
+        self.included_files_editor.update(cx, |editor, cx| {
+            editor.set_text(search.files_to_include.as_str(), cx)
+        });
+        self.excluded_files_editor.update(cx, |editor, cx| {
+            editor.set_text(search.files_to_exclude.as_str(), cx)
+        });
+        if !search.files_to_include.is_empty() || !search.files_to_exclude.is_empty() {
+            self.filters_enabled = true;
+        }
+        if let Some(replacement) = &search.replacement {
+            self.replacement_editor
+                .update(cx, |editor, cx| editor.set_text(replacement.as_str(), cx));
+            self.replace_enabled = true;
+        }
+        let (scope, resolved) = self.deserialize_scope(&search.scope, cx);
+        self.scope = scope;
+        self.scope_unresolved = !resolved;
+        cx.notify();
+    }
+
     fn build_search_query(&mut self, cx: &mut ViewContext<Self>) -> Option<SearchQuery> {
         // Do not bail early in this function, as we want to fill out `self.panels_with_errors`.
         let text = self.query_editor.read(cx).text(cx);
@@ -963,7 +1390,30 @@ impl ProjectSearchView {
                     }
                 }
             }
-            _ => match SearchQuery::text(
+            SearchMode::Structural => match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            },
+            SearchMode::Text => match SearchQuery::text(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
                 self.search_options.contains(SearchOptions::CASE_SENSITIVE),
@@ -995,7 +1445,7 @@ impl ProjectSearchView {
         if query.as_ref().is_some_and(|query| query.is_empty()) {
             return None;
         }
-        query
+        query.map(|query| query.with_scope(self.scope.clone()))
     }
 
     fn parse_path_matches(text: &str) -> anyhow::Result<Vec<PathMatcher>> {
@@ -1081,6 +1531,7 @@ impl ProjectSearchView {
             }
         }
 
+        self.update_replacement_previews(cx);
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
     }
@@ -1105,6 +1556,7 @@ impl ProjectSearchView {
     fn landing_text_minor(&self) -> SharedString {
         match self.current_mode {
             SearchMode::Text | SearchMode::Regex => "Include/exclude specific paths with the filter option. Matching exact word and/or casing is available too.".into(),
+            SearchMode::Structural => "Search for code like `$FN($$$ARGS)`, where `$NAME` matches any syntax node and `$$$NAME` any sequence of them. Replacements can reuse them.".into(),
         }
     }
     fn border_color_for(&self, panel: InputPanel, cx: &WindowContext) -> Hsla {
@@ -1135,7 +1587,7 @@ impl ProjectSearchBar {
     fn cycle_mode(&self, _: &CycleMode, cx: &mut ViewContext<Self>) {
         if let Some(view) = self.active_project_search.as_ref() {
             view.update(cx, |this, cx| {
-                let new_mode = crate::mode::next_mode(&this.current_mode);
+                let new_mode = crate::mode::next_mode(&this.current_mode, true);
                 this.activate_search_mode(new_mode, cx);
                 let editor_handle = this.query_editor.focus_handle(cx);
                 cx.focus(&editor_handle);
@@ -1224,6 +1676,7 @@ impl ProjectSearchBar {
         if let Some(search) = &self.active_project_search {
             search.update(cx, |this, cx| {
                 this.replace_enabled = !this.replace_enabled;
+                this.update_replacement_previews(cx);
                 let editor_to_focus = if !this.replace_enabled {
                     this.query_editor.focus_handle(cx)
                 } else {
@@ -1235,6 +1688,25 @@ impl ProjectSearchBar {
         }
     }
 
+    fn toggle_preserve_case(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                // The search results stay the same, only their replacements change.
+                search_view.toggle_search_option(SearchOptions::PRESERVE_CASE, cx);
+                search_view.update_replacement_previews(cx);
+            });
+            cx.notify();
+        }
+    }
+
+    fn toggle_exclusion(&mut self, whole_file: bool, cx: &mut ViewContext<Self>) {
+        if let Some(search_view) = self.active_project_search.as_ref() {
+            search_view.update(cx, |search_view, cx| {
+                search_view.toggle_exclusion(whole_file, cx);
+            });
+        }
+    }
+
     fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1285,20 +1757,26 @@ impl ProjectSearchBar {
     fn next_history_query(&mut self, _: &NextHistoryQuery, cx: &mut ViewContext<Self>) {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
-                let new_query = search_view.model.update(cx, |model, cx| {
-                    if let Some(new_query) = model.project.update(cx, |project, _| {
+                let new_search = search_view.model.update(cx, |model, cx| {
+                    let new_search = model.project.update(cx, |project, _| {
                         project
                             .search_history_mut()
-                            .next(&mut model.search_history_cursor)
-                            .map(str::to_string)
-                    }) {
-                        new_query
-                    } else {
+                            .next(&mut model.search_history_cursor)?;
+                        project
+                            .search_history()
+                            .current_search(&model.search_history_cursor)
+                            .cloned()
+                    });
+                    if new_search.is_none() {
                         model.search_history_cursor.reset();
-                        String::new()
                     }
+                    new_search
                 });
-                search_view.set_query(&new_query, cx);
+                if let Some(new_search) = new_search {
+                    search_view.apply_serialized_search(&new_search, cx);
+                } else {
+                    search_view.set_query("", cx);
+                }
             });
         }
     }
@@ -1307,29 +1785,32 @@ impl ProjectSearchBar {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
                 if search_view.query_editor.read(cx).text(cx).is_empty() {
-                    if let Some(new_query) = search_view
+                    if let Some(new_search) = search_view
                         .model
                         .read(cx)
                         .project
                         .read(cx)
                         .search_history()
-                        .current(&search_view.model.read(cx).search_history_cursor)
-                        .map(str::to_string)
+                        .current_search(&search_view.model.read(cx).search_history_cursor)
+                        .cloned()
                     {
-                        search_view.set_query(&new_query, cx);
+                        search_view.apply_serialized_search(&new_search, cx);
                         return;
                     }
                 }
 
-                if let Some(new_query) = search_view.model.update(cx, |model, cx| {
+                if let Some(new_search) = search_view.model.update(cx, |model, cx| {
                     model.project.update(cx, |project, _| {
                         project
                             .search_history_mut()
-                            .previous(&mut model.search_history_cursor)
-                            .map(str::to_string)
+                            .previous(&mut model.search_history_cursor)?;
+                        project
+                            .search_history()
+                            .current_search(&model.search_history_cursor)
+                            .cloned()
                     })
                 }) {
-                    search_view.set_query(&new_query, cx);
+                    search_view.apply_serialized_search(&new_search, cx);
                 }
             });
         }
@@ -1393,6 +1874,55 @@ impl ProjectSearchBar {
         new_placeholder_text
     }
 
+    fn render_scope_menu(
+        &self,
+        search: &View<ProjectSearchView>,
+        cx: &ViewContext<Self>,
+    ) -> impl IntoElement {
+        let search_view = search.read(cx);
+        let mut scopes = vec![
+            SearchScope::Project,
+            SearchScope::OpenBuffers,
+            SearchScope::GitChanged,
+        ];
+        scopes.extend(search_view.active_item_scopes.iter().cloned());
+        if !scopes.contains(&search_view.scope) {
+            scopes.push(search_view.scope.clone());
+        }
+        let scopes = scopes
+            .into_iter()
+            .map(|scope| (search_view.scope_label(&scope, cx), scope))
+            .collect::<Vec<_>>();
+        let current_label = search_view.scope_label(&search_view.scope, cx);
+
+        let search = search.clone();
+        popover_menu("project-search-scope")
+            .trigger(
+                Button::new("project-search-scope-button", current_label)
+                    .icon(IconName::ChevronDown)
+                    .icon_position(IconPosition::End)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Search scope", cx)),
+            )
+            .menu(move |cx| {
+                let search = search.clone();
+                let scopes = scopes.clone();
+                ContextMenu::build(cx, move |mut menu, cx| {
+                    for (label, scope) in scopes {
+                        menu = menu.entry(
+                            label,
+                            None,
+                            cx.handler_for(&search, move |search, cx| {
+                                search.set_scope(scope.clone(), cx);
+                            }),
+                        );
+                    }
+                    menu
+                })
+                .into()
+            })
+    }
+
     fn render_text_input(&self, editor: &View<Editor>, cx: &ViewContext<Self>) -> impl IntoElement {
         let settings = ThemeSettings::get_global(cx);
         let text_style = TextStyle {
@@ -1439,6 +1969,7 @@ impl Render for ProjectSearchBar {
                 })
             });
         }
+        let scope_menu = self.render_scope_menu(&search, cx);
         let search = search.read(cx);
 
         let query_column = h_flex()
@@ -1528,6 +2059,23 @@ impl Render for ProjectSearchBar {
                                         cx,
                                     )
                                 })
+                                .middle(),
+                        )
+                        .child(
+                            ToggleButton::new("project-search-structural-button", "Structural")
+                                .style(ButtonStyle::Filled)
+                                .size(ButtonSize::Large)
+                                .selected(search.current_mode == SearchMode::Structural)
+                                .on_click(cx.listener(|this, _, cx| {
+                                    this.activate_search_mode(SearchMode::Structural, cx)
+                                }))
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Toggle structural search",
+                                        &ActivateStructuralMode,
+                                        cx,
+                                    )
+                                })
                                 .last(),
                         ),
                 )
@@ -1537,7 +2085,8 @@ impl Render for ProjectSearchBar {
                             this.toggle_replace(&ToggleReplace, cx);
                         }))
                         .tooltip(|cx| Tooltip::for_action("Toggle replace", &ToggleReplace, cx)),
-                ),
+                )
+                .child(scope_menu),
         );
 
         let match_text = search
@@ -1555,6 +2104,7 @@ impl Render for ProjectSearchBar {
             .unwrap_or_else(|| "No matches".to_string());
 
         let limit_reached = search.model.read(cx).limit_reached;
+        let scope_unresolved = search.scope_unresolved;
 
         let matches_column = h_flex()
             .child(div().min_w(rems(6.)).child(Label::new(match_text)))
@@ -1590,6 +2140,16 @@ impl Render for ProjectSearchBar {
                         .child(Label::new("Search limit reached").color(Color::Warning))
                         .ml_2(),
                 )
+            })
+            .when(scope_unresolved, |this| {
+                this.child(
+                    div()
+                        .child(
+                            Label::new("Some paths of the search scope were not found")
+                                .color(Color::Warning),
+                        )
+                        .ml_2(),
+                )
             });
 
         let search_line = h_flex()
@@ -1610,7 +2170,11 @@ impl Render for ProjectSearchBar {
                 .border_1()
                 .border_color(cx.theme().colors().border)
                 .rounded_lg()
-                .child(self.render_text_input(&search.replacement_editor, cx));
+                .child(self.render_text_input(&search.replacement_editor, cx))
+                .child(SearchOptions::PRESERVE_CASE.as_button(
+                    search.search_options.contains(SearchOptions::PRESERVE_CASE),
+                    cx.listener(|this, _, cx| this.toggle_preserve_case(cx)),
+                ));
             let replace_actions = h_flex().when(search.replace_enabled, |this| {
                 this.child(
                     IconButton::new("project-search-replace-next", IconName::ReplaceNext)
@@ -1695,6 +2259,9 @@ impl Render for ProjectSearchBar {
             .on_action(cx.listener(|this, _: &ActivateRegexMode, cx| {
                 this.activate_search_mode(SearchMode::Regex, cx)
             }))
+            .on_action(cx.listener(|this, _: &ActivateStructuralMode, cx| {
+                this.activate_search_mode(SearchMode::Structural, cx)
+            }))
             .capture_action(cx.listener(|this, action, cx| {
                 this.tab(action, cx);
                 cx.stop_propagation();
@@ -1710,6 +2277,9 @@ impl Render for ProjectSearchBar {
             .on_action(cx.listener(|this, action, cx| {
                 this.toggle_replace(action, cx);
             }))
+            .on_action(cx.listener(|this, _: &TogglePreserveCase, cx| {
+                this.toggle_preserve_case(cx);
+            }))
             .on_action(cx.listener(|this, _: &ToggleWholeWord, cx| {
                 this.toggle_search_option(SearchOptions::WHOLE_WORD, cx);
             }))
@@ -1837,7 +2407,7 @@ pub mod tests {
     use project::FakeFs;
     use serde_json::json;
     use settings::SettingsStore;
-    use std::sync::Arc;
+    use std::path::Path;
     use workspace::DeploySearch;
 
     #[gpui::test]
@@ -1963,6 +2533,96 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_project_search_replacement_previews(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": "let two = Two::TWO;",
+                "b.rs": "fn two() {}",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "two", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("three", cx));
+                search_view.replace_enabled = true;
+                search_view
+                    .search_options
+                    .insert(SearchOptions::PRESERVE_CASE);
+                search_view.update_replacement_previews(cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet twothree = TwoThree::TWOTHREE;\n\n\nfn twothree() {}",
+                    "should preview every replacement, following the case of the matches"
+                );
+
+                assert_eq!(search_view.active_match_index, Some(0));
+                search_view.toggle_exclusion(true, cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet two = Two::TWO;\n\n\nfn twothree() {}",
+                    "should not preview replacements in excluded files"
+                );
+
+                search_view.toggle_exclusion(false, cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet twothree = Two::TWO;\n\n\nfn twothree() {}",
+                    "should include the active match again"
+                );
+
+                search_view.replace_all(&ReplaceAll, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet three = Two::TWO;\n\n\nfn three() {}",
+                    "should only replace the matches that weren't excluded"
+                );
+
+                search_view.active_match_index = Some(0);
+                search_view.toggle_exclusion(true, cx);
+                search_view.replace_all(&ReplaceAll, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\nlet three = Three::THREE;\n\n\nfn three() {}",
+                    "should replace the matches included again, but not the ones already replaced"
+                );
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2549,7 +3209,14 @@ pub mod tests {
         assert!(a_dir_entry.is_dir());
         window
             .update(cx, |workspace, cx| {
-                ProjectSearchView::new_search_in_directory(workspace, &a_dir_entry.path, cx)
+                ProjectSearchView::new_search_in_directory(
+                    workspace,
+                    ProjectPath {
+                        worktree_id,
+                        path: a_dir_entry.path.clone(),
+                    },
+                    cx,
+                )
             })
             .unwrap();
 
@@ -2578,12 +3245,19 @@ pub mod tests {
                         );
                     });
                     search_view.included_files_editor.update(cx, |editor, cx| {
-                        assert_eq!(
-                            editor.display_text(cx),
-                            a_dir_entry.path.to_str().unwrap(),
-                            "New search in directory should have included dir entry path"
+                        assert!(
+                            editor.display_text(cx).is_empty(),
+                            "New search in directory should not have any included files"
                         );
                     });
+                    assert_eq!(
+                        search_view.scope,
+                        SearchScope::Directory(ProjectPath {
+                            worktree_id,
+                            path: a_dir_entry.path.clone(),
+                        }),
+                        "New search in directory should be scoped to the dir entry"
+                    );
                 });
             })
             .unwrap();
@@ -2602,12 +3276,12 @@ pub mod tests {
             .update(cx, |_, cx| {
                 search_view.update(cx, |search_view, cx| {
                     assert_eq!(
-                search_view
-                    .results_editor
-                    .update(cx, |editor, cx| editor.display_text(cx)),
-                "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
-                "New search in directory should have a filter that matches a certain directory"
-            );
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\nconst ONE: usize = 1;\n\n\nconst TWO: usize = one::ONE + one::ONE;",
+                        "New search in directory should only search inside of the directory"
+                    );
                 })
             })
             .unwrap();
@@ -2931,6 +3605,168 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_search_query_history_restores_options(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    workspace.panes()[0].update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .expect("Search view expected to appear after new search event trigger")
+        });
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.current_mode = SearchMode::Regex;
+                    search_view.search_options = SearchOptions::WHOLE_WORD;
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("ON.", cx));
+                    search_view
+                        .included_files_editor
+                        .update(cx, |editor, cx| editor.set_text("one.rs", cx));
+                    let worktree_id = search_view
+                        .model
+                        .read(cx)
+                        .project
+                        .read(cx)
+                        .worktrees()
+                        .next()
+                        .unwrap()
+                        .read(cx)
+                        .id();
+                    search_view.scope =
+                        SearchScope::Files(Arc::new(HashSet::from_iter([ProjectPath {
+                            worktree_id,
+                            path: Path::new("one.rs").into(),
+                        }])));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.current_mode = SearchMode::Text;
+                    search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                    search_view
+                        .query_editor
+                        .update(cx, |query_editor, cx| query_editor.set_text("TWO", cx));
+                    search_view
+                        .included_files_editor
+                        .update(cx, |editor, cx| editor.set_text("", cx));
+                    search_view.scope = SearchScope::Project;
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                })
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ON.");
+                    assert_eq!(
+                        search_view.current_mode,
+                        SearchMode::Regex,
+                        "History navigation should restore the mode of the search"
+                    );
+                    assert_eq!(search_view.search_options, SearchOptions::WHOLE_WORD);
+                    assert_eq!(
+                        search_view.included_files_editor.read(cx).text(cx),
+                        "one.rs",
+                        "History navigation should restore the filters of the search"
+                    );
+                    assert!(search_view.filters_enabled);
+                    assert_eq!(
+                        search_view.serialize_scope(),
+                        SerializedSearchScope::Files(vec![PathBuf::from("one.rs")]),
+                        "History navigation should restore the scope of the search"
+                    );
+                });
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                })
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.current_mode, SearchMode::Text);
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                    assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
+                    assert_eq!(search_view.scope, SearchScope::Project);
+                });
+            })
+            .unwrap();
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.apply_serialized_search(
+                        &SerializedSearch {
+                            query: "TWO".to_string(),
+                            scope: SerializedSearchScope::Files(vec![PathBuf::from("missing.rs")]),
+                            ..Default::default()
+                        },
+                        cx,
+                    );
+                    assert_eq!(
+                        search_view.scope,
+                        SearchScope::Project,
+                        "Scopes whose paths are all gone should fall back to the entire project"
+                    );
+                    assert!(search_view.scope_unresolved);
+                });
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_search_query_history_with_multiple_views(cx: &mut TestAppContext) {
         init_test(cx);
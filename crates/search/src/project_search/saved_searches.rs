@@ -0,0 +1,276 @@
+use std::sync::Arc;
+
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, ParentElement,
+    Render, Styled, Task, View, ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{
+    search_history::{SavedSearch, SerializedSearch},
+    Project,
+};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use super::ProjectSearchView;
+
+enum Mode {
+    /// Runs the picked search in a new project search.
+    Open,
+    /// Saves the search of the active project search under the picked or the entered name.
+    Save(SerializedSearch),
+}
+
+/// Lists the searches saved in the `.zed/searches.json` files of the project.
+pub(super) struct SavedSearchesModal {
+    picker: View<Picker<SavedSearchesDelegate>>,
+}
+
+impl SavedSearchesModal {
+    pub(super) fn toggle_open(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        Self::toggle(workspace, Mode::Open, cx);
+    }
+
+    pub(super) fn toggle_save(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            return;
+        };
+        let search = search_view.read(cx).serialized_search(cx);
+        if search.query.is_empty() {
+            return;
+        }
+        Self::toggle(workspace, Mode::Save(search), cx);
+    }
+
+    fn toggle(workspace: &mut Workspace, mode: Mode, cx: &mut ViewContext<Workspace>) {
+        let project = workspace.project().clone();
+        let weak_workspace = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| Self::new(mode, project, weak_workspace, cx));
+    }
+
+    fn new(
+        mode: Mode,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let saved_searches = project
+            .read(cx)
+            .saved_searches()
+            .cloned()
+            .collect::<Vec<_>>();
+        let candidates = saved_searches
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, saved_search)| {
+                StringMatchCandidate::new(candidate_id, saved_search.name.clone())
+            })
+            .collect();
+        let delegate = SavedSearchesDelegate {
+            modal: cx.view().downgrade(),
+            workspace,
+            project,
+            mode,
+            saved_searches,
+            candidates,
+            matches: Vec::new(),
+            query: String::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for SavedSearchesModal {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for SavedSearchesModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearchesModal {}
+impl ModalView for SavedSearchesModal {}
+
+struct SavedSearchesDelegate {
+    modal: WeakView<SavedSearchesModal>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    mode: Mode,
+    saved_searches: Vec<SavedSearch>,
+    candidates: Vec<StringMatchCandidate>,
+    matches: Vec<StringMatch>,
+    query: String,
+    selected_index: usize,
+}
+
+impl SavedSearchesDelegate {
+    /// When saving, the entered name comes first unless a search is already saved under it.
+    fn new_name(&self) -> Option<&str> {
+        let name = self.query.trim();
+        let is_new = matches!(self.mode, Mode::Save(_))
+            && !name.is_empty()
+            && self
+                .saved_searches
+                .iter()
+                .all(|saved_search| saved_search.name != name);
+        is_new.then_some(name)
+    }
+
+    fn saved_search_at(&self, ix: usize) -> Option<&SavedSearch> {
+        let ix = if self.new_name().is_some() {
+            ix.checked_sub(1)?
+        } else {
+            ix
+        };
+        let mat = self.matches.get(ix)?;
+        self.saved_searches.get(mat.candidate_id)
+    }
+}
+
+impl PickerDelegate for SavedSearchesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.mode {
+            Mode::Open => "Select a saved search...".into(),
+            Mode::Save(_) => "Name the search...".into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + usize::from(self.new_name().is_some())
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query.clone();
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        match &self.mode {
+            Mode::Open => {
+                if let Some(saved_search) = self.saved_search_at(self.selected_index) {
+                    let search = saved_search.search.clone();
+                    self.workspace
+                        .update(cx, |workspace, cx| {
+                            ProjectSearchView::run_saved_search(workspace, &search, cx)
+                        })
+                        .log_err();
+                }
+            }
+            Mode::Save(search) => {
+                let name = if self.selected_index == 0 && self.new_name().is_some() {
+                    self.new_name().map(str::to_string)
+                } else {
+                    self.saved_search_at(self.selected_index)
+                        .map(|saved_search| saved_search.name.clone())
+                };
+                if let Some(name) = name {
+                    let saved_search = SavedSearch {
+                        name,
+                        search: search.clone(),
+                    };
+                    self.project
+                        .update(cx, |project, cx| project.save_search(saved_search, cx))
+                        .detach_and_log_err(cx);
+                }
+            }
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected);
+        if ix == 0 {
+            if let Some(new_name) = self.new_name() {
+                return Some(item.child(Label::new(format!("Save as \"{new_name}\""))));
+            }
+        }
+
+        let saved_search = self.saved_search_at(ix)?;
+        let mat = &self.matches[ix - usize::from(self.new_name().is_some())];
+        Some(
+            item.child(
+                h_flex()
+                    .gap_2()
+                    .child(HighlightedLabel::new(
+                        saved_search.name.clone(),
+                        mat.positions.clone(),
+                    ))
+                    .child(
+                        Label::new(saved_search.search.query.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            ),
+        )
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{settings_store::parse_json_with_comments, SettingsAssets};
 use anyhow::{anyhow, Context, Result};
 use collections::BTreeMap;
-use gpui::{Action, AppContext, KeyBinding, SharedString};
+use gpui::{Action, AppContext, Global, KeyBinding, SharedString};
 use schemars::{
     gen::{SchemaGenerator, SchemaSettings},
     schema::{InstanceType, Schema, SchemaObject, SingleOrVec, SubschemaValidation},
@@ -9,6 +9,7 @@ use schemars::{
 };
 use serde::Deserialize;
 use serde_json::Value;
+use std::sync::Arc;
 use util::{asset_str, ResultExt};
 
 #[derive(Debug, Deserialize, Default, Clone, JsonSchema)]
@@ -36,6 +37,40 @@ impl JsonSchema for KeymapAction {
     }
 }
 
+/// The default keymaps provided by extensions, keyed by extension id.
+///
+/// These are bound after Zed's default keymap and before the user's keymap,
+/// so that user bindings take precedence over extension bindings.
+#[derive(Default)]
+pub struct ExtensionKeymaps(BTreeMap<Arc<str>, Vec<KeymapFile>>);
+
+impl Global for ExtensionKeymaps {}
+
+impl ExtensionKeymaps {
+    pub fn insert(extension_id: Arc<str>, keymaps: Vec<KeymapFile>, cx: &mut AppContext) {
+        cx.default_global::<Self>().0.insert(extension_id, keymaps);
+    }
+
+    pub fn remove(extension_id: &str, cx: &mut AppContext) {
+        let has_keymaps = cx
+            .try_global::<Self>()
+            .map_or(false, |this| this.0.contains_key(extension_id));
+        if has_keymaps {
+            cx.global_mut::<Self>().0.remove(extension_id);
+        }
+    }
+
+    pub fn add_to_cx(cx: &mut AppContext) {
+        let Some(this) = cx.try_global::<Self>() else {
+            return;
+        };
+        let keymaps = this.0.values().flatten().cloned().collect::<Vec<_>>();
+        for keymap in keymaps {
+            keymap.add_to_cx(cx).log_err();
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct ActionWithData(Box<str>, Value);
 
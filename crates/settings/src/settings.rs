@@ -6,9 +6,12 @@ use rust_embed::RustEmbed;
 use std::{borrow::Cow, str};
 use util::asset_str;
 
-pub use keymap_file::KeymapFile;
+pub use keymap_file::{ExtensionKeymaps, KeymapFile};
 pub use settings_file::*;
-pub use settings_store::{Settings, SettingsJsonSchemaParams, SettingsLocation, SettingsStore};
+pub use settings_store::{
+    replace_top_level_array_element_in_json_text, Settings, SettingsJsonSchemaParams,
+    SettingsLocation, SettingsStore,
+};
 
 #[derive(RustEmbed)]
 #[folder = "../../assets"]
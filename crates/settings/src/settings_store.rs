@@ -135,6 +135,7 @@ pub struct SettingsJsonSchemaParams<'a> {
 pub struct SettingsStore {
     setting_values: HashMap<TypeId, Box<dyn AnySettingValue>>,
     raw_default_settings: serde_json::Value,
+    raw_vimrc_settings: serde_json::Value,
     raw_user_settings: serde_json::Value,
     raw_local_settings: BTreeMap<(usize, Arc<Path>), serde_json::Value>,
     tab_size_callback: Option<(
@@ -150,6 +151,7 @@ impl Default for SettingsStore {
         SettingsStore {
             setting_values: Default::default(),
             raw_default_settings: serde_json::json!({}),
+            raw_vimrc_settings: serde_json::json!({}),
             raw_user_settings: serde_json::json!({}),
             raw_local_settings: Default::default(),
             tab_size_callback: Default::default(),
@@ -206,11 +208,18 @@ impl SettingsStore {
         {
             let mut user_values_stack = Vec::new();
 
+            if let Some(vimrc_settings) = setting_value
+                .deserialize_setting(&self.raw_vimrc_settings)
+                .log_err()
+            {
+                user_values_stack.push(vimrc_settings);
+            }
+
             if let Some(user_settings) = setting_value
                 .deserialize_setting(&self.raw_user_settings)
                 .log_err()
             {
-                user_values_stack = vec![user_settings];
+                user_values_stack.push(user_settings);
             }
 
             if let Some(release_settings) = &self
@@ -407,6 +416,24 @@ impl SettingsStore {
         }
     }
 
+    /// Sets the settings derived from the `set` options of the user's vimrc.
+    ///
+    /// These are applied beneath the user settings file, so an explicit value in
+    /// `settings.json` always takes precedence.
+    pub fn set_vimrc_settings(
+        &mut self,
+        settings: serde_json::Value,
+        cx: &mut AppContext,
+    ) -> Result<()> {
+        if settings.is_object() {
+            self.raw_vimrc_settings = settings;
+            self.recompute_values(None, cx)?;
+            Ok(())
+        } else {
+            Err(anyhow!("settings must be an object"))
+        }
+    }
+
     /// Add or remove a set of local settings via a JSON string.
     pub fn set_local_settings(
         &mut self,
@@ -559,6 +586,14 @@ impl SettingsStore {
             user_settings_stack.clear();
             paths_stack.clear();
 
+            if let Some(vimrc_settings) = setting_value
+                .deserialize_setting(&self.raw_vimrc_settings)
+                .log_err()
+            {
+                user_settings_stack.push(vimrc_settings);
+                paths_stack.push(None);
+            }
+
             if let Some(user_settings) = setting_value
                 .deserialize_setting(&self.raw_user_settings)
                 .log_err()
@@ -643,6 +678,7 @@ impl Debug for SettingsStore {
                     .collect::<Vec<_>>(),
             )
             .field("default_settings", &self.raw_default_settings)
+            .field("vimrc_settings", &self.raw_vimrc_settings)
             .field("user_settings", &self.raw_user_settings)
             .field("local_settings", &self.raw_local_settings)
             .finish_non_exhaustive()
@@ -914,6 +950,49 @@ fn replace_value_in_json_text(
     }
 }
 
+/// Replaces the element at `index` of the array at the top of a JSON text, or appends a new
+/// element if `index` is past its end, keeping the comments and formatting of the rest of the text.
+/// Returns `None` if the text doesn't hold an array.
+pub fn replace_top_level_array_element_in_json_text(
+    text: &str,
+    index: usize,
+    tab_size: usize,
+    new_value: &serde_json::Value,
+) -> Option<(Range<usize>, String)> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_json::language()).unwrap();
+    let syntax_tree = parser.parse(text, None)?;
+    let root = syntax_tree.root_node();
+
+    let mut cursor = root.walk();
+    let array = root
+        .named_children(&mut cursor)
+        .find(|node| !node.is_extra())
+        .filter(|node| node.kind() == "array")?;
+    let mut cursor = array.walk();
+    let elements = array
+        .named_children(&mut cursor)
+        .filter(|node| !node.is_extra() && !node.is_error())
+        .collect::<Vec<_>>();
+
+    if let Some(element) = elements.get(index) {
+        let column = element.start_position().column.min(32);
+        let new_text = to_pretty_json(new_value, tab_size, column);
+        Some((element.byte_range(), new_text))
+    } else if let Some(last_element) = elements.last() {
+        let column = last_element.start_position().column.min(32);
+        let new_text = to_pretty_json(new_value, tab_size, column);
+        let end = last_element.end_byte();
+        Some((end..end, format!(",\n{}{new_text}", " ".repeat(column))))
+    } else {
+        let new_text = to_pretty_json(new_value, tab_size, tab_size);
+        Some((
+            array.byte_range(),
+            format!("[\n{}{new_text}\n]", " ".repeat(tab_size)),
+        ))
+    }
+}
+
 fn to_pretty_json(value: &impl Serialize, indent_size: usize, indent_prefix_len: usize) -> String {
     const SPACES: [u8; 32] = [b' '; 32];
 
@@ -1080,6 +1159,45 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_settings_store_vimrc_settings(cx: &mut AppContext) {
+        let mut store = SettingsStore::default();
+        store.register_setting::<UserSettings>(cx);
+        store.register_setting::<TurboSetting>(cx);
+        store
+            .set_default_settings(
+                r#"{
+                    "turbo": false,
+                    "user": {
+                        "name": "John Doe",
+                        "age": 30,
+                        "staff": false
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+
+        store
+            .set_vimrc_settings(
+                serde_json::json!({ "turbo": true, "user": { "age": 31 } }),
+                cx,
+            )
+            .unwrap();
+        assert_eq!(store.get::<TurboSetting>(None), &TurboSetting(true));
+        assert_eq!(store.get::<UserSettings>(None).age, 31);
+
+        // The user settings file takes precedence over the vimrc.
+        store
+            .set_user_settings(r#"{ "user": { "age": 32 } }"#, cx)
+            .unwrap();
+        assert_eq!(store.get::<TurboSetting>(None), &TurboSetting(true));
+        assert_eq!(store.get::<UserSettings>(None).age, 32);
+
+        store.set_vimrc_settings(serde_json::json!({}), cx).unwrap();
+        assert_eq!(store.get::<TurboSetting>(None), &TurboSetting(false));
+    }
+
     #[gpui::test]
     fn test_setting_store_assign_json_before_register(cx: &mut AppContext) {
         let mut store = SettingsStore::default();
@@ -1243,6 +1361,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_replace_top_level_array_element_in_json_text() {
+        let text = r#"[
+            // The first element.
+            { "name": "a" },
+            { "name": "b" }
+        ]"#
+        .unindent();
+        let apply = |index, value| {
+            let (range, new_text) =
+                replace_top_level_array_element_in_json_text(&text, index, 4, &value).unwrap();
+            let mut text = text.clone();
+            text.replace_range(range, &new_text);
+            text
+        };
+
+        pretty_assertions::assert_eq!(
+            apply(1, serde_json::json!({ "name": "c" })),
+            r#"[
+                // The first element.
+                { "name": "a" },
+                {
+                    "name": "c"
+                }
+            ]"#
+            .unindent()
+        );
+        pretty_assertions::assert_eq!(
+            apply(2, serde_json::json!({ "name": "d" })),
+            r#"[
+                // The first element.
+                { "name": "a" },
+                { "name": "b" },
+                {
+                    "name": "d"
+                }
+            ]"#
+            .unindent()
+        );
+
+        let (range, new_text) = replace_top_level_array_element_in_json_text(
+            "[]",
+            0,
+            4,
+            &serde_json::json!({ "name": "e" }),
+        )
+        .unwrap();
+        assert_eq!(range, 0..2);
+        assert_eq!(new_text, "[\n    {\n        \"name\": \"e\"\n    }\n]");
+
+        assert_eq!(
+            replace_top_level_array_element_in_json_text(
+                r#"{ "name": "a" }"#,
+                0,
+                4,
+                &serde_json::json!({ "name": "e" }),
+            ),
+            None
+        );
+    }
+
     fn check_settings_update<T: Settings>(
         store: &mut SettingsStore,
         old_json: String,
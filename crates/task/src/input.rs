@@ -0,0 +1,53 @@
+//! Values that a task asks the user for right before it is spawned, referenced as `${input:<id>}` in its definition.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A value to prompt the user for, before spawning the task that declares it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskInput {
+    /// Name of the input, used to reference it as `${input:<id>}` in the task's command, args, cwd and env.
+    pub id: String,
+    /// Text to show in the prompt.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// How to get the value.
+    #[serde(flatten)]
+    pub kind: TaskInputKind,
+}
+
+/// How to get the value of a [`TaskInput`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum TaskInputKind {
+    /// Let the user type any text.
+    Prompt {
+        /// Text to prefill the prompt with.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Let the user pick one of the options given.
+    Pick {
+        /// Values to choose from.
+        options: Vec<String>,
+        /// Value to select initially.
+        #[serde(default)]
+        default: Option<String>,
+    },
+    /// Let the user pick one of the lines printed by a command, run in the task's working directory.
+    Command {
+        /// Executable command to spawn.
+        command: String,
+        /// Arguments to the command.
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl TaskInput {
+    /// Name of the task variable that holds the value of the input, once it is entered.
+    pub fn variable_name(&self) -> String {
+        format!("input:{}", self.id)
+    }
+}
@@ -1,16 +1,22 @@
 //! Baseline interface of Tasks in Zed: all tasks in Zed are intended to use those for implementing their own logic.
 #![deny(missing_docs)]
 
+pub mod input;
 pub mod oneshot_source;
+pub mod problem_matcher;
 pub mod static_source;
 mod vscode_format;
 
 use collections::HashMap;
 use gpui::ModelContext;
-use static_source::RevealStrategy;
+use input::TaskInput;
+use problem_matcher::ProblemMatcher;
+use static_source::{DependsOrder, ExecutionMode, RevealStrategy};
 use std::any::Any;
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use util::paths::PathMatcher;
 pub use vscode_format::VsCodeTaskFile;
 
 /// Task identifier, unique within the application.
@@ -39,6 +45,15 @@ pub struct SpawnInTerminal {
     pub allow_concurrent_runs: bool,
     /// What to do with the terminal pane and tab, after the command was started.
     pub reveal: RevealStrategy,
+    /// How to find problems in the task's output, to report them as diagnostics.
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Tasks to run before this one, resolved from the labels the task depends on.
+    /// An empty `command` means that only these are run.
+    pub dependencies: Vec<SpawnInTerminal>,
+    /// Whether to run the dependencies in parallel or one after another.
+    pub depends_order: DependsOrder,
+    /// Whether to run the command in a terminal tab or in the background.
+    pub execution: ExecutionMode,
 }
 
 type VariableName = String;
@@ -54,6 +69,98 @@ impl FromIterator<(String, String)> for TaskVariables {
     }
 }
 
+impl TaskVariables {
+    /// Expands the variable references in the template given:
+    /// * `$NAME`, `${NAME}` and `${NAME:default}` — a task variable, e.g. `$ZED_FILE`;
+    /// * `${input:id}` — the value entered for the task's input with the id given;
+    /// * `${env:NAME}` and `${env:NAME:default}` — an environment variable of the Zed process, empty if not set.
+    ///
+    /// References to unknown variables without a default are left as is, so that the shell could expand them.
+    pub fn substitute(&self, template: &str) -> String {
+        self.substitute_references(template, true)
+    }
+
+    /// Expands only the `${input:id}` and `${env:NAME}` references in the template given, leaving
+    /// task variables to the shell.
+    ///
+    /// Used for commands and arguments, which a shell interprets: task variables such as
+    /// `ZED_SELECTED_TEXT` hold arbitrary text, so they are exported as environment variables
+    /// instead of being pasted into the command line. Since shells read `${NAME:default}` as
+    /// substring syntax, such references become `${NAME}` when the variable is set and
+    /// the default otherwise.
+    pub fn substitute_inputs_and_env(&self, template: &str) -> String {
+        self.substitute_references(template, false)
+    }
+
+    fn substitute_references(&self, template: &str, task_variables: bool) -> String {
+        let mut substituted = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(dollar_ix) = rest.find('$') {
+            substituted.push_str(&rest[..dollar_ix]);
+            rest = &rest[dollar_ix + 1..];
+            if let Some(braced) = rest.strip_prefix('{') {
+                if let Some(end_ix) = braced.find('}') {
+                    let reference = &braced[..end_ix];
+                    if task_variables
+                        || reference.starts_with("input:")
+                        || reference.starts_with("env:")
+                    {
+                        substituted.push_str(&self.resolve(reference));
+                        rest = &braced[end_ix + 1..];
+                        continue;
+                    }
+                    if let Some((name, default)) = reference.split_once(':') {
+                        if self.0.contains_key(name) {
+                            substituted.push_str(&format!("${{{name}}}"));
+                        } else {
+                            substituted.push_str(default);
+                        }
+                        rest = &braced[end_ix + 1..];
+                        continue;
+                    }
+                }
+            } else if task_variables {
+                let name_len = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                if let Some(value) = self.0.get(&rest[..name_len]) {
+                    substituted.push_str(value);
+                    rest = &rest[name_len..];
+                    continue;
+                }
+            }
+            substituted.push('$');
+        }
+        substituted.push_str(rest);
+        substituted
+    }
+
+    fn resolve<'a>(&'a self, reference: &'a str) -> Cow<'a, str> {
+        if reference.starts_with("input:") {
+            return match self.0.get(reference) {
+                Some(value) => Cow::Borrowed(value),
+                None => Cow::Owned(format!("${{{reference}}}")),
+            };
+        }
+        if let Some(env_reference) = reference.strip_prefix("env:") {
+            let (name, default) = env_reference.split_once(':').unwrap_or((env_reference, ""));
+            return match std::env::var(name) {
+                Ok(value) => Cow::Owned(value),
+                Err(_) => Cow::Borrowed(default),
+            };
+        }
+        let (name, default) = match reference.split_once(':') {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        match (self.0.get(name), default) {
+            (Some(value), _) => Cow::Borrowed(value),
+            (None, Some(default)) => Cow::Borrowed(default),
+            (None, None) => Cow::Owned(format!("${{{reference}}}")),
+        }
+    }
+}
+
 /// Keeps track of the file associated with a task and context of tasks execution (i.e. current file or current function)
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct TaskContext {
@@ -75,6 +182,22 @@ pub trait Task {
     /// Sets up everything needed to spawn the task in the given directory (`cwd`).
     /// If a task is intended to be spawned in the terminal, it should return the corresponding struct filled with the data necessary.
     fn exec(&self, cx: TaskContext) -> Option<SpawnInTerminal>;
+    /// Labels of the tasks that have to complete successfully before this one is run.
+    fn depends_on(&self) -> &[String] {
+        &[]
+    }
+    /// Tags of the runnables found in the code that this task is able to run.
+    fn tags(&self) -> &[String] {
+        &[]
+    }
+    /// Values to prompt the user for before the task is spawned, stored in its context as `input:<id>` variables.
+    fn inputs(&self) -> &[TaskInput] {
+        &[]
+    }
+    /// Glob of the worktree-relative paths that spawn the task when a file matching it is saved.
+    fn run_on_save(&self) -> Option<&PathMatcher> {
+        None
+    }
 }
 
 /// [`Source`] produces tasks that can be scheduled.
@@ -91,3 +214,52 @@ pub trait TaskSource: Any {
         cx: &mut ModelContext<Box<dyn TaskSource>>,
     ) -> Vec<Arc<dyn Task>>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variable_substitution() {
+        let variables = TaskVariables::from_iter([
+            ("ZED_FILE".to_owned(), "/dir/a.rs".to_owned()),
+            ("ZED_ROW".to_owned(), "4".to_owned()),
+            ("input:target".to_owned(), "release".to_owned()),
+        ]);
+        assert_eq!(variables.substitute("$ZED_FILE:$ZED_ROW"), "/dir/a.rs:4");
+        assert_eq!(
+            variables.substitute("${ZED_FILE} ${ZED_STEM:main} ${ZED_SYMBOL}"),
+            "/dir/a.rs main ${ZED_SYMBOL}"
+        );
+        assert_eq!(
+            variables.substitute("--profile=${input:target} ${input:missing}"),
+            "--profile=release ${input:missing}"
+        );
+        assert_eq!(
+            variables.substitute("${env:ZED_TASK_SURELY_UNSET_VARIABLE:fallback}${env:ZED_TASK_SURELY_UNSET_VARIABLE}"),
+            "fallback"
+        );
+        assert_eq!(
+            variables.substitute("$HOME and $ and ${"),
+            "$HOME and $ and ${"
+        );
+    }
+
+    #[test]
+    fn test_inputs_and_env_substitution() {
+        let variables = TaskVariables::from_iter([
+            ("ZED_FILE".to_owned(), "/dir/a.rs".to_owned()),
+            ("input:target".to_owned(), "release".to_owned()),
+        ]);
+        assert_eq!(
+            variables.substitute_inputs_and_env(
+                "$ZED_FILE ${ZED_FILE} --profile=${input:target} ${env:ZED_TASK_SURELY_UNSET_VARIABLE:x}"
+            ),
+            "$ZED_FILE ${ZED_FILE} --profile=release x"
+        );
+        assert_eq!(
+            variables.substitute_inputs_and_env("${ZED_FILE:main.rs} ${ZED_STEM:main} ${ZED_ROW}"),
+            "${ZED_FILE} main ${ZED_ROW}"
+        );
+    }
+}
@@ -54,6 +54,10 @@ impl Task for OneshotTask {
             use_new_terminal: Default::default(),
             allow_concurrent_runs: Default::default(),
             reveal: RevealStrategy::default(),
+            problem_matchers: Vec::new(),
+            dependencies: Vec::new(),
+            depends_order: Default::default(),
+            execution: Default::default(),
         })
     }
 }
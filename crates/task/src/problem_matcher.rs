@@ -0,0 +1,408 @@
+//! Problem matchers find compiler errors, warnings and lints in the output of a task,
+//! so that they can be reported as diagnostics.
+
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::ResultExt;
+
+/// Describes how to find problems in the output of a task.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemMatcher {
+    /// One of the built-in matchers: `$rustc`, `$tsc`, `$gcc`, `$go` or `$eslint-compact`.
+    BuiltIn(String),
+    /// A matcher defined by regular expressions.
+    Custom(CustomProblemMatcher),
+}
+
+/// A problem matcher defined by regular expressions.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CustomProblemMatcher {
+    /// Patterns that have to match consecutive lines of the output for a problem to be reported.
+    /// A problem's properties may be captured by any of them.
+    pub pattern: ProblemPatterns,
+    /// Severity of the problems whose pattern does not capture one.
+    #[serde(default)]
+    pub severity: Option<ProblemSeverity>,
+}
+
+/// One or several patterns of a [`CustomProblemMatcher`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ProblemPatterns {
+    /// A pattern matching a single line.
+    One(ProblemPattern),
+    /// Patterns matching consecutive lines.
+    Many(Vec<ProblemPattern>),
+}
+
+impl Default for ProblemPatterns {
+    fn default() -> Self {
+        Self::Many(Vec::new())
+    }
+}
+
+impl ProblemPatterns {
+    fn as_slice(&self) -> &[ProblemPattern] {
+        match self {
+            Self::One(pattern) => std::slice::from_ref(pattern),
+            Self::Many(patterns) => patterns,
+        }
+    }
+}
+
+/// A regular expression matching a line of output, and the indices of the capture groups
+/// containing each of the problem's properties.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProblemPattern {
+    /// The regular expression to match a line of output against.
+    pub regexp: String,
+    /// Path of the file with the problem, absolute or relative to the task's working directory.
+    #[serde(default)]
+    pub file: Option<usize>,
+    /// One-based line of the problem.
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// One-based column of the problem.
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// One-based line the problem ends at.
+    #[serde(default)]
+    pub end_line: Option<usize>,
+    /// One-based column the problem ends at.
+    #[serde(default)]
+    pub end_column: Option<usize>,
+    /// `error`, `warning`, `info` or `hint` (or something that starts like them, e.g. `warn`).
+    #[serde(default)]
+    pub severity: Option<usize>,
+    /// Code of the problem, such as `E0308`.
+    #[serde(default)]
+    pub code: Option<usize>,
+    /// Description of the problem.
+    #[serde(default)]
+    pub message: Option<usize>,
+}
+
+/// How severe a problem is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProblemSeverity {
+    /// An error (default).
+    #[default]
+    Error,
+    /// A warning.
+    Warning,
+    /// Some information.
+    Info,
+    /// A hint.
+    Hint,
+}
+
+impl ProblemSeverity {
+    pub(crate) fn parse(text: &str) -> Option<Self> {
+        let text = text.to_lowercase();
+        if text.starts_with("error") || text.starts_with("fatal") {
+            Some(Self::Error)
+        } else if text.starts_with("warn") {
+            Some(Self::Warning)
+        } else if text.starts_with("info") || text.starts_with("note") {
+            Some(Self::Info)
+        } else if text.starts_with("hint") || text.starts_with("help") {
+            Some(Self::Hint)
+        } else {
+            None
+        }
+    }
+}
+
+/// A problem found in the output of a task.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Problem {
+    /// Path of the file, absolute or relative to the task's working directory.
+    pub path: String,
+    /// Zero-based line of the problem's start.
+    pub line: u32,
+    /// Zero-based column of the problem's start.
+    pub column: u32,
+    /// Zero-based line and column of the problem's end, if the output contained them.
+    pub end: Option<(u32, u32)>,
+    /// How severe the problem is.
+    pub severity: ProblemSeverity,
+    /// Code of the problem, if the output contained it.
+    pub code: Option<String>,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl ProblemMatcher {
+    /// Finds the problems in the given output of a task.
+    /// Unknown built-in matchers and invalid regular expressions are logged and ignored.
+    pub fn find_problems(&self, output: &str) -> Vec<Problem> {
+        let matcher = match self {
+            Self::BuiltIn(name) => match built_in_matcher(name) {
+                Some(matcher) => matcher,
+                None => {
+                    log::error!("Unknown problem matcher {name:?}");
+                    return Vec::new();
+                }
+            },
+            Self::Custom(matcher) => matcher.clone(),
+        };
+        matcher.find_problems(output)
+    }
+}
+
+impl CustomProblemMatcher {
+    fn find_problems(&self, output: &str) -> Vec<Problem> {
+        let patterns = self.pattern.as_slice();
+        let Some(regexes) = patterns
+            .iter()
+            .map(|pattern| Regex::new(&pattern.regexp).log_err())
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Vec::new();
+        };
+        if regexes.is_empty() {
+            return Vec::new();
+        }
+
+        let lines = output.lines().collect::<Vec<_>>();
+        let mut problems = Vec::new();
+        let mut ix = 0;
+        while ix + regexes.len() <= lines.len() {
+            match self.match_problem(patterns, &regexes, &lines[ix..ix + regexes.len()]) {
+                Some(problem) => {
+                    problems.push(problem);
+                    ix += regexes.len();
+                }
+                None => ix += 1,
+            }
+        }
+        problems
+    }
+
+    fn match_problem(
+        &self,
+        patterns: &[ProblemPattern],
+        regexes: &[Regex],
+        lines: &[&str],
+    ) -> Option<Problem> {
+        let mut path = None;
+        let mut line = None;
+        let mut column = None;
+        let mut end_line = None;
+        let mut end_column = None;
+        let mut severity = None;
+        let mut code = None;
+        let mut message = None;
+
+        for ((pattern, regex), text) in patterns.iter().zip(regexes).zip(lines) {
+            let captures = regex.captures(text)?;
+            let capture = |group: Option<usize>| {
+                let text = captures.get(group?)?.as_str().trim();
+                (!text.is_empty()).then(|| text.to_string())
+            };
+            let number = |group| capture(group)?.parse::<u32>().ok();
+
+            path = capture(pattern.file).or(path);
+            line = number(pattern.line).or(line);
+            column = number(pattern.column).or(column);
+            end_line = number(pattern.end_line).or(end_line);
+            end_column = number(pattern.end_column).or(end_column);
+            severity = capture(pattern.severity)
+                .and_then(|severity| ProblemSeverity::parse(&severity))
+                .or(severity);
+            code = capture(pattern.code).or(code);
+            message = capture(pattern.message).or(message);
+        }
+
+        let line = line.unwrap_or(1).saturating_sub(1);
+        let column = column.unwrap_or(1).saturating_sub(1);
+        Some(Problem {
+            path: path?,
+            line,
+            column,
+            end: end_line.map(|end_line| {
+                let end_line = end_line.saturating_sub(1);
+                let end_column =
+                    end_column.map_or(column, |end_column| end_column.saturating_sub(1));
+                (end_line, end_column)
+            }),
+            severity: severity.or(self.severity).unwrap_or_default(),
+            code,
+            message: message.unwrap_or_default(),
+        })
+    }
+}
+
+fn built_in_matcher(name: &str) -> Option<CustomProblemMatcher> {
+    let pattern = |regexp: &str| ProblemPattern {
+        regexp: regexp.to_string(),
+        ..Default::default()
+    };
+    let patterns = match name {
+        "$rustc" => vec![
+            ProblemPattern {
+                severity: Some(1),
+                code: Some(2),
+                message: Some(3),
+                ..pattern(r"^(warning|error)(?:\[(\w+)\])?: (.*)$")
+            },
+            ProblemPattern {
+                file: Some(1),
+                line: Some(2),
+                column: Some(3),
+                ..pattern(r"^\s*-->\s+(.+?):(\d+):(\d+)$")
+            },
+        ],
+        "$tsc" => vec![ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            code: Some(5),
+            message: Some(6),
+            ..pattern(
+                r"^([^\s].*?)[\(:](\d+)[,:](\d+)(?:\):\s+|\s+-\s+)(error|warning|info)\s+(TS\d+)\s*:\s*(.*)$",
+            )
+        }],
+        "$gcc" => vec![ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            ..pattern(r"^(.*?):(\d+):(\d*):?\s+(?:fatal\s+)?(warning|error|note):\s+(.*)$")
+        }],
+        "$go" => vec![ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            message: Some(4),
+            ..pattern(r"^(?:\S+: )?([^:\s]+\.go):(\d+)(?::(\d+))?: (.*)$")
+        }],
+        "$eslint-compact" => vec![ProblemPattern {
+            file: Some(1),
+            line: Some(2),
+            column: Some(3),
+            severity: Some(4),
+            message: Some(5),
+            code: Some(6),
+            ..pattern(
+                r"^(.+):\sline\s(\d+),\scol\s(\d+),\s(Error|Warning|Info)\s-\s(.+)\s\((.+)\)$",
+            )
+        }],
+        _ => return None,
+    };
+    Some(CustomProblemMatcher {
+        pattern: ProblemPatterns::Many(patterns),
+        severity: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rustc_problem_matcher() {
+        let output = r#"
+   Compiling task v0.1.0 (/zed/crates/task)
+warning: unused variable: `x`
+ --> crates/task/src/lib.rs:10:9
+  |
+error[E0308]: mismatched types
+  --> crates/task/src/static_source.rs:42:5
+   |
+error: aborting due to 1 previous error
+"#;
+        let problems = ProblemMatcher::BuiltIn("$rustc".into()).find_problems(output);
+        assert_eq!(
+            problems,
+            vec![
+                Problem {
+                    path: "crates/task/src/lib.rs".into(),
+                    line: 9,
+                    column: 8,
+                    end: None,
+                    severity: ProblemSeverity::Warning,
+                    code: None,
+                    message: "unused variable: `x`".into(),
+                },
+                Problem {
+                    path: "crates/task/src/static_source.rs".into(),
+                    line: 41,
+                    column: 4,
+                    end: None,
+                    severity: ProblemSeverity::Error,
+                    code: Some("E0308".into()),
+                    message: "mismatched types".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_built_in_single_line_matchers() {
+        let tsc = ProblemMatcher::BuiltIn("$tsc".into()).find_problems(
+            "src/index.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.",
+        );
+        assert_eq!(
+            tsc,
+            vec![Problem {
+                path: "src/index.ts".into(),
+                line: 2,
+                column: 6,
+                end: None,
+                severity: ProblemSeverity::Error,
+                code: Some("TS2322".into()),
+                message: "Type 'string' is not assignable to type 'number'.".into(),
+            }]
+        );
+
+        let gcc = ProblemMatcher::BuiltIn("$gcc".into())
+            .find_problems("main.c:5:12: warning: unused variable 'y' [-Wunused-variable]");
+        assert_eq!(gcc.len(), 1);
+        assert_eq!(gcc[0].path, "main.c");
+        assert_eq!((gcc[0].line, gcc[0].column), (4, 11));
+        assert_eq!(gcc[0].severity, ProblemSeverity::Warning);
+
+        assert!(ProblemMatcher::BuiltIn("$unknown".into())
+            .find_problems("main.c:5:12: warning: unused")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_custom_problem_matcher() {
+        let matcher: ProblemMatcher = serde_json_lenient::from_str(
+            r#"{
+                "pattern": {
+                    "regexp": "^(.+):(\\d+):(\\d+)-(\\d+):(\\d+) (.*)$",
+                    "file": 1,
+                    "line": 2,
+                    "column": 3,
+                    "end_line": 4,
+                    "end_column": 5,
+                    "message": 6
+                },
+                "severity": "warning"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            matcher.find_problems("lint: ok\nsrc/a.py:2:1-2:5 line too long\n"),
+            vec![Problem {
+                path: "src/a.py".into(),
+                line: 1,
+                column: 0,
+                end: Some((1, 4)),
+                severity: ProblemSeverity::Warning,
+                code: None,
+                message: "line too long".into(),
+            }]
+        );
+    }
+}
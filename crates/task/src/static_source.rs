@@ -7,9 +7,12 @@ use futures::StreamExt;
 use gpui::{AppContext, Context, Model, ModelContext, Subscription};
 use schemars::{gen::SchemaSettings, JsonSchema};
 use serde::{Deserialize, Serialize};
-use util::ResultExt;
+use util::{paths::PathMatcher, ResultExt};
 
-use crate::{SpawnInTerminal, Task, TaskContext, TaskId, TaskSource};
+use crate::{
+    input::TaskInput, problem_matcher::ProblemMatcher, SpawnInTerminal, Task, TaskContext, TaskId,
+    TaskSource,
+};
 use futures::channel::mpsc::UnboundedReceiver;
 
 /// A single config file entry with the deserialized task definition.
@@ -17,16 +20,23 @@ use futures::channel::mpsc::UnboundedReceiver;
 struct StaticTask {
     id: TaskId,
     definition: Definition,
+    /// The `run_on_save` glob of the definition, parsed once.
+    run_on_save: Option<PathMatcher>,
 }
 
 impl StaticTask {
     fn new(definition: Definition, (id_base, index_in_file): (&str, usize)) -> Arc<Self> {
+        let run_on_save = definition
+            .run_on_save
+            .as_deref()
+            .and_then(|glob| PathMatcher::new(glob).log_err());
         Arc::new(Self {
             id: TaskId(format!(
                 "static_{id_base}_{index_in_file}_{}",
                 definition.label
             )),
             definition,
+            run_on_save,
         })
     }
 }
@@ -50,25 +60,46 @@ impl Task for StaticTask {
         let cwd = self
             .definition
             .cwd
-            .clone()
-            .and_then(|path| {
-                subst::substitute(&path, &task_variables.0)
-                    .map(Into::into)
-                    .ok()
-            })
+            .as_deref()
+            .map(|path| task_variables.substitute(path).into())
             .or(cwd);
-        let mut definition_env = self.definition.env.clone();
-        definition_env.extend(task_variables.0);
+        // Env values are not interpreted by a shell, so task variables are substituted into them.
+        let mut definition_env = self
+            .definition
+            .env
+            .iter()
+            .map(|(name, value)| (name.clone(), task_variables.substitute(value)))
+            .collect::<HashMap<_, _>>();
+        // In the command line, task variables are only exported to the environment, for the shell
+        // to expand them: pasting them in would let e.g. the selected text inject commands.
+        let command = task_variables.substitute_inputs_and_env(&self.definition.command);
+        let args = self
+            .definition
+            .args
+            .iter()
+            .map(|arg| task_variables.substitute_inputs_and_env(arg))
+            .collect();
+        // Inputs are not valid environment variable names, and are already substituted.
+        definition_env.extend(
+            task_variables
+                .0
+                .into_iter()
+                .filter(|(name, _)| !name.starts_with("input:")),
+        );
         Some(SpawnInTerminal {
             id: self.id.clone(),
             cwd,
             use_new_terminal: self.definition.use_new_terminal,
             allow_concurrent_runs: self.definition.allow_concurrent_runs,
             label: self.definition.label.clone(),
-            command: self.definition.command.clone(),
-            args: self.definition.args.clone(),
+            command,
+            args,
             reveal: self.definition.reveal,
             env: definition_env,
+            problem_matchers: self.definition.problem_matchers.clone(),
+            dependencies: Vec::new(),
+            depends_order: self.definition.depends_order,
+            execution: self.definition.execution,
         })
     }
 
@@ -83,6 +114,22 @@ impl Task for StaticTask {
     fn cwd(&self) -> Option<&str> {
         self.definition.cwd.as_deref()
     }
+
+    fn depends_on(&self) -> &[String] {
+        &self.definition.depends_on
+    }
+
+    fn tags(&self) -> &[String] {
+        &self.definition.tags
+    }
+
+    fn inputs(&self) -> &[TaskInput] {
+        &self.definition.inputs
+    }
+
+    fn run_on_save(&self) -> Option<&PathMatcher> {
+        self.run_on_save.as_ref()
+    }
 }
 
 /// The source of tasks defined in a tasks config file.
@@ -99,11 +146,15 @@ pub struct Definition {
     /// Human readable name of the task to display in the UI.
     pub label: String,
     /// Executable command to spawn.
+    /// May be omitted by tasks that only run the tasks they depend on.
+    /// Task variables such as `$ZED_FILE` are set in the command's environment, for the shell to expand.
+    #[serde(default)]
     pub command: String,
     /// Arguments to the command.
     #[serde(default)]
     pub args: Vec<String>,
     /// Env overrides for the command, will be appended to the terminal's environment from the settings.
+    /// Task variables such as `$ZED_FILE` are substituted into the values.
     #[serde(default)]
     pub env: HashMap<String, String>,
     /// Current working directory to spawn the command into, defaults to current project root.
@@ -118,8 +169,41 @@ pub struct Definition {
     /// What to do with the terminal pane and tab, after the command was started:
     /// * `always` — always show the terminal pane, add and focus the corresponding task's tab in it (default)
     /// * `never` — avoid changing current terminal pane focus, but still add/reuse the task's tab there
+    /// * `{ "split": "right" }` — like `always`, but add new task tabs to the split at the given edge
+    ///   (`up`, `down`, `left` or `right`) of the terminal pane, splitting it if there is no such split
     #[serde(default)]
     pub reveal: RevealStrategy,
+    /// Problem matchers that find errors and warnings in the task's output, to show them as diagnostics.
+    /// Either the name of a built-in matcher (`$rustc`, `$tsc`, `$gcc`, `$go` or `$eslint-compact`)
+    /// or a custom one, built of regular expressions. Diagnostics of the previous run are cleared on rerun.
+    #[serde(default)]
+    pub problem_matchers: Vec<ProblemMatcher>,
+    /// Labels of the tasks to run before this one. If any of them fails, this task is not run.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// How to run the tasks this one depends on:
+    /// * `parallel` — start all of them at once (default)
+    /// * `sequence` — start each of them after the previous one succeeds
+    #[serde(default)]
+    pub depends_order: DependsOrder,
+    /// Tags of the runnables this task can run, as set by languages' `runnables.scm` queries (e.g. `rust-test`).
+    /// Such tasks are offered by the run indicator shown in the gutter next to the runnables.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Values to ask for before spawning the task, referenced as `${input:<id>}` in its command, args, cwd and env:
+    /// * `{ "id": "target", "type": "prompt", "default": "debug" }` — any text typed
+    /// * `{ "id": "target", "type": "pick", "options": ["debug", "release"] }` — one of the options
+    /// * `{ "id": "target", "type": "command", "command": "ls", "args": ["targets"] }` — one of the lines the command prints
+    #[serde(default)]
+    pub inputs: Vec<TaskInput>,
+    /// Where to run the task:
+    /// * `terminal` — in a terminal tab of the terminal pane (default)
+    /// * `headless` — in the background, collecting the output into a log that the activity indicator links to
+    #[serde(default)]
+    pub execution: ExecutionMode,
+    /// Glob of the worktree-relative paths (e.g. `src/**/*.proto`) that spawn the task whenever a matching file is saved.
+    #[serde(default)]
+    pub run_on_save: Option<String>,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -131,6 +215,40 @@ pub enum RevealStrategy {
     Always,
     /// Do not change terminal pane focus, but still add/reuse the task's tab there.
     Never,
+    /// Always show the terminal pane, adding new task tabs to the split at the given edge of it.
+    Split(TerminalSplit),
+}
+
+/// An edge of the terminal pane, where the split that a task's tab goes to is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalSplit {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Where to run the task's command.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionMode {
+    /// In a terminal tab of the terminal pane.
+    #[default]
+    Terminal,
+    /// In the background, without a terminal, collecting the output into a log buffer.
+    Headless,
+}
+
+/// How to run the tasks another task depends on.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DependsOrder {
+    /// Start all of them at once.
+    #[default]
+    Parallel,
+    /// Start each of them after the previous one succeeds.
+    Sequence,
 }
 
 /// A group of Tasks defined in a JSON file.
@@ -282,3 +400,49 @@ impl TaskSource for StaticSource {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TaskVariables;
+
+    #[test]
+    fn test_task_variables_are_not_pasted_into_the_command() {
+        let selected_text = r#"a; touch pwned $(touch pwned) `touch pwned` "double" 'single'"#;
+        let task = tasks_for(
+            TaskDefinitions(vec![Definition {
+                label: "echo selection".to_owned(),
+                command: "echo $ZED_SELECTED_TEXT".to_owned(),
+                args: vec![
+                    "\"${ZED_SELECTED_TEXT}\"".to_owned(),
+                    "--profile=${input:target}".to_owned(),
+                ],
+                env: HashMap::from_iter([(
+                    "SELECTION".to_owned(),
+                    "${ZED_SELECTED_TEXT}".to_owned(),
+                )]),
+                ..Default::default()
+            }]),
+            "test",
+        )
+        .remove(0);
+
+        let spawn_in_terminal = task
+            .exec(TaskContext {
+                cwd: None,
+                task_variables: TaskVariables::from_iter([
+                    ("ZED_SELECTED_TEXT".to_owned(), selected_text.to_owned()),
+                    ("input:target".to_owned(), "release".to_owned()),
+                ]),
+            })
+            .unwrap();
+        assert_eq!(spawn_in_terminal.command, "echo $ZED_SELECTED_TEXT");
+        assert_eq!(
+            spawn_in_terminal.args,
+            ["\"${ZED_SELECTED_TEXT}\"", "--profile=release"]
+        );
+        assert_eq!(spawn_in_terminal.env["SELECTION"], selected_text);
+        assert_eq!(spawn_in_terminal.env["ZED_SELECTED_TEXT"], selected_text);
+        assert!(!spawn_in_terminal.env.contains_key("input:target"));
+    }
+}
@@ -3,7 +3,13 @@ use collections::HashMap;
 use serde::Deserialize;
 use util::ResultExt;
 
-use crate::static_source::{Definition, TaskDefinitions};
+use crate::{
+    input::{TaskInput, TaskInputKind},
+    problem_matcher::{
+        CustomProblemMatcher, ProblemMatcher, ProblemPattern, ProblemPatterns, ProblemSeverity,
+    },
+    static_source::{Definition, DependsOrder, TaskDefinitions},
+};
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -41,6 +47,150 @@ enum Command {
     },
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeDependsOn {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatchers {
+    One(VsCodeProblemMatcher),
+    Many(Vec<VsCodeProblemMatcher>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemMatcher {
+    Named(String),
+    Custom {
+        base: Option<String>,
+        severity: Option<String>,
+        pattern: Option<VsCodeProblemPatterns>,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodeProblemPatterns {
+    One(VsCodeProblemPattern),
+    Many(Vec<VsCodeProblemPattern>),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeProblemPattern {
+    regexp: String,
+    file: Option<usize>,
+    line: Option<usize>,
+    column: Option<usize>,
+    end_line: Option<usize>,
+    end_column: Option<usize>,
+    severity: Option<usize>,
+    code: Option<usize>,
+    message: Option<usize>,
+}
+
+impl VsCodeProblemMatcher {
+    fn to_zed_format(self) -> Option<ProblemMatcher> {
+        // Watching variants of Code's matchers also match the output of a single run.
+        let built_in = |name: String| {
+            ProblemMatcher::BuiltIn(name.strip_suffix("-watch").unwrap_or(&name).to_string())
+        };
+        match self {
+            Self::Named(name) => Some(built_in(name)),
+            Self::Custom {
+                base,
+                severity,
+                pattern,
+            } => {
+                let Some(pattern) = pattern else {
+                    return base.map(built_in);
+                };
+                let pattern = |pattern: VsCodeProblemPattern| ProblemPattern {
+                    regexp: pattern.regexp,
+                    file: pattern.file,
+                    line: pattern.line,
+                    column: pattern.column,
+                    end_line: pattern.end_line,
+                    end_column: pattern.end_column,
+                    severity: pattern.severity,
+                    code: pattern.code,
+                    message: pattern.message,
+                };
+                Some(ProblemMatcher::Custom(CustomProblemMatcher {
+                    pattern: match pattern {
+                        VsCodeProblemPatterns::One(one) => ProblemPatterns::One(pattern(one)),
+                        VsCodeProblemPatterns::Many(many) => {
+                            ProblemPatterns::Many(many.into_iter().map(pattern).collect())
+                        }
+                    },
+                    severity: severity.as_deref().and_then(ProblemSeverity::parse),
+                }))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+enum VsCodeTaskInputKind {
+    PromptString {
+        default: Option<String>,
+    },
+    PickString {
+        options: Vec<VsCodePickOption>,
+        default: Option<String>,
+    },
+    // Code's commands cannot be run outside of it.
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum VsCodePickOption {
+    Value(String),
+    Labeled { value: String },
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeTaskInput {
+    id: String,
+    description: Option<String>,
+    #[serde(flatten)]
+    kind: VsCodeTaskInputKind,
+}
+
+impl VsCodeTaskInput {
+    fn to_zed_format(self) -> Option<TaskInput> {
+        let kind = match self.kind {
+            VsCodeTaskInputKind::PromptString { default } => TaskInputKind::Prompt { default },
+            VsCodeTaskInputKind::PickString { options, default } => TaskInputKind::Pick {
+                options: options
+                    .into_iter()
+                    .map(|option| match option {
+                        VsCodePickOption::Value(value) | VsCodePickOption::Labeled { value } => {
+                            value
+                        }
+                    })
+                    .collect(),
+                default,
+            },
+            VsCodeTaskInputKind::Unsupported => return None,
+        };
+        Some(TaskInput {
+            id: self.id,
+            description: self.description,
+            kind,
+        })
+    }
+}
+
 type VsCodeEnvVariable = String;
 type ZedEnvVariable = String;
 
@@ -85,21 +235,30 @@ impl EnvVariableReplacer {
 
 impl VsCodeTaskDefinition {
     fn to_zed_format(self, replacer: &EnvVariableReplacer) -> anyhow::Result<Definition> {
-        if self.other_attributes.contains_key("dependsOn") {
-            bail!("Encountered unsupported `dependsOn` key during deserialization");
-        }
-        // `type` might not be set in e.g. tasks that use `dependsOn`; we still want to deserialize the whole object though (hence command is an Option),
-        // as that way we can provide more specific description of why deserialization failed.
-        // E.g. if the command is missing due to `dependsOn` presence, we can check other_attributes first before doing this (and provide nice error message)
-        // catch-all if on value.command presence.
-        let Some(command) = self.command else {
-            bail!("Missing `type` field in task");
+        let depends_on = match self.other_attributes.get("dependsOn") {
+            Some(depends_on) => {
+                match serde_json_lenient::from_value::<VsCodeDependsOn>(depends_on.clone())? {
+                    VsCodeDependsOn::One(label) => vec![label],
+                    VsCodeDependsOn::Many(labels) => labels,
+                }
+            }
+            None => Vec::new(),
         };
-
-        let (command, args) = match command {
-            Command::Npm { script } => ("npm".to_owned(), vec!["run".to_string(), script]),
-            Command::Shell { command, args } => (command, args),
-            Command::Gulp { task } => ("gulp".to_owned(), vec![task]),
+        let depends_order = match self
+            .other_attributes
+            .get("dependsOrder")
+            .and_then(|order| order.as_str())
+        {
+            Some("sequence") => DependsOrder::Sequence,
+            _ => DependsOrder::Parallel,
+        };
+        // `type` might not be set in tasks that only use `dependsOn` to run other tasks.
+        let (command, args) = match self.command {
+            Some(Command::Npm { script }) => ("npm".to_owned(), vec!["run".to_string(), script]),
+            Some(Command::Shell { command, args }) => (command, args),
+            Some(Command::Gulp { task }) => ("gulp".to_owned(), vec![task]),
+            None if !depends_on.is_empty() => (String::new(), Vec::new()),
+            None => bail!("Missing `type` field in task"),
         };
         // Per VSC docs, only `command`, `args` and `options` support variable substitution.
         let command = replacer.replace(&command);
@@ -108,8 +267,25 @@ impl VsCodeTaskDefinition {
             label: self.label,
             command,
             args,
+            depends_on,
+            depends_order,
             ..Default::default()
         };
+        if let Some(problem_matchers) = self.other_attributes.get("problemMatcher") {
+            let problem_matchers =
+                serde_json_lenient::from_value::<VsCodeProblemMatchers>(problem_matchers.clone())
+                    .log_err();
+            ret.problem_matchers = match problem_matchers {
+                Some(VsCodeProblemMatchers::One(matcher)) => {
+                    matcher.to_zed_format().into_iter().collect()
+                }
+                Some(VsCodeProblemMatchers::Many(matchers)) => matchers
+                    .into_iter()
+                    .filter_map(VsCodeProblemMatcher::to_zed_format)
+                    .collect(),
+                None => Vec::new(),
+            };
+        }
         if let Some(options) = self.options {
             ret.cwd = options.cwd.map(|cwd| replacer.replace(&cwd));
             ret.env = options.env;
@@ -122,6 +298,8 @@ impl VsCodeTaskDefinition {
 #[derive(Debug, Deserialize, PartialEq)]
 pub struct VsCodeTaskFile {
     tasks: Vec<VsCodeTaskDefinition>,
+    #[serde(default)]
+    inputs: Vec<VsCodeTaskInput>,
 }
 
 impl TryFrom<VsCodeTaskFile> for TaskDefinitions {
@@ -133,11 +311,41 @@ impl TryFrom<VsCodeTaskFile> for TaskDefinitions {
             ("file".to_owned(), "ZED_FILE".to_owned()),
             ("lineNumber".to_owned(), "ZED_ROW".to_owned()),
             ("selectedText".to_owned(), "ZED_SELECTED_TEXT".to_owned()),
+            ("relativeFile".to_owned(), "ZED_RELATIVE_FILE".to_owned()),
+            ("fileDirname".to_owned(), "ZED_DIRNAME".to_owned()),
+            ("fileBasenameNoExtension".to_owned(), "ZED_STEM".to_owned()),
         ]));
+        let inputs = value
+            .inputs
+            .into_iter()
+            .filter_map(VsCodeTaskInput::to_zed_format)
+            .collect::<Vec<_>>();
         let definitions = value
             .tasks
             .into_iter()
             .filter_map(|vscode_definition| vscode_definition.to_zed_format(&replacer).log_err())
+            .map(|mut definition| {
+                // Code declares inputs for the whole file, Zed does it for each task that uses them.
+                let reference = |input: &TaskInput| format!("${{{}}}", input.variable_name());
+                definition.inputs = inputs
+                    .iter()
+                    .filter(|input| {
+                        let reference = reference(input);
+                        definition.command.contains(&reference)
+                            || definition.args.iter().any(|arg| arg.contains(&reference))
+                            || definition
+                                .cwd
+                                .as_ref()
+                                .is_some_and(|cwd| cwd.contains(&reference))
+                            || definition
+                                .env
+                                .values()
+                                .any(|value| value.contains(&reference))
+                    })
+                    .cloned()
+                    .collect();
+                definition
+            })
             .collect();
         Ok(Self(definitions))
     }
@@ -148,7 +356,11 @@ mod tests {
     use std::collections::HashMap;
 
     use crate::{
-        static_source::{Definition, TaskDefinitions},
+        input::{TaskInput, TaskInputKind},
+        problem_matcher::{
+            CustomProblemMatcher, ProblemMatcher, ProblemPattern, ProblemPatterns, ProblemSeverity,
+        },
+        static_source::{Definition, DependsOrder, TaskDefinitions},
         vscode_format::{Command, VsCodeTaskDefinition},
         VsCodeTaskFile,
     };
@@ -252,6 +464,7 @@ mod tests {
                 label: "gulp: tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             Definition {
@@ -263,18 +476,21 @@ mod tests {
                     "${ZED_WORKTREE_ROOT}/src".to_string(),
                     "--watch".to_string(),
                 ],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             Definition {
                 label: "npm: build:compiler".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:compiler".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             Definition {
                 label: "npm: build:tests".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build:tests:notypecheck".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
         ];
@@ -355,32 +571,206 @@ mod tests {
                 label: "Build Extension in Background".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "watch".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             Definition {
                 label: "Build Extension".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "build".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
                 ..Default::default()
             },
             Definition {
                 label: "Build Server".to_string(),
                 command: "cargo build --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             Definition {
                 label: "Build Server (Release)".to_string(),
                 command: "cargo build --release --package rust-analyzer".to_string(),
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
             Definition {
                 label: "Pretest".to_string(),
                 command: "npm".to_string(),
                 args: vec!["run".to_string(), "pretest".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$tsc".to_string())],
+                ..Default::default()
+            },
+            Definition {
+                label: "Build Server and Extension".to_string(),
+                depends_on: vec!["Build Server".to_string(), "Build Extension".to_string()],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
+                ..Default::default()
+            },
+            Definition {
+                label: "Build Server (Release) and Extension".to_string(),
+                depends_on: vec![
+                    "Build Server (Release)".to_string(),
+                    "Build Extension".to_string(),
+                ],
+                problem_matchers: vec![ProblemMatcher::BuiltIn("$rustc".to_string())],
                 ..Default::default()
             },
         ];
         let tasks: TaskDefinitions = vscode_definitions.try_into().unwrap();
         assert_eq!(tasks.0, expected);
     }
+
+    #[test]
+    fn can_deserialize_custom_problem_matchers() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "lint",
+                        "type": "shell",
+                        "command": "lint",
+                        "problemMatcher": {
+                            "owner": "lint",
+                            "severity": "warning",
+                            "fileLocation": ["relative", "${workspaceFolder}"],
+                            "pattern": {
+                                "regexp": "^(.*):(\\d+):(\\d+):(\\d+):(\\d+): (.*)$",
+                                "file": 1,
+                                "line": 2,
+                                "column": 3,
+                                "endLine": 4,
+                                "endColumn": 5,
+                                "message": 6
+                            }
+                        }
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskDefinitions = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0[0].problem_matchers,
+            vec![ProblemMatcher::Custom(CustomProblemMatcher {
+                pattern: ProblemPatterns::One(ProblemPattern {
+                    regexp: r"^(.*):(\d+):(\d+):(\d+):(\d+): (.*)$".to_string(),
+                    file: Some(1),
+                    line: Some(2),
+                    column: Some(3),
+                    end_line: Some(4),
+                    end_column: Some(5),
+                    message: Some(6),
+                    ..Default::default()
+                }),
+                severity: Some(ProblemSeverity::Warning),
+            })]
+        );
+    }
+
+    #[test]
+    fn can_deserialize_depends_order() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "build and deploy",
+                        "dependsOn": "build",
+                        "dependsOrder": "sequence",
+                        "type": "shell",
+                        "command": "deploy"
+                    },
+                    {
+                        "label": "nothing to do"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskDefinitions = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![Definition {
+                label: "build and deploy".to_string(),
+                command: "deploy".to_string(),
+                depends_on: vec!["build".to_string()],
+                depends_order: DependsOrder::Sequence,
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn can_deserialize_inputs() {
+        let vscode_definitions: VsCodeTaskFile = serde_json_lenient::from_str(
+            r#"{
+                "tasks": [
+                    {
+                        "label": "build",
+                        "type": "shell",
+                        "command": "cargo",
+                        "args": ["build", "--profile", "${input:profile}", "${relativeFile}"]
+                    },
+                    {
+                        "label": "greet",
+                        "type": "shell",
+                        "command": "echo ${input:name}"
+                    }
+                ],
+                "inputs": [
+                    {
+                        "id": "profile",
+                        "type": "pickString",
+                        "description": "Build profile",
+                        "options": ["dev", { "label": "Release", "value": "release" }],
+                        "default": "dev"
+                    },
+                    {
+                        "id": "name",
+                        "type": "promptString"
+                    },
+                    {
+                        "id": "unused",
+                        "type": "command",
+                        "command": "extension.pickProcess"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let tasks: TaskDefinitions = vscode_definitions.try_into().unwrap();
+        assert_eq!(
+            tasks.0,
+            vec![
+                Definition {
+                    label: "build".to_string(),
+                    command: "cargo".to_string(),
+                    args: vec![
+                        "build".to_string(),
+                        "--profile".to_string(),
+                        "${input:profile}".to_string(),
+                        "${ZED_RELATIVE_FILE}".to_string(),
+                    ],
+                    inputs: vec![TaskInput {
+                        id: "profile".to_string(),
+                        description: Some("Build profile".to_string()),
+                        kind: TaskInputKind::Pick {
+                            options: vec!["dev".to_string(), "release".to_string()],
+                            default: Some("dev".to_string()),
+                        },
+                    }],
+                    ..Default::default()
+                },
+                Definition {
+                    label: "greet".to_string(),
+                    command: "echo ${input:name}".to_string(),
+                    inputs: vec![TaskInput {
+                        id: "name".to_string(),
+                        description: None,
+                        kind: TaskInputKind::Prompt { default: None },
+                    }],
+                    ..Default::default()
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use futures::channel::oneshot;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use gpui::{
+    rems, DismissEvent, EventEmitter, FocusableView, InteractiveElement, ParentElement, Render,
+    SharedString, Styled, Subscription, View, ViewContext, VisualContext,
+};
+use picker::{Picker, PickerDelegate};
+use task::input::{TaskInput, TaskInputKind};
+use ui::{
+    v_flex, Color, HighlightedLabel, Label, LabelCommon, ListItem, ListItemSpacing, Selectable,
+    WindowContext,
+};
+use util::ResultExt;
+use workspace::ModalView;
+
+/// A modal used to enter the value of a task's input, before the task is spawned.
+pub(crate) struct TaskInputDelegate {
+    input: TaskInput,
+    options: Vec<String>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    query: String,
+    value_tx: Option<oneshot::Sender<String>>,
+}
+
+impl TaskInputDelegate {
+    fn new(input: TaskInput, options: Vec<String>, value_tx: oneshot::Sender<String>) -> Self {
+        Self {
+            input,
+            options,
+            matches: Vec::new(),
+            selected_index: 0,
+            query: String::new(),
+            value_tx: Some(value_tx),
+        }
+    }
+
+    fn accepts_any_text(&self) -> bool {
+        matches!(self.input.kind, TaskInputKind::Prompt { .. })
+    }
+
+    fn submit(&mut self, value: String, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(value_tx) = self.value_tx.take() {
+            value_tx.send(value).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+pub(crate) struct TaskInputModal {
+    picker: View<Picker<TaskInputDelegate>>,
+    _subscription: Subscription,
+}
+
+impl TaskInputModal {
+    /// Creates a modal that sends the value entered over `value_tx`; the sender is dropped if the modal is dismissed.
+    pub(crate) fn new(
+        input: TaskInput,
+        options: Vec<String>,
+        value_tx: oneshot::Sender<String>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let picker = cx.new_view(|cx| {
+            let prompt_default = match &input.kind {
+                TaskInputKind::Prompt { default } => default.clone(),
+                _ => None,
+            };
+            let picker = Picker::uniform_list(TaskInputDelegate::new(input, options, value_tx), cx);
+            if let Some(prompt_default) = prompt_default {
+                picker.set_query(prompt_default, cx);
+            }
+            picker
+        });
+        let _subscription = cx.subscribe(&picker, |_, _, _, cx| {
+            cx.emit(DismissEvent);
+        });
+        Self {
+            picker,
+            _subscription,
+        }
+    }
+}
+
+impl Render for TaskInputModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl gpui::prelude::IntoElement {
+        v_flex()
+            .key_context("TaskInputModal")
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .on_mouse_down_out(cx.listener(|modal, _, cx| {
+                modal.picker.update(cx, |picker, cx| {
+                    picker.cancel(&Default::default(), cx);
+                })
+            }))
+    }
+}
+
+impl EventEmitter<DismissEvent> for TaskInputModal {}
+
+impl FocusableView for TaskInputModal {
+    fn focus_handle(&self, cx: &gpui::AppContext) -> gpui::FocusHandle {
+        self.picker.read(cx).focus_handle(cx)
+    }
+}
+
+impl ModalView for TaskInputModal {}
+
+impl PickerDelegate for TaskInputDelegate {
+    type ListItem = ListItem;
+
+    fn match_count(&self) -> usize {
+        if self.accepts_any_text() {
+            1
+        } else {
+            self.matches.len()
+        }
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match &self.input.description {
+            Some(description) => Arc::from(description.as_str()),
+            None if self.accepts_any_text() => Arc::from(format!("Enter {}…", self.input.id)),
+            None => Arc::from(format!("Pick {}…", self.input.id)),
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        if self.accepts_any_text() {
+            self.query = query;
+            return gpui::Task::ready(());
+        }
+
+        let candidates = self
+            .options
+            .iter()
+            .enumerate()
+            .map(|(id, option)| StringMatchCandidate::new(id, option.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = fuzzy::match_strings(
+                &candidates,
+                &query,
+                true,
+                1000,
+                &Default::default(),
+                cx.background_executor().clone(),
+            )
+            .await;
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    let default = match &delegate.input.kind {
+                        TaskInputKind::Pick { default, .. } if query.is_empty() => default.as_ref(),
+                        _ => None,
+                    };
+                    delegate.selected_index = default
+                        .and_then(|default| {
+                            matches
+                                .iter()
+                                .position(|string_match| &string_match.string == default)
+                        })
+                        .unwrap_or_else(|| {
+                            delegate.selected_index.min(matches.len().saturating_sub(1))
+                        });
+                    delegate.matches = matches;
+                    delegate.query = query;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let value = if self.accepts_any_text() {
+            self.query.clone()
+        } else {
+            match self.matches.get(self.selected_index) {
+                Some(string_match) => string_match.string.clone(),
+                None => return,
+            }
+        };
+        self.submit(value, cx);
+    }
+
+    fn confirm_input(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        self.submit(self.query.clone(), cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(SharedString::from(format!("task-input-{ix}")))
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected);
+        if self.accepts_any_text() {
+            let label = if self.query.is_empty() {
+                Label::new("Use an empty value").color(Color::Muted)
+            } else {
+                Label::new(self.query.clone())
+            };
+            return Some(item.child(label));
+        }
+        let hit = self.matches.get(ix)?;
+        Some(item.child(HighlightedLabel::new(
+            hit.string.clone(),
+            hit.positions.clone(),
+        )))
+    }
+}
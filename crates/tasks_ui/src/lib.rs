@@ -1,21 +1,36 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::Arc};
 
-use editor::Editor;
-use gpui::{AppContext, ViewContext, WindowContext};
+use anyhow::Context as _;
+use editor::{actions::SpawnRunnable, Editor};
+use futures::channel::oneshot;
+use gpui::{AppContext, View, ViewContext, WindowContext};
+use input_modal::TaskInputModal;
 use language::Point;
 use modal::{Spawn, TasksModal};
-use project::{Location, WorktreeId};
-use task::{Task, TaskContext, TaskVariables};
+use project::{Inventory, Location, ProjectPath, WorktreeId};
+use task::{
+    input::{TaskInput, TaskInputKind},
+    SpawnInTerminal, Task, TaskContext, TaskVariables,
+};
+use terminal_view::TerminalView;
 use util::ResultExt;
 use workspace::Workspace;
 
+mod input_modal;
 mod modal;
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
-        |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
+            cx.subscribe(workspace.project(), |workspace, _, event, cx| {
+                if let project::Event::BufferSaved(saved_path) = event {
+                    run_tasks_on_save(workspace, saved_path, cx);
+                }
+            })
+            .detach();
             workspace
                 .register_action(spawn_task_or_modal)
+                .register_action(spawn_runnable)
                 .register_action(move |workspace, action: &modal::Rerun, cx| {
                     if let Some((task, old_context)) =
                         workspace.project().update(cx, |project, cx| {
@@ -31,7 +46,7 @@ pub fn init(cx: &mut AppContext) {
                             old_context
                         };
 
-                        schedule_task(workspace, task.as_ref(), task_context, false, cx)
+                        schedule_task(workspace, task, task_context, false, cx)
                     };
                 });
         },
@@ -49,11 +64,152 @@ fn spawn_task_or_modal(workspace: &mut Workspace, action: &Spawn, cx: &mut ViewC
         spawn_task_with_name(name.clone(), cx);
     } else {
         workspace.toggle_modal(cx, |cx| {
-            TasksModal::new(inventory, task_context, workspace_handle, cx)
+            TasksModal::new(inventory, task_context, Vec::new(), workspace_handle, cx)
         })
     }
 }
 
+/// Spawns the task able to run the runnable at the cursor, e.g. the test under it.
+/// If several tasks are able to, or none is, the tasks with the runnable's tags are
+/// offered in the tasks modal.
+fn spawn_runnable(workspace: &mut Workspace, _: &SpawnRunnable, cx: &mut ViewContext<Workspace>) {
+    let Some((buffer, runnable)) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+        .and_then(|editor| editor.read(cx).runnable_at_cursor(cx))
+    else {
+        spawn_task_or_modal(workspace, &Spawn::default(), cx);
+        return;
+    };
+
+    let cwd = task_cwd(workspace, cx).log_err().flatten();
+    let mut task_context = task_context(workspace, cwd, cx);
+    task_context
+        .task_variables
+        .0
+        .extend(runnable.extra_captures.iter().map(|(name, range)| {
+            (
+                format!("ZED_CUSTOM_{name}"),
+                buffer.text_for_range(range.clone()).collect(),
+            )
+        }));
+
+    let worktree = workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx))
+        .map(|path| path.worktree_id);
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    let mut runnable_tasks = inventory
+        .update(cx, |inventory, cx| {
+            inventory.list_tasks(None, worktree, false, cx)
+        })
+        .into_iter()
+        .filter(|(_, task)| task.tags().iter().any(|tag| runnable.tags.contains(tag)));
+    match (runnable_tasks.next(), runnable_tasks.next()) {
+        (Some((_, task)), None) => schedule_task(workspace, task, task_context, false, cx),
+        _ => {
+            let workspace_handle = workspace.weak_handle();
+            workspace.toggle_modal(cx, |cx| {
+                TasksModal::new(inventory, task_context, runnable.tags, workspace_handle, cx)
+            })
+        }
+    }
+}
+
+/// Spawns the tasks whose `run_on_save` glob matches the path of the file saved.
+fn run_tasks_on_save(
+    workspace: &mut Workspace,
+    saved_path: &ProjectPath,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let inventory = workspace.project().read(cx).task_inventory().clone();
+    let tasks_to_run = inventory.update(cx, |inventory, cx| {
+        tasks_to_run_on_save(inventory, saved_path, cx)
+    });
+    if tasks_to_run.is_empty() {
+        return;
+    }
+
+    // With "save all", the buffer saved is not necessarily the active one: build the context
+    // from an editor of the saved buffer, or from the buffer alone.
+    let Some(saved_buffer) = workspace
+        .project()
+        .update(cx, |project, cx| project.get_open_buffer(saved_path, cx))
+    else {
+        return;
+    };
+    let cwd = workspace
+        .project()
+        .read(cx)
+        .worktree_for_id(saved_path.worktree_id, cx)
+        .filter(|worktree| worktree.read(cx).is_local())
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+    let saved_editor = workspace
+        .active_item_as::<Editor>(cx)
+        .into_iter()
+        .chain(workspace.items_of_type::<Editor>(cx))
+        .find(|editor| {
+            editor.read(cx).buffer().read(cx).as_singleton() == Some(saved_buffer.clone())
+        });
+    let task_context = saved_editor
+        .and_then(|editor| editor_task_context(workspace, &editor, cwd.clone(), cx))
+        .unwrap_or_else(|| {
+            let start = saved_buffer.read(cx).anchor_before(0);
+            let location = Location {
+                buffer: saved_buffer,
+                range: start..start,
+            };
+            TaskContext {
+                task_variables: location_task_variables(workspace, location, cx),
+                cwd,
+            }
+        });
+    for (task, input_values) in tasks_to_run {
+        let mut task_context = task_context.clone();
+        task_context.task_variables.0.extend(input_values);
+        spawn_task(workspace, task.as_ref(), task_context, true, cx);
+    }
+}
+
+/// Finds the tasks to run when the file at the given path is saved, along with the values of their inputs.
+///
+/// Saving never prompts for input values: a task with inputs reuses the values it was last
+/// spawned with, and is not run on save until it has been spawned once.
+fn tasks_to_run_on_save(
+    inventory: &mut Inventory,
+    saved_path: &ProjectPath,
+    cx: &mut AppContext,
+) -> Vec<(Arc<dyn Task>, Vec<(String, String)>)> {
+    inventory
+        .list_tasks(None, Some(saved_path.worktree_id), false, cx)
+        .into_iter()
+        .map(|(_, task)| task)
+        .filter(|task| {
+            task.run_on_save()
+                .map_or(false, |matcher| matcher.is_match(&saved_path.path))
+        })
+        .filter_map(|task| {
+            let last_context = inventory.last_scheduled_context(task.id());
+            let input_values = task
+                .inputs()
+                .iter()
+                .map(|input| {
+                    let name = input.variable_name();
+                    let value = last_context?.task_variables.0.get(&name)?.clone();
+                    Some((name, value))
+                })
+                .collect::<Option<Vec<_>>>();
+            if input_values.is_none() {
+                log::info!(
+                    "Not running task {:?} on save, as it has inputs with no value yet",
+                    task.name()
+                );
+            }
+            Some((task, input_values?))
+        })
+        .collect()
+}
+
 fn spawn_task_with_name(name: String, cx: &mut ViewContext<Workspace>) {
     cx.spawn(|workspace, mut cx| async move {
         let did_spawn = workspace
@@ -70,7 +226,7 @@ fn spawn_task_with_name(name: String, cx: &mut ViewContext<Workspace>) {
                 let (_, target_task) = tasks.into_iter().find(|(_, task)| task.name() == name)?;
                 let cwd = task_cwd(this, cx).log_err().flatten();
                 let task_context = task_context(this, cwd, cx);
-                schedule_task(this, target_task.as_ref(), task_context, false, cx);
+                schedule_task(this, target_task, task_context, false, cx);
                 Some(())
             })
             .ok()
@@ -92,114 +248,267 @@ fn task_context(
     cwd: Option<PathBuf>,
     cx: &mut WindowContext<'_>,
 ) -> TaskContext {
-    let current_editor = workspace
+    workspace
         .active_item(cx)
         .and_then(|item| item.act_as::<Editor>(cx))
-        .clone();
-    if let Some(current_editor) = current_editor {
-        (|| {
-            let editor = current_editor.read(cx);
-            let selection = editor.selections.newest::<usize>(cx);
-            let (buffer, _, _) = editor
-                .buffer()
-                .read(cx)
-                .point_to_buffer_offset(selection.start, cx)?;
-
-            current_editor.update(cx, |editor, cx| {
-                let snapshot = editor.snapshot(cx);
-                let selection_range = selection.range();
-                let start = snapshot
-                    .display_snapshot
-                    .buffer_snapshot
-                    .anchor_after(selection_range.start)
-                    .text_anchor;
-                let end = snapshot
-                    .display_snapshot
-                    .buffer_snapshot
-                    .anchor_after(selection_range.end)
-                    .text_anchor;
-                let Point { row, column } = snapshot
+        .and_then(|editor| editor_task_context(workspace, &editor, cwd.clone(), cx))
+        .unwrap_or_else(|| TaskContext {
+            cwd,
+            task_variables: Default::default(),
+        })
+}
+
+/// Builds the task context from the newest selection of the editor given.
+fn editor_task_context(
+    workspace: &Workspace,
+    current_editor: &View<Editor>,
+    cwd: Option<PathBuf>,
+    cx: &mut WindowContext<'_>,
+) -> Option<TaskContext> {
+    let editor = current_editor.read(cx);
+    let selection = editor.selections.newest::<usize>(cx);
+    let (buffer, _, _) = editor
+        .buffer()
+        .read(cx)
+        .point_to_buffer_offset(selection.start, cx)?;
+
+    let (location, mut task_variables) = current_editor.update(cx, |editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        let selection_range = selection.range();
+        let start = snapshot
+            .display_snapshot
+            .buffer_snapshot
+            .anchor_after(selection_range.start)
+            .text_anchor;
+        let end = snapshot
+            .display_snapshot
+            .buffer_snapshot
+            .anchor_after(selection_range.end)
+            .text_anchor;
+        let Point { row, column } = snapshot
+            .display_snapshot
+            .buffer_snapshot
+            .offset_to_point(selection_range.start);
+        let row = row + 1;
+        let column = column + 1;
+        let location = Location {
+            buffer: buffer.clone(),
+            range: start..end,
+        };
+
+        // With multiple cursors, the text of every non-empty selection is passed, one per line.
+        let selected_text = editor
+            .selections
+            .all::<usize>(cx)
+            .into_iter()
+            .filter(|selection| !selection.is_empty())
+            .map(|selection| {
+                snapshot
                     .display_snapshot
                     .buffer_snapshot
-                    .offset_to_point(selection_range.start);
-                let row = row + 1;
-                let column = column + 1;
-                let location = Location {
-                    buffer: buffer.clone(),
-                    range: start..end,
-                };
-
-                let current_file = location
-                    .buffer
-                    .read(cx)
-                    .file()
-                    .and_then(|file| file.as_local())
-                    .map(|file| file.abs_path(cx).to_string_lossy().to_string());
-                let worktree_id = location
-                    .buffer
-                    .read(cx)
-                    .file()
-                    .map(|file| WorktreeId::from_usize(file.worktree_id()));
-                let context = buffer
-                    .read(cx)
-                    .language()
-                    .and_then(|language| language.context_provider())
-                    .and_then(|provider| provider.build_context(location, cx).ok());
-
-                let worktree_path = worktree_id.and_then(|worktree_id| {
-                    workspace
-                        .project()
-                        .read(cx)
-                        .worktree_for_id(worktree_id, cx)
-                        .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().to_string())
-                });
+                    .text_for_range(selection.range())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
 
-                let selected_text = buffer.read(cx).chars_for_range(selection_range).collect();
+        let task_variables = TaskVariables::from_iter([
+            ("ZED_ROW".into(), row.to_string()),
+            ("ZED_COLUMN".into(), column.to_string()),
+            ("ZED_SELECTED_TEXT".into(), selected_text),
+        ]);
+        (location, task_variables)
+    });
+    task_variables
+        .0
+        .extend(location_task_variables(workspace, location, cx).0);
+    Some(TaskContext {
+        cwd,
+        task_variables,
+    })
+}
 
-                let mut task_variables = TaskVariables::from_iter([
-                    ("ZED_ROW".into(), row.to_string()),
-                    ("ZED_COLUMN".into(), column.to_string()),
-                    ("ZED_SELECTED_TEXT".into(), selected_text),
-                ]);
-                if let Some(path) = current_file {
-                    task_variables.0.insert("ZED_FILE".into(), path);
-                }
-                if let Some(worktree_path) = worktree_path {
-                    task_variables
-                        .0
-                        .insert("ZED_WORKTREE_ROOT".into(), worktree_path);
-                }
-                if let Some(language_context) = context {
-                    task_variables.0.extend(language_context.0);
-                }
+/// Builds the task variables describing the file of the location given, and the ones
+/// its language provides for the location.
+fn location_task_variables(
+    workspace: &Workspace,
+    location: Location,
+    cx: &mut WindowContext<'_>,
+) -> TaskVariables {
+    let buffer = location.buffer.read(cx);
+    let file = buffer.file();
+    let current_file = file
+        .and_then(|file| file.as_local())
+        .map(|file| file.abs_path(cx));
+    let relative_file = file.map(|file| file.path().to_string_lossy().to_string());
+    let worktree_id = file.map(|file| WorktreeId::from_usize(file.worktree_id()));
+    let context_provider = buffer
+        .language()
+        .and_then(|language| language.context_provider());
+    let context = context_provider.and_then(|provider| provider.build_context(location, cx).ok());
+
+    let worktree_path = worktree_id.and_then(|worktree_id| {
+        workspace
+            .project()
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_string_lossy().to_string())
+    });
+
+    let mut task_variables = TaskVariables::default();
+    if let Some(path) = current_file {
+        if let Some(dirname) = path.parent() {
+            task_variables
+                .0
+                .insert("ZED_DIRNAME".into(), dirname.to_string_lossy().to_string());
+        }
+        if let Some(stem) = path.file_stem() {
+            task_variables
+                .0
+                .insert("ZED_STEM".into(), stem.to_string_lossy().to_string());
+        }
+        task_variables
+            .0
+            .insert("ZED_FILE".into(), path.to_string_lossy().to_string());
+    }
+    if let Some(relative_file) = relative_file {
+        task_variables
+            .0
+            .insert("ZED_RELATIVE_FILE".into(), relative_file);
+    }
+    if let Some(worktree_path) = worktree_path {
+        task_variables
+            .0
+            .insert("ZED_WORKTREE_ROOT".into(), worktree_path);
+    }
+    if let Some(language_context) = context {
+        task_variables.0.extend(language_context.0);
+    }
+    task_variables
+}
+
+/// Asks for the values of the task's inputs that its context has none for yet, then spawns the task.
+fn schedule_task(
+    workspace: &mut Workspace,
+    task: Arc<dyn Task>,
+    task_cx: TaskContext,
+    omit_history: bool,
+    cx: &mut ViewContext<'_, Workspace>,
+) {
+    let missing_inputs = task
+        .inputs()
+        .iter()
+        .filter(|input| {
+            !task_cx
+                .task_variables
+                .0
+                .contains_key(&input.variable_name())
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if missing_inputs.is_empty() {
+        spawn_task(workspace, task.as_ref(), task_cx, omit_history, cx);
+        return;
+    }
 
-                Some(TaskContext {
-                    cwd: cwd.clone(),
-                    task_variables,
+    cx.spawn(|workspace, mut cx| async move {
+        let mut task_cx = task_cx;
+        for input in missing_inputs {
+            let options = match input_options(&input, &task_cx).await {
+                Ok(options) => options,
+                Err(error) => {
+                    workspace.update(&mut cx, |workspace, cx| workspace.show_error(&error, cx))?;
+                    return Ok(());
+                }
+            };
+            let (value_tx, value_rx) = oneshot::channel();
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| {
+                    TaskInputModal::new(input.clone(), options, value_tx, cx)
                 })
-            })
-        })()
-        .unwrap_or_else(|| TaskContext {
-            cwd,
-            task_variables: Default::default(),
+            })?;
+            let Ok(value) = value_rx.await else {
+                // The modal was dismissed, the task is not run then.
+                return Ok(());
+            };
+            task_cx
+                .task_variables
+                .0
+                .insert(input.variable_name(), value);
+        }
+        workspace.update(&mut cx, |workspace, cx| {
+            spawn_task(workspace, task.as_ref(), task_cx, omit_history, cx)
         })
-    } else {
-        TaskContext {
-            cwd,
-            task_variables: Default::default(),
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Lists the values to pick the input's value from; empty for inputs that accept any text.
+async fn input_options(input: &TaskInput, task_cx: &TaskContext) -> anyhow::Result<Vec<String>> {
+    match &input.kind {
+        TaskInputKind::Prompt { .. } => Ok(Vec::new()),
+        TaskInputKind::Pick { options, .. } => Ok(options.clone()),
+        TaskInputKind::Command { command, args } => {
+            let mut command =
+                smol::process::Command::new(task_cx.task_variables.substitute(command));
+            command.args(
+                args.iter()
+                    .map(|arg| task_cx.task_variables.substitute(arg)),
+            );
+            if let Some(cwd) = &task_cx.cwd {
+                command.current_dir(cwd);
+            }
+            let output = command.output().await.with_context(|| {
+                format!("Failed to run the command of task input {:?}", input.id)
+            })?;
+            anyhow::ensure!(
+                output.status.success(),
+                "Command of task input {:?} failed: {}",
+                input.id,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(ToOwned::to_owned)
+                .collect())
         }
     }
 }
 
-fn schedule_task(
-    workspace: &Workspace,
+fn spawn_task(
+    workspace: &mut Workspace,
     task: &dyn Task,
     task_cx: TaskContext,
     omit_history: bool,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
     let spawn_in_terminal = task.exec(task_cx.clone());
-    if let Some(spawn_in_terminal) = spawn_in_terminal {
+    if let Some(mut spawn_in_terminal) = spawn_in_terminal {
+        if !task.depends_on().is_empty() {
+            let worktree = workspace
+                .active_item(cx)
+                .and_then(|item| item.project_path(cx))
+                .map(|path| path.worktree_id);
+            let available_tasks = workspace
+                .project()
+                .update(cx, |project, cx| {
+                    project.task_inventory().update(cx, |inventory, cx| {
+                        inventory.list_tasks(None, worktree, false, cx)
+                    })
+                })
+                .into_iter()
+                .map(|(_, task)| task)
+                .collect::<Vec<_>>();
+            match resolve_dependencies(task, &available_tasks, &task_cx, &mut Vec::new()) {
+                Ok(dependencies) => spawn_in_terminal.dependencies = dependencies,
+                Err(error) => {
+                    workspace.show_error(&error, cx);
+                    return;
+                }
+            }
+        }
         if !omit_history {
             workspace.project().update(cx, |project, cx| {
                 project.task_inventory().update(cx, |inventory, _| {
@@ -211,6 +520,34 @@ fn schedule_task(
     }
 }
 
+/// Finds the tasks the given one depends on by their labels, along with their own dependencies.
+fn resolve_dependencies(
+    task: &dyn Task,
+    available_tasks: &[Arc<dyn Task>],
+    task_cx: &TaskContext,
+    dependents: &mut Vec<String>,
+) -> anyhow::Result<Vec<SpawnInTerminal>> {
+    dependents.push(task.name().to_string());
+    let mut dependencies = Vec::new();
+    for label in task.depends_on() {
+        anyhow::ensure!(
+            !dependents.contains(label),
+            "Task {label:?} depends on itself"
+        );
+        let dependency = available_tasks
+            .iter()
+            .find(|available_task| available_task.name() == label)
+            .with_context(|| format!("Task {:?} depends on unknown task {label:?}", task.name()))?;
+        if let Some(mut spawn_in_terminal) = dependency.exec(task_cx.clone()) {
+            spawn_in_terminal.dependencies =
+                resolve_dependencies(dependency.as_ref(), available_tasks, task_cx, dependents)?;
+            dependencies.push(spawn_in_terminal);
+        }
+    }
+    dependents.pop();
+    Ok(dependencies)
+}
+
 fn task_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result<Option<PathBuf>> {
     let project = workspace.project().read(cx);
     let available_worktrees = project
@@ -226,16 +563,30 @@ fn task_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result<Opt
         0 => None,
         1 => Some(available_worktrees[0].read(cx).abs_path()),
         _ => {
-            let cwd_for_active_entry = project.active_entry().and_then(|entry_id| {
-                available_worktrees.into_iter().find_map(|worktree| {
+            // A terminal reports its shell's working directory, pick the worktree it is in.
+            let terminal_cwd = workspace
+                .active_item_as::<TerminalView>(cx)
+                .and_then(|terminal_view| terminal_view.read(cx).terminal().read(cx).get_cwd());
+            let cwd_for_active_entry = match terminal_cwd {
+                Some(terminal_cwd) => available_worktrees.into_iter().find_map(|worktree| {
                     let worktree = worktree.read(cx);
-                    if worktree.contains_entry(entry_id) {
+                    if terminal_cwd.starts_with(worktree.abs_path()) {
                         Some(worktree.abs_path())
                     } else {
                         None
                     }
-                })
-            });
+                }),
+                None => project.active_entry().and_then(|entry_id| {
+                    available_worktrees.into_iter().find_map(|worktree| {
+                        let worktree = worktree.read(cx);
+                        if worktree.contains_entry(entry_id) {
+                            Some(worktree.abs_path())
+                        } else {
+                            None
+                        }
+                    })
+                }),
+            };
             anyhow::ensure!(
                 cwd_for_active_entry.is_some(),
                 "Cannot determine task cwd for multiple worktrees"
@@ -248,18 +599,175 @@ fn task_cwd(workspace: &Workspace, cx: &mut WindowContext) -> anyhow::Result<Opt
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{any::Any, path::Path, sync::Arc};
 
     use editor::Editor;
-    use gpui::{Entity, TestAppContext};
+    use gpui::{Context, Entity, ModelContext, TestAppContext};
     use language::{Language, LanguageConfig, SymbolContextProvider};
-    use project::{FakeFs, Project, TaskSourceKind};
+    use project::{FakeFs, Inventory, Project, ProjectPath, TaskSourceKind, WorktreeId};
     use serde_json::json;
-    use task::{oneshot_source::OneshotSource, TaskContext, TaskVariables};
+    use task::{
+        input::{TaskInput, TaskInputKind},
+        oneshot_source::OneshotSource,
+        static_source::{tasks_for, Definition, TaskDefinitions},
+        Task, TaskContext, TaskSource, TaskVariables,
+    };
     use ui::VisualContext;
     use workspace::{AppState, Workspace};
 
-    use crate::{task_context, task_cwd};
+    use crate::{resolve_dependencies, task_context, task_cwd, tasks_to_run_on_save};
+
+    struct TestSource(Vec<Arc<dyn Task>>);
+
+    impl TaskSource for TestSource {
+        fn as_any(&mut self) -> &mut dyn Any {
+            self
+        }
+
+        fn tasks_for_path(
+            &mut self,
+            _: Option<&Path>,
+            _: &mut ModelContext<Box<dyn TaskSource>>,
+        ) -> Vec<Arc<dyn Task>> {
+            self.0.clone()
+        }
+    }
+
+    #[gpui::test]
+    fn test_tasks_to_run_on_save(cx: &mut TestAppContext) {
+        let tasks = tasks_for(
+            TaskDefinitions(vec![
+                Definition {
+                    label: "generate".to_string(),
+                    command: "protoc".to_string(),
+                    run_on_save: Some("src/**/*.proto".to_string()),
+                    ..Default::default()
+                },
+                Definition {
+                    label: "deploy".to_string(),
+                    command: "deploy ${input:stage}".to_string(),
+                    run_on_save: Some("**/*.proto".to_string()),
+                    inputs: vec![TaskInput {
+                        id: "stage".to_string(),
+                        description: None,
+                        kind: TaskInputKind::Prompt { default: None },
+                    }],
+                    ..Default::default()
+                },
+                Definition {
+                    label: "build".to_string(),
+                    command: "make".to_string(),
+                    ..Default::default()
+                },
+            ]),
+            "test",
+        );
+        let inventory = cx.update(Inventory::new);
+        inventory.update(cx, |inventory, cx| {
+            inventory.add_source(
+                TaskSourceKind::UserInput,
+                |cx| cx.new_model(|_| Box::new(TestSource(tasks.clone())) as Box<_>),
+                cx,
+            )
+        });
+        let run_on_save = |path: &str, cx: &mut TestAppContext| {
+            let saved_path = ProjectPath {
+                worktree_id: WorktreeId::from_usize(1),
+                path: Arc::from(Path::new(path)),
+            };
+            let mut tasks = inventory.update(cx, |inventory, cx| {
+                tasks_to_run_on_save(inventory, &saved_path, cx)
+                    .into_iter()
+                    .map(|(task, input_values)| (task.name().to_string(), input_values))
+                    .collect::<Vec<_>>()
+            });
+            tasks.sort();
+            tasks
+        };
+
+        // The task with an input has no value for it yet, it is not run rather than prompting on save.
+        assert_eq!(
+            run_on_save("src/api/user.proto", cx),
+            [("generate".to_string(), Vec::new())]
+        );
+        assert!(run_on_save("src/main.rs", cx).is_empty());
+
+        // Once spawned, it reuses the values it was spawned with.
+        inventory.update(cx, |inventory, _| {
+            inventory.task_scheduled(
+                tasks[1].id().clone(),
+                TaskContext {
+                    cwd: None,
+                    task_variables: TaskVariables::from_iter([(
+                        "input:stage".to_string(),
+                        "staging".to_string(),
+                    )]),
+                },
+            )
+        });
+        assert_eq!(
+            run_on_save("src/api/user.proto", cx),
+            [
+                (
+                    "deploy".to_string(),
+                    vec![("input:stage".to_string(), "staging".to_string())]
+                ),
+                ("generate".to_string(), Vec::new()),
+            ]
+        );
+        assert_eq!(
+            run_on_save("proto/user.proto", cx),
+            [(
+                "deploy".to_string(),
+                vec![("input:stage".to_string(), "staging".to_string())]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_resolve_dependencies() {
+        let definition = |label: &str, depends_on: &[&str]| Definition {
+            label: label.to_string(),
+            command: label.to_string(),
+            depends_on: depends_on.iter().map(|label| label.to_string()).collect(),
+            ..Default::default()
+        };
+        let tasks = tasks_for(
+            TaskDefinitions(vec![
+                definition("deploy", &["build", "test"]),
+                definition("build", &[]),
+                definition("test", &["build"]),
+                definition("cycle", &["loop"]),
+                definition("loop", &["cycle"]),
+                definition("broken", &["missing"]),
+            ]),
+            "test",
+        );
+        let resolve = |label: &str| {
+            let task = tasks.iter().find(|task| task.name() == label).unwrap();
+            resolve_dependencies(
+                task.as_ref(),
+                &tasks,
+                &TaskContext::default(),
+                &mut Vec::new(),
+            )
+        };
+
+        let dependencies = resolve("deploy").unwrap();
+        assert_eq!(
+            dependencies
+                .iter()
+                .map(|dependency| dependency.label.as_str())
+                .collect::<Vec<_>>(),
+            ["build", "test"]
+        );
+        assert!(dependencies[0].dependencies.is_empty());
+        assert_eq!(dependencies[1].dependencies.len(), 1);
+        assert_eq!(dependencies[1].dependencies[0].label, "build");
+        assert!(resolve("build").unwrap().is_empty());
+        assert!(resolve("cycle").is_err());
+        assert!(resolve("broken").is_err());
+    }
 
     #[gpui::test]
     async fn test_default_language_context(cx: &mut TestAppContext) {
@@ -364,6 +872,9 @@ mod tests {
                     cwd: Some("/dir".into()),
                     task_variables: TaskVariables::from_iter([
                         ("ZED_FILE".into(), "/dir/rust/b.rs".into()),
+                        ("ZED_DIRNAME".into(), "/dir/rust".into()),
+                        ("ZED_STEM".into(), "b".into()),
+                        ("ZED_RELATIVE_FILE".into(), "rust/b.rs".into()),
                         ("ZED_WORKTREE_ROOT".into(), "/dir".into()),
                         ("ZED_ROW".into(), "1".into()),
                         ("ZED_COLUMN".into(), "1".into()),
@@ -381,6 +892,9 @@ mod tests {
                     cwd: Some("/dir".into()),
                     task_variables: TaskVariables::from_iter([
                         ("ZED_FILE".into(), "/dir/rust/b.rs".into()),
+                        ("ZED_DIRNAME".into(), "/dir/rust".into()),
+                        ("ZED_STEM".into(), "b".into()),
+                        ("ZED_RELATIVE_FILE".into(), "rust/b.rs".into()),
                         ("ZED_WORKTREE_ROOT".into(), "/dir".into()),
                         ("ZED_SYMBOL".into(), "this_is_a_rust_file".into()),
                         ("ZED_ROW".into(), "1".into()),
@@ -390,6 +904,20 @@ mod tests {
                 }
             );
 
+            // With multiple selections, the text of each is passed.
+            editor2.update(cx, |this, cx| {
+                this.change_selections(None, cx, |selections| {
+                    selections.select_ranges([4..7, 14..18])
+                })
+            });
+            assert_eq!(
+                task_context(this, task_cwd(this, cx).unwrap(), cx)
+                    .task_variables
+                    .0
+                    .get("ZED_SELECTED_TEXT"),
+                Some(&"std\nis_i".to_string())
+            );
+
             // Now, let's switch the active item to .ts file.
             this.activate_item(&editor1, cx);
             assert_eq!(
@@ -398,6 +926,9 @@ mod tests {
                     cwd: Some("/dir".into()),
                     task_variables: TaskVariables::from_iter([
                         ("ZED_FILE".into(), "/dir/a.ts".into()),
+                        ("ZED_DIRNAME".into(), "/dir".into()),
+                        ("ZED_STEM".into(), "a".into()),
+                        ("ZED_RELATIVE_FILE".into(), "a.ts".into()),
                         ("ZED_WORKTREE_ROOT".into(), "/dir".into()),
                         ("ZED_SYMBOL".into(), "this_is_a_test".into()),
                         ("ZED_ROW".into(), "1".into()),
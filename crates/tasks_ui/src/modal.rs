@@ -55,12 +55,15 @@ pub(crate) struct TasksModalDelegate {
     workspace: WeakView<Workspace>,
     prompt: String,
     task_context: TaskContext,
+    /// When not empty, only the tasks with one of these tags are listed.
+    tags: Vec<String>,
 }
 
 impl TasksModalDelegate {
     fn new(
         inventory: Model<Inventory>,
         task_context: TaskContext,
+        tags: Vec<String>,
         workspace: WeakView<Workspace>,
     ) -> Self {
         Self {
@@ -71,6 +74,7 @@ impl TasksModalDelegate {
             selected_index: 0,
             prompt: String::default(),
             task_context,
+            tags,
         }
     }
 
@@ -139,12 +143,13 @@ impl TasksModal {
     pub(crate) fn new(
         inventory: Model<Inventory>,
         task_context: TaskContext,
+        tags: Vec<String>,
         workspace: WeakView<Workspace>,
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let picker = cx.new_view(|cx| {
             Picker::uniform_list(
-                TasksModalDelegate::new(inventory, task_context, workspace),
+                TasksModalDelegate::new(inventory, task_context, tags, workspace),
                 cx,
             )
         });
@@ -222,9 +227,16 @@ impl PickerDelegate for TasksModalDelegate {
                                 }
                                 None => (None, None),
                             };
-                        picker.delegate.inventory.update(cx, |inventory, cx| {
+                        let tags = &picker.delegate.tags;
+                        let mut tasks = picker.delegate.inventory.update(cx, |inventory, cx| {
                             inventory.list_tasks(path.as_deref(), worktree, true, cx)
-                        })
+                        });
+                        if !tags.is_empty() {
+                            tasks.retain(|(_, task)| {
+                                task.tags().iter().any(|tag| tags.contains(tag))
+                            });
+                        }
+                        tasks
                     });
 
                     candidates
@@ -286,7 +298,7 @@ impl PickerDelegate for TasksModalDelegate {
             .update(cx, |workspace, cx| {
                 schedule_task(
                     workspace,
-                    task.as_ref(),
+                    task,
                     self.task_context.clone(),
                     omit_history_entry,
                     cx,
@@ -312,6 +324,7 @@ impl PickerDelegate for TasksModalDelegate {
         let details = match source_kind {
             TaskSourceKind::UserInput => "user input".to_string(),
             TaskSourceKind::Buffer => "language extension".to_string(),
+            TaskSourceKind::Extensions => "extension".to_string(),
             TaskSourceKind::Worktree { abs_path, .. } | TaskSourceKind::AbsPath(abs_path) => {
                 abs_path.compact().to_string_lossy().to_string()
             }
@@ -386,7 +399,7 @@ impl PickerDelegate for TasksModalDelegate {
             .update(cx, |workspace, cx| {
                 schedule_task(
                     workspace,
-                    task.as_ref(),
+                    task,
                     self.task_context.clone(),
                     omit_history_entry,
                     cx,
@@ -0,0 +1,1039 @@
+//! Inline images, printed by programs like `viu`, `timg` or matplotlib backends with the Sixel,
+//! iTerm2 (OSC 1337) or kitty graphics sequences.
+//!
+//! Alacritty ignores these sequences, so they are cut out of the bytes read from the pty,
+//! and replaced with a block of placeholder cells, printed at the cursor's column by the pty's reader,
+//! which tell the image and the row of it they show with their foreground and background colors. Alacritty scrolls, clears and overwrites these cells
+//! as any other ones, and the terminal element paints the images over them.
+//! Only the sizes of the images are read on the pty's thread, they are decoded in the background.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use std::{
+    collections::VecDeque,
+    io::{Cursor, Write as _},
+    sync::Arc,
+};
+
+use alacritty_terminal::{
+    event::WindowSize,
+    term::cell::Cell,
+    vte::ansi::{Color, Rgb},
+};
+use collections::HashMap;
+use gpui::ImageData;
+use image::{
+    imageops::FilterType, io::Reader, DynamicImage, GenericImageView, RgbImage, Rgba, RgbaImage,
+};
+
+/// The character of the cells an image is shown in.
+pub const IMAGE_PLACEHOLDER: char = '\u{10EEEE}';
+
+/// Image sequences longer than that are dropped, rather than buffered.
+const MAX_IMAGE_PAYLOAD_LEN: usize = 64 * 1024 * 1024;
+/// Larger images are scaled down, to fit into the sprite atlas they are painted from.
+/// Sixel images are cut to that size, as they are drawn pixel by pixel.
+const MAX_IMAGE_SIDE: u32 = 2048;
+/// Larger images are dropped rather than decoded, which would take 4 bytes per pixel.
+const MAX_DECODED_IMAGE_PIXELS: u64 = 4 * MAX_IMAGE_SIDE as u64 * MAX_IMAGE_SIDE as u64;
+/// Image ids are told by the 24 bits of a color.
+const MAX_IMAGE_ID: u32 = 0xFF_FFFF;
+/// How much image memory a line of the scrollback history allows for.
+const IMAGE_BYTES_PER_HISTORY_LINE: usize = 4 * 1024;
+const MIN_IMAGES_SIZE: usize = 16 * 1024 * 1024;
+const MAX_IMAGES_SIZE: usize = 256 * 1024 * 1024;
+const ITERM_FILE_PREFIX: &[u8] = b"1337;File=";
+
+/// An image printed to the terminal, shown in a block of cells.
+#[derive(Clone, Debug)]
+pub struct InlineImage {
+    pub id: u32,
+    pub data: Arc<ImageData>,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl InlineImage {
+    fn byte_len(&self) -> usize {
+        self.data.as_bytes().len()
+    }
+}
+
+/// An image cut out of the pty's output, whose cells are known, but which is yet to be decoded.
+pub struct PendingImage {
+    id: u32,
+    source: ImageSource,
+    /// The size to scale the image to.
+    width: u32,
+    height: u32,
+    columns: usize,
+    rows: usize,
+}
+
+enum ImageSource {
+    /// An image file, as PNG.
+    Encoded(Vec<u8>),
+    Rgba(RgbaImage),
+    Rgb(RgbImage),
+    /// The data of a Sixel sequence, which sets pixels of an image of the given size.
+    Sixel {
+        data: Vec<u8>,
+        width: u32,
+        height: u32,
+    },
+}
+
+/// The block of cells an image is shown in.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Placeholder {
+    id: u32,
+    columns: usize,
+    rows: usize,
+}
+
+impl PendingImage {
+    pub(crate) fn placeholder(&self) -> Placeholder {
+        Placeholder {
+            id: self.id,
+            columns: self.columns,
+            rows: self.rows,
+        }
+    }
+
+    /// Decodes the image, which takes long for large ones.
+    pub fn decode(self) -> Option<InlineImage> {
+        let image = match self.source {
+            ImageSource::Encoded(bytes) => image::load_from_memory(&bytes).ok()?,
+            ImageSource::Rgba(image) => DynamicImage::ImageRgba8(image),
+            ImageSource::Rgb(image) => DynamicImage::ImageRgb8(image),
+            ImageSource::Sixel {
+                data,
+                width,
+                height,
+            } => decode_sixel(&data, width, height),
+        };
+        let image = if image.dimensions() == (self.width, self.height) {
+            image
+        } else {
+            image.resize_exact(self.width, self.height, FilterType::Triangle)
+        };
+        Some(InlineImage {
+            id: self.id,
+            data: Arc::new(ImageData::new(image.into_bgra8())),
+            columns: self.columns,
+            rows: self.rows,
+        })
+    }
+}
+
+/// The id of the image a cell shows, and the row of the image it is in.
+pub fn image_cell(cell: &Cell) -> Option<(u32, usize)> {
+    if cell.c != IMAGE_PLACEHOLDER {
+        return None;
+    }
+    match (cell.fg, cell.bg) {
+        (Color::Spec(id), Color::Spec(row)) => Some((rgb_to_u32(id), rgb_to_u32(row) as usize)),
+        _ => None,
+    }
+}
+
+fn rgb_to_u32(rgb: Rgb) -> u32 {
+    (rgb.r as u32) << 16 | (rgb.g as u32) << 8 | rgb.b as u32
+}
+
+/// The images of a terminal, the oldest of which are dropped when they take more memory than
+/// its scrollback history allows for.
+pub(crate) struct InlineImages {
+    images: HashMap<u32, InlineImage>,
+    order: VecDeque<u32>,
+    size: usize,
+    max_size: usize,
+}
+
+impl InlineImages {
+    pub(crate) fn new(scroll_history_lines: usize) -> Self {
+        Self {
+            images: HashMap::default(),
+            order: VecDeque::new(),
+            size: 0,
+            max_size: scroll_history_lines
+                .saturating_mul(IMAGE_BYTES_PER_HISTORY_LINE)
+                .clamp(MIN_IMAGES_SIZE, MAX_IMAGES_SIZE),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, image: InlineImage) {
+        if let Some(replaced) = self.images.remove(&image.id) {
+            self.size -= replaced.byte_len();
+            self.order.retain(|id| *id != image.id);
+        }
+        self.size += image.byte_len();
+        self.order.push_back(image.id);
+        self.images.insert(image.id, image);
+        while self.size > self.max_size && self.order.len() > 1 {
+            if let Some(evicted) = self
+                .order
+                .pop_front()
+                .and_then(|id| self.images.remove(&id))
+            {
+                self.size -= evicted.byte_len();
+            }
+        }
+    }
+
+    pub(crate) fn get(&self, id: u32) -> Option<&InlineImage> {
+        self.images.get(&id)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.images.clear();
+        self.order.clear();
+        self.size = 0;
+    }
+}
+
+/// A state machine, cutting the image sequences out of the bytes read from the pty, even if split between reads,
+/// and replacing them with the placeholder cells of the decoded images.
+pub(crate) struct ImageFilter {
+    state: FilterState,
+    /// The bytes of a sequence, kept until it is known whether it is an image one.
+    held: Vec<u8>,
+    payload: Vec<u8>,
+    payload_overflow: bool,
+    /// The kitty image being transmitted in chunks.
+    kitty_transmission: Option<KittyTransmission>,
+    window_size: WindowSize,
+    next_id: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FilterState {
+    Ground,
+    Escape,
+    /// After `ESC P`, reading the parameters, which are followed by a `q` in the Sixel sequences.
+    DcsParams,
+    /// After `ESC _`, the kitty sequences continue with a `G`.
+    ApcStart,
+    /// After `ESC ]`, matching the iTerm2 file sequence's prefix.
+    OscPrefix,
+    Image(ImageProtocol),
+    ImageEscape(ImageProtocol),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ImageProtocol {
+    Sixel,
+    Kitty,
+    ITerm,
+}
+
+impl ImageFilter {
+    pub(crate) fn new(window_size: WindowSize) -> Self {
+        Self {
+            state: FilterState::Ground,
+            held: Vec::new(),
+            payload: Vec::new(),
+            payload_overflow: false,
+            kitty_transmission: None,
+            window_size,
+            next_id: 1,
+        }
+    }
+
+    pub(crate) fn set_window_size(&mut self, window_size: WindowSize) {
+        self.window_size = window_size;
+    }
+
+    /// Copies the bytes into the output, but for the image sequences, reporting their images instead,
+    /// along with the length of the output they were cut out at.
+    pub(crate) fn filter(
+        &mut self,
+        bytes: &[u8],
+        output: &mut Vec<u8>,
+        mut on_image: impl FnMut(usize, PendingImage),
+    ) {
+        for &byte in bytes {
+            self.state = match self.state {
+                FilterState::Ground => {
+                    if byte == 0x1b {
+                        self.held.clear();
+                        self.held.push(byte);
+                        FilterState::Escape
+                    } else {
+                        output.push(byte);
+                        FilterState::Ground
+                    }
+                }
+                FilterState::Escape => self.after_escape(byte, output),
+                FilterState::DcsParams => match byte {
+                    b'0'..=b'9' | b';' if self.held.len() < 32 => {
+                        self.held.push(byte);
+                        FilterState::DcsParams
+                    }
+                    b'q' => self.start_image(ImageProtocol::Sixel),
+                    _ => self.pass_through(byte, output),
+                },
+                FilterState::ApcStart => match byte {
+                    b'G' => self.start_image(ImageProtocol::Kitty),
+                    _ => self.pass_through(byte, output),
+                },
+                FilterState::OscPrefix => {
+                    let matched = self.held.len() - 2;
+                    if ITERM_FILE_PREFIX.get(matched) == Some(&byte) {
+                        self.held.push(byte);
+                        if matched + 1 == ITERM_FILE_PREFIX.len() {
+                            self.start_image(ImageProtocol::ITerm)
+                        } else {
+                            FilterState::OscPrefix
+                        }
+                    } else {
+                        self.pass_through(byte, output)
+                    }
+                }
+                FilterState::Image(protocol) => match byte {
+                    0x1b => FilterState::ImageEscape(protocol),
+                    0x07 if protocol == ImageProtocol::ITerm => {
+                        self.finish_image(protocol, output, &mut on_image);
+                        FilterState::Ground
+                    }
+                    _ => {
+                        self.push_payload(byte);
+                        FilterState::Image(protocol)
+                    }
+                },
+                FilterState::ImageEscape(protocol) => {
+                    if byte == b'\\' {
+                        self.finish_image(protocol, output, &mut on_image);
+                        FilterState::Ground
+                    } else {
+                        // An unterminated sequence, which is dropped.
+                        self.payload.clear();
+                        self.held.clear();
+                        self.held.push(0x1b);
+                        self.after_escape(byte, output)
+                    }
+                }
+            };
+        }
+    }
+
+    /// Gives up on the held bytes being an image sequence, and copies them to the output, with the byte after them.
+    fn pass_through(&mut self, byte: u8, output: &mut Vec<u8>) -> FilterState {
+        if byte == 0x1b {
+            output.extend_from_slice(&self.held);
+            self.held.clear();
+            self.held.push(byte);
+            FilterState::Escape
+        } else {
+            output.extend_from_slice(&self.held);
+            output.push(byte);
+            self.held.clear();
+            FilterState::Ground
+        }
+    }
+
+    /// Handles the byte after a held `ESC`, which may start an image sequence.
+    fn after_escape(&mut self, byte: u8, output: &mut Vec<u8>) -> FilterState {
+        let state = match byte {
+            b'P' => FilterState::DcsParams,
+            b'_' => FilterState::ApcStart,
+            b']' => FilterState::OscPrefix,
+            _ => return self.pass_through(byte, output),
+        };
+        self.held.push(byte);
+        state
+    }
+
+    fn start_image(&mut self, protocol: ImageProtocol) -> FilterState {
+        self.payload.clear();
+        self.payload_overflow = false;
+        self.held.clear();
+        FilterState::Image(protocol)
+    }
+
+    fn push_payload(&mut self, byte: u8) {
+        if self.payload.len() < MAX_IMAGE_PAYLOAD_LEN {
+            self.payload.push(byte);
+        } else {
+            self.payload_overflow = true;
+        }
+    }
+
+    fn finish_image(
+        &mut self,
+        protocol: ImageProtocol,
+        output: &mut Vec<u8>,
+        on_image: &mut impl FnMut(usize, PendingImage),
+    ) {
+        let payload = std::mem::take(&mut self.payload);
+        if self.payload_overflow {
+            self.kitty_transmission = None;
+            return;
+        }
+        let image = match protocol {
+            ImageProtocol::Sixel => sixel_image(payload),
+            ImageProtocol::ITerm => iterm_image(&payload),
+            ImageProtocol::Kitty => self.kitty_image(&payload),
+        };
+        let Some(((source, width, height), columns, rows)) = image else {
+            return;
+        };
+        let image = self.pending_image(source, width, height, columns, rows);
+        on_image(output.len(), image);
+    }
+
+    fn pending_image(
+        &mut self,
+        source: ImageSource,
+        width: u32,
+        height: u32,
+        columns: Option<usize>,
+        rows: Option<usize>,
+    ) -> PendingImage {
+        let (width, height) = fit_to_max_side(width.max(1), height.max(1));
+        let cell_width = self.window_size.cell_width.max(1) as f32;
+        let cell_height = self.window_size.cell_height.max(1) as f32;
+        let max_columns = (self.window_size.num_cols as usize).max(1);
+        // The sizes in cells are the program's, and may be arbitrarily large.
+        let max_rows = (self.window_size.num_lines as usize).max(1);
+
+        let image_width = width as f32;
+        let image_height = height as f32;
+        // Images take the cells their pixels cover, unless sized in cells, keeping their aspect ratio.
+        let (mut columns, mut rows) = match (columns, rows) {
+            (Some(columns), Some(rows)) => (columns, rows),
+            (Some(columns), None) => {
+                let scale = columns as f32 * cell_width / image_width;
+                (
+                    columns,
+                    (image_height * scale / cell_height).ceil() as usize,
+                )
+            }
+            (None, Some(rows)) => {
+                let scale = rows as f32 * cell_height / image_height;
+                ((image_width * scale / cell_width).ceil() as usize, rows)
+            }
+            (None, None) => (
+                (image_width / cell_width).ceil() as usize,
+                (image_height / cell_height).ceil() as usize,
+            ),
+        };
+        if columns > max_columns {
+            rows = (rows * max_columns).div_ceil(columns);
+            columns = max_columns;
+        }
+        if rows > max_rows {
+            columns = (columns * max_rows).div_ceil(rows);
+            rows = max_rows;
+        }
+
+        let id = self.next_id;
+        self.next_id = if id >= MAX_IMAGE_ID { 1 } else { id + 1 };
+        PendingImage {
+            id,
+            source,
+            width,
+            height,
+            columns: columns.max(1),
+            rows: rows.max(1),
+        }
+    }
+
+    /// Handles a chunk of a kitty image transmission, returning the image when it was the last one.
+    fn kitty_image(
+        &mut self,
+        payload: &[u8],
+    ) -> Option<((ImageSource, u32, u32), Option<usize>, Option<usize>)> {
+        let (control, data) = match payload.iter().position(|byte| *byte == b';') {
+            Some(ix) => (&payload[..ix], &payload[ix + 1..]),
+            None => (payload, &payload[payload.len()..]),
+        };
+        let control = std::str::from_utf8(control).ok()?;
+        let keys = control
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .collect::<Vec<_>>();
+        let key = |name: &str| {
+            keys.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+        let more = key("m") == Some("1");
+
+        let mut transmission = match self.kitty_transmission.take() {
+            Some(transmission) => transmission,
+            None => KittyTransmission {
+                action: key("a").unwrap_or("t").to_string(),
+                format: key("f").and_then(|value| value.parse().ok()).unwrap_or(32),
+                medium: key("t").unwrap_or("d").to_string(),
+                compressed: key("o").is_some(),
+                width: key("s").and_then(|value| value.parse().ok()).unwrap_or(0),
+                height: key("v").and_then(|value| value.parse().ok()).unwrap_or(0),
+                columns: key("c").and_then(|value| value.parse().ok()),
+                rows: key("r").and_then(|value| value.parse().ok()),
+                data: Vec::new(),
+            },
+        };
+        transmission
+            .data
+            .extend(data.iter().filter(|byte| !byte.is_ascii_whitespace()));
+        if more {
+            self.kitty_transmission = Some(transmission);
+            return None;
+        }
+
+        // Only the images transmitted in the sequences and displayed right away are supported.
+        if transmission.action != "T" || transmission.medium != "d" || transmission.compressed {
+            return None;
+        }
+        let bytes = base64::decode(&transmission.data).ok()?;
+        let (width, height) = (transmission.width, transmission.height);
+        let image = match transmission.format {
+            100 => encoded_image(bytes)?,
+            32 => (
+                ImageSource::Rgba(RgbaImage::from_raw(width, height, bytes)?),
+                width,
+                height,
+            ),
+            24 => (
+                ImageSource::Rgb(RgbImage::from_raw(width, height, bytes)?),
+                width,
+                height,
+            ),
+            _ => return None,
+        };
+        Some((image, transmission.columns, transmission.rows))
+    }
+}
+
+struct KittyTransmission {
+    action: String,
+    format: u32,
+    medium: String,
+    compressed: bool,
+    width: u32,
+    height: u32,
+    columns: Option<usize>,
+    rows: Option<usize>,
+    /// The base64 data of the chunks received so far.
+    data: Vec<u8>,
+}
+
+impl Placeholder {
+    /// Prints the block of cells showing the image, starting at the cursor, and leaving the cursor after its last row.
+    /// Rows start at the given column, and are followed by the given colors, which the cursor had before.
+    pub(crate) fn write(
+        &self,
+        start_column: usize,
+        foreground: Color,
+        background: Color,
+        output: &mut Vec<u8>,
+    ) {
+        let id = self.id;
+        for row in 0..self.rows {
+            if row > 0 {
+                // Line feeds keep the column, and scroll the screen at its bottom,
+                // but the column is past the row after its last cell, so move back to where it started.
+                write!(output, "\n\x1b[{}G", start_column + 1).ok();
+            }
+            write!(
+                output,
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
+                (id >> 16) & 0xff,
+                (id >> 8) & 0xff,
+                id & 0xff,
+                (row >> 16) & 0xff,
+                (row >> 8) & 0xff,
+                row & 0xff,
+            )
+            .ok();
+            let mut placeholder = [0; 4];
+            let placeholder = IMAGE_PLACEHOLDER.encode_utf8(&mut placeholder).as_bytes();
+            for _ in 0..self.columns {
+                output.extend_from_slice(placeholder);
+            }
+            // Set the colors back before the line feed, which clears the lines it scrolls in with them.
+            output.extend_from_slice(b"\x1b[");
+            write_sgr_color(foreground, false, output);
+            output.push(b';');
+            write_sgr_color(background, true, output);
+            output.push(b'm');
+        }
+    }
+}
+
+/// Writes the SGR parameters setting the color, as a foreground or a background one.
+fn write_sgr_color(color: Color, background: bool, output: &mut Vec<u8>) {
+    let offset = if background { 10 } else { 0 };
+    match color {
+        Color::Spec(rgb) => write!(output, "{};2;{};{};{}", 38 + offset, rgb.r, rgb.g, rgb.b),
+        Color::Indexed(index) => write!(output, "{};5;{index}", 38 + offset),
+        Color::Named(named) => match named as usize {
+            index @ 0..=7 => write!(output, "{}", 30 + offset + index),
+            index @ 8..=15 => write!(output, "{}", 90 + offset + index - 8),
+            _ => write!(output, "{}", 39 + offset),
+        },
+    }
+    .ok();
+}
+
+/// Reads the size of an image file, without decoding it, unless it is too large to.
+fn encoded_image(bytes: Vec<u8>) -> Option<(ImageSource, u32, u32)> {
+    let (width, height) = Reader::new(Cursor::new(&bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()?;
+    (width as u64 * height as u64 <= MAX_DECODED_IMAGE_PIXELS).then_some((
+        ImageSource::Encoded(bytes),
+        width,
+        height,
+    ))
+}
+
+/// Scales the size down to [`MAX_IMAGE_SIDE`], as [`DynamicImage::resize`] does.
+fn fit_to_max_side(width: u32, height: u32) -> (u32, u32) {
+    if width <= MAX_IMAGE_SIDE && height <= MAX_IMAGE_SIDE {
+        return (width, height);
+    }
+    let max_side = MAX_IMAGE_SIDE as u64;
+    if height <= width {
+        let height = height as u64 * max_side / width as u64;
+        (MAX_IMAGE_SIDE, height.max(1) as u32)
+    } else {
+        let width = width as u64 * max_side / height as u64;
+        (width.max(1) as u32, MAX_IMAGE_SIDE)
+    }
+}
+
+/// Reads the payload of an iTerm2 `File=` sequence, returning the image, with the size to scale it to,
+/// and the columns and rows it should take, if given in cells.
+fn iterm_image(payload: &[u8]) -> Option<((ImageSource, u32, u32), Option<usize>, Option<usize>)> {
+    let separator = payload.iter().position(|byte| *byte == b':')?;
+    let arguments = std::str::from_utf8(&payload[..separator]).ok()?;
+    let arguments = arguments
+        .split(';')
+        .filter_map(|argument| argument.split_once('='))
+        .collect::<Vec<_>>();
+    let argument = |name: &str| {
+        arguments
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    };
+    // Files not marked as inline ones are downloads.
+    if argument("inline") != Some("1") {
+        return None;
+    }
+
+    let data = payload[separator + 1..]
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+    let (source, width, height) = encoded_image(base64::decode(data).ok()?)?;
+    let cells = |value: Option<&str>| -> Option<usize> { value?.parse().ok() };
+    let pixels = |value: Option<&str>| -> Option<u64> { value?.strip_suffix("px")?.parse().ok() };
+    let (width, height) = match (pixels(argument("width")), pixels(argument("height"))) {
+        (None, None) => (width, height),
+        (target_width, target_height) => {
+            let (width, height) = (width as u64, height as u64);
+            let target_width = target_width
+                .unwrap_or_else(|| width * target_height.unwrap_or(height) / height.max(1));
+            let target_height =
+                target_height.unwrap_or_else(|| height * target_width / width.max(1));
+            // Scaled to fit into the maximum side, keeping the aspect ratio the sizes ask for.
+            let scale = (MAX_IMAGE_SIDE as f64 / target_width.max(target_height) as f64).min(1.);
+            (
+                (target_width as f64 * scale) as u32,
+                (target_height as f64 * scale) as u32,
+            )
+        }
+    };
+    Some((
+        (source, width, height),
+        cells(argument("width")),
+        cells(argument("height")),
+    ))
+}
+
+/// Reads the size of a Sixel image, from the data of its sequence, after its parameters.
+fn sixel_image(data: Vec<u8>) -> Option<((ImageSource, u32, u32), Option<usize>, Option<usize>)> {
+    let mut width = 0;
+    let mut height = 0;
+    walk_sixel(&data, |x, y, _| {
+        width = width.max(x + 1);
+        height = height.max(y + 1);
+    });
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let (width, height) = (width as u32, height as u32);
+    let source = ImageSource::Sixel {
+        data,
+        width,
+        height,
+    };
+    Some(((source, width, height), None, None))
+}
+
+/// Draws a Sixel image of the given size, cropped to the pixels it sets. The pixels it does not set are transparent.
+fn decode_sixel(data: &[u8], width: u32, height: u32) -> DynamicImage {
+    let mut image = RgbaImage::new(width, height);
+    walk_sixel(data, |x, y, color| {
+        let (x, y) = (x as u32, y as u32);
+        if x < width && y < height {
+            image.put_pixel(x, y, Rgba(color));
+        }
+    });
+    DynamicImage::ImageRgba8(image)
+}
+
+/// Calls `set_pixel` for every pixel the Sixel data sets, dropping the ones beyond [`MAX_IMAGE_SIDE`].
+fn walk_sixel(data: &[u8], mut set_pixel: impl FnMut(usize, usize, [u8; 4])) {
+    let max_side = MAX_IMAGE_SIDE as usize;
+    let mut palette = default_sixel_palette();
+    let mut color = 0;
+    let mut x = 0;
+    let mut y = 0;
+    let mut repeat = 1;
+    let mut ix = 0;
+    while ix < data.len() {
+        let byte = data[ix];
+        ix += 1;
+        match byte {
+            // The raster attributes, whose size only pads the image with transparent pixels.
+            b'"' => ix = parse_sixel_numbers(data, ix).1,
+            b'#' => {
+                let (params, next_ix) = parse_sixel_numbers(data, ix);
+                ix = next_ix;
+                match params[..] {
+                    [index] => color = index as usize % palette.len(),
+                    [index, space, a, b, c] => {
+                        color = index as usize % palette.len();
+                        palette[color] = match space {
+                            1 => hls_to_rgba(a, b, c),
+                            _ => percent_rgba(a, b, c),
+                        };
+                    }
+                    _ => {}
+                }
+            }
+            b'!' => {
+                let (params, next_ix) = parse_sixel_numbers(data, ix);
+                ix = next_ix;
+                repeat = params.first().copied().unwrap_or(1).max(1) as usize;
+                continue;
+            }
+            b'$' => x = 0,
+            b'-' => {
+                x = 0;
+                y += 6;
+                if y >= max_side {
+                    break;
+                }
+            }
+            b'?'..=b'~' => {
+                let bits = byte - b'?';
+                for _ in 0..repeat.min(max_side.saturating_sub(x)) {
+                    for bit in 0..6 {
+                        if bits & (1 << bit) != 0 && y + bit < max_side {
+                            set_pixel(x, y + bit, palette[color]);
+                        }
+                    }
+                    x += 1;
+                }
+            }
+            _ => {}
+        }
+        repeat = 1;
+    }
+}
+
+fn parse_sixel_numbers(payload: &[u8], mut ix: usize) -> (Vec<u32>, usize) {
+    let mut numbers = Vec::new();
+    let mut current = None::<u32>;
+    while let Some(&byte) = payload.get(ix) {
+        match byte {
+            b'0'..=b'9' => {
+                let digit = (byte - b'0') as u32;
+                current = Some(
+                    current
+                        .unwrap_or(0)
+                        .saturating_mul(10)
+                        .saturating_add(digit),
+                );
+            }
+            b';' => numbers.push(current.take().unwrap_or(0)),
+            _ => break,
+        }
+        ix += 1;
+    }
+    if let Some(current) = current {
+        numbers.push(current);
+    }
+    (numbers, ix)
+}
+
+/// The colors of the VT340, which Sixel images use unless they define their own.
+fn default_sixel_palette() -> Vec<[u8; 4]> {
+    let mut palette = [
+        (0, 0, 0),
+        (20, 20, 80),
+        (80, 13, 13),
+        (20, 80, 20),
+        (80, 20, 80),
+        (20, 80, 80),
+        (80, 80, 20),
+        (53, 53, 53),
+        (26, 26, 26),
+        (33, 33, 60),
+        (60, 26, 26),
+        (33, 60, 33),
+        (60, 33, 60),
+        (33, 60, 60),
+        (60, 60, 33),
+        (80, 80, 80),
+    ]
+    .into_iter()
+    .map(|(r, g, b)| percent_rgba(r, g, b))
+    .collect::<Vec<_>>();
+    palette.resize(256, [0, 0, 0, 255]);
+    palette
+}
+
+fn percent_rgba(r: u32, g: u32, b: u32) -> [u8; 4] {
+    let channel = |percent: u32| (percent.min(100) * 255 / 100) as u8;
+    [channel(r), channel(g), channel(b), 255]
+}
+
+/// Converts a Sixel HLS color, whose hue starts from blue rather than red, to RGBA.
+fn hls_to_rgba(hue: u32, lightness: u32, saturation: u32) -> [u8; 4] {
+    let hue = ((hue + 240) % 360) as f32;
+    let lightness = lightness.min(100) as f32 / 100.;
+    let saturation = saturation.min(100) as f32 / 100.;
+    let chroma = (1. - (2. * lightness - 1.).abs()) * saturation;
+    let x = chroma * (1. - ((hue / 60.) % 2. - 1.).abs());
+    let (r, g, b) = match hue as u32 / 60 {
+        0 => (chroma, x, 0.),
+        1 => (x, chroma, 0.),
+        2 => (0., chroma, x),
+        3 => (0., x, chroma),
+        4 => (x, 0., chroma),
+        _ => (chroma, 0., x),
+    };
+    let m = lightness - chroma / 2.;
+    let channel = |value: f32| ((value + m) * 255.).round().clamp(0., 255.) as u8;
+    [channel(r), channel(g), channel(b), 255]
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::vte::ansi::NamedColor;
+
+    use super::*;
+
+    fn window_size() -> WindowSize {
+        WindowSize {
+            num_lines: 24,
+            num_cols: 80,
+            cell_width: 10,
+            cell_height: 20,
+        }
+    }
+
+    fn filter(chunks: &[&[u8]]) -> (Vec<u8>, Vec<InlineImage>) {
+        let mut filter = ImageFilter::new(window_size());
+        let mut output = Vec::new();
+        let mut images = Vec::new();
+        for chunk in chunks {
+            let mut placeholders = Vec::new();
+            let start = output.len();
+            filter.filter(chunk, &mut output, |offset, image| {
+                placeholders.push((offset, image.placeholder()));
+                images.extend(image.decode());
+            });
+            // Print the placeholders where the images were cut out, as the pty's reader does.
+            let filtered = output.split_off(start);
+            let mut copied = 0;
+            for (offset, placeholder) in placeholders {
+                let offset = offset - start;
+                output.extend_from_slice(&filtered[copied..offset]);
+                copied = offset;
+                placeholder.write(
+                    0,
+                    Color::Named(NamedColor::Foreground),
+                    Color::Named(NamedColor::Background),
+                    &mut output,
+                );
+            }
+            output.extend_from_slice(&filtered[copied..]);
+        }
+        (output, images)
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            width,
+            height,
+            image::Rgba([255, 0, 0, 255]),
+        ));
+        let mut bytes = Vec::new();
+        image
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    fn placeholder_cells(output: &[u8]) -> usize {
+        String::from_utf8_lossy(output)
+            .chars()
+            .filter(|c| *c == IMAGE_PLACEHOLDER)
+            .count()
+    }
+
+    /// The checksum of a PNG chunk.
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for byte in bytes {
+            crc ^= *byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0xEDB8_8320
+                } else {
+                    crc >> 1
+                };
+            }
+        }
+        !crc
+    }
+
+    #[test]
+    fn test_other_sequences_pass_through() {
+        let bytes: &[u8] =
+            b"a\x1b[1mb\x1bP$qm\x1b\\\x1b]0;title\x07\x1b_Xapc\x1b\\\x1b]1337;SetMark\x07c";
+        let (output, images) = filter(&[&bytes[..5], &bytes[5..12], &bytes[12..]]);
+        assert_eq!(output, bytes);
+        assert!(images.is_empty());
+    }
+
+    #[test]
+    fn test_iterm_images() {
+        let sequence = format!(
+            "\x1b]1337;File=name=cmVk;inline=1:{}\x07",
+            base64::encode(png(25, 30))
+        );
+        let bytes = format!("before{sequence}after");
+        let (output, images) = filter(&[&bytes.as_bytes()[..20], &bytes.as_bytes()[20..]]);
+        assert_eq!(images.len(), 1);
+        assert_eq!((images[0].columns, images[0].rows), (3, 2));
+        assert_eq!(placeholder_cells(&output), 6);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("before\x1b[38;2;0;0;1m\x1b[48;2;0;0;0m"));
+        assert!(output.contains("\x1b[39;49m\n\x1b[1G\x1b[38;2;0;0;1m\x1b[48;2;0;0;1m"));
+        assert!(output.ends_with("\x1b[39;49mafter"));
+
+        // Downloads are not shown.
+        let download = format!("\x1b]1337;File=name=cmVk:{}\x07", base64::encode(png(1, 1)));
+        let (output, images) = filter(&[download.as_bytes()]);
+        assert!(output.is_empty());
+        assert!(images.is_empty());
+    }
+
+    #[test]
+    fn test_kitty_images() {
+        let pixels = base64::encode([0, 0, 255, 255].repeat(4));
+        let (head, tail) = pixels.split_at(8);
+        let chunks = format!("\x1b_Ga=T,f=32,s=2,v=2,c=4,m=1;{head}\x1b\\\x1b_Gm=0;{tail}\x1b\\");
+        let (output, images) = filter(&[chunks.as_bytes()]);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].data.as_bytes().len(), 2 * 2 * 4);
+        assert_eq!((images[0].columns, images[0].rows), (4, 2));
+        assert_eq!(placeholder_cells(&output), 8);
+
+        // Sized beyond the screen, whose height limits the rows.
+        let tall = format!("\x1b_Ga=T,f=32,s=2,v=2,c=4,r=1000;{pixels}\x1b\\");
+        let (_, images) = filter(&[tall.as_bytes()]);
+        assert_eq!((images[0].columns, images[0].rows), (1, 24));
+
+        // Only transmitted, to be displayed later.
+        let transmission = format!("\x1b_Ga=t,f=100;{}\x1b\\", base64::encode(png(1, 1)));
+        assert!(filter(&[transmission.as_bytes()]).1.is_empty());
+    }
+
+    #[test]
+    fn test_sixel_images() {
+        // A red column of 12 pixels, and a blue one of 6 pixels next to its bottom half.
+        let (output, images) =
+            filter(&[b"\x1bP0;1q\"1;1;2;12#1;2;100;0;0#1~-~$#2;2;0;0;100?~\x1b\\"]);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].data.as_bytes().len(), 2 * 12 * 4);
+        assert_eq!((images[0].columns, images[0].rows), (1, 1));
+        assert_eq!(placeholder_cells(&output), 1);
+    }
+
+    #[test]
+    fn test_images_are_evicted() {
+        let image = |id| InlineImage {
+            id,
+            data: Arc::new(ImageData::new(
+                DynamicImage::ImageRgba8(RgbaImage::new(1024, 1024)).into_bgra8(),
+            )),
+            columns: 1,
+            rows: 1,
+        };
+        // 4MB images, and 16MB allowed for them.
+        let mut images = InlineImages::new(0);
+        for id in 1..=5 {
+            images.insert(image(id));
+        }
+        assert!(images.get(1).is_none());
+        assert!((2..=5).all(|id| images.get(id).is_some()));
+    }
+
+    #[test]
+    fn test_oversized_images() {
+        // Repeats far beyond the maximum width, and bands far below the maximum height, are cut.
+        let sixel = format!(
+            "\x1bP0;1q\"1;1;99999;99999#1~!4000000000~{}~\x1b\\",
+            "-".repeat(1000)
+        );
+        let (output, images) = filter(&[sixel.as_bytes()]);
+        assert_eq!(images.len(), 1);
+        let size = MAX_IMAGE_SIDE as usize;
+        assert_eq!(images[0].data.as_bytes().len(), size * 6 * 4);
+        assert_eq!(
+            placeholder_cells(&output),
+            images[0].columns * images[0].rows
+        );
+
+        // Too large to decode, according to the header of the file.
+        let mut header = png(1, 1);
+        header[16..24].copy_from_slice(&[0, 1, 0, 0, 0, 1, 0, 0]);
+        let crc = crc32(&header[12..29]);
+        header[29..33].copy_from_slice(&crc.to_be_bytes());
+        assert_eq!(
+            Reader::new(Cursor::new(&header))
+                .with_guessed_format()
+                .unwrap()
+                .into_dimensions()
+                .unwrap(),
+            (0x10000, 0x10000)
+        );
+        let sequence = format!("\x1b]1337;File=inline=1:{}\x07", base64::encode(header));
+        let (output, images) = filter(&[sequence.as_bytes()]);
+        assert!(output.is_empty());
+        assert!(images.is_empty());
+
+        // Scaled down to the maximum side, keeping the aspect ratio of the sizes given.
+        let sequence = format!(
+            "\x1b]1337;File=inline=1;height=8192px:{}\x07",
+            base64::encode(png(1, 8))
+        );
+        let (_, images) = filter(&[sequence.as_bytes()]);
+        assert_eq!(images.len(), 1);
+        assert_eq!(
+            images[0].data.as_bytes().len(),
+            256 * MAX_IMAGE_SIDE as usize * 4
+        );
+    }
+}
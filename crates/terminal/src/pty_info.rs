@@ -24,6 +24,14 @@ impl ProcessIdGetter {
         }
     }
 
+    /// For a process whose pty is not ours, only the process itself can be tracked.
+    fn for_process(pid: u32) -> ProcessIdGetter {
+        ProcessIdGetter {
+            handle: -1,
+            fallback_pid: pid,
+        }
+    }
+
     fn pid(&self) -> Option<Pid> {
         let pid = unsafe { libc::tcgetpgrp(self.handle) };
         if pid < 0 {
@@ -81,6 +89,16 @@ pub struct PtyProcessInfo {
 
 impl PtyProcessInfo {
     pub fn new(pty: &Pty) -> PtyProcessInfo {
+        Self::with_pid_getter(ProcessIdGetter::new(pty))
+    }
+
+    /// Tracks the process running in a pty owned by another process, e.g. a detached session's shell.
+    #[cfg(unix)]
+    pub fn for_process(pid: u32) -> PtyProcessInfo {
+        Self::with_pid_getter(ProcessIdGetter::for_process(pid))
+    }
+
+    fn with_pid_getter(pid_getter: ProcessIdGetter) -> PtyProcessInfo {
         let process_refresh_kind = ProcessRefreshKind::new()
             .with_cmd(UpdateKind::Always)
             .with_cwd(UpdateKind::Always)
@@ -91,7 +109,7 @@ impl PtyProcessInfo {
         PtyProcessInfo {
             system,
             refresh_kind: process_refresh_kind,
-            pid_getter: ProcessIdGetter::new(pty),
+            pid_getter,
             current: None,
         }
     }
@@ -0,0 +1,587 @@
+//! Terminal sessions kept alive in a detached process, so that shells and the jobs they run
+//! (e.g. dev servers) survive Zed quitting or reloading.
+//!
+//! The daemon is Zed's own executable, started with [`SESSION_DAEMON_ENV_VAR`] set to the session to run:
+//! it spawns the shell in a pty and serves it over a unix socket, replaying the latest output to every client
+//! that attaches. Clients send frames to the daemon: a tag byte, the payload length as a little-endian `u32`
+//! and the payload. The daemon answers an attach frame with the shell's pid, then streams the raw pty output.
+//!
+//! A session no client attached to for [`DETACHED_IDLE_TIMEOUT`] is ended, as the workspace that
+//! would reopen it is likely gone.
+
+use std::{
+    collections::VecDeque,
+    fs,
+    io::{self, Read, Write},
+    os::{
+        fd::AsRawFd,
+        unix::{
+            fs::PermissionsExt,
+            net::{UnixListener, UnixStream},
+            process::CommandExt,
+        },
+    },
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{self, ChildEvent, EventedPty, EventedReadWrite, Pty},
+};
+use anyhow::{anyhow, Context as _};
+use collections::HashMap;
+use polling::{Event as PollingEvent, PollMode, Poller};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{pty_options, terminal_settings::Shell};
+
+/// Set in the environment of a Zed process started as a session daemon, to the JSON of its [`SessionSpec`].
+pub const SESSION_DAEMON_ENV_VAR: &str = "ZED_TERMINAL_SESSION_DAEMON";
+
+/// How much of the latest output to replay to clients attaching to the session.
+const REPLAY_BUFFER_LEN: usize = 256 * 1024;
+const MAX_FRAME_LEN: usize = 1024 * 1024;
+const DAEMON_STARTUP_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a session is kept running without any client attached to it.
+const DETACHED_IDLE_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+const FRAME_ATTACH: u8 = 0;
+const FRAME_INPUT: u8 = 1;
+const FRAME_RESIZE: u8 = 2;
+const FRAME_TERMINATE: u8 = 3;
+
+/// The keys alacritty's event loop expects the pty's events under, see its `PTY_READ_WRITE_TOKEN`
+/// and `PTY_CHILD_EVENT_TOKEN`.
+const READ_WRITE_KEY: usize = 0;
+const CHILD_EVENT_KEY: usize = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SessionSpec {
+    socket_path: PathBuf,
+    shell: Shell,
+    working_directory: Option<PathBuf>,
+    env: HashMap<String, String>,
+}
+
+fn socket_path(session_id: &str) -> PathBuf {
+    util::paths::SUPPORT_DIR
+        .join("terminal_sessions")
+        .join(format!("{session_id}.sock"))
+}
+
+/// Runs the session daemon instead of Zed, if this process was started as one.
+/// Returns whether it did, once the session's shell exited.
+pub fn run_if_requested() -> bool {
+    let Some(spec) = std::env::var_os(SESSION_DAEMON_ENV_VAR) else {
+        return false;
+    };
+    std::env::remove_var(SESSION_DAEMON_ENV_VAR);
+    if let Some(spec) = spec
+        .to_str()
+        .and_then(|spec| serde_json::from_str::<SessionSpec>(spec).ok())
+    {
+        run_daemon(spec).ok();
+    }
+    true
+}
+
+struct SessionState {
+    replay: VecDeque<u8>,
+    /// The attached client, along with the id it was given.
+    client: Option<(usize, UnixStream)>,
+    next_client_id: usize,
+    /// When the last client detached, or the daemon started if none ever attached.
+    detached_since: Option<Instant>,
+}
+
+fn run_daemon(spec: SessionSpec) -> anyhow::Result<()> {
+    let listener = UnixListener::bind(&spec.socket_path)?;
+    tty::setup_env();
+    let options = pty_options(spec.shell, spec.working_directory, spec.env);
+    let pty = tty::new(
+        &options,
+        WindowSize {
+            num_lines: 24,
+            num_cols: 80,
+            cell_width: 1,
+            cell_height: 1,
+        },
+        0,
+    )?;
+    // The output is read on its own thread, and the input written from the clients' threads.
+    let mut output = pty.file().try_clone()?;
+    set_blocking(output.as_raw_fd())?;
+    let shell_pid = pty.child().id();
+    let pty = Arc::new(Mutex::new(pty));
+    let state = Arc::new(Mutex::new(SessionState {
+        replay: VecDeque::new(),
+        client: None,
+        next_client_id: 0,
+        detached_since: Some(Instant::now()),
+    }));
+
+    thread::spawn({
+        let state = state.clone();
+        move || loop {
+            thread::sleep(IDLE_CHECK_INTERVAL);
+            let Ok(state) = state.lock() else {
+                break;
+            };
+            if state.detached_since.map_or(false, |detached_since| {
+                detached_since.elapsed() > DETACHED_IDLE_TIMEOUT
+            }) {
+                // The output thread ends the daemon once the shell exits.
+                unsafe { libc::kill(shell_pid as i32, libc::SIGHUP) };
+                break;
+            }
+        }
+    });
+
+    thread::spawn({
+        let state = state.clone();
+        move || {
+            for stream in listener.incoming().flatten() {
+                let pty = pty.clone();
+                let state = state.clone();
+                thread::spawn(move || serve_client(stream, shell_pid, &pty, &state).ok());
+            }
+        }
+    });
+
+    let mut buffer = [0; 4096];
+    loop {
+        let read = match output.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            // The shell exited.
+            Err(_) => break,
+        };
+        let mut state = state
+            .lock()
+            .map_err(|_| anyhow!("session state poisoned"))?;
+        state.replay.extend(&buffer[..read]);
+        let excess = state.replay.len().saturating_sub(REPLAY_BUFFER_LEN);
+        state.replay.drain(..excess);
+        if let Some((_, client)) = state.client.as_mut() {
+            if client.write_all(&buffer[..read]).is_err() {
+                state.client = None;
+                state.detached_since = Some(Instant::now());
+            }
+        }
+    }
+    fs::remove_file(&spec.socket_path).ok();
+    Ok(())
+}
+
+fn serve_client(
+    mut stream: UnixStream,
+    shell_pid: u32,
+    pty: &Mutex<Pty>,
+    state: &Mutex<SessionState>,
+) -> anyhow::Result<()> {
+    let poisoned = || anyhow!("session poisoned");
+    let client_id = match read_frame(&mut stream)? {
+        (FRAME_ATTACH, _) => {
+            let mut state = state.lock().map_err(|_| poisoned())?;
+            stream.write_all(&shell_pid.to_le_bytes())?;
+            let (front, back) = state.replay.as_slices();
+            stream.write_all(front)?;
+            stream.write_all(back)?;
+            // Only the latest client gets the output, the previous one was left behind by a crash.
+            let client_id = state.next_client_id;
+            state.next_client_id += 1;
+            state.client = Some((client_id, stream.try_clone()?));
+            state.detached_since = None;
+            client_id
+        }
+        (FRAME_TERMINATE, _) => {
+            unsafe { libc::kill(shell_pid as i32, libc::SIGHUP) };
+            return Ok(());
+        }
+        _ => return Ok(()),
+    };
+
+    let result = serve_attached_client(&mut stream, shell_pid, pty);
+    let mut state = state.lock().map_err(|_| poisoned())?;
+    if state
+        .client
+        .as_ref()
+        .map_or(false, |(id, _)| *id == client_id)
+    {
+        state.client = None;
+        state.detached_since = Some(Instant::now());
+    }
+    result
+}
+
+fn serve_attached_client(
+    stream: &mut UnixStream,
+    shell_pid: u32,
+    pty: &Mutex<Pty>,
+) -> anyhow::Result<()> {
+    let poisoned = || anyhow!("session poisoned");
+    loop {
+        let (tag, payload) = read_frame(stream)?;
+        match tag {
+            FRAME_INPUT => {
+                let pty = pty.lock().map_err(|_| poisoned())?;
+                let mut file = pty.file();
+                file.write_all(&payload)?;
+            }
+            FRAME_RESIZE => {
+                if let Some(window_size) = decode_window_size(&payload) {
+                    pty.lock().map_err(|_| poisoned())?.on_resize(window_size);
+                }
+            }
+            FRAME_TERMINATE => {
+                unsafe { libc::kill(shell_pid as i32, libc::SIGHUP) };
+            }
+            _ => {}
+        }
+    }
+}
+
+fn read_frame(stream: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0; 5];
+    stream.read_exact(&mut header)?;
+    let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too long"));
+    }
+    let mut payload = vec![0; len];
+    stream.read_exact(&mut payload)?;
+    Ok((header[0], payload))
+}
+
+fn write_frame(stream: &mut impl Write, tag: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 5);
+    frame.push(tag);
+    frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame)
+}
+
+fn encode_window_size(window_size: WindowSize) -> Vec<u8> {
+    [
+        window_size.num_lines,
+        window_size.num_cols,
+        window_size.cell_width,
+        window_size.cell_height,
+    ]
+    .iter()
+    .flat_map(|value| value.to_le_bytes())
+    .collect()
+}
+
+fn decode_window_size(payload: &[u8]) -> Option<WindowSize> {
+    let value = |ix: usize| {
+        Some(u16::from_le_bytes(
+            payload.get(ix * 2..ix * 2 + 2)?.try_into().ok()?,
+        ))
+    };
+    Some(WindowSize {
+        num_lines: value(0)?,
+        num_cols: value(1)?,
+        cell_width: value(2)?,
+        cell_height: value(3)?,
+    })
+}
+
+/// Alacritty makes the pty non-blocking, for its event loop.
+fn set_blocking(fd: i32) -> io::Result<()> {
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Stops the shell of a detached session, when its terminal is closed.
+pub(crate) fn terminate(session_id: &str) -> io::Result<()> {
+    let mut stream = UnixStream::connect(socket_path(session_id))?;
+    write_frame(&mut stream, FRAME_TERMINATE, &[])
+}
+
+/// A connection to a session daemon, standing in for the pty of the terminal.
+pub(crate) struct SessionClient {
+    stream: UnixStream,
+    reader: SessionReader,
+    writer: SessionWriter,
+    exited_rx: UnixStream,
+    shell_pid: u32,
+    started_daemon: bool,
+}
+
+impl SessionClient {
+    /// Attaches to the session, starting its daemon with the shell given if it is not running.
+    pub(crate) fn attach(
+        session_id: &str,
+        shell: &Shell,
+        working_directory: Option<PathBuf>,
+        env: HashMap<String, String>,
+    ) -> anyhow::Result<Self> {
+        let socket_path = socket_path(session_id);
+        let (mut stream, started_daemon) = match UnixStream::connect(&socket_path) {
+            Ok(stream) => (stream, false),
+            Err(_) => {
+                start_daemon(SessionSpec {
+                    socket_path: socket_path.clone(),
+                    shell: shell.clone(),
+                    working_directory,
+                    env,
+                })?;
+                (connect_to_started_daemon(&socket_path)?, true)
+            }
+        };
+        write_frame(&mut stream, FRAME_ATTACH, &[])?;
+        let mut shell_pid = [0; 4];
+        stream
+            .read_exact(&mut shell_pid)
+            .context("reading the session's shell pid")?;
+
+        let (exited_tx, exited_rx) = UnixStream::pair()?;
+        exited_rx.set_nonblocking(true)?;
+        Ok(Self {
+            reader: SessionReader {
+                stream: stream.try_clone()?,
+                exited_tx: Some(exited_tx),
+            },
+            writer: SessionWriter {
+                stream: stream.try_clone()?,
+            },
+            stream,
+            exited_rx,
+            shell_pid: u32::from_le_bytes(shell_pid),
+            started_daemon,
+        })
+    }
+
+    /// Another handle on the session's output.
+    pub(crate) fn reader_handle(&self) -> io::Result<SessionReader> {
+        self.reader.try_clone()
+    }
+
+    pub(crate) fn shell_pid(&self) -> u32 {
+        self.shell_pid
+    }
+
+    /// Whether the session was started by this client, rather than running from a previous Zed session.
+    pub(crate) fn started_daemon(&self) -> bool {
+        self.started_daemon
+    }
+}
+
+fn start_daemon(spec: SessionSpec) -> anyhow::Result<()> {
+    let sessions_dir = spec
+        .socket_path
+        .parent()
+        .context("invalid session socket path")?;
+    fs::create_dir_all(sessions_dir)?;
+    fs::set_permissions(sessions_dir, fs::Permissions::from_mode(0o700))?;
+    // A daemon that crashed leaves its socket behind.
+    fs::remove_file(&spec.socket_path).ok();
+
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .env(SESSION_DAEMON_ENV_VAR, serde_json::to_string(&spec)?)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Detach from Zed's session, so that the daemon outlives it.
+    unsafe {
+        command.pre_exec(|| {
+            libc::setsid();
+            Ok(())
+        });
+    }
+    let mut child = command.spawn().context("starting the session daemon")?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
+
+fn connect_to_started_daemon(socket_path: &Path) -> anyhow::Result<UnixStream> {
+    let started_at = Instant::now();
+    loop {
+        match UnixStream::connect(socket_path) {
+            Ok(stream) => return Ok(stream),
+            Err(error) if started_at.elapsed() > DAEMON_STARTUP_TIMEOUT => {
+                return Err(error).context("connecting to the session daemon")
+            }
+            Err(_) => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+}
+
+/// Reads the session's output without blocking, and reports the end of the session as the exit of its shell.
+pub(crate) struct SessionReader {
+    stream: UnixStream,
+    exited_tx: Option<UnixStream>,
+}
+
+impl SessionReader {
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            stream: self.stream.try_clone()?,
+            exited_tx: self
+                .exited_tx
+                .as_ref()
+                .map(|exited_tx| exited_tx.try_clone())
+                .transpose()?,
+        })
+    }
+}
+
+impl Read for SessionReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // The stream stays blocking for the writes, so only the reads are made non-blocking.
+        let read = unsafe {
+            libc::recv(
+                self.stream.as_raw_fd(),
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+                libc::MSG_DONTWAIT,
+            )
+        };
+        if read < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if read == 0 && !buf.is_empty() {
+            if let Some(mut exited_tx) = self.exited_tx.take() {
+                exited_tx.write_all(&[0]).ok();
+            }
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        Ok(read as usize)
+    }
+}
+
+/// Sends the terminal's input to the session, framed.
+pub(crate) struct SessionWriter {
+    stream: UnixStream,
+}
+
+impl Write for SessionWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let buf = &buf[..buf.len().min(MAX_FRAME_LEN)];
+        write_frame(&mut self.stream, FRAME_INPUT, buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+impl EventedReadWrite for SessionClient {
+    type Reader = SessionReader;
+    type Writer = SessionWriter;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        mut interest: PollingEvent,
+        poll_opts: PollMode,
+    ) -> io::Result<()> {
+        interest.key = READ_WRITE_KEY;
+        poll.add_with_mode(&self.stream, interest, poll_opts)?;
+        poll.add_with_mode(
+            &self.exited_rx,
+            PollingEvent::readable(CHILD_EVENT_KEY),
+            PollMode::Level,
+        )
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        mut interest: PollingEvent,
+        poll_opts: PollMode,
+    ) -> io::Result<()> {
+        interest.key = READ_WRITE_KEY;
+        poll.modify_with_mode(&self.stream, interest, poll_opts)?;
+        poll.modify_with_mode(
+            &self.exited_rx,
+            PollingEvent::readable(CHILD_EVENT_KEY),
+            PollMode::Level,
+        )
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        poll.delete(&self.stream)?;
+        poll.delete(&self.exited_rx)
+    }
+
+    fn reader(&mut self) -> &mut Self::Reader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut Self::Writer {
+        &mut self.writer
+    }
+}
+
+impl EventedPty for SessionClient {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        let mut byte = [0];
+        match self.exited_rx.read(&mut byte) {
+            Ok(1) => Some(ChildEvent::Exited(None)),
+            _ => None,
+        }
+    }
+}
+
+impl OnResize for SessionClient {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        write_frame(
+            &mut self.writer.stream,
+            FRAME_RESIZE,
+            &encode_window_size(window_size),
+        )
+        .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_round_trip() {
+        let window_size = WindowSize {
+            num_lines: 40,
+            num_cols: 120,
+            cell_width: 9,
+            cell_height: 18,
+        };
+        let mut stream = Vec::new();
+        write_frame(&mut stream, FRAME_INPUT, b"ls\r").unwrap();
+        write_frame(&mut stream, FRAME_RESIZE, &encode_window_size(window_size)).unwrap();
+
+        let mut stream = stream.as_slice();
+        assert_eq!(
+            read_frame(&mut stream).unwrap(),
+            (FRAME_INPUT, b"ls\r".to_vec())
+        );
+        let (tag, payload) = read_frame(&mut stream).unwrap();
+        assert_eq!(tag, FRAME_RESIZE);
+        let decoded = decode_window_size(&payload).unwrap();
+        assert_eq!(
+            (
+                decoded.num_lines,
+                decoded.num_cols,
+                decoded.cell_width,
+                decoded.cell_height
+            ),
+            (40, 120, 9, 18)
+        );
+        assert!(read_frame(&mut stream).is_err());
+    }
+}
@@ -0,0 +1,691 @@
+//! Shell integration: prompt and command boundaries, reported by shells with
+//! OSC 133 (FinalTerm) or OSC 633 (VS Code) sequences, and the working directory,
+//! reported with OSC 7.
+//!
+//! Alacritty ignores these sequences, so the bytes read from the pty are scanned for them,
+//! and parsed into the terminal by the reader itself: the line of each sequence is taken from
+//! the cursor right when the terminal parsed the bytes before it, under the terminal's lock.
+//! The inline image sequences are replaced there too, see [`crate::inline_images`].
+//! Only unix ptys are scanned so far.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+};
+
+use alacritty_terminal::{grid::Grid, index::Line, term::cell::Cell};
+
+/// Payloads longer than that are not shell integration sequences, and are not worth buffering.
+const MAX_OSC_PAYLOAD_LEN: usize = 4096;
+/// How many lines alacritty keeps in its scrollback history above the terminal's own limit.
+/// Once its history is full, alacritty drops the oldest lines without telling how many,
+/// so the history is trimmed, counting the lines dropped, before it gets there.
+pub(crate) const SCROLLBACK_SLACK_LINES: usize = 1_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellIntegrationEvent {
+    /// The shell is about to print the prompt.
+    PromptStart,
+    /// The prompt was printed, the user types the command now.
+    CommandStart,
+    /// The command was submitted, its output follows.
+    OutputStart,
+    /// The command finished, with the exit code given if the shell reported it.
+    CommandFinished { exit_code: Option<i32> },
+    /// The command line submitted, as reported by shells integrated with the OSC 633 sequences.
+    CommandLine(String),
+    /// The current working directory of the shell.
+    WorkingDirectory(PathBuf),
+}
+
+/// A command run in the shell, with the lines of its parts, numbered by [`ScrollbackLines`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShellCommand {
+    pub prompt_line: i32,
+    /// Where the user started typing the command, after the prompt.
+    pub input_line: Option<i32>,
+    pub output_line: Option<i32>,
+    /// Where the next prompt started, after the command finished.
+    pub end_line: Option<i32>,
+    pub command_line: Option<String>,
+    pub exit_code: Option<i32>,
+    pub finished: bool,
+}
+
+impl ShellCommand {
+    /// Lines of the command's output, if it finished.
+    pub fn output_lines(&self) -> Option<std::ops::Range<i32>> {
+        let end_line = self.end_line.filter(|_| self.finished)?;
+        let start_line = self
+            .output_line
+            .or(self.input_line.map(|line| line + 1))
+            .unwrap_or(self.prompt_line + 1);
+        Some(start_line..end_line.max(start_line))
+    }
+}
+
+/// Numbers the lines of the terminal from the first one ever printed, so that the lines stored for
+/// the shell's commands stay where they are while the scrollback history is full and scrolls.
+///
+/// Clones share the count of lines dropped, so that the pty reader numbers the lines the same way.
+/// It is only read and updated with the terminal locked, along with the grid it counts the lines of.
+#[derive(Clone)]
+pub(crate) struct ScrollbackLines {
+    max_history_lines: usize,
+    /// How many lines were dropped from the top of the scrollback history.
+    scrolled_out: Arc<AtomicI32>,
+}
+
+impl ScrollbackLines {
+    /// Alacritty has to be given [`SCROLLBACK_SLACK_LINES`] more scrollback history than `max_history_lines`.
+    pub(crate) fn new(max_history_lines: usize) -> Self {
+        Self {
+            max_history_lines,
+            scrolled_out: Arc::default(),
+        }
+    }
+
+    /// Trims the scrollback history to its size, counting the lines dropped from it.
+    /// Returns false if alacritty could have dropped lines itself since the last call, so that the count is off.
+    pub(crate) fn trim_history(&mut self, grid: &mut Grid<Cell>) -> bool {
+        let history_size = grid.history_size();
+        if history_size <= self.max_history_lines {
+            return true;
+        }
+        grid.update_history(self.max_history_lines);
+        grid.update_history(self.max_history_lines + SCROLLBACK_SLACK_LINES);
+        self.scrolled_out.fetch_add(
+            (history_size - self.max_history_lines) as i32,
+            Ordering::Relaxed,
+        );
+        history_size < self.max_history_lines + SCROLLBACK_SLACK_LINES
+    }
+
+    /// The number of the first line still in the scrollback history.
+    pub(crate) fn first_line_number(&self) -> i32 {
+        self.scrolled_out.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn line_number(&self, grid: &Grid<Cell>, line: Line) -> i32 {
+        self.first_line_number() + grid.history_size() as i32 + line.0
+    }
+
+    /// The grid line with the given number, above the topmost line of the grid if it was dropped.
+    pub(crate) fn grid_line(&self, grid: &Grid<Cell>, line_number: i32) -> Line {
+        Line(line_number - self.first_line_number() - grid.history_size() as i32)
+    }
+}
+
+/// A state machine, finding OSC sequences in the bytes read from the pty, even if split between reads.
+#[derive(Default)]
+pub(crate) struct OscScanner {
+    state: ScannerState,
+    payload: Vec<u8>,
+}
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ScannerState {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+impl OscScanner {
+    /// Calls `on_event` with each event found, along with the offset in `bytes` right after its sequence.
+    pub(crate) fn scan(
+        &mut self,
+        bytes: &[u8],
+        mut on_event: impl FnMut(usize, ShellIntegrationEvent),
+    ) {
+        for (ix, &byte) in bytes.iter().enumerate() {
+            self.state = match (self.state, byte) {
+                (ScannerState::Ground, 0x1b) => ScannerState::Escape,
+                (ScannerState::Ground, _) => ScannerState::Ground,
+                (ScannerState::Escape | ScannerState::OscEscape, b']') => {
+                    self.payload.clear();
+                    ScannerState::Osc
+                }
+                (ScannerState::Escape | ScannerState::OscEscape, 0x1b) => ScannerState::Escape,
+                (ScannerState::OscEscape, b'\\') | (ScannerState::Osc, 0x07) => {
+                    if let Some(event) = parse_osc(&self.payload) {
+                        on_event(ix + 1, event);
+                    }
+                    ScannerState::Ground
+                }
+                (ScannerState::Escape | ScannerState::OscEscape, _) => ScannerState::Ground,
+                (ScannerState::Osc, 0x1b) => ScannerState::OscEscape,
+                (ScannerState::Osc, _) => {
+                    if self.payload.len() < MAX_OSC_PAYLOAD_LEN {
+                        self.payload.push(byte);
+                    }
+                    ScannerState::Osc
+                }
+            };
+        }
+    }
+}
+
+/// Parses the payload of an OSC sequence, between `ESC ]` and its terminator.
+fn parse_osc(payload: &[u8]) -> Option<ShellIntegrationEvent> {
+    let payload = std::str::from_utf8(payload).ok()?;
+    let (command, rest) = payload.split_once(';').unwrap_or((payload, ""));
+    match command {
+        "7" => parse_file_url(rest).map(ShellIntegrationEvent::WorkingDirectory),
+        "133" | "633" => {
+            let mut params = rest.split(';');
+            match params.next()? {
+                "A" => Some(ShellIntegrationEvent::PromptStart),
+                "B" => Some(ShellIntegrationEvent::CommandStart),
+                "C" => Some(ShellIntegrationEvent::OutputStart),
+                "D" => Some(ShellIntegrationEvent::CommandFinished {
+                    exit_code: params.next().and_then(|code| code.parse().ok()),
+                }),
+                "E" if command == "633" => Some(ShellIntegrationEvent::CommandLine(
+                    unescape_vscode_value(params.next()?),
+                )),
+                "P" if command == "633" => {
+                    let cwd = params.find_map(|property| property.strip_prefix("Cwd="))?;
+                    Some(ShellIntegrationEvent::WorkingDirectory(PathBuf::from(
+                        unescape_vscode_value(cwd),
+                    )))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Parses `file://hostname/path` URLs, with the path percent-encoded.
+fn parse_file_url(url: &str) -> Option<PathBuf> {
+    let host_and_path = url.strip_prefix("file://")?;
+    let path = &host_and_path[host_and_path.find('/')?..];
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let high = hex_digit(bytes.next()?)?;
+            let low = hex_digit(bytes.next()?)?;
+            decoded.push(high << 4 | low);
+        } else {
+            decoded.push(byte);
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(decoded).ok()?))
+}
+
+/// VS Code escapes backslashes as `\\` and other special characters as `\xAB` in the values it reports.
+fn unescape_vscode_value(value: &str) -> String {
+    let mut unescaped = Vec::with_capacity(value.len());
+    let bytes = value.as_bytes();
+    let mut ix = 0;
+    while ix < bytes.len() {
+        match (bytes[ix], bytes.get(ix + 1)) {
+            (b'\\', Some(b'\\')) => {
+                unescaped.push(b'\\');
+                ix += 2;
+            }
+            (b'\\', Some(b'x')) => {
+                let code = bytes
+                    .get(ix + 2..ix + 4)
+                    .and_then(|digits| Some(hex_digit(digits[0])? << 4 | hex_digit(digits[1])?));
+                match code {
+                    Some(code) => {
+                        unescaped.push(code);
+                        ix += 4;
+                    }
+                    None => {
+                        unescaped.push(b'\\');
+                        ix += 1;
+                    }
+                }
+            }
+            (byte, _) => {
+                unescaped.push(byte);
+                ix += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+#[cfg(unix)]
+pub(crate) use unix::ShellIntegrationPty;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        fs::File,
+        io::{self, Read},
+        sync::Arc,
+    };
+
+    use alacritty_terminal::{
+        event::{OnResize, WindowSize},
+        sync::FairMutex,
+        tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+        vte::ansi::Processor,
+        Term,
+    };
+    use futures::channel::mpsc::UnboundedSender;
+    use polling::{Event as PollingEvent, PollMode, Poller};
+
+    use crate::{
+        inline_images::{ImageFilter, PendingImage, Placeholder},
+        session_daemon::{SessionClient, SessionReader},
+        TerminalSize, ZedListener,
+    };
+
+    use super::*;
+
+    /// A pty whose output can be read from another handle too.
+    pub(crate) trait ScannedPty: EventedPty + OnResize {
+        type Output: io::Read + Send;
+
+        fn output(&self) -> io::Result<Self::Output>;
+    }
+
+    impl ScannedPty for Pty {
+        type Output = File;
+
+        fn output(&self) -> io::Result<File> {
+            self.file().try_clone()
+        }
+    }
+
+    impl ScannedPty for SessionClient {
+        type Output = SessionReader;
+
+        fn output(&self) -> io::Result<SessionReader> {
+            self.reader_handle()
+        }
+    }
+
+    /// How many bytes are read from the pty at once, before filtering them.
+    const READ_BUFFER_LEN: usize = 0x10000;
+
+    /// A pty, whose output is scanned for the shell integration sequences, stripped of the inline images,
+    /// and parsed into the terminal given.
+    ///
+    /// Alacritty's event loop still does the pty's I/O, but it is only handed bytes it ignores, in place
+    /// of the output parsed already: it has to be given a terminal of its own, as it holds its terminal
+    /// locked while reading.
+    pub(crate) struct ShellIntegrationPty<P: ScannedPty> {
+        pty: P,
+        reader: ScanningReader<P::Output>,
+    }
+
+    impl<P: ScannedPty> ShellIntegrationPty<P> {
+        pub(crate) fn new(
+            pty: P,
+            term: Arc<FairMutex<Term<ZedListener>>>,
+            scrollback_lines: ScrollbackLines,
+            events_tx: UnboundedSender<(ShellIntegrationEvent, i32)>,
+            images_tx: UnboundedSender<PendingImage>,
+        ) -> io::Result<Self> {
+            let reader =
+                ScanningReader::new(pty.output()?, term, scrollback_lines, events_tx, images_tx);
+            Ok(Self { pty, reader })
+        }
+    }
+
+    pub(crate) struct ScanningReader<R> {
+        output: R,
+        read_buffer: Vec<u8>,
+        filtered: Vec<u8>,
+        /// The images cut out of the filtered bytes, with the offsets to print their cells at.
+        placeholders: Vec<(usize, Placeholder)>,
+        image_filter: ImageFilter,
+        scanner: OscScanner,
+        term: Arc<FairMutex<Term<ZedListener>>>,
+        parser: Processor,
+        scrollback_lines: ScrollbackLines,
+        /// The events, along with the line of the cursor right after their sequence was parsed.
+        events_tx: UnboundedSender<(ShellIntegrationEvent, i32)>,
+        images_tx: UnboundedSender<PendingImage>,
+    }
+
+    impl<R: io::Read> ScanningReader<R> {
+        pub(crate) fn new(
+            output: R,
+            term: Arc<FairMutex<Term<ZedListener>>>,
+            scrollback_lines: ScrollbackLines,
+            events_tx: UnboundedSender<(ShellIntegrationEvent, i32)>,
+            images_tx: UnboundedSender<PendingImage>,
+        ) -> Self {
+            Self {
+                output,
+                read_buffer: vec![0; READ_BUFFER_LEN],
+                filtered: Vec::new(),
+                placeholders: Vec::new(),
+                image_filter: ImageFilter::new(TerminalSize::default().into()),
+                scanner: OscScanner::default(),
+                term,
+                parser: Processor::new(),
+                scrollback_lines,
+                events_tx,
+                images_tx,
+            }
+        }
+
+        /// Parses the filtered bytes into the terminal, noting the cursor's line after each shell integration sequence,
+        /// and printing the cells of the images where they were cut out.
+        fn parse_filtered(&mut self) {
+            let Self {
+                filtered,
+                placeholders,
+                scanner,
+                term,
+                parser,
+                scrollback_lines,
+                events_tx,
+                ..
+            } = self;
+            let filtered = filtered.as_slice();
+            let mut placeholders = placeholders.drain(..).peekable();
+            let mut term = term.lock();
+            let mut parsed = 0;
+            // Images cut out right after a sequence are printed after its line is taken, unless it is the last parse.
+            let mut parse_until = |term: &mut Term<ZedListener>, end: usize, last: bool| {
+                while let Some((offset, placeholder)) =
+                    placeholders.next_if(|(offset, _)| *offset < end || last)
+                {
+                    for &byte in &filtered[parsed..offset] {
+                        parser.advance(term, byte);
+                    }
+                    parsed = offset;
+                    let mut cells = Vec::new();
+                    let cursor = &term.grid().cursor;
+                    // A cursor past the last column wraps to the first one when printing.
+                    let start_column = if cursor.input_needs_wrap {
+                        0
+                    } else {
+                        cursor.point.column.0
+                    };
+                    placeholder.write(
+                        start_column,
+                        cursor.template.fg,
+                        cursor.template.bg,
+                        &mut cells,
+                    );
+                    for &byte in &cells {
+                        parser.advance(term, byte);
+                    }
+                }
+                for &byte in &filtered[parsed..end] {
+                    parser.advance(term, byte);
+                }
+                parsed = end;
+            };
+            scanner.scan(filtered, |end, event| {
+                parse_until(&mut term, end, false);
+                let grid = term.grid();
+                let line = scrollback_lines.line_number(grid, grid.cursor.point.line);
+                events_tx.unbounded_send((event, line)).ok();
+            });
+            parse_until(&mut term, filtered.len(), true);
+        }
+    }
+
+    impl<R: io::Read> io::Read for ScanningReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            // Image sequences are dropped whole, so reads may filter down to nothing,
+            // which must not be mistaken for the end of the output.
+            loop {
+                let read = match self.output.read(&mut self.read_buffer) {
+                    Ok(read) => read,
+                    Err(error) => {
+                        // The event loop ends synchronized updates after a timeout, but it never sees them here:
+                        // end them once the shell has nothing more to print for now instead.
+                        if error.kind() == io::ErrorKind::WouldBlock
+                            && self.parser.sync_bytes_count() > 0
+                        {
+                            self.parser.stop_sync(&mut *self.term.lock());
+                        }
+                        return Err(error);
+                    }
+                };
+                if read == 0 {
+                    return Ok(0);
+                }
+
+                self.filtered.clear();
+                let images_tx = &self.images_tx;
+                let placeholders = &mut self.placeholders;
+                self.image_filter.filter(
+                    &self.read_buffer[..read],
+                    &mut self.filtered,
+                    |offset, image| {
+                        placeholders.push((offset, image.placeholder()));
+                        images_tx.unbounded_send(image).ok();
+                    },
+                );
+                if self.filtered.is_empty() && self.placeholders.is_empty() {
+                    continue;
+                }
+                self.parse_filtered();
+
+                // A NUL byte, which alacritty's parser ignores, for the event loop to notify about the output parsed.
+                buf[0] = 0;
+                return Ok(1);
+            }
+        }
+    }
+
+    impl<P: ScannedPty> EventedReadWrite for ShellIntegrationPty<P> {
+        type Reader = ScanningReader<P::Output>;
+        type Writer = <P as EventedReadWrite>::Writer;
+
+        unsafe fn register(
+            &mut self,
+            poll: &Arc<Poller>,
+            interest: PollingEvent,
+            poll_opts: PollMode,
+        ) -> io::Result<()> {
+            self.pty.register(poll, interest, poll_opts)
+        }
+
+        fn reregister(
+            &mut self,
+            poll: &Arc<Poller>,
+            interest: PollingEvent,
+            poll_opts: PollMode,
+        ) -> io::Result<()> {
+            self.pty.reregister(poll, interest, poll_opts)
+        }
+
+        fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+            self.pty.deregister(poll)
+        }
+
+        fn reader(&mut self) -> &mut Self::Reader {
+            &mut self.reader
+        }
+
+        fn writer(&mut self) -> &mut Self::Writer {
+            self.pty.writer()
+        }
+    }
+
+    impl<P: ScannedPty> EventedPty for ShellIntegrationPty<P> {
+        fn next_child_event(&mut self) -> Option<ChildEvent> {
+            self.pty.next_child_event()
+        }
+    }
+
+    impl<P: ScannedPty> OnResize for ShellIntegrationPty<P> {
+        fn on_resize(&mut self, window_size: WindowSize) {
+            self.reader.image_filter.set_window_size(window_size);
+            self.pty.on_resize(window_size)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alacritty_terminal::{
+        event::VoidListener,
+        index::{Column, Point},
+        term::Config,
+        vte::ansi::Processor,
+        Term,
+    };
+    use gpui::{px, size};
+
+    use super::*;
+    use crate::TerminalSize;
+
+    fn scan(chunks: &[&[u8]]) -> Vec<ShellIntegrationEvent> {
+        let mut scanner = OscScanner::default();
+        let mut events = Vec::new();
+        for chunk in chunks {
+            scanner.scan(chunk, |_, event| events.push(event));
+        }
+        events
+    }
+
+    #[test]
+    fn test_scanning_shell_integration_sequences() {
+        assert_eq!(
+            scan(&[
+                b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.rs\r\n\x1b]133;D;1\x1b\\"
+            ]),
+            [
+                ShellIntegrationEvent::PromptStart,
+                ShellIntegrationEvent::CommandStart,
+                ShellIntegrationEvent::OutputStart,
+                ShellIntegrationEvent::CommandFinished { exit_code: Some(1) },
+            ]
+        );
+        // Sequences split between reads, and the ones unrelated to shell integration.
+        assert_eq!(
+            scan(&[
+                b"\x1b]0;title\x07\x1b]13",
+                b"3;D\x1b",
+                b"\\\x1b]7;file://host/home/a%20b\x07"
+            ]),
+            [
+                ShellIntegrationEvent::CommandFinished { exit_code: None },
+                ShellIntegrationEvent::WorkingDirectory(PathBuf::from("/home/a b")),
+            ]
+        );
+        assert_eq!(
+            scan(&[b"\x1b]633;E;echo a\\x3bb \\\\;nonce\x07\x1b]633;P;Cwd=/tmp\x07"]),
+            [
+                ShellIntegrationEvent::CommandLine("echo a;b \\".to_string()),
+                ShellIntegrationEvent::WorkingDirectory(PathBuf::from("/tmp")),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_integration_lines_are_taken_when_parsed() {
+        use std::io::Read as _;
+
+        use alacritty_terminal::sync::FairMutex;
+        use futures::channel::mpsc::unbounded;
+
+        let term = Arc::new(FairMutex::new(Term::new(
+            Config::default(),
+            &TerminalSize::new(px(10.), px(10.), size(px(200.), px(50.))),
+            crate::ZedListener(unbounded().0),
+        )));
+        let (events_tx, mut events_rx) = unbounded();
+        // The command's output follows its marks in the same read, before the event about them is handled.
+        let output: &[u8] = b"\x1b]133;A\x07$ \x1b]133;B\x07ls\r\n\x1b]133;C\x07a.rs\r\nb.rs\r\n\x1b]133;D;0\x07\x1b]133;A\x07$ ";
+        let mut reader = unix::ScanningReader::new(
+            output,
+            term.clone(),
+            ScrollbackLines::new(100),
+            events_tx,
+            unbounded().0,
+        );
+        let mut buf = [0; 16];
+        while reader.read(&mut buf).unwrap() > 0 {}
+
+        let events = std::iter::from_fn(|| events_rx.try_next().ok().flatten()).collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                (ShellIntegrationEvent::PromptStart, 0),
+                (ShellIntegrationEvent::CommandStart, 0),
+                (ShellIntegrationEvent::OutputStart, 1),
+                (
+                    ShellIntegrationEvent::CommandFinished { exit_code: Some(0) },
+                    3
+                ),
+                (ShellIntegrationEvent::PromptStart, 3),
+            ]
+        );
+        assert_eq!(term.lock().grid().cursor.point.line, Line(3));
+    }
+
+    #[test]
+    fn test_line_numbers_with_full_scrollback_history() {
+        let max_history_lines = 10;
+        let mut term = Term::new(
+            Config {
+                scrolling_history: max_history_lines + SCROLLBACK_SLACK_LINES,
+                ..Config::default()
+            },
+            &TerminalSize::new(px(10.), px(10.), size(px(200.), px(50.))),
+            VoidListener,
+        );
+        let mut parser: Processor = Processor::new();
+        let mut print = |term: &mut Term<VoidListener>, text: &str| {
+            for byte in text.bytes() {
+                parser.advance(term, byte);
+            }
+        };
+        let line_text = |term: &Term<VoidListener>, line: Line| {
+            term.bounds_to_string(
+                Point::new(line, Column(0)),
+                Point::new(line, term.last_column()),
+            )
+            .trim_end()
+            .to_string()
+        };
+        let mut scrollback_lines = ScrollbackLines::new(max_history_lines);
+
+        for chunk in 0..30 {
+            for line in 0..100 {
+                print(&mut term, &format!("{chunk} {line}\r\n"));
+            }
+            assert!(scrollback_lines.trim_history(term.grid_mut()));
+            assert_eq!(term.grid().history_size(), max_history_lines);
+        }
+        print(&mut term, "$ ls");
+        let prompt_line = scrollback_lines.line_number(term.grid(), term.grid().cursor.point.line);
+        print(&mut term, "\r\n");
+        for line in 0..8 {
+            print(&mut term, &format!("file {line}\r\n"));
+            assert!(scrollback_lines.trim_history(term.grid_mut()));
+        }
+        assert_eq!(
+            line_text(&term, scrollback_lines.grid_line(term.grid(), prompt_line)),
+            "$ ls"
+        );
+        assert!(scrollback_lines.first_line_number() < prompt_line);
+
+        // Too many lines printed at once, alacritty dropped some of them itself.
+        for line in 0..SCROLLBACK_SLACK_LINES + 100 {
+            print(&mut term, &format!("{line}\r\n"));
+        }
+        assert!(!scrollback_lines.trim_history(term.grid_mut()));
+        assert!(scrollback_lines.grid_line(term.grid(), prompt_line) < term.topmost_line());
+    }
+}
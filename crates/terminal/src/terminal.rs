@@ -2,12 +2,16 @@ pub mod mappings;
 
 pub use alacritty_terminal;
 
+pub mod inline_images;
 mod pty_info;
+#[cfg(unix)]
+pub mod session_daemon;
+pub mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
-    event::{Event as AlacTermEvent, EventListener, Notify, WindowSize},
-    event_loop::{EventLoop, Msg, Notifier},
+    event::{Event as AlacTermEvent, EventListener, Notify, OnResize, WindowSize},
+    event_loop::{EventLoop, EventLoopSender, Msg, Notifier},
     grid::{Dimensions, Scroll as AlacScroll},
     index::{Boundary, Column, Direction as AlacDirection, Line, Point as AlacPoint},
     selection::{Selection, SelectionRange, SelectionType},
@@ -17,8 +21,8 @@ use alacritty_terminal::{
         search::{Match, RegexIter, RegexSearch},
         Config, RenderableCursor, TermMode,
     },
-    tty::{self, setup_env},
-    vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode, Rgb},
+    tty::{self, setup_env, EventedPty},
+    vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode, Processor, Rgb},
     Term,
 };
 use anyhow::{bail, Result};
@@ -35,14 +39,19 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use inline_images::{InlineImage, InlineImages, PendingImage, IMAGE_PLACEHOLDER};
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{
+    ScrollbackLines, ShellCommand, ShellIntegrationEvent, SCROLLBACK_SLACK_LINES,
+};
 use smol::channel::{Receiver, Sender};
-use task::{static_source::RevealStrategy, TaskId};
+use task::{problem_matcher::ProblemMatcher, static_source::RevealStrategy, TaskId};
 use terminal_settings::{AlternateScroll, Shell, TerminalBlink, TerminalSettings};
 use theme::{ActiveTheme, Theme};
 use util::truncate_and_trailoff;
+use uuid::Uuid;
 
 use std::{
     cmp::{self, min},
@@ -64,7 +73,16 @@ use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
 
 actions!(
     terminal,
-    [Clear, Copy, Paste, ShowCharacterPalette, SearchTest,]
+    [
+        Clear,
+        Copy,
+        Paste,
+        ShowCharacterPalette,
+        SearchTest,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        CopyLastCommandOutput,
+    ]
 );
 
 ///Scrolling is unbearably sluggish by default. Alacritty supports a configurable
@@ -293,15 +311,63 @@ pub struct SpawnTask {
     pub args: Vec<String>,
     pub env: HashMap<String, String>,
     pub reveal: RevealStrategy,
+    pub problem_matchers: Vec<ProblemMatcher>,
 }
 
 // https://github.com/alacritty/alacritty/blob/cb3a79dbf6472740daca8440d5166c1d4af5029e/extra/man/alacritty.5.scd?plain=1#L207-L213
 const DEFAULT_SCROLL_HISTORY_LINES: usize = 10_000;
 const MAX_SCROLL_HISTORY_LINES: usize = 100_000;
+/// How many of the latest shell commands to keep the boundaries of.
+const MAX_SHELL_COMMANDS: usize = 1_000;
 
 pub struct TerminalBuilder {
     terminal: Terminal,
     events_rx: UnboundedReceiver<AlacTermEvent>,
+    /// The shell integration events, along with the line the cursor was on when they were parsed.
+    shell_events_rx: UnboundedReceiver<(ShellIntegrationEvent, i32)>,
+    images_rx: UnboundedReceiver<PendingImage>,
+}
+
+/// The environment variables set for every terminal's shell, on top of the ones configured.
+fn insert_terminal_env(env: &mut HashMap<String, String>) {
+    // TODO: Properly set the current locale,
+    env.entry("LC_ALL".to_string())
+        .or_insert_with(|| "en_US.UTF-8".to_string());
+
+    env.insert("ZED_TERM".to_string(), "true".to_string());
+}
+
+/// A shell kept running in a detached process, for its terminal to be built with.
+pub struct DetachedSession {
+    id: String,
+    #[cfg(unix)]
+    client: session_daemon::SessionClient,
+}
+
+impl DetachedSession {
+    /// Attaches to the detached session with the id given, or to a new one, starting the session's
+    /// daemon with the shell given if it is not running.
+    ///
+    /// Starting the daemon takes a while, so this is to be called on the background executor.
+    pub fn attach(
+        id: Option<String>,
+        shell: &Shell,
+        working_directory: Option<PathBuf>,
+        mut env: HashMap<String, String>,
+    ) -> Result<Self> {
+        #[cfg(unix)]
+        {
+            let id = id.unwrap_or_else(|| Uuid::new_v4().to_string());
+            insert_terminal_env(&mut env);
+            let client = session_daemon::SessionClient::attach(&id, shell, working_directory, env)?;
+            Ok(Self { id, client })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (id, shell, working_directory, &mut env);
+            bail!("detached terminal sessions are only supported on macOS and Linux")
+        }
+    }
 }
 
 impl TerminalBuilder {
@@ -316,31 +382,16 @@ impl TerminalBuilder {
         max_scroll_history_lines: Option<usize>,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
+        session: Option<TerminalSession>,
+        detached_session: Option<DetachedSession>,
     ) -> Result<TerminalBuilder> {
-        // TODO: Properly set the current locale,
-        env.entry("LC_ALL".to_string())
-            .or_insert_with(|| "en_US.UTF-8".to_string());
+        insert_terminal_env(&mut env);
 
-        env.insert("ZED_TERM".to_string(), "true".to_string());
-
-        let pty_options = {
-            let alac_shell = match shell.clone() {
-                Shell::System => None,
-                Shell::Program(program) => {
-                    Some(alacritty_terminal::tty::Shell::new(program, Vec::new()))
-                }
-                Shell::WithArguments { program, args } => {
-                    Some(alacritty_terminal::tty::Shell::new(program, args))
-                }
-            };
-
-            alacritty_terminal::tty::Options {
-                shell: alac_shell,
-                working_directory: working_directory.clone(),
-                hold: false,
-                env: env.into_iter().collect(),
-            }
-        };
+        let session = session.unwrap_or_default();
+        let detached_session_id = detached_session
+            .as_ref()
+            .map(|detached_session| detached_session.id.clone());
+        let pty_options = pty_options(shell.clone(), working_directory.clone(), env);
 
         // Setup Alacritty's env
         setup_env();
@@ -356,7 +407,7 @@ impl TerminalBuilder {
                 .min(MAX_SCROLL_HISTORY_LINES)
         };
         let config = Config {
-            scrolling_history,
+            scrolling_history: scrolling_history + SCROLLBACK_SLACK_LINES,
             ..Config::default()
         };
 
@@ -381,38 +432,73 @@ impl TerminalBuilder {
         }
 
         let term = Arc::new(FairMutex::new(term));
+        let scrollback_lines = ScrollbackLines::new(scrolling_history);
+        let (shell_events_tx, shell_events_rx) = unbounded();
+        let (images_tx, images_rx) = unbounded();
+        let (pty_tx, pty_info, attached_to_running_session) = match detached_session {
+            #[cfg(unix)]
+            Some(DetachedSession { client, .. }) => {
+                // A running session replays its own output.
+                let attached_to_running_session = !client.started_daemon();
+                if !attached_to_running_session {
+                    restore_scrollback(&term, session.scrollback.as_deref());
+                }
+                let pty_info = PtyProcessInfo::for_process(client.shell_pid());
+                let pty = shell_integration::ShellIntegrationPty::new(
+                    client,
+                    term.clone(),
+                    scrollback_lines.clone(),
+                    shell_events_tx,
+                    images_tx,
+                )?;
+                let pty_tx = spawn_event_loop(
+                    &unparsed_event_loop_term(),
+                    ZedListener(events_tx.clone()),
+                    pty,
+                )?;
+                (pty_tx, pty_info, attached_to_running_session)
+            }
+            _ => {
+                //Setup the pty...
+                let pty = match tty::new(
+                    &pty_options,
+                    TerminalSize::default().into(),
+                    window.window_id().as_u64(),
+                ) {
+                    Ok(pty) => pty,
+                    Err(error) => {
+                        bail!(TerminalError {
+                            directory: working_directory,
+                            shell,
+                            source: error,
+                        });
+                    }
+                };
 
-        //Setup the pty...
-        let pty = match tty::new(
-            &pty_options,
-            TerminalSize::default().into(),
-            window.window_id().as_u64(),
-        ) {
-            Ok(pty) => pty,
-            Err(error) => {
-                bail!(TerminalError {
-                    directory: working_directory,
-                    shell,
-                    source: error,
-                });
+                let pty_info = PtyProcessInfo::new(&pty);
+                restore_scrollback(&term, session.scrollback.as_deref());
+
+                #[cfg(unix)]
+                let pty_tx = spawn_event_loop(
+                    &unparsed_event_loop_term(),
+                    ZedListener(events_tx.clone()),
+                    shell_integration::ShellIntegrationPty::new(
+                        pty,
+                        term.clone(),
+                        scrollback_lines.clone(),
+                        shell_events_tx,
+                        images_tx,
+                    )?,
+                )?;
+                #[cfg(not(unix))]
+                let pty_tx = {
+                    drop((shell_events_tx, images_tx));
+                    spawn_event_loop(&term, ZedListener(events_tx.clone()), pty)?
+                };
+                (pty_tx, pty_info, false)
             }
         };
 
-        let pty_info = PtyProcessInfo::new(&pty);
-
-        //And connect them together
-        let event_loop = EventLoop::new(
-            term.clone(),
-            ZedListener(events_tx.clone()),
-            pty,
-            pty_options.hold,
-            false,
-        )?;
-
-        //Kick things off
-        let pty_tx = event_loop.channel();
-        let _io_thread = event_loop.spawn(); // DANGER
-
         let url_regex = RegexSearch::new(r#"(ipfs:|ipns:|magnet:|mailto:|gemini://|gopher://|https://|http://|news:|file://|git://|ssh:|ftp://)[^\u{0000}-\u{001F}\u{007F}-\u{009F}<>"\s{-}\^⟨⟩`]+"#).unwrap();
         let word_regex = RegexSearch::new(r#"[\$\+\w.\[\]:/@\-~]+"#).unwrap();
 
@@ -427,7 +513,7 @@ impl TerminalBuilder {
             matches: Vec::new(),
             selection_head: None,
             pty_info,
-            breadcrumb_text: String::new(),
+            breadcrumb_text: session.title.unwrap_or_default(),
             scroll_px: px(0.),
             last_mouse_position: None,
             next_link_id: 0,
@@ -436,15 +522,54 @@ impl TerminalBuilder {
             hovered_word: false,
             url_regex,
             word_regex,
+            shell_commands: VecDeque::new(),
+            scrollback_lines,
+            shell_cwd: None,
+            inline_images: InlineImages::new(scrolling_history),
+            shell,
+            detached_session_id,
+            attached_to_running_session,
+            close_detached_session: false,
         };
 
         Ok(TerminalBuilder {
             terminal,
             events_rx,
+            shell_events_rx,
+            images_rx,
         })
     }
 
     pub fn subscribe(mut self, cx: &mut ModelContext<Terminal>) -> Terminal {
+        let mut shell_events_rx = self.shell_events_rx;
+        cx.spawn(|terminal, mut cx| async move {
+            while let Some((event, line)) = shell_events_rx.next().await {
+                terminal.update(&mut cx, |terminal, cx| {
+                    terminal.process_shell_integration_event(event, line, cx);
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach();
+
+        let mut images_rx = self.images_rx;
+        cx.spawn(|terminal, mut cx| async move {
+            while let Some(image) = images_rx.next().await {
+                let image = cx
+                    .background_executor()
+                    .spawn(async move { image.decode() })
+                    .await;
+                if let Some(image) = image {
+                    terminal.update(&mut cx, |terminal, cx| {
+                        terminal.inline_images.insert(image);
+                        cx.notify();
+                    })?;
+                }
+            }
+            anyhow::Ok(())
+        })
+        .detach();
+
         //Event loop
         cx.spawn(|terminal, mut cx| async move {
             while let Some(event) = self.events_rx.next().await {
@@ -507,6 +632,89 @@ impl TerminalBuilder {
     }
 }
 
+/// Creates the options to spawn the shell in a pty with.
+pub(crate) fn pty_options(
+    shell: Shell,
+    working_directory: Option<PathBuf>,
+    env: HashMap<String, String>,
+) -> tty::Options {
+    let alac_shell = match shell {
+        Shell::System => None,
+        Shell::Program(program) => Some(alacritty_terminal::tty::Shell::new(program, Vec::new())),
+        Shell::WithArguments { program, args } => {
+            Some(alacritty_terminal::tty::Shell::new(program, args))
+        }
+    };
+
+    tty::Options {
+        shell: alac_shell,
+        working_directory,
+        hold: false,
+        env: env.into_iter().collect(),
+    }
+}
+
+/// Connects the terminal to its pty, and starts reading from it.
+fn spawn_event_loop<T>(
+    term: &Arc<FairMutex<Term<ZedListener>>>,
+    listener: ZedListener,
+    pty: T,
+) -> Result<EventLoopSender>
+where
+    T: EventedPty + OnResize + Send + 'static,
+{
+    let event_loop = EventLoop::new(term.clone(), listener, pty, false, false)?;
+    let pty_tx = event_loop.channel();
+    let _io_thread = event_loop.spawn(); // DANGER
+    Ok(pty_tx)
+}
+
+/// The terminal for alacritty's event loop to lock, when the output is parsed into the actual one by
+/// [`shell_integration::ShellIntegrationPty`]: it only ever gets bytes its parser ignores.
+#[cfg(unix)]
+fn unparsed_event_loop_term() -> Arc<FairMutex<Term<ZedListener>>> {
+    let config = Config {
+        scrolling_history: 0,
+        ..Config::default()
+    };
+    let term = Term::new(config, &TerminalSize::default(), ZedListener(unbounded().0));
+    Arc::new(FairMutex::new(term))
+}
+
+/// Prints the text of a previous session's scrollback, for the new shell to start below it.
+fn restore_scrollback(term: &FairMutex<Term<ZedListener>>, scrollback: Option<&str>) {
+    let Some(scrollback) = scrollback.filter(|scrollback| !scrollback.is_empty()) else {
+        return;
+    };
+    let mut parser: Processor = Processor::new();
+    let mut term = term.lock();
+    for line in scrollback.lines() {
+        for byte in line.bytes().chain(*b"\r\n") {
+            parser.advance(&mut *term, byte);
+        }
+    }
+}
+
+/// Removes the cells showing inline images from the terminal's text, as they are not characters anyone typed.
+fn strip_image_placeholders(text: String) -> String {
+    if text.contains(IMAGE_PLACEHOLDER) {
+        text.replace(IMAGE_PLACEHOLDER, "")
+    } else {
+        text
+    }
+}
+
+/// What is kept of a terminal when Zed quits, to reopen it with.
+#[derive(Clone, Debug, Default)]
+pub struct TerminalSession {
+    /// The title the shell had set.
+    pub title: Option<String>,
+    /// The text of the latest lines printed.
+    pub scrollback: Option<String>,
+    /// The detached process running the shell, when the sessions are detached.
+    pub detached_session_id: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct IndexedCell {
     pub point: AlacPoint,
@@ -590,6 +798,20 @@ pub struct Terminal {
     url_regex: RegexSearch,
     word_regex: RegexSearch,
     task: Option<TaskState>,
+    /// The latest commands run in the shell, as reported by its shell integration.
+    shell_commands: VecDeque<ShellCommand>,
+    /// Numbers the lines of the shell commands, keeping the scrollback history to its size.
+    scrollback_lines: ScrollbackLines,
+    /// The working directory reported by the shell, more precise than the one of the pty's foreground process.
+    shell_cwd: Option<PathBuf>,
+    /// The images printed in the terminal, which its cells refer to.
+    inline_images: InlineImages,
+    shell: Shell,
+    detached_session_id: Option<String>,
+    attached_to_running_session: bool,
+    /// Whether to end the detached session once the terminal is dropped, because it was closed.
+    /// Otherwise it keeps running, as when Zed quits or the workspace is closed, to be attached to again.
+    close_detached_session: bool,
 }
 
 pub struct TaskState {
@@ -665,6 +887,8 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                let term = self.term.clone();
+                self.trim_scrollback_history(&mut term.lock());
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -688,8 +912,201 @@ impl Terminal {
         self.selection_phase == SelectionPhase::Selecting
     }
 
+    pub fn shell(&self) -> &Shell {
+        &self.shell
+    }
+
+    /// The detached process running the shell, if the terminal's session outlives Zed.
+    pub fn detached_session_id(&self) -> Option<&str> {
+        self.detached_session_id.as_deref()
+    }
+
+    /// Ends the detached session when the terminal is dropped, as nothing will reopen it.
+    pub fn close_detached_session_on_drop(&mut self) {
+        self.close_detached_session = true;
+    }
+
+    /// Whether the terminal attached to a shell still running from a previous Zed session.
+    pub fn attached_to_running_session(&self) -> bool {
+        self.attached_to_running_session
+    }
+
+    /// The text of the terminal's last lines, above the cursor, to restore it with.
+    pub fn restorable_scrollback_text(&self, max_lines: usize) -> String {
+        let term = self.term.lock();
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return String::new();
+        }
+        let end_line = term.grid().cursor.point.line.0 - 1;
+        let start_line = (end_line + 1 - max_lines as i32).max(term.topmost_line().0);
+        if end_line < start_line {
+            return String::new();
+        }
+        strip_image_placeholders(term.bounds_to_string(
+            AlacPoint::new(Line(start_line), Column(0)),
+            AlacPoint::new(Line(end_line), term.last_column()),
+        ))
+    }
+
+    /// The inline image, which the placeholder cells with that id show.
+    pub fn inline_image(&self, id: u32) -> Option<&InlineImage> {
+        self.inline_images.get(id)
+    }
+
     pub fn get_cwd(&self) -> Option<PathBuf> {
-        self.pty_info.current.as_ref().map(|info| info.cwd.clone())
+        self.shell_cwd
+            .clone()
+            .or_else(|| self.pty_info.current.as_ref().map(|info| info.cwd.clone()))
+    }
+
+    /// Records the boundaries of the shell's commands, as the shell reports them,
+    /// at the line the cursor was on when the terminal parsed the report.
+    fn process_shell_integration_event(
+        &mut self,
+        event: ShellIntegrationEvent,
+        line: i32,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match event {
+            ShellIntegrationEvent::PromptStart => {
+                if let Some(command) = self.shell_commands.back_mut() {
+                    command.end_line.get_or_insert(line);
+                }
+                if self.shell_commands.len() >= MAX_SHELL_COMMANDS {
+                    self.shell_commands.pop_front();
+                }
+                self.shell_commands.push_back(ShellCommand {
+                    prompt_line: line,
+                    ..ShellCommand::default()
+                });
+            }
+            ShellIntegrationEvent::CommandStart => {
+                if let Some(command) = self.shell_commands.back_mut() {
+                    command.input_line = Some(line);
+                }
+            }
+            ShellIntegrationEvent::OutputStart => {
+                if let Some(command) = self.shell_commands.back_mut() {
+                    // The output starts right after the command line, unless it was wrapped.
+                    let input_line = command.input_line.unwrap_or(command.prompt_line);
+                    command.output_line = Some(line.min(input_line + 1).max(input_line));
+                }
+            }
+            ShellIntegrationEvent::CommandFinished { exit_code } => {
+                if let Some(command) = self.shell_commands.back_mut() {
+                    // Shells report the end of the previous command before the first prompt too.
+                    if command.input_line.is_some() {
+                        command.finished = true;
+                        command.exit_code = exit_code;
+                        command.end_line = Some(line);
+                    }
+                }
+            }
+            ShellIntegrationEvent::CommandLine(command_line) => {
+                if let Some(command) = self.shell_commands.back_mut() {
+                    command.command_line = Some(command_line);
+                }
+            }
+            ShellIntegrationEvent::WorkingDirectory(cwd) => {
+                self.shell_cwd = Some(cwd);
+                cx.emit(Event::TitleChanged);
+            }
+        }
+        cx.emit(Event::Wakeup);
+    }
+
+    /// Keeps the scrollback history to its size, forgetting the commands that scrolled out of it.
+    fn trim_scrollback_history(&mut self, term: &mut Term<ZedListener>) {
+        if !self.scrollback_lines.trim_history(term.grid_mut()) {
+            // The lines of the commands are off by an unknown number of lines.
+            self.shell_commands.clear();
+        }
+        let first_line = self.scrollback_lines.first_line_number();
+        while self
+            .shell_commands
+            .front()
+            .map_or(false, |command| command.prompt_line < first_line)
+        {
+            self.shell_commands.pop_front();
+        }
+    }
+
+    /// The commands whose prompts are on the screen, with the rows of their prompts, counted from the top of the screen.
+    pub fn visible_shell_commands(&self) -> Vec<(usize, &ShellCommand)> {
+        let top_line = self.top_line();
+        let screen_lines = self.last_content.size.num_lines() as i32;
+        self.shell_commands
+            .iter()
+            .filter_map(|command| {
+                let row = command.prompt_line - top_line;
+                (0..screen_lines)
+                    .contains(&row)
+                    .then_some((row as usize, command))
+            })
+            .collect()
+    }
+
+    /// The number of the line displayed at the top of the screen.
+    fn top_line(&self) -> i32 {
+        let term = self.term.lock();
+        self.scrollback_lines.line_number(
+            term.grid(),
+            Line(-(self.last_content.display_offset as i32)),
+        )
+    }
+
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let top_line = self.top_line();
+        if let Some(command) = self
+            .shell_commands
+            .iter()
+            .rev()
+            .find(|command| command.prompt_line < top_line)
+        {
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                    top_line - command.prompt_line,
+                )));
+        }
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        let top_line = self.top_line();
+        let scroll = match self
+            .shell_commands
+            .iter()
+            .find(|command| command.prompt_line > top_line)
+        {
+            Some(command) => AlacScroll::Delta(top_line - command.prompt_line),
+            None => AlacScroll::Bottom,
+        };
+        self.events.push_back(InternalEvent::Scroll(scroll));
+    }
+
+    /// The text printed by the latest command that finished, if the shell reported its boundaries.
+    pub fn last_command_output(&self) -> Option<String> {
+        let output_lines = self
+            .shell_commands
+            .iter()
+            .rev()
+            .find_map(|command| command.output_lines())?;
+        let term = self.term.lock();
+        let start_line = self
+            .scrollback_lines
+            .grid_line(term.grid(), output_lines.start)
+            .max(term.topmost_line())
+            .0;
+        let end_line = self
+            .scrollback_lines
+            .grid_line(term.grid(), output_lines.end)
+            .0
+            .min(term.bottommost_line().0 + 1);
+        if start_line >= end_line {
+            return Some(String::new());
+        }
+        let start = AlacPoint::new(Line(start_line), Column(0));
+        let end = AlacPoint::new(Line(end_line - 1), term.last_column());
+        Some(strip_image_placeholders(term.bounds_to_string(start, end)))
     }
 
     ///Takes events from Alacritty and translates them to behavior on this view
@@ -719,6 +1136,8 @@ impl Terminal {
             InternalEvent::Clear => {
                 // Clear back buffer
                 term.clear_screen(ClearMode::Saved);
+                self.shell_commands.clear();
+                self.inline_images.clear();
 
                 let cursor = term.grid().cursor.point;
 
@@ -778,7 +1197,7 @@ impl Terminal {
 
             InternalEvent::Copy => {
                 if let Some(txt) = term.selection_to_string() {
-                    cx.write_to_clipboard(ClipboardItem::new(txt))
+                    cx.write_to_clipboard(ClipboardItem::new(strip_image_placeholders(txt)))
                 }
             }
             InternalEvent::ScrollToAlacPoint(point) => {
@@ -975,6 +1394,14 @@ impl Terminal {
         self.set_selection(Some((make_selection(&(start..=end)), end)));
     }
 
+    /// The text of the whole terminal, including the scrollback history.
+    pub fn scrollback_text(&self) -> String {
+        let term = self.term.lock();
+        let start = AlacPoint::new(term.topmost_line(), Column(0));
+        let end = AlacPoint::new(term.bottommost_line(), term.last_column());
+        strip_image_placeholders(term.bounds_to_string(start, end))
+    }
+
     fn set_selection(&mut self, selection: Option<(Selection, AlacPoint)>) {
         self.events
             .push_back(InternalEvent::SetSelection(selection));
@@ -1055,6 +1482,7 @@ impl Terminal {
         while let Some(e) = self.events.pop_front() {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
+        self.trim_scrollback_history(&mut terminal);
 
         self.last_content = Self::make_content(&terminal, &self.last_content);
     }
@@ -1425,6 +1853,12 @@ impl Terminal {
 impl Drop for Terminal {
     fn drop(&mut self) {
         self.pty_tx.0.send(Msg::Shutdown).ok();
+        #[cfg(unix)]
+        if let Some(session_id) = &self.detached_session_id {
+            if self.close_detached_session {
+                session_daemon::terminate(session_id).ok();
+            }
+        }
     }
 }
 
@@ -42,6 +42,21 @@ pub struct TerminalSettings {
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
     pub toolbar: Toolbar,
+    pub sessions: TerminalSessions,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalSessions {
+    /// Start the terminals from scratch when reopening a workspace, only keeping their working directory.
+    #[default]
+    Off,
+    /// Restore the shell, title and scrollback of the terminals when reopening a workspace.
+    Restore,
+    /// Keep the shells running in a detached process when Zed quits, and attach to them again
+    /// when reopening a workspace. Only supported on macOS and Linux.
+    /// Shells not attached to again within a day are stopped.
+    Detach,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -163,6 +178,10 @@ pub struct TerminalSettingsContent {
     pub max_scroll_history_lines: Option<usize>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
+    /// What to keep of the terminals when Zed quits, to reopen them with.
+    ///
+    /// Default: off
+    pub sessions: Option<TerminalSessions>,
 }
 
 impl settings::Settings for TerminalSettings {
@@ -34,6 +34,12 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN shell TEXT;
+            ALTER TABLE terminals ADD COLUMN title TEXT;
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+            ALTER TABLE terminals ADD COLUMN detached_session_id TEXT;
         )];
 }
 
@@ -51,13 +57,25 @@ impl TerminalDb {
     }
 
     query! {
-        pub async fn save_working_directory(
+        pub async fn save_session(
             item_id: ItemId,
             workspace_id: WorkspaceId,
-            working_directory: PathBuf
+            working_directory: Option<PathBuf>,
+            shell: Option<String>,
+            title: Option<String>,
+            scrollback: Option<String>,
+            detached_session_id: Option<String>
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
-            VALUES (?, ?, ?)
+            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory, shell, title, scrollback, detached_session_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+        }
+    }
+
+    query! {
+        pub fn get_session(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Option<(Option<String>, Option<String>, Option<String>, Option<String>)>> {
+            SELECT shell, title, scrollback, detached_session_id
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
         }
     }
 
@@ -1,11 +1,11 @@
 use editor::{CursorLayout, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    div, fill, point, px, relative, AnyElement, Bounds, DispatchPhase, Element, ElementContext,
-    FocusHandle, Font, FontStyle, FontWeight, HighlightStyle, Hitbox, Hsla, InputHandler,
-    InteractiveElement, Interactivity, IntoElement, LayoutId, Model, ModelContext,
-    ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point, ShapedLine,
-    StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle, UnderlineStyle,
-    WeakView, WhiteSpace, WindowContext, WindowTextSystem,
+    div, fill, point, px, relative, size, AnyElement, Bounds, ContentMask, Corners, DispatchPhase,
+    Element, ElementContext, FocusHandle, Font, FontStyle, FontWeight, HighlightStyle, Hitbox,
+    Hsla, ImageData, InputHandler, InteractiveElement, Interactivity, IntoElement, LayoutId, Model,
+    ModelContext, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point, ShapedLine,
+    Size, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle,
+    UnderlineStyle, WeakView, WhiteSpace, WindowContext, WindowTextSystem,
 };
 use itertools::Itertools;
 use language::CursorShape;
@@ -20,21 +20,24 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    inline_images::{image_cell, IMAGE_PLACEHOLDER},
     terminal_settings::TerminalSettings,
     HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
 use theme::{ActiveTheme, Theme, ThemeSettings};
 use ui::Tooltip;
+use util::ResultExt;
 use workspace::Workspace;
 
-use std::mem;
 use std::{fmt::Debug, ops::RangeInclusive};
+use std::{mem, sync::Arc};
 
 /// The information generated during layout that is necessary for painting.
 pub struct LayoutState {
     hitbox: Hitbox,
     cells: Vec<LayoutCell>,
     rects: Vec<LayoutRect>,
+    images: Vec<LayoutImageRow>,
     relative_highlighted_ranges: Vec<(RangeInclusive<AlacPoint>, Hsla)>,
     cursor: Option<CursorLayout>,
     background_color: Hsla,
@@ -44,6 +47,56 @@ pub struct LayoutState {
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
+    /// Rows of the shell prompts on the screen, with the color showing how their commands exited.
+    prompt_markers: Vec<(usize, Hsla)>,
+}
+
+/// A row of an inline image, shown by a run of placeholder cells on a line.
+struct LayoutImageRow {
+    line: usize,
+    column: usize,
+    /// How many cells of the row are still showing the image, as text may have been printed over it.
+    len: usize,
+    /// Which row of the image the line shows.
+    image_row: usize,
+    data: Arc<ImageData>,
+    columns: usize,
+    rows: usize,
+}
+
+impl LayoutImageRow {
+    fn paint(&self, origin: Point<Pixels>, layout: &LayoutState, cx: &mut ElementContext) {
+        let cell_width = layout.dimensions.cell_width;
+        let line_height = layout.dimensions.line_height;
+        let image_origin = origin
+            + Point::new(
+                cell_width * self.column as f32,
+                line_height * (self.line as f32 - self.image_row as f32),
+            );
+
+        // The image keeps its aspect ratio within its cells, as they rarely match it exactly.
+        let image_size: Size<Pixels> = self.data.size().into();
+        let cells_size = size(
+            cell_width * self.columns as f32,
+            line_height * self.rows as f32,
+        );
+        let scale =
+            (cells_size.width / image_size.width).min(cells_size.height / image_size.height);
+        let image_bounds = Bounds::new(image_origin, image_size.map(|length| length * scale));
+
+        let row_bounds = Bounds::new(
+            origin
+                + Point::new(
+                    cell_width * self.column as f32,
+                    line_height * self.line as f32,
+                ),
+            size(cell_width * self.len as f32, line_height),
+        );
+        cx.with_content_mask(Some(ContentMask { bounds: row_bounds }), |cx| {
+            cx.paint_image(image_bounds, Corners::default(), self.data.clone(), false)
+                .log_err();
+        });
+    }
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -204,6 +257,15 @@ impl TerminalElement {
         let linegroups = grid.into_iter().group_by(|i| i.point.line);
         for (line_index, (_, line)) in linegroups.into_iter().enumerate() {
             for cell in line {
+                // Inline images are painted over their cells instead.
+                if cell.c == IMAGE_PLACEHOLDER {
+                    cur_alac_color = None;
+                    if let Some(rect) = cur_rect.take() {
+                        rects.push(rect);
+                    }
+                    continue;
+                }
+
                 let mut fg = cell.fg;
                 let mut bg = cell.bg;
                 if cell.flags.contains(Flags::INVERSE) {
@@ -296,6 +358,45 @@ impl TerminalElement {
         (cells, rects)
     }
 
+    /// Finds the rows of the inline images on the screen, from the placeholder cells showing them.
+    fn layout_images(grid: &[IndexedCell], terminal: &Terminal) -> Vec<LayoutImageRow> {
+        let mut images = Vec::new();
+        let linegroups = grid.iter().group_by(|i| i.point.line);
+        for (line_index, (_, line)) in linegroups.into_iter().enumerate() {
+            let mut current: Option<(u32, LayoutImageRow)> = None;
+            for cell in line {
+                let column = cell.point.column.0;
+                let image = image_cell(cell);
+                if let Some((id, row)) = current.as_mut() {
+                    if image == Some((*id, row.image_row)) && column == row.column + row.len {
+                        row.len += 1;
+                        continue;
+                    }
+                    images.extend(current.take().map(|(_, row)| row));
+                }
+                let Some((id, image_row)) = image else {
+                    continue;
+                };
+                if let Some(image) = terminal.inline_image(id) {
+                    current = Some((
+                        id,
+                        LayoutImageRow {
+                            line: line_index,
+                            column,
+                            len: 1,
+                            image_row,
+                            data: image.data.clone(),
+                            columns: image.columns,
+                            rows: image.rows,
+                        },
+                    ));
+                }
+            }
+            images.extend(current.map(|(_, row)| row));
+        }
+        images
+    }
+
     /// Computes the cursor position and expected block width, may return a zero width if x_for_index returns
     /// the same position for sequential indexes. Use em_width instead
     fn shape_cursor(
@@ -662,6 +763,21 @@ impl Element for TerminalElement {
                     }
                 });
 
+                let prompt_markers = self
+                    .terminal
+                    .read(cx)
+                    .visible_shell_commands()
+                    .into_iter()
+                    .map(|(row, command)| {
+                        let color = match (command.finished, command.exit_code) {
+                            (true, Some(0)) => theme.status().success,
+                            (true, Some(_)) => theme.status().error,
+                            _ => theme.colors().text_muted,
+                        };
+                        (row, color)
+                    })
+                    .collect();
+
                 let hyperlink_tooltip = last_hovered_word.clone().map(|hovered_word| {
                     let offset = bounds.origin + Point::new(gutter, px(0.));
                     let mut element = div()
@@ -704,6 +820,7 @@ impl Element for TerminalElement {
                         .map(|last_hovered_word| (link_style, &last_hovered_word.word_match)),
                     cx,
                 );
+                let images = TerminalElement::layout_images(cells, self.terminal.read(cx));
 
                 // Layout cursor. Rectangle is used for IME, so we should lay it out even
                 // if we don't end up showing it.
@@ -762,12 +879,14 @@ impl Element for TerminalElement {
                     background_color,
                     dimensions,
                     rects,
+                    images,
                     relative_highlighted_ranges,
                     mode: *mode,
                     display_offset: *display_offset,
                     hyperlink_tooltip,
                     gutter,
                     last_hovered_word,
+                    prompt_markers,
                 }
             })
     }
@@ -820,10 +939,28 @@ impl Element for TerminalElement {
                     }
                 });
 
+                for (row, color) in &layout.prompt_markers {
+                    let marker_origin = bounds.origin
+                        + Point::new(
+                            layout.gutter * 0.25,
+                            layout.dimensions.line_height * *row as f32,
+                        );
+                    let marker_size =
+                        gpui::size(layout.gutter * 0.5, layout.dimensions.line_height);
+                    cx.paint_quad(
+                        fill(Bounds::new(marker_origin, marker_size), *color)
+                            .corner_radii(layout.gutter * 0.25),
+                    );
+                }
+
                 for rect in &layout.rects {
                     rect.paint(origin, &layout, cx);
                 }
 
+                for image in &layout.images {
+                    image.paint(origin, &layout, cx);
+                }
+
                 for (relative_highlighted_range, color) in layout.relative_highlighted_ranges.iter()
                 {
                     if let Some((start_y, highlighted_range_lines)) =
@@ -1,23 +1,31 @@
-use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
+use std::{cell::RefCell, ops::ControlFlow, path::PathBuf, rc::Rc, sync::Arc};
 
 use crate::TerminalView;
-use collections::{HashMap, HashSet};
+use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
-use futures::future::join_all;
+use futures::{
+    channel::oneshot,
+    future::{join_all, LocalBoxFuture, Shared},
+    FutureExt,
+};
 use gpui::{
-    actions, Action, AppContext, AsyncWindowContext, Entity, EventEmitter, ExternalPaths,
-    FocusHandle, FocusableView, IntoElement, ParentElement, Pixels, Render, Styled, Subscription,
-    Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    actions, point, px, Action, AppContext, AsyncWindowContext, Axis, Entity, EntityId,
+    EventEmitter, ExternalPaths, FocusHandle, FocusableView, InteractiveElement, IntoElement,
+    Model, ParentElement, Pixels, Render, Styled, Subscription, Task, View, ViewContext,
+    VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{Fs, ProjectEntryId};
+use project::{Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
-use task::{static_source::RevealStrategy, SpawnInTerminal, TaskId};
+use task::{
+    static_source::{DependsOrder, ExecutionMode, RevealStrategy, TerminalSplit},
+    SpawnInTerminal, TaskId,
+};
 use terminal::{
     terminal_settings::{Shell, TerminalDockPosition, TerminalSettings},
-    SpawnTask,
+    SpawnTask, TaskStatus, Terminal,
 };
 use ui::{h_flex, ButtonCommon, Clickable, IconButton, IconSize, Selectable, Tooltip};
 use util::{ResultExt, TryFutureExt};
@@ -26,12 +34,14 @@ use workspace::{
     item::Item,
     pane,
     ui::IconName,
-    DraggedTab, NewTerminal, Pane, Workspace,
+    ActivatePaneInDirection, DraggedTab, NewTerminal, Pane, PaneGroup, PaneLayout, SplitDirection,
+    SwapPaneInDirection, Toast, Workspace, HANDLE_HITBOX_SIZE,
 };
 
 use anyhow::Result;
 
 const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
+const TASK_DEPENDENCY_FAILURE_ID: usize = 0x7a5dd3;
 
 actions!(terminal_panel, [ToggleFocus]);
 
@@ -49,118 +59,25 @@ pub fn init(cx: &mut AppContext) {
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    center: PaneGroup,
+    active_pane: View<Pane>,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
     height: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
     pending_terminals_to_add: usize,
+    pane_subscriptions: HashMap<EntityId, Vec<Subscription>>,
     _subscriptions: Vec<Subscription>,
     deferred_tasks: HashMap<TaskId, Task<()>>,
 }
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
-        let terminal_panel = cx.view().downgrade();
-        let pane = cx.new_view(|cx| {
-            let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
-                Default::default(),
-                None,
-                NewTerminal.boxed_clone(),
-                cx,
-            );
-            pane.set_can_split(false, cx);
-            pane.set_can_navigate(false, cx);
-            pane.display_nav_history_buttons(false);
-            pane.set_render_tab_bar_buttons(cx, move |pane, cx| {
-                let terminal_panel = terminal_panel.clone();
-                h_flex()
-                    .gap_2()
-                    .child(
-                        IconButton::new("plus", IconName::Plus)
-                            .icon_size(IconSize::Small)
-                            .on_click(move |_, cx| {
-                                terminal_panel
-                                    .update(cx, |panel, cx| panel.add_terminal(None, None, cx))
-                                    .log_err();
-                            })
-                            .tooltip(|cx| Tooltip::text("New Terminal", cx)),
-                    )
-                    .child({
-                        let zoomed = pane.is_zoomed();
-                        IconButton::new("toggle_zoom", IconName::Maximize)
-                            .icon_size(IconSize::Small)
-                            .selected(zoomed)
-                            .selected_icon(IconName::Minimize)
-                            .on_click(cx.listener(|pane, _, cx| {
-                                pane.toggle_zoom(&workspace::ToggleZoom, cx);
-                            }))
-                            .tooltip(move |cx| {
-                                Tooltip::text(if zoomed { "Zoom Out" } else { "Zoom In" }, cx)
-                            })
-                    })
-                    .into_any_element()
-            });
-
-            let workspace = workspace.weak_handle();
-            pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
-                if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
-                    let item = if &tab.pane == cx.view() {
-                        pane.item_for_index(tab.ix)
-                    } else {
-                        tab.pane.read(cx).item_for_index(tab.ix)
-                    };
-                    if let Some(item) = item {
-                        if item.downcast::<TerminalView>().is_some() {
-                            return ControlFlow::Continue(());
-                        } else if let Some(project_path) = item.project_path(cx) {
-                            if let Some(entry_path) = workspace
-                                .update(cx, |workspace, cx| {
-                                    workspace
-                                        .project()
-                                        .read(cx)
-                                        .absolute_path(&project_path, cx)
-                                })
-                                .log_err()
-                                .flatten()
-                            {
-                                add_paths_to_terminal(pane, &[entry_path], cx);
-                            }
-                        }
-                    }
-                } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
-                    if let Some(entry_path) = workspace
-                        .update(cx, |workspace, cx| {
-                            let project = workspace.project().read(cx);
-                            project
-                                .path_for_entry(entry_id, cx)
-                                .and_then(|project_path| project.absolute_path(&project_path, cx))
-                        })
-                        .log_err()
-                        .flatten()
-                    {
-                        add_paths_to_terminal(pane, &[entry_path], cx);
-                    }
-                } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
-                    add_paths_to_terminal(pane, paths.paths(), cx);
-                }
-
-                ControlFlow::Break(())
-            });
-            let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
-            pane.toolbar()
-                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
-            pane
-        });
-        let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
-        ];
-        let this = Self {
-            pane,
+        let pane = new_terminal_pane(workspace.weak_handle(), workspace.project().clone(), cx);
+        let mut this = Self {
+            center: PaneGroup::new(pane.clone()),
+            active_pane: pane.clone(),
             fs: workspace.app_state().fs.clone(),
             workspace: workspace.weak_handle(),
             pending_serialization: Task::ready(None),
@@ -168,8 +85,10 @@ impl TerminalPanel {
             height: None,
             pending_terminals_to_add: 0,
             deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
+            pane_subscriptions: HashMap::default(),
+            _subscriptions: Vec::new(),
         };
+        this.subscribe_to_pane(&pane, cx);
         this
     }
 
@@ -188,34 +107,62 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, pane_items) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
-            let items = if let Some(serialized_panel) = serialized_panel.as_ref() {
+            let mut pane_items = Vec::new();
+            if let Some(serialized_panel) = serialized_panel.as_ref() {
                 panel.update(cx, |panel, cx| {
                     cx.notify();
                     panel.height = serialized_panel.height.map(|h| h.round());
                     panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    workspace.database_id(),
-                                    *item_id,
-                                    cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
+                    match &serialized_panel.panes {
+                        Some(serialized_panes) => {
+                            if let Some(layout) = deserialize_pane_layout(serialized_panes) {
+                                let old_pane = panel.active_pane.clone();
+                                panel.pane_subscriptions.remove(&old_pane.entity_id());
+                                let mut active_pane = None;
+                                let layout = layout.map(&mut |serialized_pane| {
+                                    let pane = new_terminal_pane(
+                                        workspace.weak_handle(),
+                                        workspace.project().clone(),
+                                        cx,
+                                    );
+                                    panel.subscribe_to_pane(&pane, cx);
+                                    let items = deserialize_terminals(
+                                        &pane,
+                                        &serialized_pane.items,
+                                        workspace,
+                                        cx,
+                                    );
+                                    pane_items.push((
+                                        pane.clone(),
+                                        items,
+                                        serialized_pane.active_item_id,
+                                    ));
+                                    if serialized_pane.active {
+                                        active_pane = Some(pane.clone());
+                                    }
+                                    pane
+                                });
+                                panel.center = PaneGroup::from_layout(layout);
+                                panel.active_pane =
+                                    active_pane.unwrap_or_else(|| panel.center.panes()[0].clone());
+                            }
+                        }
+                        None => {
+                            let pane = panel.active_pane.clone();
+                            let items = deserialize_terminals(
+                                &pane,
+                                &serialized_panel.items,
+                                workspace,
+                                cx,
+                            );
+                            pane_items.push((pane, items, serialized_panel.active_item_id));
+                        }
+                    }
                 })
-            } else {
-                Vec::new()
-            };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+            }
+            (panel, pane_items)
         })?;
 
         if let Some(workspace) = workspace.upgrade() {
@@ -233,47 +180,102 @@ impl TerminalPanel {
                 .ok();
         }
 
-        let pane = pane.downgrade();
-        let items = futures::future::join_all(items).await;
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
-                if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
+        for (pane, items, active_item_id) in pane_items {
+            let items = futures::future::join_all(items).await;
+            pane.update(&mut cx, |pane, cx| {
+                let mut active_ix = None;
+                for item in items {
+                    if let Some(item) = item.log_err() {
+                        let item_id = item.entity_id().as_u64();
+                        pane.add_item(Box::new(item), false, false, None, cx);
+                        if Some(item_id) == active_item_id {
+                            active_ix = Some(pane.items_len() - 1);
+                        }
                     }
                 }
-            }
 
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
+                if let Some(active_ix) = active_ix {
+                    pane.activate_item(active_ix, false, false, cx)
+                }
+            })?;
+        }
+
+        panel.update(&mut cx, |panel, cx| {
+            // Drop the splits whose terminals could not be restored.
+            let empty_panes = panel
+                .center
+                .panes()
+                .into_iter()
+                .filter(|pane| pane.read(cx).items_len() == 0)
+                .cloned()
+                .collect::<Vec<_>>();
+            for pane in empty_panes {
+                panel.remove_pane(&pane, cx);
             }
+            panel.serialize(cx);
         })?;
 
         Ok(panel)
     }
 
+    fn subscribe_to_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        self.pane_subscriptions.insert(
+            pane.entity_id(),
+            vec![
+                cx.observe(pane, |_, _, cx| cx.notify()),
+                cx.subscribe(pane, Self::handle_pane_event),
+            ],
+        );
+    }
+
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemoveItem { .. } => self.serialize(cx),
-            pane::Event::Remove => cx.emit(PanelEvent::Close),
-            pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
+            pane::Event::Remove => {
+                if self.center.panes().len() > 1 {
+                    self.remove_pane(&pane, cx);
+                    cx.focus_view(&self.active_pane);
+                } else {
+                    cx.emit(PanelEvent::Close);
+                }
+            }
+            pane::Event::Split(direction) => {
+                let working_directory = pane
+                    .read(cx)
+                    .active_item()
+                    .and_then(|item| item.downcast::<TerminalView>())
+                    .and_then(|terminal_view| terminal_view.read(cx).terminal().read(cx).get_cwd());
+                if let Some(new_pane) = self.split_pane(&pane, *direction, cx) {
+                    self.active_pane = new_pane.clone();
+                    let terminal = self.add_terminal(working_directory, None, cx);
+                    cx.spawn(|_, mut cx| async move {
+                        terminal.await?;
+                        new_pane.update(&mut cx, |new_pane, cx| new_pane.focus(cx))
+                    })
+                    .detach_and_log_err(cx);
+                }
+            }
+            pane::Event::Focus => {
+                if self.active_pane != pane {
+                    self.active_pane = pane;
+                    self.serialize(cx);
+                    cx.notify();
+                }
+            }
+            pane::Event::ZoomIn => {
+                self.active_pane = pane;
+                cx.emit(PanelEvent::ZoomIn);
+            }
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
 
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
@@ -282,6 +284,94 @@ impl TerminalPanel {
         }
     }
 
+    /// Creates an empty split next to the given pane, in the given direction.
+    fn split_pane(
+        &mut self,
+        pane: &View<Pane>,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Pane>> {
+        let project = self.workspace.upgrade()?.read(cx).project().clone();
+        let new_pane = new_terminal_pane(self.workspace.clone(), project, cx);
+        self.center.split(pane, &new_pane, direction).log_err()?;
+        self.subscribe_to_pane(&new_pane, cx);
+        self.serialize(cx);
+        cx.notify();
+        Some(new_pane)
+    }
+
+    fn remove_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        if self.center.remove(pane).log_err() != Some(true) {
+            return;
+        }
+        self.pane_subscriptions.remove(&pane.entity_id());
+        if &self.active_pane == pane {
+            self.active_pane = self.center.panes()[0].clone();
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    /// Returns the split at the given edge of the panel, splitting the panel there if there is no such split.
+    fn pane_at_edge(&mut self, split: TerminalSplit, cx: &mut ViewContext<Self>) -> View<Pane> {
+        let direction = match split {
+            TerminalSplit::Up => SplitDirection::Up,
+            TerminalSplit::Down => SplitDirection::Down,
+            TerminalSplit::Left => SplitDirection::Left,
+            TerminalSplit::Right => SplitDirection::Right,
+        };
+
+        let (edge_pane, split_exists) = self.center.pane_at_edge(direction);
+        if split_exists {
+            edge_pane
+        } else {
+            self.split_pane(&edge_pane, direction, cx)
+                .unwrap_or(edge_pane)
+        }
+    }
+
+    fn find_pane_in_direction(
+        &self,
+        direction: SplitDirection,
+        cx: &WindowContext,
+    ) -> Option<View<Pane>> {
+        let bounding_box = self.center.bounding_box_for_pane(&self.active_pane)?;
+        let cursor = self.active_pane.read(cx).pixel_position_of_cursor(cx);
+        let center = match cursor {
+            Some(cursor) if bounding_box.contains(&cursor) => cursor,
+            _ => bounding_box.center(),
+        };
+
+        let distance_to_next = px(HANDLE_HITBOX_SIZE);
+        let target = match direction {
+            SplitDirection::Left => point(bounding_box.left() - distance_to_next, center.y),
+            SplitDirection::Right => point(bounding_box.right() + distance_to_next, center.y),
+            SplitDirection::Up => point(center.x, bounding_box.top() - distance_to_next),
+            SplitDirection::Down => point(center.x, bounding_box.bottom() + distance_to_next),
+        };
+        self.center.pane_at_pixel_position(target).cloned()
+    }
+
+    fn activate_pane_in_direction(
+        &mut self,
+        action: &ActivatePaneInDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match self.find_pane_in_direction(action.0, cx) {
+            Some(pane) => cx.focus_view(&pane),
+            // Let the workspace move the focus out of the panel.
+            None => cx.propagate(),
+        }
+    }
+
+    fn swap_pane_in_direction(&mut self, action: &SwapPaneInDirection, cx: &mut ViewContext<Self>) {
+        if let Some(to) = self.find_pane_in_direction(action.0, cx) {
+            self.center.swap(&self.active_pane, &to);
+            self.serialize(cx);
+            cx.notify();
+        }
+    }
+
     pub fn open_terminal(
         workspace: &mut Workspace,
         action: &workspace::OpenTerminal,
@@ -293,10 +383,121 @@ impl TerminalPanel {
 
         this.update(cx, |this, cx| {
             this.add_terminal(Some(action.working_directory.clone()), None, cx)
+                .detach_and_log_err(cx)
         })
     }
 
     pub fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        if spawn_in_terminal.dependencies.is_empty()
+            && spawn_in_terminal.execution == ExecutionMode::Terminal
+        {
+            self.spawn_task_terminal(spawn_in_terminal, cx).detach();
+        } else {
+            self.run_task(spawn_in_terminal.clone(), cx).detach();
+        }
+    }
+
+    /// Runs the tasks the given one depends on, then the task itself, resolving to whether all of them succeeded.
+    /// The task is not started if any of its dependencies fails, and a task that several others
+    /// depend on is only run once.
+    fn run_task(
+        &mut self,
+        spawn_in_terminal: SpawnInTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<bool> {
+        cx.spawn(|terminal_panel, mut cx| async move {
+            let failed_task = Rc::new(RefCell::new(None));
+            let run = {
+                let terminal_panel = terminal_panel.clone();
+                let cx = cx.clone();
+                let failed_task = failed_task.clone();
+                Rc::new(move |spawn_in_terminal: SpawnInTerminal| {
+                    let terminal_panel = terminal_panel.clone();
+                    let failed_task = failed_task.clone();
+                    let mut cx = cx.clone();
+                    async move {
+                        let label = spawn_in_terminal.label.clone();
+                        let succeeded =
+                            Self::run_single_task(terminal_panel, spawn_in_terminal, &mut cx).await;
+                        if !succeeded {
+                            failed_task.borrow_mut().get_or_insert(label);
+                        }
+                        succeeded
+                    }
+                    .boxed_local()
+                }) as TaskRunner
+            };
+            let label = spawn_in_terminal.label.clone();
+            let succeeded = run_task_graph(spawn_in_terminal, &mut HashMap::default(), &run).await;
+
+            // Report a failed dependency once, however many tasks depended on it.
+            let failed_task = failed_task.borrow_mut().take();
+            if let Some(failed_task) = failed_task.filter(|failed_task| *failed_task != label) {
+                terminal_panel
+                    .update(&mut cx, |terminal_panel, cx| {
+                        let message =
+                            format!("Task \"{label}\" was not run, as \"{failed_task}\" failed");
+                        terminal_panel.workspace.update(cx, |workspace, cx| {
+                            workspace
+                                .show_toast(Toast::new(TASK_DEPENDENCY_FAILURE_ID, message), cx)
+                        })
+                    })
+                    .ok();
+            }
+            succeeded
+        })
+    }
+
+    /// Runs the given task on its own, ignoring its dependencies.
+    async fn run_single_task(
+        terminal_panel: WeakView<Self>,
+        spawn_in_terminal: SpawnInTerminal,
+        cx: &mut AsyncWindowContext,
+    ) -> bool {
+        if spawn_in_terminal.command.is_empty() {
+            return true;
+        }
+        if spawn_in_terminal.execution == ExecutionMode::Headless {
+            let Ok(Ok(run)) = terminal_panel.update(cx, |terminal_panel, cx| {
+                terminal_panel.workspace.update(cx, |workspace, cx| {
+                    workspace.project().update(cx, |project, cx| {
+                        project.run_headless_task(&spawn_in_terminal, cx)
+                    })
+                })
+            }) else {
+                return false;
+            };
+            return run.await.log_err().unwrap_or(false);
+        }
+
+        let Ok(terminal) = terminal_panel.update(cx, |terminal_panel, cx| {
+            terminal_panel.spawn_task_terminal(&spawn_in_terminal, cx)
+        }) else {
+            return false;
+        };
+        let Some(terminal) = terminal.await else {
+            return false;
+        };
+        let Ok(task_completed) =
+            terminal.update(cx, |terminal, cx| terminal.wait_for_completed_task(cx))
+        else {
+            return false;
+        };
+        task_completed.await;
+        terminal
+            .update(cx, |terminal, _| {
+                terminal.task().map_or(false, |task| {
+                    task.status == TaskStatus::Completed { success: true }
+                })
+            })
+            .unwrap_or(false)
+    }
+
+    fn spawn_task_terminal(
+        &mut self,
+        spawn_in_terminal: &SpawnInTerminal,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Option<Model<Terminal>>> {
         let mut spawn_task = SpawnTask {
             id: spawn_in_terminal.id.clone(),
             label: spawn_in_terminal.label.clone(),
@@ -304,6 +505,7 @@ impl TerminalPanel {
             args: spawn_in_terminal.args.clone(),
             env: spawn_in_terminal.env.clone(),
             reveal: spawn_in_terminal.reveal,
+            problem_matchers: spawn_in_terminal.problem_matchers.clone(),
         };
         // Set up shell args unconditionally, as tasks are always spawned inside of a shell.
         let Some((shell, mut user_args)) = (match TerminalSettings::get_global(cx).shell.clone() {
@@ -311,7 +513,7 @@ impl TerminalPanel {
             Shell::Program(shell) => Some((shell, vec![])),
             Shell::WithArguments { program, args } => Some((program, args)),
         }) else {
-            return;
+            return Task::ready(None);
         };
 
         let mut command = std::mem::take(&mut spawn_task.command);
@@ -330,16 +532,14 @@ impl TerminalPanel {
         let use_new_terminal = spawn_in_terminal.use_new_terminal;
 
         if allow_concurrent_runs && use_new_terminal {
-            self.spawn_in_new_terminal(spawn_task, working_directory, cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, working_directory, cx);
         }
 
         let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.id, cx);
         if terminals_for_task.is_empty() {
-            self.spawn_in_new_terminal(spawn_task, working_directory, cx);
-            return;
+            return self.spawn_in_new_terminal(spawn_task, working_directory, cx);
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let (existing_pane, existing_terminal) = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -351,40 +551,38 @@ impl TerminalPanel {
             self.replace_terminal(
                 working_directory,
                 spawn_task,
-                existing_item_index,
+                existing_pane,
                 existing_terminal,
                 cx,
-            );
+            )
         } else {
+            let (terminal_tx, terminal_rx) = oneshot::channel();
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
                 cx.spawn(|terminal_panel, mut cx| async move {
                     wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                    terminal_panel
-                        .update(&mut cx, |terminal_panel, cx| {
-                            if use_new_terminal {
-                                terminal_panel.spawn_in_new_terminal(
-                                    spawn_task,
-                                    working_directory,
-                                    cx,
-                                );
-                            } else {
-                                terminal_panel.replace_terminal(
-                                    working_directory,
-                                    spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
-                                    cx,
-                                );
-                            }
-                        })
-                        .ok();
+                    let Ok(terminal) = terminal_panel.update(&mut cx, |terminal_panel, cx| {
+                        if use_new_terminal {
+                            terminal_panel.spawn_in_new_terminal(spawn_task, working_directory, cx)
+                        } else {
+                            terminal_panel.replace_terminal(
+                                working_directory,
+                                spawn_task,
+                                existing_pane,
+                                existing_terminal,
+                                cx,
+                            )
+                        }
+                    }) else {
+                        return;
+                    };
+                    terminal_tx.send(terminal.await).ok();
                 }),
             );
 
             match reveal {
-                RevealStrategy::Always => {
-                    self.activate_terminal_view(existing_item_index, cx);
+                RevealStrategy::Always | RevealStrategy::Split(_) => {
+                    self.activate_terminal_view(&existing_pane, &existing_terminal, cx);
                     let task_workspace = self.workspace.clone();
                     cx.spawn(|_, mut cx| async move {
                         task_workspace
@@ -395,6 +593,7 @@ impl TerminalPanel {
                 }
                 RevealStrategy::Never => {}
             }
+            cx.spawn(|_, _| async move { terminal_rx.await.ok().flatten() })
         }
     }
 
@@ -403,11 +602,15 @@ impl TerminalPanel {
         spawn_task: SpawnTask,
         working_directory: Option<PathBuf>,
         cx: &mut ViewContext<Self>,
-    ) {
+    ) -> Task<Option<Model<Terminal>>> {
         let reveal = spawn_task.reveal;
-        self.add_terminal(working_directory, Some(spawn_task), cx);
+        let pane = match reveal {
+            RevealStrategy::Split(split) => self.pane_at_edge(split, cx),
+            RevealStrategy::Always | RevealStrategy::Never => self.active_pane.clone(),
+        };
+        let terminal = self.add_terminal_to_pane(pane, working_directory, Some(spawn_task), cx);
         match reveal {
-            RevealStrategy::Always => {
+            RevealStrategy::Always | RevealStrategy::Split(_) => {
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -418,6 +621,7 @@ impl TerminalPanel {
             }
             RevealStrategy::Never => {}
         }
+        cx.spawn(|_, _| async move { terminal.await.log_err() })
     }
 
     ///Create a new Terminal in the current working directory or the user's home directory
@@ -430,33 +634,48 @@ impl TerminalPanel {
             return;
         };
 
-        this.update(cx, |this, cx| this.add_terminal(None, None, cx))
+        this.update(cx, |this, cx| {
+            this.add_terminal(None, None, cx).detach_and_log_err(cx)
+        })
     }
 
     fn terminals_for_task(
         &self,
         id: &TaskId,
         cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
-                let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
-                if &task_state.id == id {
-                    Some((index, terminal_view))
-                } else {
-                    None
-                }
+    ) -> Vec<(View<Pane>, View<TerminalView>)> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| {
+                pane.read(cx)
+                    .items()
+                    .filter_map(|item| item.act_as::<TerminalView>(cx))
+                    .map(|terminal_view| (pane.clone(), terminal_view))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|(_, terminal_view)| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .map_or(false, |task_state| &task_state.id == id)
             })
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
-            pane.activate_item(item_index, true, true, cx)
+    fn activate_terminal_view(
+        &mut self,
+        pane: &View<Pane>,
+        terminal_view: &View<TerminalView>,
+        cx: &mut WindowContext,
+    ) {
+        self.active_pane = pane.clone();
+        pane.update(cx, |pane, cx| {
+            if let Some(item_index) = pane.index_for_item(terminal_view) {
+                pane.activate_item(item_index, true, true, cx)
+            }
         })
     }
 
@@ -465,29 +684,41 @@ impl TerminalPanel {
         working_directory: Option<PathBuf>,
         spawn_task: Option<SpawnTask>,
         cx: &mut ViewContext<Self>,
-    ) {
+    ) -> Task<Result<Model<Terminal>>> {
+        let pane = self.active_pane.clone();
+        self.add_terminal_to_pane(pane, working_directory, spawn_task, cx)
+    }
+
+    fn add_terminal_to_pane(
+        &mut self,
+        pane: View<Pane>,
+        working_directory: Option<PathBuf>,
+        spawn_task: Option<SpawnTask>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<Model<Terminal>>> {
         let workspace = self.workspace.clone();
         self.pending_terminals_to_add += 1;
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
-            workspace.update(&mut cx, |workspace, cx| {
-                let working_directory = if let Some(working_directory) = working_directory {
-                    Some(working_directory)
-                } else {
-                    let working_directory_strategy =
-                        TerminalSettings::get_global(cx).working_directory.clone();
-                    crate::get_working_directory(workspace, cx, working_directory_strategy)
-                };
+            let terminal = async {
+                let terminal = workspace.update(&mut cx, |workspace, cx| {
+                    let working_directory = if let Some(working_directory) = working_directory {
+                        Some(working_directory)
+                    } else {
+                        let working_directory_strategy =
+                            TerminalSettings::get_global(cx).working_directory.clone();
+                        crate::get_working_directory(workspace, cx, working_directory_strategy)
+                    };
 
-                let window = cx.window_handle();
-                if let Some(terminal) = workspace.project().update(cx, |project, cx| {
-                    project
-                        .create_terminal(working_directory, spawn_task, window, cx)
-                        .log_err()
-                }) {
-                    let terminal = Box::new(cx.new_view(|cx| {
+                    let window = cx.window_handle();
+                    workspace.project().update(cx, |project, cx| {
+                        project.create_terminal(working_directory, spawn_task, window, cx)
+                    })
+                })?;
+                let terminal = terminal.await?;
+                workspace.update(&mut cx, |workspace, cx| {
+                    let terminal_view = Box::new(cx.new_view(|cx| {
                         TerminalView::new(
-                            terminal,
+                            terminal.clone(),
                             workspace.weak_handle(),
                             workspace.database_id(),
                             cx,
@@ -495,42 +726,28 @@ impl TerminalPanel {
                     }));
                     pane.update(cx, |pane, cx| {
                         let focus = pane.has_focus(cx);
-                        pane.add_item(terminal, true, focus, None, cx);
+                        pane.add_item(terminal_view, true, focus, None, cx);
                     });
-                }
-            })?;
+                })?;
+                anyhow::Ok(terminal)
+            }
+            .await;
             terminal_panel.update(&mut cx, |this, cx| {
                 this.pending_terminals_to_add = this.pending_terminals_to_add.saturating_sub(1);
                 this.serialize(cx)
             })?;
-            anyhow::Ok(())
+            terminal
         })
-        .detach_and_log_err(cx);
     }
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
-        let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
-                }
-            })
-            .collect::<Vec<_>>();
-        let active_item_id = self
-            .pane
-            .read(cx)
-            .active_item()
-            .map(|item| item.item_id().as_u64())
-            .filter(|active_id| items_to_serialize.contains(active_id));
+        let mut items = Vec::new();
+        let panes = serialize_pane_layout(
+            self.center
+                .layout()
+                .map(&mut |pane| serialize_pane(&pane, &self.active_pane, &mut items, cx)),
+        );
+        let active_item_id = serialized_active_item_id(&self.active_pane, &items, cx);
         let height = self.height;
         let width = self.width;
         self.pending_serialization = cx.background_executor().spawn(
@@ -543,6 +760,7 @@ impl TerminalPanel {
                             active_item_id,
                             height,
                             width,
+                            panes: Some(panes),
                         })?,
                     )
                     .await?;
@@ -553,51 +771,347 @@ impl TerminalPanel {
     }
 
     fn replace_terminal(
-        &self,
+        &mut self,
         working_directory: Option<PathBuf>,
         spawn_task: SpawnTask,
-        terminal_item_index: usize,
+        terminal_pane: View<Pane>,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
-    ) -> Option<()> {
-        let project = self
+    ) -> Task<Option<Model<Terminal>>> {
+        let Ok(project) = self
             .workspace
             .update(cx, |workspace, _| workspace.project().clone())
-            .ok()?;
+        else {
+            return Task::ready(None);
+        };
         let reveal = spawn_task.reveal;
         let window = cx.window_handle();
         let new_terminal = project.update(cx, |project, cx| {
-            project
-                .create_terminal(working_directory, Some(spawn_task), window, cx)
-                .log_err()
-        })?;
-        terminal_to_replace.update(cx, |terminal_to_replace, cx| {
-            terminal_to_replace.set_terminal(new_terminal, cx);
+            project.create_terminal(working_directory, Some(spawn_task), window, cx)
         });
+        cx.spawn(|terminal_panel, mut cx| async move {
+            let new_terminal = new_terminal.await.log_err()?;
+            terminal_panel
+                .update(&mut cx, |terminal_panel, cx| {
+                    terminal_to_replace.update(cx, |terminal_to_replace, cx| {
+                        terminal_to_replace.set_terminal(new_terminal.clone(), cx);
+                    });
 
-        match reveal {
-            RevealStrategy::Always => {
-                self.activate_terminal_view(terminal_item_index, cx);
-                let task_workspace = self.workspace.clone();
-                cx.spawn(|_, mut cx| async move {
-                    task_workspace
-                        .update(&mut cx, |workspace, cx| workspace.focus_panel::<Self>(cx))
-                        .ok()
+                    match reveal {
+                        RevealStrategy::Always | RevealStrategy::Split(_) => {
+                            terminal_panel.activate_terminal_view(
+                                &terminal_pane,
+                                &terminal_to_replace,
+                                cx,
+                            );
+                            let task_workspace = terminal_panel.workspace.clone();
+                            cx.spawn(|_, mut cx| async move {
+                                task_workspace
+                                    .update(&mut cx, |workspace, cx| {
+                                        workspace.focus_panel::<Self>(cx)
+                                    })
+                                    .ok()
+                            })
+                            .detach();
+                        }
+                        RevealStrategy::Never => {}
+                    }
                 })
-                .detach();
+                .ok()?;
+            Some(new_terminal)
+        })
+    }
+
+    /// The split of the panel that was focused last.
+    pub fn pane(&self) -> &View<Pane> {
+        &self.active_pane
+    }
+}
+
+type TaskRunner = Rc<dyn Fn(SpawnInTerminal) -> LocalBoxFuture<'static, bool>>;
+type TaskRun = Shared<LocalBoxFuture<'static, bool>>;
+
+/// Builds the run of a task and its dependencies, resolving to whether all of them succeeded.
+///
+/// A task that several others depend on (directly or not) is only run once, and all of
+/// them wait for that same run. Dependencies only start once they are awaited, so that
+/// `DependsOrder::Sequence` runs them one after another.
+fn run_task_graph(
+    spawn_in_terminal: SpawnInTerminal,
+    runs: &mut HashMap<TaskId, TaskRun>,
+    run: &TaskRunner,
+) -> TaskRun {
+    if let Some(existing_run) = runs.get(&spawn_in_terminal.id) {
+        return existing_run.clone();
+    }
+
+    let dependency_runs = spawn_in_terminal
+        .dependencies
+        .iter()
+        .map(|dependency| run_task_graph(dependency.clone(), runs, run))
+        .collect::<Vec<_>>();
+    let id = spawn_in_terminal.id.clone();
+    let run = run.clone();
+    let task_run = async move {
+        let dependencies_succeeded = match spawn_in_terminal.depends_order {
+            DependsOrder::Parallel => join_all(dependency_runs)
+                .await
+                .into_iter()
+                .all(|succeeded| succeeded),
+            DependsOrder::Sequence => {
+                let mut succeeded = true;
+                for dependency_run in dependency_runs {
+                    if !dependency_run.await {
+                        succeeded = false;
+                        break;
+                    }
+                }
+                succeeded
             }
-            RevealStrategy::Never => {}
-        }
+        };
+        dependencies_succeeded && run(spawn_in_terminal).await
+    }
+    .boxed_local()
+    .shared();
+    runs.insert(id, task_run.clone());
+    task_run
+}
 
-        Some(())
+fn new_terminal_pane(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    cx: &mut ViewContext<TerminalPanel>,
+) -> View<Pane> {
+    let terminal_panel = cx.view().downgrade();
+    cx.new_view(|cx| {
+        let mut pane = Pane::new(
+            workspace.clone(),
+            project,
+            Default::default(),
+            None,
+            NewTerminal.boxed_clone(),
+            cx,
+        );
+        pane.set_can_navigate(false, cx);
+        pane.display_nav_history_buttons(false);
+        pane.set_render_tab_bar_buttons(cx, {
+            let terminal_panel = terminal_panel.clone();
+            move |pane, cx| {
+                let terminal_panel = terminal_panel.clone();
+                let this_pane = cx.view().clone();
+                h_flex()
+                    .gap_2()
+                    .child(
+                        IconButton::new("plus", IconName::Plus)
+                            .icon_size(IconSize::Small)
+                            .on_click(move |_, cx| {
+                                terminal_panel
+                                    .update(cx, |panel, cx| {
+                                        panel.active_pane = this_pane.clone();
+                                        panel.add_terminal(None, None, cx).detach_and_log_err(cx)
+                                    })
+                                    .log_err();
+                            })
+                            .tooltip(|cx| Tooltip::text("New Terminal", cx)),
+                    )
+                    .child({
+                        let zoomed = pane.is_zoomed();
+                        IconButton::new("toggle_zoom", IconName::Maximize)
+                            .icon_size(IconSize::Small)
+                            .selected(zoomed)
+                            .selected_icon(IconName::Minimize)
+                            .on_click(cx.listener(|pane, _, cx| {
+                                pane.toggle_zoom(&workspace::ToggleZoom, cx);
+                            }))
+                            .tooltip(move |cx| {
+                                Tooltip::text(if zoomed { "Zoom Out" } else { "Zoom In" }, cx)
+                            })
+                    })
+                    .into_any_element()
+            }
+        });
+
+        pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
+            if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
+                let item = if &tab.pane == cx.view() {
+                    pane.item_for_index(tab.ix)
+                } else {
+                    tab.pane.read(cx).item_for_index(tab.ix)
+                };
+                if let Some(item) = item {
+                    if item.downcast::<TerminalView>().is_some() {
+                        let Some(direction) = pane.drag_split_direction() else {
+                            return ControlFlow::Continue(());
+                        };
+                        // Split the terminal panel rather than the workspace center.
+                        let to_pane = cx.view().clone();
+                        let from_pane = tab.pane.clone();
+                        let item_id = item.item_id();
+                        let terminal_panel = terminal_panel.clone();
+                        let workspace = workspace.clone();
+                        cx.spawn(|_, mut cx| async move {
+                            terminal_panel.update(&mut cx, |panel, cx| {
+                                let Some(new_pane) = panel.split_pane(&to_pane, direction, cx)
+                                else {
+                                    return;
+                                };
+                                panel.active_pane = new_pane.clone();
+                                workspace
+                                    .update(cx, |workspace, cx| {
+                                        workspace.move_item(from_pane, new_pane, item_id, 0, cx)
+                                    })
+                                    .log_err();
+                            })
+                        })
+                        .detach_and_log_err(cx);
+                    } else if let Some(project_path) = item.project_path(cx) {
+                        if let Some(entry_path) = workspace
+                            .update(cx, |workspace, cx| {
+                                workspace
+                                    .project()
+                                    .read(cx)
+                                    .absolute_path(&project_path, cx)
+                            })
+                            .log_err()
+                            .flatten()
+                        {
+                            add_paths_to_terminal(pane, &[entry_path], cx);
+                        }
+                    }
+                }
+            } else if let Some(&entry_id) = dropped_item.downcast_ref::<ProjectEntryId>() {
+                if let Some(entry_path) = workspace
+                    .update(cx, |workspace, cx| {
+                        let project = workspace.project().read(cx);
+                        project
+                            .path_for_entry(entry_id, cx)
+                            .and_then(|project_path| project.absolute_path(&project_path, cx))
+                    })
+                    .log_err()
+                    .flatten()
+                {
+                    add_paths_to_terminal(pane, &[entry_path], cx);
+                }
+            } else if let Some(paths) = dropped_item.downcast_ref::<ExternalPaths>() {
+                add_paths_to_terminal(pane, paths.paths(), cx);
+            }
+
+            ControlFlow::Break(())
+        });
+        let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
+        pane.toolbar()
+            .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+        pane
+    })
+}
+
+fn deserialize_terminals(
+    pane: &View<Pane>,
+    item_ids: &[u64],
+    workspace: &Workspace,
+    cx: &mut WindowContext,
+) -> Vec<Task<Result<View<TerminalView>>>> {
+    pane.update(cx, |_, cx| {
+        item_ids
+            .iter()
+            .map(|item_id| {
+                TerminalView::deserialize(
+                    workspace.project().clone(),
+                    workspace.weak_handle(),
+                    workspace.database_id(),
+                    *item_id,
+                    cx,
+                )
+            })
+            .collect()
+    })
+}
+
+fn serialize_pane(
+    pane: &View<Pane>,
+    active_pane: &View<Pane>,
+    all_items: &mut Vec<u64>,
+    cx: &WindowContext,
+) -> SerializedPane {
+    // Task terminals are not restored, as their tasks are not rerun.
+    let items = pane
+        .read(cx)
+        .items()
+        .filter_map(|item| {
+            let terminal_view = item.act_as::<TerminalView>(cx)?;
+            if terminal_view.read(cx).terminal().read(cx).task().is_some() {
+                None
+            } else {
+                Some(item.item_id().as_u64())
+            }
+        })
+        .collect::<Vec<_>>();
+    all_items.extend(items.iter().copied());
+    SerializedPane {
+        active_item_id: serialized_active_item_id(pane, &items, cx),
+        items,
+        active: pane == active_pane,
     }
-    pub fn pane(&self) -> &View<Pane> {
-        &self.pane
+}
+
+fn serialize_pane_layout(layout: PaneLayout<SerializedPane>) -> SerializedPaneGroup {
+    match layout {
+        PaneLayout::Split {
+            axis,
+            flexes,
+            members,
+        } => SerializedPaneGroup::Group {
+            axis: match axis {
+                Axis::Horizontal => SerializedAxis::Horizontal,
+                Axis::Vertical => SerializedAxis::Vertical,
+            },
+            flexes,
+            children: members.into_iter().map(serialize_pane_layout).collect(),
+        },
+        PaneLayout::Pane(pane) => SerializedPaneGroup::Pane(pane),
     }
 }
 
+/// Drops the empty groups, and replaces the groups of a single member with it.
+fn deserialize_pane_layout(
+    serialized: &SerializedPaneGroup,
+) -> Option<PaneLayout<&SerializedPane>> {
+    match serialized {
+        SerializedPaneGroup::Group {
+            axis,
+            flexes,
+            children,
+        } => {
+            let mut members = children
+                .iter()
+                .filter_map(deserialize_pane_layout)
+                .collect::<Vec<_>>();
+            match members.len() {
+                0 => None,
+                1 => members.pop(),
+                len => Some(PaneLayout::Split {
+                    axis: match axis {
+                        SerializedAxis::Horizontal => Axis::Horizontal,
+                        SerializedAxis::Vertical => Axis::Vertical,
+                    },
+                    flexes: flexes.clone().filter(|flexes| flexes.len() == len),
+                    members,
+                }),
+            }
+        }
+        SerializedPaneGroup::Pane(pane) => Some(PaneLayout::Pane(pane)),
+    }
+}
+
+fn serialized_active_item_id(pane: &View<Pane>, items: &[u64], cx: &WindowContext) -> Option<u64> {
+    pane.read(cx)
+        .active_item()
+        .map(|item| item.item_id().as_u64())
+        .filter(|active_id| items.contains(active_id))
+}
+
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<(View<Pane>, View<TerminalView>)>,
     cx: &mut AsyncWindowContext,
 ) {
     let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
@@ -635,7 +1149,7 @@ impl Render for TerminalPanel {
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -644,13 +1158,34 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        let panel = registrar
+            .into_div()
+            .size_full()
+            .on_action(cx.listener(Self::activate_pane_in_direction))
+            .on_action(cx.listener(Self::swap_pane_in_direction));
+        if self.active_pane.read(cx).is_zoomed() {
+            return panel.child(self.active_pane.clone());
+        }
+        let Some(workspace) = self.workspace.upgrade() else {
+            return panel;
+        };
+        let center = workspace.update(cx, |workspace, cx| {
+            self.center
+                .render_in_panel(
+                    workspace.project(),
+                    &self.active_pane,
+                    workspace.app_state(),
+                    cx,
+                )
+                .into_any_element()
+        });
+        panel.child(center)
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -698,21 +1233,36 @@ impl Panel for TerminalPanel {
     }
 
     fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+        self.active_pane.read(cx).is_zoomed()
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        for pane in self.center.panes() {
+            let zoomed = zoomed && pane == &self.active_pane;
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
-        if active && self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0 {
-            self.add_terminal(None, None, cx)
+        if active
+            && self
+                .center
+                .panes()
+                .into_iter()
+                .all(|pane| pane.read(cx).items_len() == 0)
+            && self.pending_terminals_to_add == 0
+        {
+            self.add_terminal(None, None, cx).detach_and_log_err(cx)
         }
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self
+            .center
+            .panes()
+            .into_iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum::<usize>();
         if count == 0 {
             None
         } else {
@@ -739,8 +1289,177 @@ impl Panel for TerminalPanel {
 
 #[derive(Serialize, Deserialize)]
 struct SerializedTerminalPanel {
+    /// Terminals of all splits, for the versions that restore a single split.
     items: Vec<u64>,
     active_item_id: Option<u64>,
     width: Option<Pixels>,
     height: Option<Pixels>,
+    #[serde(default)]
+    panes: Option<SerializedPaneGroup>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SerializedPaneGroup {
+    Group {
+        axis: SerializedAxis,
+        flexes: Option<Vec<f32>>,
+        children: Vec<SerializedPaneGroup>,
+    },
+    Pane(SerializedPane),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SerializedPane {
+    items: Vec<u64>,
+    active_item_id: Option<u64>,
+    active: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SerializedAxis {
+    Horizontal,
+    Vertical,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use futures::{executor::block_on, FutureExt};
+    use task::{
+        static_source::{DependsOrder, ExecutionMode, RevealStrategy},
+        SpawnInTerminal, TaskId,
+    };
+
+    use super::{
+        deserialize_pane_layout, run_task_graph, serialize_pane_layout, SerializedAxis,
+        SerializedPane, SerializedPaneGroup, TaskRunner,
+    };
+
+    fn spawn_in_terminal(
+        label: &str,
+        dependencies: Vec<SpawnInTerminal>,
+        depends_order: DependsOrder,
+    ) -> SpawnInTerminal {
+        SpawnInTerminal {
+            id: TaskId(label.to_string()),
+            label: label.to_string(),
+            command: label.to_string(),
+            args: Vec::new(),
+            cwd: None,
+            env: Default::default(),
+            use_new_terminal: false,
+            allow_concurrent_runs: false,
+            reveal: RevealStrategy::default(),
+            problem_matchers: Vec::new(),
+            dependencies,
+            depends_order,
+            execution: ExecutionMode::default(),
+        }
+    }
+
+    #[test]
+    fn test_diamond_dependencies_run_once() {
+        for depends_order in [DependsOrder::Parallel, DependsOrder::Sequence] {
+            for shared_dependency_succeeds in [true, false] {
+                // "deploy" depends on "build" and "test", which both depend on "compile".
+                let compile = spawn_in_terminal("compile", Vec::new(), depends_order);
+                let deploy = spawn_in_terminal(
+                    "deploy",
+                    vec![
+                        spawn_in_terminal("build", vec![compile.clone()], depends_order),
+                        spawn_in_terminal("test", vec![compile], depends_order),
+                    ],
+                    depends_order,
+                );
+
+                let runs = Rc::new(RefCell::new(Vec::new()));
+                let run: TaskRunner = {
+                    let runs = runs.clone();
+                    Rc::new(move |spawn_in_terminal: SpawnInTerminal| {
+                        runs.borrow_mut().push(spawn_in_terminal.label.clone());
+                        let succeeded =
+                            shared_dependency_succeeds || spawn_in_terminal.label != "compile";
+                        async move { succeeded }.boxed_local()
+                    })
+                };
+                let succeeded = block_on(run_task_graph(deploy, &mut Default::default(), &run));
+
+                assert_eq!(succeeded, shared_dependency_succeeds);
+                let runs = runs.borrow();
+                assert_eq!(
+                    runs.iter().filter(|label| *label == "compile").count(),
+                    1,
+                    "{depends_order:?}: {runs:?}"
+                );
+                if shared_dependency_succeeds {
+                    assert_eq!(runs.len(), 4, "{depends_order:?}: {runs:?}");
+                    assert_eq!(runs.last().map(String::as_str), Some("deploy"));
+                } else {
+                    assert_eq!(*runs, ["compile"], "{depends_order:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialized_pane_group_round_trip() {
+        let pane = |item: u64, active: bool| {
+            SerializedPaneGroup::Pane(SerializedPane {
+                items: vec![item, item + 1],
+                active_item_id: Some(item + 1),
+                active,
+            })
+        };
+        let group = |axis, flexes: Option<Vec<f32>>, children| SerializedPaneGroup::Group {
+            axis,
+            flexes,
+            children,
+        };
+        let restore = |serialized: &SerializedPaneGroup| {
+            deserialize_pane_layout(serialized)
+                .map(|layout| serialize_pane_layout(layout.map(&mut SerializedPane::clone)))
+        };
+
+        let serialized = group(
+            SerializedAxis::Horizontal,
+            Some(vec![0.5, 1.5]),
+            vec![
+                pane(1, false),
+                group(
+                    SerializedAxis::Vertical,
+                    None,
+                    vec![pane(3, true), pane(5, false), pane(7, false)],
+                ),
+            ],
+        );
+        let json = serde_json::to_string(&serialized).unwrap();
+        let deserialized = serde_json::from_str::<SerializedPaneGroup>(&json).unwrap();
+        assert_eq!(deserialized, serialized);
+        assert_eq!(restore(&deserialized), Some(serialized));
+
+        // Groups left with a single split are replaced by it, and sizes of the wrong splits are dropped.
+        assert_eq!(
+            restore(&group(
+                SerializedAxis::Vertical,
+                Some(vec![1., 1., 1.]),
+                vec![
+                    group(SerializedAxis::Horizontal, None, vec![pane(1, true)]),
+                    group(SerializedAxis::Horizontal, None, Vec::new()),
+                    pane(3, false),
+                ],
+            )),
+            Some(group(
+                SerializedAxis::Vertical,
+                None,
+                vec![pane(1, true), pane(3, false)],
+            ))
+        );
+        assert_eq!(
+            restore(&group(SerializedAxis::Vertical, None, Vec::new())),
+            None
+        );
+    }
 }
@@ -6,9 +6,10 @@ use collections::HashSet;
 use editor::{scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    anchored, deferred, div, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
-    MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext, WeakView,
+    anchored, deferred, div, impl_actions, AnyElement, AppContext, ClipboardItem, DismissEvent,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model,
+    MouseButton, MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext,
+    WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
@@ -19,8 +20,9 @@ use terminal::{
         index::Point,
         term::{search::RegexSearch, TermMode},
     },
-    terminal_settings::{TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ShowCharacterPalette, TaskStatus, Terminal,
+    terminal_settings::{TerminalBlink, TerminalSessions, TerminalSettings, WorkingDirectory},
+    Clear, Copy, CopyLastCommandOutput, Event, MaybeNavigationTarget, Paste, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ShowCharacterPalette, TaskStatus, Terminal, TerminalSession,
 };
 use terminal_element::TerminalElement;
 use ui::{h_flex, prelude::*, ContextMenu, Icon, IconName, Label};
@@ -30,7 +32,7 @@ use workspace::{
     notifications::NotifyResultExt,
     register_deserializable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
-    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, Workspace,
+    CloseActiveItem, ItemId, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, Workspace,
     WorkspaceId,
 };
 
@@ -53,6 +55,11 @@ const REGEX_SPECIAL_CHARS: &[char] = &[
 
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 
+/// How many of the last lines of a terminal to restore it with, after Zed restarts.
+const MAX_RESTORED_SCROLLBACK_LINES: usize = 1_000;
+/// Titles change with every command run, so the session is saved once they settle.
+const SAVE_SESSION_DEBOUNCE: Duration = Duration::from_secs(1);
+
 ///Event to transmit the scroll from the element to the view
 #[derive(Clone, Debug, PartialEq)]
 pub struct ScrollTerminal(pub i32);
@@ -92,6 +99,7 @@ pub struct TerminalView {
     can_navigate_to_selected_word: bool,
     workspace_id: WorkspaceId,
     show_title: bool,
+    pending_session_save: Option<Task<()>>,
     _subscriptions: Vec<Subscription>,
     _terminal_subscriptions: Vec<Subscription>,
 }
@@ -118,24 +126,26 @@ impl TerminalView {
             get_working_directory(workspace, cx, strategy.working_directory.clone());
 
         let window = cx.window_handle();
-        let terminal = workspace
-            .project()
-            .update(cx, |project, cx| {
-                project.create_terminal(working_directory, None, window, cx)
-            })
-            .notify_err(workspace, cx);
-
-        if let Some(terminal) = terminal {
-            let view = cx.new_view(|cx| {
-                TerminalView::new(
-                    terminal,
-                    workspace.weak_handle(),
-                    workspace.database_id(),
-                    cx,
-                )
-            });
-            workspace.add_item_to_active_pane(Box::new(view), cx)
-        }
+        let terminal = workspace.project().update(cx, |project, cx| {
+            project.create_terminal(working_directory, None, window, cx)
+        });
+        cx.spawn(|workspace, mut cx| async move {
+            let terminal = terminal.await.notify_async_err(&mut cx)?;
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    let view = cx.new_view(|cx| {
+                        TerminalView::new(
+                            terminal,
+                            workspace.weak_handle(),
+                            workspace.database_id(),
+                            cx,
+                        )
+                    });
+                    workspace.add_item_to_active_pane(Box::new(view), cx)
+                })
+                .ok()
+        })
+        .detach();
     }
 
     pub fn new(
@@ -154,6 +164,14 @@ impl TerminalView {
         let focus_out = cx.on_focus_out(&focus_handle, |terminal_view, cx| {
             terminal_view.focus_out(cx);
         });
+        // Closing the workspace drops it before its items, which are restored with it, along with their sessions.
+        let release = cx.on_release(|terminal_view, _, cx| {
+            if terminal_view.workspace.upgrade().is_some() {
+                terminal_view.terminal.update(cx, |terminal, _| {
+                    terminal.close_detached_session_on_drop();
+                });
+            }
+        });
 
         Self {
             terminal,
@@ -168,9 +186,11 @@ impl TerminalView {
             can_navigate_to_selected_word: false,
             workspace_id,
             show_title: TerminalSettings::get_global(cx).toolbar.title,
+            pending_session_save: None,
             _subscriptions: vec![
                 focus_in,
                 focus_out,
+                release,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
             ],
             _terminal_subscriptions: terminal_subscriptions,
@@ -197,6 +217,7 @@ impl TerminalView {
     ) {
         let context_menu = ContextMenu::build(cx, |menu, _| {
             menu.action("Clear", Box::new(Clear))
+                .action("Copy Last Command Output", Box::new(CopyLastCommandOutput))
                 .action("Close", Box::new(CloseActiveItem { save_intent: None }))
         });
 
@@ -250,6 +271,28 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn copy_last_command_output(&mut self, _: &CopyLastCommandOutput, cx: &mut ViewContext<Self>) {
+        if let Some(output) = self.terminal.read(cx).last_command_output() {
+            cx.write_to_clipboard(ClipboardItem::new(output));
+        }
+    }
+
     pub fn should_show_cursor(&self, focused: bool, cx: &mut gpui::ViewContext<Self>) -> bool {
         //Don't blink the cursor when not focused, blinking is disabled, or paused
         if !focused
@@ -423,6 +466,9 @@ impl TerminalView {
     }
 
     fn set_terminal(&mut self, terminal: Model<Terminal>, cx: &mut ViewContext<'_, TerminalView>) {
+        self.terminal.update(cx, |terminal, _| {
+            terminal.close_detached_session_on_drop();
+        });
         self._terminal_subscriptions =
             subscribe_for_terminal_events(&terminal, self.workspace.clone(), cx);
         self.terminal = terminal;
@@ -453,21 +499,16 @@ fn subscribe_for_terminal_events(
 
             Event::TitleChanged => {
                 cx.emit(ItemEvent::UpdateTab);
-                let terminal = this.terminal().read(cx);
-                if terminal.task().is_none() {
-                    if let Some(cwd) = terminal.get_cwd() {
-                        let item_id = cx.entity_id();
-                        let workspace_id = this.workspace_id;
-                        cx.background_executor()
-                            .spawn(async move {
-                                TERMINAL_DB
-                                    .save_working_directory(item_id.as_u64(), workspace_id, cwd)
-                                    .await
-                                    .log_err();
-                            })
-                            .detach();
+                this.pending_session_save = Some(cx.spawn(|this, mut cx| async move {
+                    cx.background_executor().timer(SAVE_SESSION_DEBOUNCE).await;
+                    let save = this.update(&mut cx, |this, cx| {
+                        let item_id = cx.entity_id().as_u64();
+                        save_session(item_id, this.workspace_id, this.terminal().read(cx), cx)
+                    });
+                    if let Ok(Some(save)) = save {
+                        save.await;
                     }
-                }
+                }));
             }
 
             Event::NewNavigationTarget(maybe_navigation_target) => {
@@ -590,7 +631,63 @@ fn subscribe_for_terminal_events(
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
             Event::SelectionsChanged => cx.emit(SearchEvent::ActiveMatchChanged),
         });
-    vec![terminal_subscription, terminal_events_subscription]
+    // The scrollback is saved on quit too, as it has changed since the last command, most likely.
+    let view = cx.view().downgrade();
+    let quit_subscription = terminal.update(cx, |_, cx| {
+        cx.on_app_quit(move |terminal, cx| {
+            let save = view.upgrade().and_then(|view| {
+                let workspace_id = view.read(cx).workspace_id;
+                save_session(view.entity_id().as_u64(), workspace_id, terminal, cx)
+            });
+            async move {
+                if let Some(save) = save {
+                    save.await;
+                }
+            }
+        })
+    });
+
+    vec![
+        terminal_subscription,
+        terminal_events_subscription,
+        quit_subscription,
+    ]
+}
+
+/// Saves what is needed to reopen the terminal after Zed restarts, unless it runs a task.
+fn save_session(
+    item_id: ItemId,
+    workspace_id: WorkspaceId,
+    terminal: &Terminal,
+    cx: &AppContext,
+) -> Option<Task<()>> {
+    if terminal.task().is_some() {
+        return None;
+    }
+    let working_directory = terminal.get_cwd();
+    let (shell, title, scrollback) = match TerminalSettings::get_global(cx).sessions {
+        TerminalSessions::Off => (None, None, None),
+        TerminalSessions::Restore | TerminalSessions::Detach => (
+            serde_json::to_string(terminal.shell()).log_err(),
+            Some(terminal.breadcrumb_text.clone()).filter(|title| !title.is_empty()),
+            Some(terminal.restorable_scrollback_text(MAX_RESTORED_SCROLLBACK_LINES)),
+        ),
+    };
+    let detached_session_id = terminal.detached_session_id().map(ToOwned::to_owned);
+    Some(cx.background_executor().spawn(async move {
+        TERMINAL_DB
+            .save_session(
+                item_id,
+                workspace_id,
+                working_directory,
+                shell,
+                title,
+                scrollback,
+                detached_session_id,
+            )
+            .await
+            .log_err();
+    }))
 }
 
 fn possible_open_paths_metadata(
@@ -741,6 +838,9 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::copy))
             .on_action(cx.listener(TerminalView::paste))
             .on_action(cx.listener(TerminalView::clear))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::copy_last_command_output))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
             .on_key_down(cx.listener(Self::key_down))
@@ -898,9 +998,28 @@ impl Item for TerminalView {
                 })
                 .filter(|cwd| !cwd.as_os_str().is_empty());
 
-            let terminal = project.update(&mut cx, |project, cx| {
-                project.create_terminal(cwd, None, window, cx)
-            })??;
+            let restore_session =
+                cx.update(|cx| TerminalSettings::get_global(cx).sessions != TerminalSessions::Off)?;
+            let saved_session = TERMINAL_DB
+                .get_session(item_id, workspace_id)
+                .log_err()
+                .flatten()
+                .filter(|_| restore_session);
+
+            let terminal = project
+                .update(&mut cx, |project, cx| match saved_session {
+                    Some((shell, title, scrollback, detached_session_id)) => {
+                        let shell = shell.and_then(|shell| serde_json::from_str(&shell).log_err());
+                        let session = TerminalSession {
+                            title,
+                            scrollback,
+                            detached_session_id,
+                        };
+                        project.restore_terminal(cwd, shell, session, window, cx)
+                    }
+                    None => project.create_terminal(cwd, None, window, cx),
+                })?
+                .await?;
             pane.update(&mut cx, |_, cx| {
                 cx.new_view(|cx| TerminalView::new(terminal, workspace, workspace_id, cx))
             })
@@ -991,6 +1110,8 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // The terminal's text has no syntax to match.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
@@ -96,6 +96,7 @@ pub enum IconName {
     Pencil,
     Play,
     Plus,
+    PreserveCase,
     Public,
     Quote,
     Replace,
@@ -192,6 +193,7 @@ impl IconName {
             IconName::Pencil => "icons/pencil.svg",
             IconName::Play => "icons/play.svg",
             IconName::Plus => "icons/plus.svg",
+            IconName::PreserveCase => "icons/preserve_case.svg",
             IconName::Public => "icons/public.svg",
             IconName::Quote => "icons/quote.svg",
             IconName::Replace => "icons/replace.svg",
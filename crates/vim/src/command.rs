@@ -8,10 +8,11 @@ use crate::{
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
         move_cursor,
-        search::{FindCommand, ReplaceCommand},
+        search::{parse_replace_all, FindCommand, ReplaceCommand},
         JoinLines,
     },
     state::Mode,
+    window::parse_resize_command,
     Vim,
 };
 
@@ -39,8 +40,9 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
     // We also need to support passing arguments to commands like :w
     // (ideally with filename autocompletion).
     //
-    // For now, you can only do a replace on the % range, and you can
-    // only use a specific line number range to "go to line"
+    // For now, only `:s` accepts a range (`%`, `'<,'>`, `N` or `N,M`, defaulting
+    // to the current line, see `parse_replace_all`), and a bare line number is
+    // treated as "go to line".
     while query.starts_with(':') {
         query = &query[1..];
     }
@@ -330,7 +332,7 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
                     }
                     .boxed_clone(),
                 )
-            } else if query.starts_with('%') {
+            } else if parse_replace_all(query).is_some() {
                 (
                     query,
                     ReplaceCommand {
@@ -338,6 +340,8 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
                     }
                     .boxed_clone(),
                 )
+            } else if let Some(resize) = parse_resize_command(query) {
+                (query, resize.boxed_clone())
             } else if let Ok(line) = query.parse::<u32>() {
                 (query, GoToLine { line }.boxed_clone())
             } else {
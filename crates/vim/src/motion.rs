@@ -13,6 +13,7 @@ use workspace::Workspace;
 use crate::{
     normal::normal_motion,
     state::{Mode, Operator},
+    surrounds::SurroundsType,
     utils::coerce_punctuation,
     visual::visual_motion,
     Vim,
@@ -386,14 +387,30 @@ pub(crate) fn motion(motion: Motion, cx: &mut WindowContext) {
 
     let count = Vim::update(cx, |vim, cx| vim.take_count(cx));
     let operator = Vim::read(cx).active_operator();
+    let mut waiting_operator = None;
     match Vim::read(cx).state().mode {
-        Mode::Normal | Mode::Replace => normal_motion(motion, operator, count, cx),
+        Mode::Normal | Mode::Replace => {
+            if operator == Some(Operator::AddSurrounds { target: None }) {
+                // `ys` still needs the surrounding character before it can run the motion.
+                waiting_operator = Some(Operator::AddSurrounds {
+                    target: Some(SurroundsType::Motion(motion)),
+                });
+            } else {
+                normal_motion(motion, operator, count, cx)
+            }
+        }
         Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_motion(motion, count, cx),
         Mode::Insert => {
             // Shouldn't execute a motion in insert mode. Ignoring
         }
     }
-    Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+    Vim::update(cx, |vim, cx| {
+        vim.clear_operator(cx);
+        if let Some(operator) = waiting_operator {
+            vim.push_operator(operator, cx);
+            vim.update_state(|state| state.pre_count = count);
+        }
+    });
 }
 
 // Motion handling is specified here:
@@ -1,12 +1,15 @@
-use gpui::{actions, impl_actions, ViewContext};
+use std::{ops::Range, sync::Arc};
+
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{actions, impl_actions, AppContext, ViewContext, WindowContext};
+use language::Point;
+use regex::{Regex, RegexBuilder};
 use search::{buffer_search, BufferSearchBar, SearchMode, SearchOptions};
 use serde_derive::Deserialize;
-use workspace::{searchable::Direction, Workspace};
+use workspace::{searchable::Direction, Toast, Workspace};
 
 use crate::{
-    motion::Motion,
-    normal::move_cursor,
-    state::{Mode, SearchState},
+    state::{ConfirmReplaceState, Mode, Operator, SearchState},
     Vim,
 };
 
@@ -41,15 +44,35 @@ pub struct ReplaceCommand {
     pub query: String,
 }
 
-#[derive(Debug, Default)]
-struct Replacement {
+/// The lines a `:s` command applies to.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum ReplaceRange {
+    #[default]
+    CurrentLine,
+    All,
+    /// `'<,'>`, the selection the command was typed from.
+    Selection,
+    /// One-based and inclusive.
+    Lines(u32, u32),
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Replacement {
+    range: ReplaceRange,
     search: String,
     replacement: String,
-    should_replace_all: bool,
+    is_global: bool,
     is_case_sensitive: bool,
+    confirm: bool,
+    count_only: bool,
 }
 
-actions!(vim, [SearchSubmit]);
+/// Highlights the matches of a `:s` command being typed or confirmed.
+enum ReplacePreview {}
+
+const REPLACE_TOAST_ID: usize = 0x7e9ac3;
+
+actions!(vim, [SearchSubmit, RepeatReplace, RepeatReplaceAllLines]);
 impl_actions!(
     vim,
     [FindCommand, ReplaceCommand, Search, MoveToPrev, MoveToNext]
@@ -64,6 +87,8 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
 
     workspace.register_action(find_command);
     workspace.register_action(replace_command);
+    workspace.register_action(repeat_replace);
+    workspace.register_action(repeat_replace_all_lines);
 }
 
 fn move_to_next(workspace: &mut Workspace, action: &MoveToNext, cx: &mut ViewContext<Workspace>) {
@@ -231,77 +256,364 @@ fn replace_command(
     action: &ReplaceCommand,
     cx: &mut ViewContext<Workspace>,
 ) {
-    let replacement = parse_replace_all(&action.query);
-    let pane = workspace.active_pane().clone();
-    pane.update(cx, |pane, cx| {
-        let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() else {
+    let Some(mut replacement) = parse_replace_all(&action.query) else {
+        return;
+    };
+    if replacement.search.is_empty() {
+        // Like vim, an empty pattern reuses the previous one.
+        replacement.search = Vim::read(cx)
+            .workspace_state
+            .last_replacement
+            .as_ref()
+            .map(|last| last.search.clone())
+            .filter(|search| !search.is_empty())
+            .or_else(|| {
+                let pane = workspace.active_pane().read(cx);
+                let search_bar = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()?;
+                Some(search_bar.read(cx).query(cx))
+            })
+            .unwrap_or_default();
+    }
+    replace(workspace, replacement, cx)
+}
+
+fn repeat_replace(workspace: &mut Workspace, _: &RepeatReplace, cx: &mut ViewContext<Workspace>) {
+    // `&` is `:s`, which repeats the last substitution on the current line without its flags.
+    let Some(last) = Vim::read(cx).workspace_state.last_replacement.clone() else {
+        return;
+    };
+    let replacement = Replacement {
+        range: ReplaceRange::CurrentLine,
+        search: last.search,
+        replacement: last.replacement,
+        is_case_sensitive: true,
+        ..Default::default()
+    };
+    replace(workspace, replacement, cx)
+}
+
+fn repeat_replace_all_lines(
+    workspace: &mut Workspace,
+    _: &RepeatReplaceAllLines,
+    cx: &mut ViewContext<Workspace>,
+) {
+    // `g&` is `:%s//~/&`, which repeats the last substitution on every line with its flags.
+    let Some(last) = Vim::read(cx).workspace_state.last_replacement.clone() else {
+        return;
+    };
+    let replacement = Replacement {
+        range: ReplaceRange::All,
+        ..last
+    };
+    replace(workspace, replacement, cx)
+}
+
+fn replace(
+    workspace: &mut Workspace,
+    mut replacement: Replacement,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let regex = match replacement.regex() {
+        Ok(regex) => regex,
+        Err(error) => {
+            workspace.show_toast(
+                Toast::new(REPLACE_TOAST_ID, format!("Invalid pattern: {error}")),
+                cx,
+            );
+            return;
+        }
+    };
+
+    let message = Vim::update(cx, |vim, cx| {
+        vim.workspace_state.last_replacement = Some(replacement.clone());
+        let is_visual = vim.state().mode.is_visual();
+        if replacement.range == ReplaceRange::CurrentLine && is_visual {
+            replacement.range = ReplaceRange::Selection;
+        }
+
+        let matches =
+            vim.update_active_editor(cx, |_, editor, cx| replacement.matches(&regex, editor, cx))?;
+        if matches.is_empty() {
+            return Some(format!("Pattern not found: {}", replacement.search));
+        }
+        if replacement.count_only {
+            let mut lines = matches
+                .iter()
+                .map(|(range, _)| range.start.row)
+                .collect::<Vec<_>>();
+            lines.dedup();
+            return Some(format!(
+                "{} on {}",
+                pluralize(matches.len(), "match", "matches"),
+                pluralize(lines.len(), "line", "lines")
+            ));
+        }
+
+        if is_visual {
+            vim.switch_mode(Mode::Normal, true, cx);
+        }
+        if replacement.confirm {
+            start_confirm_replace(vim, matches, cx);
+            return None;
+        }
+
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let last_row = matches.last().map_or(0, |(range, _)| range.start.row);
+            editor.transact(cx, |editor, cx| editor.edit(matches, cx));
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_ranges([Point::new(last_row, 0)..Point::new(last_row, 0)])
+            });
+        });
+        None
+    });
+
+    if let Some(message) = message {
+        workspace.show_toast(Toast::new(REPLACE_TOAST_ID, message), cx);
+    }
+}
+
+fn pluralize(count: usize, singular: &str, plural: &str) -> String {
+    format!("{count} {}", if count == 1 { singular } else { plural })
+}
+
+fn start_confirm_replace(
+    vim: &mut Vim,
+    matches: Vec<(Range<Point>, String)>,
+    cx: &mut WindowContext,
+) {
+    let Some(matches) = vim.update_active_editor(cx, |_, editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        matches
+            .into_iter()
+            .map(|(range, text)| {
+                (
+                    snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+                    text,
+                )
+            })
+            .collect()
+    }) else {
+        return;
+    };
+
+    vim.push_operator(Operator::ConfirmReplace, cx);
+    vim.update_state(|state| {
+        state.confirm_replace = Some(ConfirmReplaceState {
+            matches,
+            transaction: None,
+        })
+    });
+    show_confirm_replace(vim, cx);
+}
+
+/// Highlights the next match awaiting confirmation, or finishes once there are none left.
+fn show_confirm_replace(vim: &mut Vim, cx: &mut WindowContext) {
+    let Some((range, text)) = vim
+        .state()
+        .confirm_replace
+        .as_ref()
+        .and_then(|state| state.matches.first().cloned())
+    else {
+        vim.clear_operator(cx);
+        return;
+    };
+
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.highlight_background::<ReplacePreview>(
+            &[range.clone()],
+            |colors| colors.search_match_background,
+            cx,
+        );
+        editor.set_edit_preview(vec![(range.end, text)], cx);
+        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+            s.select_anchor_ranges([range.start..range.start])
+        });
+    });
+}
+
+/// Handles the answer to "replace this match?": `y`es, `n`o, `a`ll, `q`uit or `l`ast.
+pub(crate) fn confirm_replace_input(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let Some(mut state) = vim.state().confirm_replace.clone() else {
             return;
         };
-        let search = search_bar.update(cx, |search_bar, cx| {
-            if !search_bar.show(cx) {
-                return None;
+        if state.matches.is_empty() || !["y", "l", "n", "a", "q"].contains(&text.as_ref()) {
+            return;
+        }
+
+        let current = state.matches.remove(0);
+        let mut edits = Vec::new();
+        match text.as_ref() {
+            "y" => edits.push(current),
+            "l" => {
+                edits.push(current);
+                state.matches.clear();
+            }
+            "a" => {
+                edits.push(current);
+                edits.append(&mut state.matches);
+            }
+            "q" => state.matches.clear(),
+            _ => {}
+        }
+
+        if !edits.is_empty() {
+            let transaction = vim
+                .update_active_editor(cx, |_, editor, cx| {
+                    editor.transact(cx, |editor, cx| editor.edit(edits, cx))
+                })
+                .flatten();
+            state.transaction = state.transaction.or(transaction);
+        }
+        vim.update_state(|editor_state| editor_state.confirm_replace = Some(state));
+        show_confirm_replace(vim, cx);
+    })
+}
+
+/// Ends a `:s///c` that is awaiting confirmation, so that all of the replacements
+/// made so far can be undone at once.
+pub(crate) fn stop_confirm_replace(vim: &mut Vim, cx: &mut WindowContext) {
+    let Some(state) = vim.update_state(|state| state.confirm_replace.take()) else {
+        return;
+    };
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.clear_background_highlights::<ReplacePreview>(cx);
+        editor.set_edit_preview(Vec::new(), cx);
+        if let Some(transaction) = state.transaction {
+            editor.group_until_transaction(transaction, cx);
+        }
+    });
+}
+
+/// Highlights the matches of a `:s` command as it is typed into the command palette,
+/// showing the text each one would be replaced with.
+pub(crate) fn preview_replace(query: Option<&str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let mut replacement =
+            query.and_then(|query| parse_replace_all(query.trim().trim_start_matches(':')));
+        if let Some(replacement) = replacement.as_mut() {
+            if replacement.search.is_empty() {
+                if let Some(last) = vim.workspace_state.last_replacement.as_ref() {
+                    replacement.search = last.search.clone();
+                }
+            }
+            if replacement.range == ReplaceRange::CurrentLine && vim.state().mode.is_visual() {
+                replacement.range = ReplaceRange::Selection;
             }
+        }
 
-            let mut options = SearchOptions::default();
-            if replacement.is_case_sensitive {
-                options.set(SearchOptions::CASE_SENSITIVE, true)
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let matches = replacement
+                .as_ref()
+                .filter(|replacement| !replacement.search.is_empty())
+                .and_then(|replacement| {
+                    let regex = replacement.regex().ok()?;
+                    Some(replacement.matches(&regex, editor, cx))
+                })
+                .unwrap_or_default();
+            if matches.is_empty() {
+                editor.clear_background_highlights::<ReplacePreview>(cx);
+                editor.set_edit_preview(Vec::new(), cx);
+                return;
             }
-            let search = if replacement.search == "" {
-                search_bar.query(cx)
+
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let ranges = matches
+                .iter()
+                .map(|(range, _)| {
+                    snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end)
+                })
+                .collect::<Vec<_>>();
+            editor.highlight_background::<ReplacePreview>(
+                &ranges,
+                |colors| colors.search_match_background,
+                cx,
+            );
+            let previews = if replacement.map_or(false, |replacement| replacement.count_only) {
+                Vec::new()
             } else {
-                replacement.search
+                ranges
+                    .into_iter()
+                    .zip(matches)
+                    .map(|(range, (_, text))| (range.end, text))
+                    .collect()
             };
-
-            search_bar.set_replacement(Some(&replacement.replacement), cx);
-            search_bar.activate_search_mode(SearchMode::Regex, cx);
-            Some(search_bar.search(&search, Some(options), cx))
+            editor.set_edit_preview(previews, cx);
         });
-        let Some(search) = search else { return };
-        let search_bar = search_bar.downgrade();
-        cx.spawn(|_, mut cx| async move {
-            search.await?;
-            search_bar.update(&mut cx, |search_bar, cx| {
-                if replacement.should_replace_all {
-                    search_bar.select_last_match(cx);
-                    search_bar.replace_all(&Default::default(), cx);
-                    Vim::update(cx, |vim, cx| {
-                        move_cursor(
-                            vim,
-                            Motion::StartOfLine {
-                                display_lines: false,
-                            },
-                            None,
-                            cx,
-                        )
-                    })
-                }
-            })?;
-            anyhow::Ok(())
-        })
-        .detach_and_log_err(cx);
     })
 }
 
+impl Replacement {
+    fn regex(&self) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(&self.search)
+            .case_insensitive(!self.is_case_sensitive)
+            .build()
+    }
+
+    /// The matches on the lines the command applies to, along with their replacements.
+    fn matches(
+        &self,
+        regex: &Regex,
+        editor: &Editor,
+        cx: &AppContext,
+    ) -> Vec<(Range<Point>, String)> {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let max_row = snapshot.max_point().row;
+        let rows = match self.range {
+            ReplaceRange::CurrentLine => {
+                let row = editor.selections.newest::<Point>(cx).head().row;
+                row..=row
+            }
+            ReplaceRange::All => 0..=max_row,
+            ReplaceRange::Selection => {
+                let selection = editor.selections.newest::<Point>(cx);
+                selection.start.row..=selection.end.row
+            }
+            ReplaceRange::Lines(start, end) => {
+                let (start, end) = (start.min(end), start.max(end));
+                start.saturating_sub(1)..=end.saturating_sub(1).min(max_row)
+            }
+        };
+
+        let mut matches = Vec::new();
+        for row in rows {
+            let line_end = Point::new(row, snapshot.line_len(row));
+            let line = snapshot
+                .text_for_range(Point::new(row, 0)..line_end)
+                .collect::<String>();
+            let limit = if self.is_global { usize::MAX } else { 1 };
+            for captures in regex.captures_iter(&line).take(limit) {
+                let Some(found) = captures.get(0) else {
+                    continue;
+                };
+                let mut text = String::new();
+                captures.expand(&self.replacement, &mut text);
+                matches.push((
+                    Point::new(row, found.start() as u32)..Point::new(row, found.end() as u32),
+                    text,
+                ));
+            }
+        }
+        matches
+    }
+}
+
 // convert a vim query into something more usable by zed.
 // we don't attempt to fully convert between the two regex syntaxes,
 // but we do flip \( and \) to ( and ) (and vice-versa) in the pattern,
-// and convert \0..\9 to $0..$9 in the replacement so that common idioms work.
-fn parse_replace_all(query: &str) -> Replacement {
-    let mut chars = query.chars();
-    if Some('%') != chars.next() || Some('s') != chars.next() {
-        return Replacement::default();
-    }
-
-    let Some(delimiter) = chars.next() else {
-        return Replacement::default();
-    };
+// convert \< and \> to word boundaries, and convert \0..\9 and & to
+// capture groups in the replacement so that common idioms work.
+pub(crate) fn parse_replace_all(query: &str) -> Option<Replacement> {
+    let (range, query) = parse_replace_range(query)?;
+    let mut chars = query.strip_prefix('s')?.chars();
+    let delimiter = chars.next().filter(|&c| {
+        !c.is_alphanumeric() && !c.is_whitespace() && !matches!(c, '\\' | '"' | '|')
+    })?;
 
     let mut search = String::new();
     let mut replacement = String::new();
     let mut flags = String::new();
 
-    let mut buffer = &mut search;
-
     let mut escaped = false;
     // 0 - parsing search
     // 1 - parsing replacement
@@ -309,62 +621,109 @@ fn parse_replace_all(query: &str) -> Replacement {
     let mut phase = 0;
 
     for c in chars {
-        if escaped {
-            escaped = false;
-            if phase == 1 && c.is_digit(10) {
-                buffer.push('$')
-            // unescape escaped parens
-            } else if phase == 0 && c == '(' || c == ')' {
-            } else if c != delimiter {
-                buffer.push('\\')
+        match phase {
+            0 if escaped => {
+                escaped = false;
+                match c {
+                    // unescape escaped parens
+                    '(' | ')' => search.push(c),
+                    '<' | '>' => search.push_str("\\b"),
+                    c if c == delimiter => search.push(c),
+                    c => {
+                        search.push('\\');
+                        search.push(c);
+                    }
+                }
             }
-            buffer.push(c)
-        } else if c == '\\' {
-            escaped = true;
-        } else if c == delimiter {
-            if phase == 0 {
-                buffer = &mut replacement;
-                phase = 1;
-            } else if phase == 1 {
-                buffer = &mut flags;
-                phase = 2;
-            } else {
-                break;
+            1 if escaped => {
+                escaped = false;
+                match c {
+                    '0'..='9' => {
+                        replacement.push_str("${");
+                        replacement.push(c);
+                        replacement.push('}');
+                    }
+                    'r' | 'n' => replacement.push('\n'),
+                    't' => replacement.push('\t'),
+                    '$' => replacement.push_str("$$"),
+                    c => replacement.push(c),
+                }
             }
-        } else {
-            // escape unescaped parens
-            if phase == 0 && c == '(' || c == ')' {
-                buffer.push('\\')
+            0 | 1 if c == '\\' => escaped = true,
+            0 if c == delimiter => phase = 1,
+            1 if c == delimiter => phase = 2,
+            0 => {
+                // escape unescaped parens
+                if c == '(' || c == ')' {
+                    search.push('\\')
+                }
+                search.push(c)
             }
-            buffer.push(c)
+            1 => match c {
+                '&' => replacement.push_str("${0}"),
+                '$' => replacement.push_str("$$"),
+                c => replacement.push(c),
+            },
+            _ => flags.push(c),
         }
     }
 
     let mut replacement = Replacement {
+        range,
         search,
         replacement,
-        should_replace_all: true,
         is_case_sensitive: true,
+        ..Default::default()
     };
 
     for c in flags.chars() {
         match c {
-            'g' | 'I' => {}
-            'c' | 'n' => replacement.should_replace_all = false,
+            'g' => replacement.is_global = true,
+            'c' => replacement.confirm = true,
+            'n' => replacement.count_only = true,
             'i' => replacement.is_case_sensitive = false,
+            'I' => replacement.is_case_sensitive = true,
             _ => {}
         }
     }
 
-    replacement
+    Some(replacement)
+}
+
+fn parse_replace_range(query: &str) -> Option<(ReplaceRange, &str)> {
+    if let Some(query) = query.strip_prefix('%') {
+        return Some((ReplaceRange::All, query));
+    }
+    if let Some(query) = query.strip_prefix("'<,'>") {
+        return Some((ReplaceRange::Selection, query));
+    }
+
+    let parse_line = |query: &str| -> Option<(u32, usize)> {
+        let len = query
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(query.len());
+        Some((query[..len].parse().ok()?, len))
+    };
+    let Some((start, len)) = parse_line(query) else {
+        return Some((ReplaceRange::CurrentLine, query));
+    };
+    let query = &query[len..];
+    if let Some(query) = query.strip_prefix(',') {
+        let (end, len) = parse_line(query)?;
+        Some((ReplaceRange::Lines(start, end), &query[len..]))
+    } else {
+        Some((ReplaceRange::Lines(start, start), query))
+    }
 }
 
 #[cfg(test)]
 mod test {
     use editor::DisplayPoint;
+    use indoc::indoc;
     use search::BufferSearchBar;
 
     use crate::{
+        normal::search::{parse_replace_all, ReplaceCommand, ReplaceRange, Replacement},
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
     };
@@ -508,4 +867,184 @@ mod test {
         cx.assert_shared_state("a.c. abcd ˇa.c. abcd").await;
         cx.assert_shared_mode(Mode::Normal).await;
     }
+
+    #[test]
+    fn test_parse_replace_all() {
+        assert_eq!(
+            parse_replace_all("%s/a/b/g"),
+            Some(Replacement {
+                range: ReplaceRange::All,
+                search: "a".into(),
+                replacement: "b".into(),
+                is_global: true,
+                is_case_sensitive: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            parse_replace_all(r"2,4s#\(a\)\<(#\1&\&$#cin"),
+            Some(Replacement {
+                range: ReplaceRange::Lines(2, 4),
+                search: r"(a)\b\(".into(),
+                replacement: "${1}${0}&$$".into(),
+                confirm: true,
+                count_only: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            parse_replace_all("'<,'>s/a/b/iI").map(|r| (r.range, r.is_case_sensitive)),
+            Some((ReplaceRange::Selection, true))
+        );
+        assert_eq!(
+            parse_replace_all("s/a").map(|r| r.range),
+            Some(ReplaceRange::CurrentLine)
+        );
+        assert_eq!(parse_replace_all("s"), None);
+        assert_eq!(parse_replace_all("sa/b/"), None);
+        assert_eq!(parse_replace_all("%x/a/b/"), None);
+    }
+
+    #[gpui::test]
+    async fn test_replace_flags(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state(
+            indoc! {"
+                ˇaa Aa
+                aa Aa"},
+            Mode::Normal,
+        );
+
+        cx.dispatch_action(ReplaceCommand {
+            query: "s/a/b".into(),
+        });
+        cx.assert_state(
+            indoc! {"
+                ˇba Aa
+                aa Aa"},
+            Mode::Normal,
+        );
+
+        cx.dispatch_action(ReplaceCommand {
+            query: "2s/a/b/gi".into(),
+        });
+        cx.assert_state(
+            indoc! {"
+                ba Aa
+                ˇbb bb"},
+            Mode::Normal,
+        );
+
+        cx.dispatch_action(ReplaceCommand {
+            query: "%s/a//n".into(),
+        });
+        cx.assert_state(
+            indoc! {"
+                ba Aa
+                ˇbb bb"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_repeat_replace(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state(
+            indoc! {"
+                ˇa a
+                a a
+                a a"},
+            Mode::Normal,
+        );
+
+        cx.dispatch_action(ReplaceCommand {
+            query: "s/a/b/g".into(),
+        });
+        cx.simulate_keystrokes(["j", "&"]);
+        cx.assert_state(
+            indoc! {"
+                b b
+                ˇb a
+                a a"},
+            Mode::Normal,
+        );
+
+        cx.dispatch_action(ReplaceCommand {
+            query: "s/a/c/g".into(),
+        });
+        cx.simulate_keystrokes(["g", "&"]);
+        cx.assert_state(
+            indoc! {"
+                b b
+                b c
+                ˇc c"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_confirm_replace(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state(
+            indoc! {"
+                ˇa a
+                a a"},
+            Mode::Normal,
+        );
+
+        cx.dispatch_action(ReplaceCommand {
+            query: "%s/a/b/gc".into(),
+        });
+        cx.assert_state(
+            indoc! {"
+                ˇa a
+                a a"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["y", "n", "y"]);
+        cx.assert_state(
+            indoc! {"
+                b a
+                b ˇa"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["q"]);
+        cx.assert_state(
+            indoc! {"
+                b a
+                b ˇa"},
+            Mode::Normal,
+        );
+
+        // all of the confirmed replacements are undone together
+        cx.simulate_keystrokes(["u"]);
+        assert_eq!(cx.buffer_text(), "a a\na a");
+
+        cx.dispatch_action(ReplaceCommand {
+            query: "%s/a/b/gc".into(),
+        });
+        cx.simulate_keystrokes(["n", "a"]);
+        assert_eq!(cx.buffer_text(), "a b\nb b");
+    }
+
+    #[gpui::test]
+    async fn test_replace_preview(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state(
+            indoc! {"
+                ˇa
+                b
+                a"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes([":", "%", "s", "/", "a", "/", "c"]);
+        cx.run_until_parked();
+        cx.update_editor(|editor, cx| assert_eq!(editor.display_text(cx), "ac\nb\nac"));
+
+        cx.simulate_keystrokes(["escape"]);
+        cx.run_until_parked();
+        cx.update_editor(|editor, cx| assert_eq!(editor.display_text(cx), "a\nb\na"));
+        assert_eq!(cx.buffer_text(), "a\nb\na");
+    }
 }
@@ -232,7 +232,7 @@ mod test {
 
         // visual mode
         cx.set_shared_state(initial_state).await;
-        cx.simulate_shared_keystrokes(["v", "k", "shift-s", "o"])
+        cx.simulate_shared_keystrokes(["v", "k", "shift-r", "o"])
             .await;
         cx.assert_shared_state(indoc! {"
             oˇ
@@ -242,7 +242,7 @@ mod test {
 
         // visual block mode
         cx.set_shared_state(initial_state).await;
-        cx.simulate_shared_keystrokes(["ctrl-v", "j", "shift-s", "o"])
+        cx.simulate_shared_keystrokes(["ctrl-v", "j", "shift-r", "o"])
             .await;
         cx.assert_shared_state(indoc! {"
             The quick brown
@@ -252,7 +252,7 @@ mod test {
 
         // visual mode including newline
         cx.set_shared_state(initial_state).await;
-        cx.simulate_shared_keystrokes(["v", "$", "shift-s", "o"])
+        cx.simulate_shared_keystrokes(["v", "$", "shift-r", "o"])
             .await;
         cx.assert_shared_state(indoc! {"
             The quick brown
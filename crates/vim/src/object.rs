@@ -10,11 +10,11 @@ use editor::{
     Bias, DisplayPoint,
 };
 use gpui::{actions, impl_actions, ViewContext, WindowContext};
-use language::{char_kind, BufferSnapshot, CharKind, Point, Selection};
+use language::{char_kind, BufferSnapshot, CharKind, Point, Selection, TextObject};
 use serde::Deserialize;
 use workspace::Workspace;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Object {
     Word { ignore_punctuation: bool },
     Sentence,
@@ -29,6 +29,9 @@ pub enum Object {
     AngleBrackets,
     Argument,
     Tag,
+    Function,
+    Class,
+    Comment,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -54,7 +57,10 @@ actions!(
         CurlyBrackets,
         AngleBrackets,
         Argument,
-        Tag
+        Tag,
+        Function,
+        Class,
+        Comment
     ]
 );
 
@@ -92,6 +98,10 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     });
     workspace
         .register_action(|_: &mut Workspace, _: &Argument, cx: _| object(Object::Argument, cx));
+    workspace
+        .register_action(|_: &mut Workspace, _: &Function, cx: _| object(Object::Function, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Class, cx: _| object(Object::Class, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Comment, cx: _| object(Object::Comment, cx));
 }
 
 fn object(object: Object, cx: &mut WindowContext) {
@@ -119,13 +129,22 @@ impl Object {
             | Object::AngleBrackets
             | Object::CurlyBrackets
             | Object::SquareBrackets
-            | Object::Argument => true,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
     pub fn always_expands_both_ways(self) -> bool {
         match self {
-            Object::Word { .. } | Object::Sentence | Object::Paragraph | Object::Argument => false,
+            Object::Word { .. }
+            | Object::Sentence
+            | Object::Paragraph
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => false,
             Object::Quotes
             | Object::BackQuotes
             | Object::DoubleQuotes
@@ -157,7 +176,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::VerticalBars
             | Object::Tag
-            | Object::Argument => Mode::Visual,
+            | Object::Argument
+            | Object::Function
+            | Object::Class
+            | Object::Comment => Mode::Visual,
             Object::Paragraph => Mode::VisualLine,
         }
     }
@@ -204,7 +226,56 @@ impl Object {
             Object::AngleBrackets => {
                 surrounding_markers(map, relative_to, around, self.is_multiline(), '<', '>')
             }
-            Object::Argument => argument(map, relative_to, around),
+            Object::Argument => {
+                // Use the tightest of the language's parameter text object and the bracket-based
+                // heuristic for the inner range, as the heuristic can't find parameters in every
+                // grammar. The around range keeps the heuristic so that separating commas are
+                // included.
+                if around {
+                    argument(map, relative_to, true)
+                } else {
+                    let parameter = text_object(map, relative_to, TextObject::InsideParameter);
+                    let argument = argument(map, relative_to, false);
+                    match (parameter, argument) {
+                        (Some(parameter), Some(argument)) => {
+                            let len = |range: &Range<DisplayPoint>| {
+                                range.end.to_offset(map, Bias::Right)
+                                    - range.start.to_offset(map, Bias::Left)
+                            };
+                            if len(&parameter) <= len(&argument) {
+                                Some(parameter)
+                            } else {
+                                Some(argument)
+                            }
+                        }
+                        (parameter, argument) => parameter.or(argument),
+                    }
+                }
+            }
+            Object::Function => {
+                let target = if around {
+                    TextObject::AroundFunction
+                } else {
+                    TextObject::InsideFunction
+                };
+                text_object(map, relative_to, target)
+            }
+            Object::Class => {
+                let target = if around {
+                    TextObject::AroundClass
+                } else {
+                    TextObject::InsideClass
+                };
+                text_object(map, relative_to, target)
+            }
+            Object::Comment => {
+                let target = if around {
+                    TextObject::AroundComment
+                } else {
+                    TextObject::InsideComment
+                };
+                text_object(map, relative_to, target)
+            }
         }
     }
 
@@ -558,6 +629,66 @@ fn argument(
     }
 }
 
+/// Returns the range of a text object captured by the language's `textobjects` query.
+///
+/// The smallest matching object containing the cursor is used. For inner objects, if the
+/// cursor isn't within one (e.g. it's on a function signature rather than in the body), the
+/// inner object of the smallest enclosing "around" object is used instead.
+fn text_object(
+    map: &DisplaySnapshot,
+    relative_to: DisplayPoint,
+    target: TextObject,
+) -> Option<Range<DisplayPoint>> {
+    let snapshot = &map.buffer_snapshot;
+    let offset = relative_to.to_offset(map, Bias::Left);
+
+    // Text objects come from the syntax tree, so we operate at the buffer level and map back to the display level
+    let excerpt = snapshot.excerpt_containing(offset..offset)?;
+    let buffer = excerpt.buffer();
+    let offset = excerpt.map_offset_to_buffer(offset);
+
+    let smallest_containing = |target: TextObject| {
+        buffer
+            .text_object_ranges(offset..offset)
+            .filter(|(range, text_object)| {
+                *text_object == target && range.start <= offset && offset < range.end
+            })
+            .map(|(range, _)| range)
+            .min_by_key(|range| range.len())
+    };
+
+    let around = target.around();
+    let range = if let Some(range) = smallest_containing(target) {
+        range
+    } else if target == around {
+        return None;
+    } else {
+        let around_range = smallest_containing(around)?;
+        let inner_range = buffer
+            .text_object_ranges(around_range.clone())
+            .filter(|(range, text_object)| {
+                *text_object == target
+                    && range.start >= around_range.start
+                    && range.end <= around_range.end
+            })
+            .map(|(range, _)| range)
+            .max_by_key(|range| range.len());
+        match inner_range {
+            Some(inner_range) => inner_range,
+            // Most comments have no separate body, so `ic` falls back to the whole comment.
+            None if target == TextObject::InsideComment => around_range,
+            None => return None,
+        }
+    };
+
+    if excerpt.contains_buffer_range(range.clone()) {
+        let result = excerpt.map_range_from_buffer(range);
+        Some(result.start.to_display_point(map)..result.end.to_display_point(map))
+    } else {
+        None
+    }
+}
+
 fn sentence(
     map: &DisplaySnapshot,
     relative_to: DisplayPoint,
@@ -1565,6 +1696,115 @@ mod test {
         cx.assert_state("let a = [«test::call(first_arg)ˇ»]", Mode::Visual);
     }
 
+    #[gpui::test]
+    async fn test_syntax_text_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                impl Foo {
+                    fn boop() {
+                        let a = 1;
+                        ˇlet b = 2;
+                    }
+                }"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["v", "i", "f"]);
+        cx.assert_state(
+            indoc! {"
+                impl Foo {
+                    fn boop() {
+                        «let a = 1;
+                        let b = 2;ˇ»
+                    }
+                }"},
+            Mode::Visual,
+        );
+
+        // Inner objects can be selected from outside the body
+        cx.set_state(
+            indoc! {"
+                impl Foo {
+                    fn bˇoop() {
+                        let a = 1;
+                    }
+                }"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["v", "i", "f"]);
+        cx.assert_state(
+            indoc! {"
+                impl Foo {
+                    fn boop() {
+                        «let a = 1;ˇ»
+                    }
+                }"},
+            Mode::Visual,
+        );
+
+        cx.set_state(
+            indoc! {"
+                impl Foo {
+                    fn boop() {
+                        let ˇa = 1;
+                    }
+                }"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["v", "a", "f"]);
+        cx.assert_state(
+            indoc! {"
+                impl Foo {
+                    «fn boop() {
+                        let a = 1;
+                    }ˇ»
+                }"},
+            Mode::Visual,
+        );
+
+        cx.set_state(
+            indoc! {"
+                impl Foo {
+                    fn boop() {
+                        let ˇa = 1;
+                    }
+                }"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["v", "a", "c"]);
+        cx.assert_state(
+            indoc! {"
+                «impl Foo {
+                    fn boop() {
+                        let a = 1;
+                    }
+                }ˇ»"},
+            Mode::Visual,
+        );
+
+        cx.set_state(
+            indoc! {"
+                // one
+                // ˇtwo
+                fn boop() {}"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["v", "a", "g", "c"]);
+        cx.assert_state(
+            indoc! {"
+                «// one
+                // twoˇ»
+                fn boop() {}"},
+            Mode::Visual,
+        );
+
+        // No enclosing function
+        cx.set_state("let ˇa = 1;", Mode::Normal);
+        cx.simulate_keystrokes(["v", "i", "f"]);
+        cx.assert_state("let «aˇ» = 1;", Mode::Visual);
+    }
+
     #[gpui::test]
     async fn test_delete_surrounding_character_objects(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
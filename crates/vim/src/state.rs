@@ -1,6 +1,8 @@
 use std::{fmt::Display, ops::Range, sync::Arc};
 
-use crate::motion::Motion;
+use crate::{
+    motion::Motion, normal::search::Replacement, object::Object, surrounds::SurroundsType,
+};
 use collections::HashMap;
 use editor::Anchor;
 use gpui::{Action, KeyContext};
@@ -52,9 +54,27 @@ pub enum Operator {
     Delete,
     Yank,
     Replace,
-    Object { around: bool },
-    FindForward { before: bool },
-    FindBackward { after: bool },
+    Object {
+        around: bool,
+    },
+    FindForward {
+        before: bool,
+    },
+    FindBackward {
+        after: bool,
+    },
+    AddSurrounds {
+        target: Option<SurroundsType>,
+    },
+    ChangeSurrounds {
+        #[serde(skip)]
+        target: Option<Object>,
+    },
+    DeleteSurrounds,
+    SurroundTag {
+        tag: String,
+    },
+    ConfirmReplace,
 }
 
 #[derive(Default, Clone)]
@@ -73,6 +93,9 @@ pub struct EditorState {
     pub current_tx: Option<TransactionId>,
     pub current_anchor: Option<Selection<Anchor>>,
     pub undo_modes: HashMap<TransactionId, Mode>,
+
+    /// The matches of a `:s///c` still waiting to be confirmed.
+    pub confirm_replace: Option<ConfirmReplaceState>,
 }
 
 #[derive(Default, Clone, Debug)]
@@ -108,6 +131,10 @@ pub struct WorkspaceState {
     pub recorded_selection: RecordedSelection,
 
     pub registers: HashMap<String, String>,
+    pub last_replacement: Option<Replacement>,
+
+    /// Set while the right hand side of a vimrc `noremap` is being replayed.
+    pub noremap: bool,
 }
 
 #[derive(Debug)]
@@ -134,6 +161,14 @@ impl Clone for ReplayableAction {
     }
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct ConfirmReplaceState {
+    /// The remaining matches and their replacement text, in buffer order.
+    pub matches: Vec<(Range<Anchor>, String)>,
+    /// The transaction of the first confirmed replacement, which the rest are grouped into.
+    pub transaction: Option<TransactionId>,
+}
+
 #[derive(Clone)]
 pub struct SearchState {
     pub direction: Direction,
@@ -253,15 +288,25 @@ impl Operator {
             Operator::FindForward { before: true } => "t",
             Operator::FindBackward { after: false } => "F",
             Operator::FindBackward { after: true } => "T",
+            Operator::AddSurrounds { .. } => "ys",
+            Operator::ChangeSurrounds { .. } => "cs",
+            Operator::DeleteSurrounds => "ds",
+            Operator::SurroundTag { .. } => "<",
+            Operator::ConfirmReplace => "confirm_replace",
         }
     }
 
     pub fn context_flags(&self) -> &'static [&'static str] {
         match self {
             Operator::Object { .. } => &["VimObject"],
-            Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace => {
-                &["VimWaiting"]
-            }
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::AddSurrounds { target: Some(_) }
+            | Operator::ChangeSurrounds { .. }
+            | Operator::DeleteSurrounds
+            | Operator::SurroundTag { .. }
+            | Operator::ConfirmReplace => &["VimWaiting"],
             _ => &[],
         }
     }
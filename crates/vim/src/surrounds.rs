@@ -0,0 +1,560 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::{
+    motion::{self, Motion},
+    object::Object,
+    state::{Mode, Operator},
+    Vim,
+};
+use editor::{
+    display_map::{DisplaySnapshot, ToDisplayPoint},
+    scroll::Autoscroll,
+    Bias, DisplayPoint, ToOffset,
+};
+use gpui::WindowContext;
+use language::{LanguageScope, Point, Selection};
+use serde::Deserialize;
+
+/// What `ys` (or a visual-mode `AddSurrounds`) should surround.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub enum SurroundsType {
+    #[serde(skip)]
+    Motion(Motion),
+    #[serde(skip)]
+    Object(Object, bool),
+    Selection,
+}
+
+/// The text to surround with, as typed after `ys{motion}`, `cs{old}` or `S`.
+enum Surround {
+    Char(char),
+    Tag(String),
+}
+
+impl Surround {
+    /// Returns the opening and closing text to insert.
+    ///
+    /// Opening brackets add a space inside the pair, as in vim-surround, and
+    /// brackets come from the language's configuration where possible.
+    fn pair(&self, scope: Option<&LanguageScope>) -> (String, String) {
+        let ch = match self {
+            Surround::Tag(tag) => {
+                let name = tag.split_whitespace().next().unwrap_or_default();
+                return (format!("<{tag}>"), format!("</{name}>"));
+            }
+            Surround::Char(ch) => *ch,
+        };
+        let ch = match ch {
+            'b' => ')',
+            'B' => '}',
+            'r' => ']',
+            'a' => '>',
+            ch => ch,
+        };
+
+        let language_pair = scope.and_then(|scope| {
+            scope
+                .brackets()
+                .map(|(pair, _)| pair)
+                .find(|pair| {
+                    is_single_char(&pair.start, ch) && pair.end.chars().count() == 1
+                        || is_single_char(&pair.end, ch) && pair.start.chars().count() == 1
+                })
+                .map(|pair| (pair.start.clone(), pair.end.clone()))
+        });
+        let (open, close) = language_pair
+            .or_else(|| {
+                DEFAULT_PAIRS
+                    .iter()
+                    .find(|(open, close)| *open == ch || *close == ch)
+                    .map(|(open, close)| (open.to_string(), close.to_string()))
+            })
+            .unwrap_or_else(|| (ch.to_string(), ch.to_string()));
+
+        if open != close && is_single_char(&open, ch) {
+            (format!("{open} "), format!(" {close}"))
+        } else {
+            (open, close)
+        }
+    }
+}
+
+const DEFAULT_PAIRS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+fn is_single_char(text: &str, ch: char) -> bool {
+    let mut chars = text.chars();
+    chars.next() == Some(ch) && chars.next().is_none()
+}
+
+/// Returns the object whose delimiters `cs` and `ds` should act on for the given character.
+fn object_for_char(ch: char) -> Option<Object> {
+    match ch {
+        '\'' => Some(Object::Quotes),
+        '`' => Some(Object::BackQuotes),
+        '"' => Some(Object::DoubleQuotes),
+        '|' => Some(Object::VerticalBars),
+        '(' | ')' | 'b' => Some(Object::Parentheses),
+        '[' | ']' | 'r' => Some(Object::SquareBrackets),
+        '{' | '}' | 'B' => Some(Object::CurlyBrackets),
+        '<' | '>' | 'a' => Some(Object::AngleBrackets),
+        't' => Some(Object::Tag),
+        _ => None,
+    }
+}
+
+fn is_tag_prompt(ch: char) -> bool {
+    ch == '<' || ch == 't'
+}
+
+/// Handles the character typed after `ys{motion}` or visual `S`.
+pub(crate) fn add_surrounds(text: Arc<str>, target: SurroundsType, cx: &mut WindowContext) {
+    let Some(ch) = text.chars().next() else {
+        return;
+    };
+    if is_tag_prompt(ch) {
+        Vim::update(cx, |vim, cx| {
+            vim.push_operator(Operator::SurroundTag { tag: String::new() }, cx)
+        });
+        return;
+    }
+    Vim::update(cx, |vim, cx| {
+        surround_target(vim, Surround::Char(ch), target, cx)
+    });
+}
+
+/// Handles the first character typed after `cs`, which picks the surrounds to change.
+pub(crate) fn change_surrounds_target(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let object = text.chars().next().and_then(object_for_char);
+        vim.pop_operator(cx);
+        match object {
+            Some(object) => vim.push_operator(
+                Operator::ChangeSurrounds {
+                    target: Some(object),
+                },
+                cx,
+            ),
+            None => vim.clear_operator(cx),
+        }
+    });
+}
+
+/// Handles the replacement character typed after `cs{old}`.
+pub(crate) fn change_surrounds(text: Arc<str>, target: Object, cx: &mut WindowContext) {
+    let Some(ch) = text.chars().next() else {
+        return;
+    };
+    if is_tag_prompt(ch) {
+        Vim::update(cx, |vim, cx| {
+            vim.push_operator(Operator::SurroundTag { tag: String::new() }, cx)
+        });
+        return;
+    }
+    Vim::update(cx, |vim, cx| {
+        replace_surrounds(vim, Some(Surround::Char(ch)), target, cx)
+    });
+}
+
+/// Handles the character typed after `ds`.
+pub(crate) fn delete_surrounds(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        match text.chars().next().and_then(object_for_char) {
+            Some(object) => replace_surrounds(vim, None, object, cx),
+            None => vim.clear_operator(cx),
+        }
+    });
+}
+
+/// Accumulates the tag typed after `<` or `t`, until the tag is closed with `>` or enter.
+pub(crate) fn surround_tag_input(text: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| {
+        let Some(Operator::SurroundTag { mut tag }) = vim.active_operator() else {
+            return;
+        };
+        vim.pop_operator(cx);
+
+        let mut closed = false;
+        for ch in text.chars() {
+            if ch == '>' || ch == '\n' {
+                closed = true;
+                break;
+            }
+            tag.push(ch);
+        }
+        if !closed {
+            vim.push_operator(Operator::SurroundTag { tag }, cx);
+            return;
+        }
+
+        let surround = Surround::Tag(tag.trim().to_string());
+        match vim.active_operator() {
+            Some(Operator::AddSurrounds {
+                target: Some(target),
+            }) => surround_target(vim, surround, target, cx),
+            Some(Operator::ChangeSurrounds {
+                target: Some(target),
+            }) => replace_surrounds(vim, Some(surround), target, cx),
+            _ => vim.clear_operator(cx),
+        }
+    });
+}
+
+fn surround_target(
+    vim: &mut Vim,
+    surround: Surround,
+    target: SurroundsType,
+    cx: &mut WindowContext,
+) {
+    vim.stop_recording();
+    let count = vim.take_count(cx);
+    let mode = vim.state().mode;
+    vim.update_active_editor(cx, |_, editor, cx| {
+        let text_layout_details = editor.text_layout_details(cx);
+        editor.transact(cx, |editor, cx| {
+            editor.set_clip_at_line_ends(false, cx);
+
+            let (display_map, display_selections) = editor.selections.all_display(cx);
+            let mut edits = Vec::new();
+            let mut cursors = Vec::new();
+            for selection in display_selections {
+                let target_range = match &target {
+                    SurroundsType::Object(object, around) => object
+                        .range(&display_map, selection.clone(), *around)
+                        .map(|range| (range, false)),
+                    SurroundsType::Motion(motion) => {
+                        let mut selection = selection.clone();
+                        motion
+                            .expand_selection(
+                                &display_map,
+                                &mut selection,
+                                count,
+                                false,
+                                &text_layout_details,
+                            )
+                            .then(|| {
+                                // `yss` surrounds the contents of the line, ignoring indentation.
+                                if *motion == Motion::CurrentLine {
+                                    (line_contents_range(&display_map, &selection), false)
+                                } else {
+                                    (selection.range(), motion.linewise())
+                                }
+                            })
+                    }
+                    SurroundsType::Selection => Some((selection.range(), mode == Mode::VisualLine)),
+                };
+                let Some((range, linewise)) = target_range else {
+                    continue;
+                };
+
+                let snapshot = &display_map.buffer_snapshot;
+                let (start, end, indent, rows) = if linewise {
+                    let start_row = range.start.to_point(&display_map).row;
+                    let mut end = range.end.to_point(&display_map);
+                    if end.row > start_row && end.column == 0 {
+                        end.row -= 1;
+                    }
+                    let indent = snapshot
+                        .indent_size_for_line(start_row)
+                        .chars()
+                        .collect::<String>();
+                    (
+                        Point::new(start_row, 0).to_offset(snapshot),
+                        Point::new(end.row, snapshot.line_len(end.row)).to_offset(snapshot),
+                        Some(indent),
+                        start_row..end.row + 1,
+                    )
+                } else {
+                    (
+                        range.start.to_offset(&display_map, Bias::Left),
+                        range.end.to_offset(&display_map, Bias::Right),
+                        None,
+                        0..0,
+                    )
+                };
+
+                let scope = snapshot.language_scope_at(start);
+                let (mut open, mut close) = surround.pair(scope.as_ref());
+                // Linewise targets get their surrounds on lines of their own.
+                if let Some(indent) = indent {
+                    open = format!("{indent}{}\n", open.trim_end());
+                    close = format!("\n{indent}{}", close.trim_start());
+                }
+
+                edits.push((start..start, open));
+                // The wrapped lines move one level in, as vim-surround reindents them.
+                if !rows.is_empty() {
+                    let settings = snapshot.settings_at(start, cx);
+                    let indent_unit = if settings.hard_tabs {
+                        "\t".to_string()
+                    } else {
+                        " ".repeat(settings.tab_size.get() as usize)
+                    };
+                    for row in rows {
+                        if snapshot.line_len(row) > 0 {
+                            let line_start = Point::new(row, 0).to_offset(snapshot);
+                            edits.push((line_start..line_start, indent_unit.clone()));
+                        }
+                    }
+                }
+                edits.push((end..end, close));
+                let cursor = snapshot.anchor_before(start);
+                cursors.push(cursor..cursor);
+            }
+
+            editor.buffer().update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+            editor.set_clip_at_line_ends(true, cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_anchor_ranges(cursors);
+            });
+        });
+    });
+    vim.switch_mode(Mode::Normal, true, cx);
+}
+
+/// Replaces (or, when `surround` is `None`, deletes) the delimiters of the innermost
+/// `target` object around each cursor.
+fn replace_surrounds(
+    vim: &mut Vim,
+    surround: Option<Surround>,
+    target: Object,
+    cx: &mut WindowContext,
+) {
+    vim.stop_recording();
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.transact(cx, |editor, cx| {
+            editor.set_clip_at_line_ends(false, cx);
+
+            let (display_map, display_selections) = editor.selections.all_display(cx);
+            let mut edits = Vec::new();
+            let mut cursors = Vec::new();
+            for selection in display_selections {
+                let Some((open_range, close_range)) =
+                    delimiter_ranges(&display_map, selection, target)
+                else {
+                    continue;
+                };
+
+                let (open, close) = match &surround {
+                    Some(surround) => {
+                        let scope = display_map
+                            .buffer_snapshot
+                            .language_scope_at(open_range.start);
+                        surround.pair(scope.as_ref())
+                    }
+                    None => (String::new(), String::new()),
+                };
+
+                let cursor = display_map.buffer_snapshot.anchor_before(open_range.start);
+                cursors.push(cursor..cursor);
+                edits.push((open_range, open));
+                edits.push((close_range, close));
+            }
+
+            editor.buffer().update(cx, |buffer, cx| {
+                buffer.edit(edits, None, cx);
+            });
+            editor.set_clip_at_line_ends(true, cx);
+            editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_anchor_ranges(cursors);
+            });
+        });
+    });
+    vim.clear_operator(cx);
+}
+
+/// Returns the range from the first non-whitespace character of the selection's first
+/// line to the end of its last line.
+fn line_contents_range(
+    map: &DisplaySnapshot,
+    selection: &Selection<DisplayPoint>,
+) -> Range<DisplayPoint> {
+    let start = motion::first_non_whitespace(map, false, selection.start);
+    let mut end_row = selection.end.to_point(map).row;
+    if end_row > selection.start.to_point(map).row && selection.end.to_point(map).column == 0 {
+        end_row -= 1;
+    }
+    let end = Point::new(end_row, map.buffer_snapshot.line_len(end_row));
+    start..end.to_display_point(map)
+}
+
+/// Returns the buffer ranges of the opening and closing delimiters of `object` around
+/// the selection.
+fn delimiter_ranges(
+    map: &DisplaySnapshot,
+    selection: Selection<DisplayPoint>,
+    object: Object,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let around = object.range(map, selection.clone(), true)?;
+    let around = around.start.to_offset(map, Bias::Left)..around.end.to_offset(map, Bias::Right);
+
+    if object == Object::Tag {
+        let inner = object.range(map, selection, false)?;
+        let inner = inner.start.to_offset(map, Bias::Left)..inner.end.to_offset(map, Bias::Right);
+        return Some((around.start..inner.start, inner.end..around.end));
+    }
+
+    // The around range of a marker object may include whitespace outside the
+    // delimiters, so find the delimiters themselves within it.
+    let (open_marker, close_marker) = match object {
+        Object::Quotes => ('\'', '\''),
+        Object::BackQuotes => ('`', '`'),
+        Object::DoubleQuotes => ('"', '"'),
+        Object::VerticalBars => ('|', '|'),
+        Object::Parentheses => ('(', ')'),
+        Object::SquareBrackets => ('[', ']'),
+        Object::CurlyBrackets => ('{', '}'),
+        Object::AngleBrackets => ('<', '>'),
+        _ => return None,
+    };
+    let snapshot = &map.buffer_snapshot;
+    let open_start = around.start
+        + snapshot
+            .chars_at(around.start)
+            .take_while(|ch| *ch != open_marker)
+            .map(char::len_utf8)
+            .sum::<usize>();
+    let close_end = around.end
+        - snapshot
+            .reversed_chars_at(around.end)
+            .take_while(|ch| *ch != close_marker)
+            .map(char::len_utf8)
+            .sum::<usize>();
+    let open = open_start..open_start + open_marker.len_utf8();
+    let close = close_end - close_marker.len_utf8()..close_end;
+    if open.end > close.start {
+        return None;
+    }
+    Some((open, close))
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_add_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", ")"]);
+        cx.assert_state("The ˇ(quick) brown fox", Mode::Normal);
+
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "("]);
+        cx.assert_state("The ˇ( quick ) brown fox", Mode::Normal);
+
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "e", "*"]);
+        cx.assert_state("The qu*ˇick* brown fox", Mode::Normal);
+
+        // counts
+        cx.set_state("The ˇquick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "2", "e", "]"]);
+        cx.assert_state("The ˇ[quick brown] fox", Mode::Normal);
+
+        // whole line
+        cx.set_state("    The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "s", "\""]);
+        cx.assert_state("    ˇ\"The quick brown fox\"", Mode::Normal);
+
+        // tags
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "<", "e", "m", ">"]);
+        cx.assert_state("The ˇ<em>quick</em> brown fox", Mode::Normal);
+
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes([
+            "y", "s", "i", "w", "t", "a", "space", "i", "d", "=", "x", "enter",
+        ]);
+        cx.assert_state("The ˇ<a id=x>quick</a> brown fox", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_add_surrounds_visual(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("The «quickˇ» brown fox", Mode::Visual);
+        cx.simulate_keystrokes(["shift-s", "'"]);
+        cx.assert_state("The ˇ'quick' brown fox", Mode::Normal);
+
+        cx.set_state("The quˇick brown fox", Mode::Normal);
+        cx.simulate_keystrokes(["v", "i", "w", "shift-s", ")"]);
+        cx.assert_state("The ˇ(quick) brown fox", Mode::Normal);
+
+        cx.set_state(
+            indoc! {"
+                fn boop() {
+                    ˇlet a = 1;
+                }"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(["shift-v", "shift-s", "}"]);
+        cx.assert_state(
+            indoc! {"
+                fn boop() {
+                ˇ    {
+                        let a = 1;
+                    }
+                }"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_change_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("let a = (quˇick, brown);", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "(", "]"]);
+        cx.assert_state("let a = ˇ[quick, brown];", Mode::Normal);
+
+        cx.set_state("let a = \"quˇick\";", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "\"", "'"]);
+        cx.assert_state("let a = ˇ'quick';", Mode::Normal);
+
+        cx.set_state("let a = \"quˇick\";", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "\"", "<", "b", ">"]);
+        cx.assert_state("let a = ˇ<b>quick</b>;", Mode::Normal);
+
+        // unknown surrounds are ignored
+        cx.set_state("let a = quˇick;", Mode::Normal);
+        cx.simulate_keystrokes(["c", "s", "(", "]"]);
+        cx.assert_state("let a = quˇick;", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_delete_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("let a = call(quˇick, brown);", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", ")"]);
+        cx.assert_state("let a = callˇquick, brown;", Mode::Normal);
+
+        cx.set_state("let a = \"quˇick\"  ;", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "\""]);
+        cx.assert_state("let a = ˇquick  ;", Mode::Normal);
+
+        cx.set_state("let a = [1, [quˇick], 2];", Mode::Normal);
+        cx.simulate_keystrokes(["d", "s", "]"]);
+        cx.assert_state("let a = [1, ˇquick, 2];", Mode::Normal);
+
+        // dot repeat
+        cx.simulate_keystrokes(["."]);
+        cx.assert_state("let a = ˇ1, quick, 2;", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_surrounds_dot_repeat(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two", Mode::Normal);
+        cx.simulate_keystrokes(["y", "s", "i", "w", "<", "b", ">"]);
+        cx.assert_state("ˇ<b>one</b> two", Mode::Normal);
+        cx.simulate_keystrokes(["shift-w", "."]);
+        cx.assert_state("<b>one</b> ˇ<b>two</b>", Mode::Normal);
+    }
+}
@@ -12,8 +12,11 @@ mod normal;
 mod object;
 mod replace;
 mod state;
+mod surrounds;
 mod utils;
+mod vimrc;
 mod visual;
+mod window;
 
 use anyhow::Result;
 use collections::HashMap;
@@ -29,15 +32,23 @@ use gpui::{
 use language::{CursorShape, Point, Selection, SelectionGoal, TransactionId};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
-use normal::normal_replace;
+use normal::{
+    normal_replace,
+    search::{confirm_replace_input, preview_replace, stop_confirm_replace},
+};
 use replace::multi_replace;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use settings::{update_settings_file, Settings, SettingsStore};
 use state::{EditorState, Mode, Operator, RecordedSelection, WorkspaceState};
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, rc::Rc, sync::Arc};
+use surrounds::{
+    add_surrounds, change_surrounds, change_surrounds_target, delete_surrounds, surround_tag_input,
+    SurroundsType,
+};
 use ui::BorrowAppContext;
+pub use vimrc::Vimrc;
 use visual::{visual_block_motion, visual_replace};
 use workspace::{self, Workspace};
 
@@ -138,6 +149,8 @@ fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     replace::register(workspace, cx);
     object::register(workspace, cx);
     visual::register(workspace, cx);
+    window::register(workspace, cx);
+    vimrc::register(workspace, cx);
 }
 
 /// Called whenever an keystroke is typed so vim can observe all actions
@@ -148,6 +161,13 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
         .as_ref()
         .map(|action| action.boxed_clone())
     {
+        // Keystrokes mapped in the vimrc are observed (and recorded) as they are replayed.
+        if action.as_any().is::<vimrc::MapKeystrokes>()
+            || action.as_any().is::<vimrc::NoremapKeystrokes>()
+        {
+            return;
+        }
+
         Vim::update(cx, |vim, _| {
             if vim.workspace_state.recording {
                 vim.workspace_state
@@ -170,7 +190,16 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
     }
 
     Vim::update(cx, |vim, cx| match vim.active_operator() {
-        Some(Operator::FindForward { .. } | Operator::FindBackward { .. } | Operator::Replace) => {}
+        Some(
+            Operator::FindForward { .. }
+            | Operator::FindBackward { .. }
+            | Operator::Replace
+            | Operator::AddSurrounds { target: Some(_) }
+            | Operator::ChangeSurrounds { .. }
+            | Operator::DeleteSurrounds
+            | Operator::SurroundTag { .. }
+            | Operator::ConfirmReplace,
+        ) => {}
         Some(_) => {
             vim.clear_operator(cx);
         }
@@ -362,6 +391,7 @@ impl Vim {
     }
 
     fn switch_mode(&mut self, mode: Mode, leave_selections: bool, cx: &mut WindowContext) {
+        stop_confirm_replace(self, cx);
         let state = self.state();
         let last_mode = state.mode;
         let prior_mode = state.last_mode;
@@ -470,10 +500,25 @@ impl Vim {
     }
 
     fn push_operator(&mut self, operator: Operator, cx: &mut WindowContext) {
-        if matches!(
-            operator,
-            Operator::Change | Operator::Delete | Operator::Replace
-        ) {
+        // The surround operators are reached through `y`, `c` and `d`, which they replace.
+        let starts_surround = match &operator {
+            Operator::AddSurrounds { target } => !matches!(
+                target,
+                Some(SurroundsType::Motion(_) | SurroundsType::Object(..))
+            ),
+            Operator::ChangeSurrounds { target } => target.is_none(),
+            Operator::DeleteSurrounds => true,
+            _ => false,
+        };
+        if starts_surround {
+            self.update_state(|state| state.operator_stack.clear());
+        }
+        if starts_surround
+            || matches!(
+                operator,
+                Operator::Change | Operator::Delete | Operator::Replace
+            )
+        {
             self.start_recording(cx)
         };
         self.update_state(|state| state.operator_stack.push(operator));
@@ -491,6 +536,7 @@ impl Vim {
         popped_operator
     }
     fn clear_operator(&mut self, cx: &mut WindowContext) {
+        stop_confirm_replace(self, cx);
         self.take_count(cx);
         self.update_state(|state| state.operator_stack.clear());
         self.sync_vim_settings(cx);
@@ -622,6 +668,16 @@ impl Vim {
                 Mode::Visual | Mode::VisualLine | Mode::VisualBlock => visual_replace(text, cx),
                 _ => Vim::update(cx, |vim, cx| vim.clear_operator(cx)),
             },
+            Some(Operator::AddSurrounds {
+                target: Some(target),
+            }) => add_surrounds(text, target, cx),
+            Some(Operator::ChangeSurrounds { target: None }) => change_surrounds_target(text, cx),
+            Some(Operator::ChangeSurrounds {
+                target: Some(target),
+            }) => change_surrounds(text, target, cx),
+            Some(Operator::DeleteSurrounds) => delete_surrounds(text, cx),
+            Some(Operator::SurroundTag { .. }) => surround_tag_input(text, cx),
+            Some(Operator::ConfirmReplace) => confirm_replace_input(text, cx),
             _ => match Vim::read(cx).state().mode {
                 Mode::Replace => multi_replace(text, cx),
                 _ => {}
@@ -650,6 +706,7 @@ impl Vim {
         });
         CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
             interceptor.set(Box::new(command::command_interceptor));
+            interceptor.set_preview(Rc::new(preview_replace));
         });
 
         if let Some(active_window) = cx
@@ -701,7 +758,11 @@ impl Vim {
             editor.set_autoindent(state.should_autoindent());
             editor.selections.line_mode = matches!(state.mode, Mode::VisualLine);
             if editor.is_focused(cx) {
-                editor.set_keymap_context_layer::<Self>(state.keymap_context_layer(), cx);
+                let mut context = state.keymap_context_layer();
+                if vim.workspace_state.noremap {
+                    context.add("VimNoremap");
+                }
+                editor.set_keymap_context_layer::<Self>(context, cx);
             // disables vim if the rename editor is focused,
             // but not if the command palette is open.
             } else if editor.focus_handle(cx).contains_focused(cx) {
@@ -0,0 +1,523 @@
+//! Support for a subset of vim's own configuration language.
+//!
+//! Zed reads `~/.config/zed/vimrc` and understands the mapping commands
+//! (`map`, `noremap`, `nmap`, `nnoremap`, `vmap`, `vnoremap`, `xmap`, `xnoremap`,
+//! `imap` and `inoremap`), `let mapleader` and `set` for a handful of common options.
+//! Mappings are compiled into key bindings whose right hand side is replayed as
+//! keystrokes through vim, options are translated into the equivalent settings.
+
+use anyhow::{anyhow, Context, Result};
+use gpui::{impl_actions, Keystroke, ViewContext, WindowContext};
+use serde_derive::Deserialize;
+use serde_json::{json, Value};
+use settings::KeymapFile;
+use util::{merge_non_null_json_value_into, ResultExt};
+use workspace::{QueuedInput, SendKeystrokes, Workspace};
+
+use crate::Vim;
+
+/// Sends the given keystrokes, expanding any mappings defined in the vimrc, as the
+/// right hand side of a `map` does.
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct MapKeystrokes(pub String);
+
+/// Sends the given keystrokes without expanding any of the mappings defined in the
+/// vimrc, as the right hand side of a `noremap` does.
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct NoremapKeystrokes(pub String);
+
+impl_actions!(vim, [MapKeystrokes, NoremapKeystrokes]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(map_keystrokes);
+    workspace.register_action(noremap_keystrokes);
+}
+
+fn map_keystrokes(_: &mut Workspace, action: &MapKeystrokes, cx: &mut ViewContext<Workspace>) {
+    // Dispatched as an action rather than bound directly, so that vim can tell
+    // keystrokes replayed by a vimrc mapping apart from user `SendKeystrokes` bindings.
+    cx.dispatch_action(Box::new(SendKeystrokes(action.0.clone())));
+}
+
+fn noremap_keystrokes(
+    workspace: &mut Workspace,
+    action: &NoremapKeystrokes,
+    cx: &mut ViewContext<Workspace>,
+) {
+    // The keystrokes share the workspace's queue with those of `map`, so that a
+    // mapping that expands to both runs them in order. The flag is only raised
+    // around them, so that the keystrokes queued after them are still expanded.
+    let mut input = vec![QueuedInput::Callback(Box::new(|cx| {
+        Vim::update(cx, |vim, cx| vim.set_noremap(true, cx))
+    }))];
+    input.extend(
+        action
+            .0
+            .split(' ')
+            .flat_map(|k| Keystroke::parse(k).log_err())
+            .map(QueuedInput::Keystroke),
+    );
+    input.push(QueuedInput::Callback(Box::new(|cx| {
+        Vim::update(cx, |vim, cx| vim.set_noremap(false, cx))
+    })));
+    workspace.send_input(input, cx);
+}
+
+impl Vim {
+    fn set_noremap(&mut self, noremap: bool, cx: &mut WindowContext) {
+        self.workspace_state.noremap = noremap;
+        self.sync_vim_settings(cx);
+    }
+}
+
+/// The modes a mapping applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MapMode {
+    /// `map`: normal, visual and operator pending.
+    All,
+    Normal,
+    Visual,
+    Insert,
+}
+
+impl MapMode {
+    fn context(&self) -> &'static str {
+        match self {
+            MapMode::All => "Editor && VimControl && !VimWaiting && !menu && !VimNoremap",
+            MapMode::Normal => {
+                "Editor && vim_mode == normal && vim_operator == none && !VimWaiting && !VimNoremap"
+            }
+            MapMode::Visual => {
+                "Editor && vim_mode == visual && !VimWaiting && !VimObject && !VimNoremap"
+            }
+            MapMode::Insert => "Editor && vim_mode == insert && !VimNoremap",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Mapping {
+    mode: MapMode,
+    noremap: bool,
+    /// The keystrokes to bind, in the format used by keymap files.
+    lhs: String,
+    /// The keystrokes to send, or `None` for `<Nop>`.
+    rhs: Option<String>,
+}
+
+impl Mapping {
+    fn action(&self) -> Value {
+        match &self.rhs {
+            None => Value::Null,
+            Some(rhs) if self.noremap => json!(["vim::NoremapKeystrokes", rhs]),
+            Some(rhs) => json!(["vim::MapKeystrokes", rhs]),
+        }
+    }
+}
+
+/// The parsed contents of a vimrc file.
+#[derive(Clone, Debug)]
+pub struct Vimrc {
+    mappings: Vec<Mapping>,
+    settings: Value,
+}
+
+impl Default for Vimrc {
+    fn default() -> Self {
+        Self::parse("")
+    }
+}
+
+impl Vimrc {
+    /// Parses the given vimrc. Like vim, lines that cannot be understood are
+    /// reported (to the log) and skipped.
+    pub fn parse(content: &str) -> Self {
+        let mut vimrc = Self {
+            mappings: Vec::new(),
+            settings: json!({}),
+        };
+        let mut leader = "\\".to_string();
+        for (ix, line) in content.lines().enumerate() {
+            vimrc
+                .parse_line(line, &mut leader)
+                .with_context(|| format!("invalid vimrc line {}: {:?}", ix + 1, line.trim()))
+                .log_err();
+        }
+        vimrc
+    }
+
+    /// The key bindings for the mappings, in the order they were defined.
+    pub fn keymap(&self) -> Result<KeymapFile> {
+        let blocks = self
+            .mappings
+            .iter()
+            .map(|mapping| {
+                json!({
+                    "context": mapping.mode.context(),
+                    "bindings": { (mapping.lhs.clone()): mapping.action() },
+                })
+            })
+            .collect();
+        Ok(serde_json::from_value(Value::Array(blocks))?)
+    }
+
+    /// The settings corresponding to the `set` options.
+    pub fn settings(&self) -> &Value {
+        &self.settings
+    }
+
+    fn parse_line(&mut self, line: &str, leader: &mut String) -> Result<()> {
+        let line = line.trim().trim_start_matches(':');
+        if line.is_empty() || line.starts_with('"') {
+            return Ok(());
+        }
+
+        let (command, args) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(command, args)| (command, args.trim()));
+        let (mode, noremap) = match command {
+            "let" => return parse_let(args, leader),
+            "set" | "se" => return self.parse_set(args),
+            "map" => (MapMode::All, false),
+            "noremap" => (MapMode::All, true),
+            "nmap" => (MapMode::Normal, false),
+            "nnoremap" => (MapMode::Normal, true),
+            "vmap" | "xmap" => (MapMode::Visual, false),
+            "vnoremap" | "xnoremap" => (MapMode::Visual, true),
+            "imap" => (MapMode::Insert, false),
+            "inoremap" => (MapMode::Insert, true),
+            _ => return Err(anyhow!("unsupported command {command:?}")),
+        };
+
+        let mut args = args;
+        let lhs = loop {
+            let (arg, rest) = args
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| anyhow!("expected a left and right hand side"))?;
+            args = rest.trim_start();
+            match arg.to_ascii_lowercase().as_str() {
+                "<silent>" | "<unique>" | "<nowait>" => {}
+                "<buffer>" | "<expr>" | "<script>" | "<special>" => {
+                    return Err(anyhow!("unsupported map argument {arg}"))
+                }
+                _ => break arg,
+            }
+        };
+        let rhs = args.trim_end();
+        if rhs.is_empty() {
+            return Err(anyhow!("expected a right hand side for {lhs}"));
+        }
+
+        self.mappings.push(Mapping {
+            mode,
+            noremap,
+            lhs: parse_keys(lhs, leader)?.join(" "),
+            rhs: if rhs.eq_ignore_ascii_case("<nop>") {
+                None
+            } else {
+                Some(parse_keys(rhs, leader)?.join(" "))
+            },
+        });
+        Ok(())
+    }
+
+    fn parse_set(&mut self, args: &str) -> Result<()> {
+        for option in args.split_whitespace() {
+            let (name, value) = match option.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (option, None),
+            };
+            let (name, enabled) = match name.strip_prefix("no") {
+                Some(name) if value.is_none() => (name, false),
+                _ => (name, true),
+            };
+            let number = || -> Result<u32> {
+                value
+                    .ok_or_else(|| anyhow!("option {name} requires a value"))?
+                    .parse()
+                    .with_context(|| format!("invalid value for option {name}"))
+            };
+            let flag = || -> Result<bool> {
+                if value.is_some() {
+                    Err(anyhow!("option {name} does not take a value"))
+                } else {
+                    Ok(enabled)
+                }
+            };
+
+            let settings = match name {
+                "number" | "nu" => json!({ "gutter": { "line_numbers": flag()? } }),
+                "relativenumber" | "rnu" => json!({ "relative_line_numbers": flag()? }),
+                "wrap" => {
+                    json!({ "soft_wrap": if flag()? { "editor_width" } else { "none" } })
+                }
+                "expandtab" | "et" => json!({ "hard_tabs": !flag()? }),
+                "smartcase" | "scs" => json!({ "vim": { "use_smartcase_find": flag()? } }),
+                "tabstop" | "ts" | "shiftwidth" | "sw" | "softtabstop" | "sts" => {
+                    match number()? {
+                        // A width of zero defers to `tabstop` in vim.
+                        0 => continue,
+                        tab_size => json!({ "tab_size": tab_size }),
+                    }
+                }
+                "scrolloff" | "so" => json!({ "vertical_scroll_margin": number()? }),
+                "clipboard" | "cb" => {
+                    let value = value.unwrap_or_default();
+                    let use_system_clipboard = if value
+                        .split(',')
+                        .any(|value| value == "unnamed" || value == "unnamedplus")
+                    {
+                        "always"
+                    } else {
+                        "never"
+                    };
+                    json!({ "vim": { "use_system_clipboard": use_system_clipboard } })
+                }
+                _ => return Err(anyhow!("unsupported option {name:?}")),
+            };
+            merge_non_null_json_value_into(settings, &mut self.settings);
+        }
+        Ok(())
+    }
+}
+
+fn parse_let(args: &str, leader: &mut String) -> Result<()> {
+    let (name, value) = args
+        .split_once('=')
+        .ok_or_else(|| anyhow!("expected an assignment"))?;
+    let name = name.trim();
+    if name != "mapleader" && name != "g:mapleader" {
+        return Err(anyhow!("unsupported variable {name:?}"));
+    }
+
+    let value = value.trim();
+    *leader = if let Some(value) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        // In double quoted strings `\<Space>` is key notation and `\\` is a backslash.
+        value.replace("\\<", "<").replace("\\\\", "\\")
+    } else if let Some(value) = value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+    {
+        value.to_string()
+    } else {
+        return Err(anyhow!("expected a string, got {value:?}"));
+    };
+    parse_keys(leader, "")?;
+    Ok(())
+}
+
+/// Translates vim's key notation (`<C-w>j`, `<leader>f`, `ZZ`) into keystrokes as
+/// written in keymap files.
+fn parse_keys(keys: &str, leader: &str) -> Result<Vec<String>> {
+    let mut keystrokes = Vec::new();
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>').filter(|end| *end > 1) {
+                let name = &rest[1..end];
+                if name.eq_ignore_ascii_case("leader") {
+                    if leader.is_empty() {
+                        return Err(anyhow!("<leader> cannot be used here"));
+                    }
+                    keystrokes.extend(parse_keys(leader, "")?);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+                if let Some(keystroke) = parse_special_key(name) {
+                    keystrokes.push(keystroke);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+        keystrokes.push(key_for_char(c));
+        rest = &rest[c.len_utf8()..];
+    }
+    Ok(keystrokes)
+}
+
+fn parse_special_key(name: &str) -> Option<String> {
+    let mut modifiers = Vec::new();
+    let mut key = name;
+    while let Some((modifier, rest)) = key.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+        modifiers.push(match modifier.to_ascii_lowercase().as_str() {
+            "c" => "ctrl",
+            "s" => "shift",
+            "a" | "m" => "alt",
+            "d" => "cmd",
+            _ => return None,
+        });
+        key = rest;
+    }
+
+    let key = match key.to_ascii_lowercase().as_str() {
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "esc" => "escape".to_string(),
+        "space" => "space".to_string(),
+        "tab" => "tab".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        key @ ("up" | "down" | "left" | "right" | "home" | "end" | "pageup" | "pagedown") => {
+            key.to_string()
+        }
+        key if key.len() > 1
+            && key.starts_with('f')
+            && key[1..]
+                .parse::<u8>()
+                .map_or(false, |n| (1..=12).contains(&n)) =>
+        {
+            key.to_string()
+        }
+        _ => {
+            let mut chars = key.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            // `<C-A>` is the same as `<C-a>`, but `<S-a>` is `A`.
+            if modifiers.is_empty() || modifiers == ["shift"] {
+                key_for_char(c)
+            } else {
+                c.to_ascii_lowercase().to_string()
+            }
+        }
+    };
+
+    if modifiers.is_empty() || modifiers == ["shift"] && key.starts_with("shift-") {
+        Some(key)
+    } else {
+        Some(format!("{}-{}", modifiers.join("-"), key))
+    }
+}
+
+fn key_for_char(c: char) -> String {
+    match c {
+        ' ' => "space".to_string(),
+        c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+        c => c.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+    use serde_json::json;
+
+    use super::{parse_keys, Vimrc};
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(parse_keys("gg", "\\").unwrap(), ["g", "g"]);
+        assert_eq!(parse_keys("ZZ", "\\").unwrap(), ["shift-z", "shift-z"]);
+        assert_eq!(
+            parse_keys("<C-w>j<CR>", "\\").unwrap(),
+            ["ctrl-w", "j", "enter"]
+        );
+        assert_eq!(parse_keys("<leader>f", " ").unwrap(), ["space", "f"]);
+        assert_eq!(parse_keys("<Leader><lt>", ",").unwrap(), [",", "<"]);
+        assert_eq!(
+            parse_keys("<S-a><C-S-K>", "").unwrap(),
+            ["shift-a", "ctrl-shift-k"]
+        );
+        assert_eq!(parse_keys("<>", "").unwrap(), ["<", ">"]);
+        assert_eq!(parse_keys("<Esc>:w", "").unwrap(), ["escape", ":", "w"]);
+    }
+
+    #[test]
+    fn test_parse_vimrc() {
+        let vimrc = Vimrc::parse(indoc! {r#"
+            " comments and blank lines are ignored
+
+            let mapleader = "\<Space>"
+            set number norelativenumber tabstop=2 scrolloff=8
+            set clipboard=unnamedplus
+            nnoremap <silent> <leader>w :w<CR>
+            imap jk <Esc>
+            vmap < <gv
+            map Q <Nop>
+            unknown command
+        "#});
+
+        let mappings = vimrc
+            .mappings
+            .iter()
+            .map(|mapping| {
+                (
+                    mapping.lhs.as_str(),
+                    mapping.rhs.as_deref(),
+                    mapping.noremap,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mappings,
+            [
+                ("space w", Some(": w enter"), true),
+                ("j k", Some("escape"), false),
+                ("<", Some("< g v"), false),
+                ("shift-q", None, false),
+            ]
+        );
+        assert_eq!(
+            vimrc.settings(),
+            &json!({
+                "gutter": { "line_numbers": true },
+                "relative_line_numbers": false,
+                "tab_size": 2,
+                "vertical_scroll_margin": 8,
+                "vim": { "use_system_clipboard": "always" },
+            })
+        );
+    }
+
+    #[gpui::test]
+    async fn test_vimrc_mappings(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|cx| {
+            Vimrc::parse(indoc! {"
+                let mapleader = ','
+                nnoremap <leader>l $
+                nnoremap l h
+                nnoremap L ll
+                nmap H ll
+                nmap <leader>x Lx
+                inoremap jk <Esc>
+            "})
+            .keymap()
+            .unwrap()
+            .add_to_cx(cx)
+            .unwrap()
+        });
+
+        cx.set_state("ˇThe quick brown", Mode::Normal);
+        cx.simulate_keystrokes([",", "l"]);
+        cx.assert_state("The quick browˇn", Mode::Normal);
+
+        // `noremap` sends the keystrokes as if nothing was mapped...
+        cx.set_state("The ˇquick brown", Mode::Normal);
+        cx.simulate_keystrokes(["shift-l"]);
+        cx.assert_state("The quiˇck brown", Mode::Normal);
+
+        // ...whereas `map` expands mappings in its right hand side.
+        cx.set_state("The ˇquick brown", Mode::Normal);
+        cx.simulate_keystrokes(["shift-h"]);
+        cx.assert_state("Thˇe quick brown", Mode::Normal);
+
+        // Keystrokes of a `noremap` that are sent by a `map` keep their place among the
+        // other keystrokes of the `map`.
+        cx.set_state("The ˇquick brown", Mode::Normal);
+        cx.simulate_keystrokes([",", "x"]);
+        cx.assert_state("The quiˇk brown", Mode::Normal);
+
+        cx.set_state("The ˇquick brown", Mode::Normal);
+        cx.simulate_keystrokes(["i", "j", "k"]);
+        cx.assert_state("Theˇ quick brown", Mode::Normal);
+    }
+}
@@ -0,0 +1,196 @@
+use gpui::{actions, impl_actions, px, size, Axis, Pixels, Size, ViewContext, WindowContext};
+use serde_derive::Deserialize;
+use workspace::Workspace;
+
+use crate::Vim;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum ResizeIntent {
+    Lengthen,
+    Shorten,
+    Widen,
+    Narrow,
+}
+
+/// Grows or shrinks the active pane by [count] lines or columns (`ctrl-w +`, `-`, `>` and `<`).
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ResizePane(pub ResizeIntent);
+
+/// `:resize` and `:vertical resize`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ResizeCommand {
+    pub vertical: bool,
+    /// The new size in lines (or columns if vertical), or the change in size if relative.
+    /// Without one, the pane is made as large as possible.
+    pub size: Option<i32>,
+    pub relative: bool,
+}
+
+impl_actions!(vim, [ResizePane, ResizeCommand]);
+
+// `ctrl-w _` and `ctrl-w |` set the height or width of the active pane to [count]
+// lines or columns, or make it as large as possible without a count.
+actions!(vim, [SetPaneHeight, SetPaneWidth]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|workspace, action: &ResizePane, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx)).unwrap_or(1) as f32;
+        let Some(cell) = cell_size(cx) else {
+            return;
+        };
+        match action.0 {
+            ResizeIntent::Lengthen => {
+                workspace.resize_pane(Axis::Vertical, cell.height * count, cx)
+            }
+            ResizeIntent::Shorten => {
+                workspace.resize_pane(Axis::Vertical, -cell.height * count, cx)
+            }
+            ResizeIntent::Widen => workspace.resize_pane(Axis::Horizontal, cell.width * count, cx),
+            ResizeIntent::Narrow => {
+                workspace.resize_pane(Axis::Horizontal, -cell.width * count, cx)
+            }
+        }
+    });
+    workspace.register_action(|workspace, _: &SetPaneHeight, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx));
+        set_pane_size(
+            workspace,
+            Axis::Vertical,
+            count.map(|count| count as i32),
+            cx,
+        );
+    });
+    workspace.register_action(|workspace, _: &SetPaneWidth, cx| {
+        let count = Vim::update(cx, |vim, cx| vim.take_count(cx));
+        set_pane_size(
+            workspace,
+            Axis::Horizontal,
+            count.map(|count| count as i32),
+            cx,
+        );
+    });
+    workspace.register_action(|workspace, action: &ResizeCommand, cx| {
+        let axis = if action.vertical {
+            Axis::Horizontal
+        } else {
+            Axis::Vertical
+        };
+        match action.size {
+            Some(amount) if action.relative => {
+                let Some(cell) = cell_size(cx) else {
+                    return;
+                };
+                workspace.resize_pane(axis, cell.along(axis) * amount as f32, cx);
+            }
+            size => set_pane_size(workspace, axis, size, cx),
+        }
+    });
+}
+
+fn set_pane_size(
+    workspace: &mut Workspace,
+    axis: Axis,
+    cells: Option<i32>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    match cells {
+        Some(cells) => {
+            let Some(cell) = cell_size(cx) else {
+                return;
+            };
+            workspace.set_pane_size(axis, cell.along(axis) * cells.max(1) as f32, cx);
+        }
+        None => workspace.resize_pane(axis, px(f32::MAX), cx),
+    }
+}
+
+/// The size of a line and column in the active editor, which vim counts window sizes in.
+fn cell_size(cx: &mut WindowContext) -> Option<Size<Pixels>> {
+    Vim::update(cx, |vim, cx| {
+        vim.update_active_editor(cx, |_, editor, cx| {
+            let style = editor.style()?.text.clone();
+            let font_size = style.font_size.to_pixels(cx.rem_size());
+            let line_height = style.line_height_in_pixels(cx.rem_size());
+            let font_id = cx.text_system().resolve_font(&style.font());
+            let em_width = cx
+                .text_system()
+                .advance(font_id, font_size, 'm')
+                .ok()?
+                .width;
+            Some(size(em_width, line_height))
+        })
+        .flatten()
+    })
+}
+
+/// Parses `:res[ize] [+-]N` and `:vert[ical] res[ize] [+-]N`.
+pub(crate) fn parse_resize_command(query: &str) -> Option<ResizeCommand> {
+    let (vertical, query) = match query.split_once(char::is_whitespace) {
+        Some((modifier, rest)) if "vertical".starts_with(modifier) && modifier.len() >= 4 => {
+            (true, rest.trim_start())
+        }
+        _ => (false, query),
+    };
+    let (command, argument) = query
+        .split_once(char::is_whitespace)
+        .map_or((query, ""), |(command, argument)| {
+            (command, argument.trim())
+        });
+    if command.len() < 3 || !"resize".starts_with(command) {
+        return None;
+    }
+
+    let (size, relative) = match argument {
+        "" => (None, false),
+        argument => {
+            let relative = argument.starts_with(['+', '-']);
+            let argument = argument.strip_prefix('+').unwrap_or(argument);
+            (Some(argument.parse().ok()?), relative)
+        }
+    };
+    Some(ResizeCommand {
+        vertical,
+        size,
+        relative,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_resize_command, ResizeCommand};
+
+    #[test]
+    fn test_parse_resize_command() {
+        let command = |vertical, size, relative| {
+            Some(ResizeCommand {
+                vertical,
+                size,
+                relative,
+            })
+        };
+        assert_eq!(parse_resize_command("res"), command(false, None, false));
+        assert_eq!(
+            parse_resize_command("resize 10"),
+            command(false, Some(10), false)
+        );
+        assert_eq!(
+            parse_resize_command("res +5"),
+            command(false, Some(5), true)
+        );
+        assert_eq!(
+            parse_resize_command("resi -5"),
+            command(false, Some(-5), true)
+        );
+        assert_eq!(
+            parse_resize_command("vert res 80"),
+            command(true, Some(80), false)
+        );
+        assert_eq!(
+            parse_resize_command("vertical resize -2"),
+            command(true, Some(-2), true)
+        );
+        assert_eq!(parse_resize_command("re"), None);
+        assert_eq!(parse_resize_command("resize x"), None);
+        assert_eq!(parse_resize_command("ver res"), None);
+    }
+}
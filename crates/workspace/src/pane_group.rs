@@ -24,6 +24,34 @@ pub struct PaneGroup {
     pub(crate) root: Member,
 }
 
+/// The splits of a pane group and their sizes, for panels to save and restore their panes with.
+pub enum PaneLayout<P> {
+    Split {
+        axis: Axis,
+        /// How much of the split each member takes, all of them alike if not known.
+        flexes: Option<Vec<f32>>,
+        members: Vec<PaneLayout<P>>,
+    },
+    Pane(P),
+}
+
+impl<P> PaneLayout<P> {
+    pub fn map<Q>(self, f: &mut impl FnMut(P) -> Q) -> PaneLayout<Q> {
+        match self {
+            PaneLayout::Split {
+                axis,
+                flexes,
+                members,
+            } => PaneLayout::Split {
+                axis,
+                flexes,
+                members: members.into_iter().map(|member| member.map(f)).collect(),
+            },
+            PaneLayout::Pane(pane) => PaneLayout::Pane(f(pane)),
+        }
+    }
+}
+
 impl PaneGroup {
     pub(crate) fn with_root(root: Member) -> Self {
         Self { root }
@@ -91,6 +119,171 @@ impl PaneGroup {
         };
     }
 
+    /// Grows the pane (or the group containing it) along the given axis by `amount`,
+    /// shrinking its siblings, or shrinks it if `amount` is negative.
+    /// Sizes never go below the minimum pane size.
+    pub fn resize(&mut self, pane: &View<Pane>, axis: Axis, amount: Pixels) {
+        match &mut self.root {
+            Member::Pane(_) => {}
+            Member::Axis(root) => {
+                root.resize(pane, axis, amount);
+            }
+        }
+    }
+
+    /// Gives every pane an equal share of its group.
+    pub fn reset_pane_sizes(&mut self) {
+        if let Member::Axis(axis) = &mut self.root {
+            axis.reset_flexes();
+        }
+    }
+
+    /// Rotates the pane and its siblings, moving each one to the next position in their
+    /// group (or to the previous one if `reverse`) and wrapping around at the end.
+    pub fn rotate(&mut self, pane: &View<Pane>, reverse: bool) {
+        if let Some(parent) = self.parent_of(pane) {
+            // Each member keeps its size as it moves.
+            let mut flexes = parent.flexes.lock();
+            if reverse {
+                parent.members.rotate_left(1);
+                flexes.rotate_left(1);
+            } else {
+                parent.members.rotate_right(1);
+                flexes.rotate_right(1);
+            }
+        }
+    }
+
+    /// Exchanges the pane with the next member of its group, or with the previous one if
+    /// it is the last.
+    pub fn exchange(&mut self, pane: &View<Pane>) {
+        if let Some(parent) = self.parent_of(pane) {
+            if let Some(ix) = parent
+                .members
+                .iter()
+                .position(|member| member.contains(pane))
+            {
+                let other_ix = if ix + 1 < parent.members.len() {
+                    ix + 1
+                } else {
+                    ix - 1
+                };
+                parent.members.swap(ix, other_ix);
+                parent.flexes.lock().swap(ix, other_ix);
+            }
+        }
+    }
+
+    /// Moves the pane to the given edge of the group, where it spans the whole
+    /// height or width of the group.
+    pub fn move_to_edge(&mut self, pane: &View<Pane>, direction: SplitDirection) -> Result<()> {
+        if !self.remove(pane)? {
+            return Ok(());
+        }
+
+        let axis = direction.axis();
+        let new_member = Member::Pane(pane.clone());
+        match &mut self.root {
+            Member::Axis(root) if root.axis == axis => {
+                if direction.increasing() {
+                    root.members.push(new_member);
+                } else {
+                    root.members.insert(0, new_member);
+                }
+                *root.flexes.lock() = vec![1.; root.members.len()];
+            }
+            root => {
+                let old_root = root.clone();
+                let members = if direction.increasing() {
+                    vec![old_root, new_member]
+                } else {
+                    vec![new_member, old_root]
+                };
+                *root = Member::Axis(PaneAxis::new(axis, members));
+            }
+        }
+        Ok(())
+    }
+
+    fn parent_of(&mut self, pane: &View<Pane>) -> Option<&mut PaneAxis> {
+        match &mut self.root {
+            Member::Pane(_) => None,
+            Member::Axis(axis) => axis.parent_of(pane),
+        }
+    }
+
+    /// Restores a pane group, with splits of at least two members each.
+    pub fn from_layout(layout: PaneLayout<View<Pane>>) -> Self {
+        fn member(layout: PaneLayout<View<Pane>>) -> Member {
+            match layout {
+                PaneLayout::Split {
+                    axis,
+                    flexes,
+                    members,
+                } => {
+                    let members = members.into_iter().map(member).collect::<Vec<_>>();
+                    let flexes = flexes.filter(|flexes| flexes.len() == members.len());
+                    Member::Axis(PaneAxis::load(axis, members, flexes))
+                }
+                PaneLayout::Pane(pane) => Member::Pane(pane),
+            }
+        }
+        Self::with_root(member(layout))
+    }
+
+    pub fn layout(&self) -> PaneLayout<View<Pane>> {
+        fn layout(member: &Member) -> PaneLayout<View<Pane>> {
+            match member {
+                Member::Axis(axis) => PaneLayout::Split {
+                    axis: axis.axis,
+                    flexes: Some(axis.flexes.lock().clone()),
+                    members: axis.members.iter().map(layout).collect(),
+                },
+                Member::Pane(pane) => PaneLayout::Pane(pane.clone()),
+            }
+        }
+        layout(&self.root)
+    }
+
+    /// The pane at the given edge of the group, and whether the group is split along that edge.
+    pub fn pane_at_edge(&self, direction: SplitDirection) -> (View<Pane>, bool) {
+        let mut member = &self.root;
+        let mut split_along_edge = false;
+        loop {
+            match member {
+                Member::Pane(pane) => return (pane.clone(), split_along_edge),
+                Member::Axis(axis) => {
+                    let along_edge = axis.axis == direction.axis();
+                    split_along_edge |= along_edge;
+                    member = if along_edge && direction.increasing() {
+                        &axis.members[axis.members.len() - 1]
+                    } else {
+                        &axis.members[0]
+                    };
+                }
+            }
+        }
+    }
+
+    /// Renders a pane group outside of the workspace's center, as in a panel, where nobody is followed.
+    pub fn render_in_panel(
+        &self,
+        project: &Model<Project>,
+        active_pane: &View<Pane>,
+        app_state: &Arc<AppState>,
+        cx: &mut ViewContext<Workspace>,
+    ) -> impl IntoElement {
+        self.render(
+            project,
+            &HashMap::default(),
+            None,
+            active_pane,
+            None,
+            app_state,
+            cx,
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn render(
         &self,
@@ -114,7 +307,7 @@ impl PaneGroup {
         )
     }
 
-    pub(crate) fn panes(&self) -> Vec<&View<Pane>> {
+    pub fn panes(&self) -> Vec<&View<Pane>> {
         let mut panes = Vec::new();
         self.root.collect_panes(&mut panes);
         panes
@@ -440,6 +633,88 @@ impl PaneAxis {
         }
     }
 
+    /// Returns whether the resize could be applied to this axis or one of its descendants.
+    fn resize(&mut self, pane: &View<Pane>, axis: Axis, amount: Pixels) -> bool {
+        let Some(ix) = self.members.iter().position(|member| member.contains(pane)) else {
+            return false;
+        };
+        if let Member::Axis(child) = &mut self.members[ix] {
+            if child.resize(pane, axis, amount) {
+                return true;
+            }
+        }
+        if self.axis != axis || self.members.len() < 2 {
+            return false;
+        }
+
+        let Some(container_size) = self
+            .bounding_boxes
+            .lock()
+            .iter()
+            .try_fold(px(0.), |total, bounds| {
+                Some(total + bounds?.size.along(axis))
+            })
+        else {
+            return false;
+        };
+        if container_size <= px(0.) {
+            return false;
+        }
+
+        let min_size = match axis {
+            Axis::Horizontal => px(HORIZONTAL_MIN_SIZE),
+            Axis::Vertical => px(VERTICAL_MIN_SIZE),
+        };
+        let mut flexes = self.flexes.lock();
+        let len = flexes.len();
+        let size = |flex: f32| container_size * (flex / len as f32);
+        let flex = |size: Pixels| size / container_size * len as f32;
+
+        if amount > px(0.) {
+            // Take the space from the nearest siblings first, preferring those after the pane.
+            let mut remaining = amount;
+            for sibling_ix in (ix + 1..len).chain((0..ix).rev()) {
+                let available = (size(flexes[sibling_ix]) - min_size).max(px(0.));
+                let taken = available.min(remaining);
+                flexes[sibling_ix] -= flex(taken);
+                flexes[ix] += flex(taken);
+                remaining -= taken;
+                if remaining <= px(0.) {
+                    break;
+                }
+            }
+        } else {
+            let given = (size(flexes[ix]) - min_size).max(px(0.)).min(-amount);
+            let sibling_ix = if ix + 1 < len { ix + 1 } else { ix - 1 };
+            flexes[ix] -= flex(given);
+            flexes[sibling_ix] += flex(given);
+        }
+        true
+    }
+
+    fn reset_flexes(&mut self) {
+        *self.flexes.lock() = vec![1.; self.members.len()];
+        for member in &mut self.members {
+            if let Member::Axis(axis) = member {
+                axis.reset_flexes();
+            }
+        }
+    }
+
+    fn parent_of(&mut self, pane: &View<Pane>) -> Option<&mut PaneAxis> {
+        let ix = self
+            .members
+            .iter()
+            .position(|member| member.contains(pane))?;
+        if matches!(self.members[ix], Member::Pane(_)) {
+            return Some(self);
+        }
+        match &mut self.members[ix] {
+            Member::Axis(axis) => axis.parent_of(pane),
+            Member::Pane(_) => None,
+        }
+    }
+
     fn bounding_box_for_pane(&self, pane: &View<Pane>) -> Option<Bounds<Pixels>> {
         debug_assert!(self.members.len() == self.bounding_boxes.lock().len());
 
@@ -27,10 +27,10 @@ use futures::{
 };
 use gpui::{
     actions, canvas, impl_actions, point, size, Action, AnyElement, AnyView, AnyWeakView,
-    AppContext, AsyncAppContext, AsyncWindowContext, Bounds, DevicePixels, DragMoveEvent,
+    AppContext, AsyncAppContext, AsyncWindowContext, Axis, Bounds, DevicePixels, DragMoveEvent,
     Entity as _, EntityId, EventEmitter, FocusHandle, FocusableView, Global, KeyContext, Keystroke,
-    LayoutId, ManagedView, Model, ModelContext, PathPromptOptions, Point, PromptLevel, Render,
-    Size, Subscription, Task, View, WeakView, WindowHandle, WindowOptions,
+    LayoutId, ManagedView, Model, ModelContext, PathPromptOptions, Pixels, Point, PromptLevel,
+    Render, Size, Subscription, Task, View, WeakView, WindowHandle, WindowOptions,
 };
 use item::{FollowableItem, FollowableItemHandle, Item, ItemHandle, ItemSettings, ProjectItem};
 use itertools::Itertools;
@@ -128,6 +128,10 @@ actions!(
         ToggleRightDock,
         ToggleBottomDock,
         CloseAllDocks,
+        ResetPaneSizes,
+        RotatePanesForward,
+        RotatePanesBackward,
+        ExchangePaneWithNext,
     ]
 );
 
@@ -145,6 +149,9 @@ pub struct ActivatePaneInDirection(pub SplitDirection);
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct SwapPaneInDirection(pub SplitDirection);
 
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct MovePaneToEdge(pub SplitDirection);
+
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct NewFileInDirection(pub SplitDirection);
 
@@ -175,6 +182,14 @@ pub struct CloseInactiveTabsAndPanes {
 #[derive(Clone, Deserialize, PartialEq)]
 pub struct SendKeystrokes(pub String);
 
+/// Input dispatched by [`Workspace::send_input`].
+pub enum QueuedInput {
+    Keystroke(Keystroke),
+    /// Runs between the keystrokes around it, e.g. to change how the ones after it
+    /// are interpreted.
+    Callback(Box<dyn FnOnce(&mut WindowContext)>),
+}
+
 impl_actions!(
     workspace,
     [
@@ -182,6 +197,7 @@ impl_actions!(
         ActivatePaneInDirection,
         CloseAllItemsAndPanes,
         CloseInactiveTabsAndPanes,
+        MovePaneToEdge,
         NewFileInDirection,
         OpenTerminal,
         Save,
@@ -257,6 +273,12 @@ impl Column for WorkspaceId {
             .with_context(|| format!("Failed to read WorkspaceId at index {start_index}"))
     }
 }
+
+impl From<WorkspaceId> for i64 {
+    fn from(val: WorkspaceId) -> Self {
+        val.0
+    }
+}
 pub fn init_settings(cx: &mut AppContext) {
     WorkspaceSettings::register(cx);
     ItemSettings::register(cx);
@@ -563,7 +585,7 @@ pub struct Workspace {
     leader_updates_tx: mpsc::UnboundedSender<(PeerId, proto::UpdateFollowers)>,
     database_id: WorkspaceId,
     app_state: Arc<AppState>,
-    dispatching_keystrokes: Rc<RefCell<Vec<Keystroke>>>,
+    dispatching_keystrokes: Rc<RefCell<Vec<QueuedInput>>>,
     _subscriptions: Vec<Subscription>,
     _apply_leader_updates: Task<Result<()>>,
     _observe_current_user: Task<Result<()>>,
@@ -1303,25 +1325,39 @@ impl Workspace {
     }
 
     fn send_keystrokes(&mut self, action: &SendKeystrokes, cx: &mut ViewContext<Self>) {
-        let mut keystrokes: Vec<Keystroke> = action
+        let input = action
             .0
             .split(' ')
             .flat_map(|k| Keystroke::parse(k).log_err())
+            .map(QueuedInput::Keystroke)
             .collect();
-        keystrokes.reverse();
+        self.send_input(input, cx);
+    }
 
-        self.dispatching_keystrokes
-            .borrow_mut()
-            .append(&mut keystrokes);
+    /// Dispatches the given input as if it was typed, before any input that is still
+    /// queued by an earlier call, so that keystrokes sent by a binding that was itself
+    /// triggered by sent keystrokes run in order.
+    pub fn send_input(&mut self, mut input: Vec<QueuedInput>, cx: &mut ViewContext<Self>) {
+        input.reverse();
+        self.dispatching_keystrokes.borrow_mut().append(&mut input);
 
-        let keystrokes = self.dispatching_keystrokes.clone();
+        let queue = self.dispatching_keystrokes.clone();
         cx.window_context()
             .spawn(|mut cx| async move {
                 // limit to 100 keystrokes to avoid infinite recursion.
-                for _ in 0..100 {
-                    let Some(keystroke) = keystrokes.borrow_mut().pop() else {
+                let mut keystroke_count = 0;
+                while keystroke_count < 100 {
+                    let Some(input) = queue.borrow_mut().pop() else {
                         return Ok(());
                     };
+                    let keystroke = match input {
+                        QueuedInput::Keystroke(keystroke) => keystroke,
+                        QueuedInput::Callback(callback) => {
+                            cx.update(callback)?;
+                            continue;
+                        }
+                    };
+                    keystroke_count += 1;
                     cx.update(|cx| {
                         let focused = cx.focused();
                         cx.dispatch_keystroke(keystroke.clone());
@@ -1336,7 +1372,21 @@ impl Workspace {
                         }
                     })?;
                 }
-                keystrokes.borrow_mut().clear();
+                // Run any callbacks that are still queued, as they may undo state set up
+                // by earlier ones.
+                let callbacks = queue
+                    .borrow_mut()
+                    .drain(..)
+                    .filter_map(|input| match input {
+                        QueuedInput::Callback(callback) => Some(callback),
+                        QueuedInput::Keystroke(_) => None,
+                    })
+                    .collect::<Vec<_>>();
+                cx.update(|cx| {
+                    for callback in callbacks.into_iter().rev() {
+                        callback(cx);
+                    }
+                })?;
                 Err(anyhow!("over 100 keystrokes passed to send_keystrokes"))
             })
             .detach_and_log_err(cx);
@@ -2313,6 +2363,47 @@ impl Workspace {
         }
     }
 
+    /// Grows the active pane along the given axis, or shrinks it if `amount` is negative.
+    pub fn resize_pane(&mut self, axis: Axis, amount: Pixels, cx: &mut ViewContext<Self>) {
+        self.center.resize(&self.active_pane.clone(), axis, amount);
+        cx.notify();
+        self.schedule_serialize(cx);
+    }
+
+    /// Resizes the active pane to be as close to `size` along the given axis as its
+    /// siblings allow.
+    pub fn set_pane_size(&mut self, axis: Axis, size: Pixels, cx: &mut ViewContext<Self>) {
+        if let Some(bounds) = self.center.bounding_box_for_pane(&self.active_pane) {
+            self.resize_pane(axis, size - bounds.size.along(axis), cx);
+        }
+    }
+
+    pub fn reset_pane_sizes(&mut self, cx: &mut ViewContext<Self>) {
+        self.center.reset_pane_sizes();
+        cx.notify();
+        self.schedule_serialize(cx);
+    }
+
+    pub fn rotate_panes(&mut self, reverse: bool, cx: &mut ViewContext<Self>) {
+        self.center.rotate(&self.active_pane.clone(), reverse);
+        cx.notify();
+        self.schedule_serialize(cx);
+    }
+
+    pub fn exchange_pane_with_next(&mut self, cx: &mut ViewContext<Self>) {
+        self.center.exchange(&self.active_pane.clone());
+        cx.notify();
+        self.schedule_serialize(cx);
+    }
+
+    pub fn move_pane_to_edge(&mut self, direction: SplitDirection, cx: &mut ViewContext<Self>) {
+        self.center
+            .move_to_edge(&self.active_pane.clone(), direction)
+            .log_err();
+        cx.notify();
+        self.schedule_serialize(cx);
+    }
+
     fn handle_pane_focused(&mut self, pane: View<Pane>, cx: &mut ViewContext<Self>) {
         if self.active_pane != pane {
             self.active_pane = pane.clone();
@@ -3607,6 +3698,21 @@ impl Workspace {
             .on_action(cx.listener(|workspace, action: &SwapPaneInDirection, cx| {
                 workspace.swap_pane_in_direction(action.0, cx)
             }))
+            .on_action(cx.listener(|workspace, action: &MovePaneToEdge, cx| {
+                workspace.move_pane_to_edge(action.0, cx)
+            }))
+            .on_action(
+                cx.listener(|workspace, _: &ResetPaneSizes, cx| workspace.reset_pane_sizes(cx)),
+            )
+            .on_action(cx.listener(|workspace, _: &RotatePanesForward, cx| {
+                workspace.rotate_panes(false, cx)
+            }))
+            .on_action(cx.listener(|workspace, _: &RotatePanesBackward, cx| {
+                workspace.rotate_panes(true, cx)
+            }))
+            .on_action(cx.listener(|workspace, _: &ExchangePaneWithNext, cx| {
+                workspace.exchange_pane_with_next(cx)
+            }))
             .on_action(cx.listener(|this, _: &ToggleLeftDock, cx| {
                 this.toggle_dock(DockPosition::Left, cx);
             }))
@@ -5423,6 +5529,95 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_rearranging_panes(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        workspace.update(cx, |workspace, cx| {
+            let a = workspace.active_pane().clone();
+            let b = workspace.split_pane(a.clone(), SplitDirection::Right, cx);
+            let c = workspace.split_pane(b.clone(), SplitDirection::Down, cx);
+            let panes = |workspace: &Workspace| {
+                workspace
+                    .center
+                    .panes()
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
+
+            workspace
+                .center
+                .move_to_edge(&c, SplitDirection::Left)
+                .unwrap();
+            assert_eq!(panes(workspace), [c.clone(), a.clone(), b.clone()]);
+
+            workspace.center.rotate(&a, false);
+            assert_eq!(panes(workspace), [b.clone(), c.clone(), a.clone()]);
+
+            workspace.center.rotate(&a, true);
+            assert_eq!(panes(workspace), [c.clone(), a.clone(), b.clone()]);
+
+            workspace.center.exchange(&b);
+            assert_eq!(panes(workspace), [c.clone(), b.clone(), a.clone()]);
+
+            workspace
+                .center
+                .move_to_edge(&c, SplitDirection::Down)
+                .unwrap();
+            assert_eq!(panes(workspace), [b.clone(), a.clone(), c.clone()]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_rearranging_panes_keeps_their_sizes(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+
+        workspace.update(cx, |workspace, cx| {
+            let a = workspace.active_pane().clone();
+            let b = workspace.split_pane(a.clone(), SplitDirection::Right, cx);
+            let c = workspace.split_pane(b.clone(), SplitDirection::Right, cx);
+            let flexes = |workspace: &Workspace| match &workspace.center.root {
+                Member::Axis(axis) => axis.flexes.lock().clone(),
+                Member::Pane(_) => panic!("expected a split"),
+            };
+
+            // Lay the three panes out side by side and grow the first one.
+            if let Member::Axis(axis) = &workspace.center.root {
+                *axis.bounding_boxes.lock() = (0..3)
+                    .map(|ix| {
+                        Some(Bounds::new(
+                            point(px(100. * ix as f32), px(0.)),
+                            size(px(100.), px(100.)),
+                        ))
+                    })
+                    .collect();
+            }
+            workspace.center.resize(&a, Axis::Horizontal, px(10.));
+            let [a_flex, b_flex, c_flex] = flexes(workspace)[..] else {
+                panic!("expected three panes");
+            };
+            assert!(a_flex > b_flex && c_flex == 1.);
+
+            workspace.center.rotate(&a, false);
+            assert_eq!(flexes(workspace), [c_flex, a_flex, b_flex]);
+
+            workspace.center.rotate(&a, true);
+            assert_eq!(flexes(workspace), [a_flex, b_flex, c_flex]);
+
+            workspace.center.exchange(&c);
+            assert_eq!(flexes(workspace), [a_flex, c_flex, b_flex]);
+        });
+    }
+
     #[gpui::test]
     async fn test_toggle_docks_and_panels(cx: &mut gpui::TestAppContext) {
         init_test(cx);
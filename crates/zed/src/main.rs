@@ -71,6 +71,12 @@ use zed::{
 static GLOBAL: MiMalloc = MiMalloc;
 
 fn main() {
+    // Terminal sessions are detached into Zed processes of their own.
+    #[cfg(unix)]
+    if terminal::session_daemon::run_if_requested() {
+        return;
+    }
+
     menu::init();
     zed_actions::init();
 
@@ -112,6 +118,8 @@ fn main() {
         fs.clone(),
         paths::KEYMAP.clone(),
     );
+    let user_vimrc_file_rx =
+        watch_config_file(&app.background_executor(), fs.clone(), paths::VIMRC.clone());
 
     let login_shell_env_loaded = if stdout_is_a_pty() {
         Task::ready(())
@@ -152,7 +160,7 @@ fn main() {
             .unwrap();
         cx.set_global(store);
         handle_settings_file_changes(user_settings_file_rx, cx);
-        handle_keymap_file_changes(user_keymap_file_rx, cx);
+        handle_keymap_file_changes(user_keymap_file_rx, user_vimrc_file_rx, cx);
         client::init_settings(cx);
 
         let clock = Arc::new(clock::RealSystemClock);
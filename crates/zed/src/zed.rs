@@ -17,6 +17,7 @@ pub use open_listener::*;
 
 use anyhow::Context as _;
 use assets::Assets;
+use extension::{ExtensionStore, ExtensionTaskSource};
 use futures::{channel::mpsc, select_biased, StreamExt};
 use language::LanguageSource;
 use project::TaskSourceKind;
@@ -26,8 +27,8 @@ use release_channel::{AppCommitSha, ReleaseChannel};
 use rope::Rope;
 use search::project_search::ProjectSearchBar;
 use settings::{
-    initial_local_settings_content, initial_tasks_content, watch_config_file, KeymapFile, Settings,
-    SettingsStore, DEFAULT_KEYMAP_PATH,
+    initial_local_settings_content, initial_tasks_content, watch_config_file, ExtensionKeymaps,
+    KeymapFile, Settings, SettingsStore, DEFAULT_KEYMAP_PATH,
 };
 use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
 use task::{
@@ -43,7 +44,7 @@ use util::{
     ResultExt,
 };
 use uuid::Uuid;
-use vim::VimModeSetting;
+use vim::{VimModeSetting, Vimrc};
 use welcome::BaseKeymap;
 use workspace::{
     create_and_open_local_file, notifications::simple_message_notification::MessageNotification,
@@ -186,6 +187,13 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                         |cx| LanguageSource::new(app_state.languages.clone(), cx),
                         cx,
                     );
+                    if let Some(extension_store) = ExtensionStore::try_global(cx) {
+                        inventory.add_source(
+                            TaskSourceKind::Extensions,
+                            |cx| ExtensionTaskSource::new(extension_store, cx),
+                            cx,
+                        );
+                    }
                 })
             });
         }
@@ -600,12 +608,20 @@ fn open_log_file(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
 
 pub fn handle_keymap_file_changes(
     mut user_keymap_file_rx: mpsc::UnboundedReceiver<String>,
+    mut user_vimrc_file_rx: mpsc::UnboundedReceiver<String>,
     cx: &mut AppContext,
 ) {
     BaseKeymap::register(cx);
     VimModeSetting::register(cx);
 
     let (base_keymap_tx, mut base_keymap_rx) = mpsc::unbounded();
+    cx.observe_global::<ExtensionKeymaps>({
+        let base_keymap_tx = base_keymap_tx.clone();
+        move |_| {
+            base_keymap_tx.unbounded_send(()).unwrap();
+        }
+    })
+    .detach();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
     cx.observe_global::<SettingsStore>(move |cx| {
@@ -623,6 +639,9 @@ pub fn handle_keymap_file_changes(
 
     cx.spawn(move |cx| async move {
         let mut user_keymap = KeymapFile::default();
+        let mut vimrc_keymap = KeymapFile::default();
+        let mut vimrc_settings = serde_json::json!({});
+        let mut applied_vimrc_settings = serde_json::json!({});
         loop {
             select_biased! {
                 _ = base_keymap_rx.next() => {}
@@ -635,16 +654,43 @@ pub fn handle_keymap_file_changes(
                         }
                     }
                 }
+                user_vimrc_content = user_vimrc_file_rx.next() => {
+                    if let Some(user_vimrc_content) = user_vimrc_content {
+                        let vimrc = Vimrc::parse(&user_vimrc_content);
+                        vimrc_keymap = vimrc.keymap().log_err().unwrap_or_default();
+                        vimrc_settings = vimrc.settings().clone();
+                    }
+                }
             }
-            cx.update(|cx| reload_keymaps(cx, &user_keymap)).ok();
+            cx.update(|cx| {
+                // Like its mappings, the vimrc's options only apply while vim mode is enabled.
+                let settings = if VimModeSetting::get_global(cx).0 {
+                    vimrc_settings.clone()
+                } else {
+                    serde_json::json!({})
+                };
+                if settings != applied_vimrc_settings {
+                    cx.update_global(|store: &mut SettingsStore, cx| {
+                        store.set_vimrc_settings(settings.clone(), cx).log_err();
+                    });
+                    applied_vimrc_settings = settings;
+                }
+                reload_keymaps(cx, &user_keymap, &vimrc_keymap)
+            })
+            .ok();
         }
     })
     .detach();
 }
 
-fn reload_keymaps(cx: &mut AppContext, keymap_content: &KeymapFile) {
+fn reload_keymaps(cx: &mut AppContext, keymap_content: &KeymapFile, vimrc_keymap: &KeymapFile) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
+    ExtensionKeymaps::add_to_cx(cx);
+    // The vimrc comes before the user keymap, so that `keymap.json` can still override it.
+    if VimModeSetting::get_global(cx).0 {
+        vimrc_keymap.clone().add_to_cx(cx).log_err();
+    }
     keymap_content.clone().add_to_cx(cx).log_err();
     cx.set_menus(app_menus());
 }
@@ -884,7 +930,7 @@ mod tests {
         actions, Action, AnyWindowHandle, AppContext, AssetSource, BorrowAppContext, Entity,
         TestAppContext, VisualTestContext, WindowHandle,
     };
-    use language::{LanguageMatcher, LanguageRegistry};
+    use language::{language_settings::language_settings, LanguageMatcher, LanguageRegistry};
     use project::{Project, ProjectPath, WorktreeSettings};
     use serde_json::json;
     use settings::{handle_settings_file_changes, watch_config_file, SettingsStore};
@@ -2805,8 +2851,10 @@ mod tests {
                 app_state.fs.clone(),
                 PathBuf::from("/keymap.json"),
             );
+            let vimrc_rx =
+                watch_config_file(&executor, app_state.fs.clone(), PathBuf::from("/vimrc"));
             handle_settings_file_changes(settings_rx, cx);
-            handle_keymap_file_changes(keymap_rx, cx);
+            handle_keymap_file_changes(keymap_rx, vimrc_rx, cx);
         });
         workspace
             .update(cx, |workspace, _| {
@@ -2943,9 +2991,11 @@ mod tests {
                 app_state.fs.clone(),
                 PathBuf::from("/keymap.json"),
             );
+            let vimrc_rx =
+                watch_config_file(&executor, app_state.fs.clone(), PathBuf::from("/vimrc"));
 
             handle_settings_file_changes(settings_rx, cx);
-            handle_keymap_file_changes(keymap_rx, cx);
+            handle_keymap_file_changes(keymap_rx, vimrc_rx, cx);
         });
 
         cx.background_executor.run_until_parked();
@@ -3014,6 +3064,74 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_vimrc_settings_require_vim_mode(cx: &mut gpui::TestAppContext) {
+        let executor = cx.executor();
+        let app_state = init_keymap_test(cx);
+        app_state
+            .fs
+            .save("/settings.json".as_ref(), &"{}".into(), Default::default())
+            .await
+            .unwrap();
+        app_state
+            .fs
+            .save("/keymap.json".as_ref(), &"[]".into(), Default::default())
+            .await
+            .unwrap();
+        app_state
+            .fs
+            .save(
+                "/vimrc".as_ref(),
+                &"set tabstop=2".into(),
+                Default::default(),
+            )
+            .await
+            .unwrap();
+
+        cx.update(|cx| {
+            let settings_rx = watch_config_file(
+                &executor,
+                app_state.fs.clone(),
+                PathBuf::from("/settings.json"),
+            );
+            let keymap_rx = watch_config_file(
+                &executor,
+                app_state.fs.clone(),
+                PathBuf::from("/keymap.json"),
+            );
+            let vimrc_rx =
+                watch_config_file(&executor, app_state.fs.clone(), PathBuf::from("/vimrc"));
+            handle_settings_file_changes(settings_rx, cx);
+            handle_keymap_file_changes(keymap_rx, vimrc_rx, cx);
+        });
+        executor.run_until_parked();
+        cx.update(|cx| assert_eq!(language_settings(None, None, cx).tab_size.get(), 4));
+
+        app_state
+            .fs
+            .save(
+                "/settings.json".as_ref(),
+                &r#"{ "vim_mode": true }"#.into(),
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        executor.run_until_parked();
+        cx.update(|cx| assert_eq!(language_settings(None, None, cx).tab_size.get(), 2));
+
+        app_state
+            .fs
+            .save(
+                "/settings.json".as_ref(),
+                &r#"{ "vim_mode": false }"#.into(),
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        executor.run_until_parked();
+        cx.update(|cx| assert_eq!(language_settings(None, None, cx).tab_size.get(), 4));
+    }
+
     #[gpui::test]
     fn test_bundled_settings_and_themes(cx: &mut AppContext) {
         cx.text_system()